@@ -0,0 +1,143 @@
+#!/usr/bin/env python3
+"""Subsets a TrueType font down to the characters the tray icon can draw.
+
+Produces src-tauri/crates/render/assets/fonts/DejaVuSans-subset.ttf, the
+fallback the render crate uses when no system sans-serif font is found:
+
+    python3 scripts/subset-font.py /usr/share/fonts/truetype/dejavu/DejaVuSans.ttf \
+        src-tauri/crates/render/assets/fonts/DejaVuSans-subset.ttf
+
+Keeps Basic Latin, Latin-1 and a few symbols, renumbers the glyphs and drops
+hinting and OpenType layout tables, which the tray renderer does not use.
+Standard library only, so it runs without fonttools.
+"""
+
+import struct
+import sys
+
+src, dst = sys.argv[1], sys.argv[2]
+data = open(src,'rb').read()
+numTables = struct.unpack('>H', data[4:6])[0]
+tables = {}
+for i in range(numTables):
+    tag, cs, off, ln = struct.unpack('>4sIII', data[12+16*i:28+16*i])
+    tables[tag.decode()] = data[off:off+ln]
+head = tables['head']; indexToLoc = struct.unpack('>h', head[50:52])[0]
+maxp = tables['maxp']; numGlyphs = struct.unpack('>H', maxp[4:6])[0]
+loca = tables['loca']
+if indexToLoc == 0:
+    offs = [struct.unpack('>H', loca[2*i:2*i+2])[0]*2 for i in range(numGlyphs+1)]
+else:
+    offs = [struct.unpack('>I', loca[4*i:4*i+4])[0] for i in range(numGlyphs+1)]
+glyf = tables['glyf']
+# cmap format 4 / 12 lookup
+cmap = tables['cmap']
+n = struct.unpack('>H', cmap[2:4])[0]
+sub = None
+for i in range(n):
+    pid, eid, off = struct.unpack('>HHI', cmap[4+8*i:12+8*i])
+    fmt = struct.unpack('>H', cmap[off:off+2])[0]
+    if fmt == 4 and pid == 3 and eid == 1: sub = off
+def lookup(c):
+    off = sub
+    segX2 = struct.unpack('>H', cmap[off+6:off+8])[0]; seg = segX2//2
+    ends = off+14; starts = ends+segX2+2; deltas = starts+segX2; ranges = deltas+segX2
+    for s in range(seg):
+        end = struct.unpack('>H', cmap[ends+2*s:ends+2*s+2])[0]
+        if c <= end:
+            start = struct.unpack('>H', cmap[starts+2*s:starts+2*s+2])[0]
+            if c < start: return 0
+            delta = struct.unpack('>h', cmap[deltas+2*s:deltas+2*s+2])[0]
+            ro = struct.unpack('>H', cmap[ranges+2*s:ranges+2*s+2])[0]
+            if ro == 0: return (c+delta) & 0xFFFF
+            p = ranges+2*s+ro+2*(c-start)
+            g = struct.unpack('>H', cmap[p:p+2])[0]
+            return (g+delta) & 0xFFFF if g else 0
+    return 0
+chars = list(range(0x20,0x7F)) + list(range(0xA0,0x100)) + [0x2013,0x2014,0x2026,0x2191,0x2193,0x2212]
+keep = {0}
+for c in chars:
+    keep.add(lookup(c))
+# composite closure
+todo = list(keep)
+while todo:
+    g = todo.pop(); gd = glyf[offs[g]:offs[g+1]]
+    if len(gd) >= 10 and struct.unpack('>h', gd[0:2])[0] < 0:
+        p = 10
+        while True:
+            flags, comp = struct.unpack('>HH', gd[p:p+4])
+            if comp not in keep: keep.add(comp); todo.append(comp)
+            p += 4 + (4 if flags & 1 else 2)
+            if flags & 8: p += 2
+            elif flags & 0x40: p += 4
+            elif flags & 0x80: p += 8
+            if not flags & 0x20: break
+
+order = sorted(keep)
+remap = {g: i for i, g in enumerate(order)}
+newglyf = b''; newoffs = []
+for g in order:
+    newoffs.append(len(newglyf))
+    gd = bytearray(glyf[offs[g]:offs[g+1]])
+    if len(gd) >= 10 and struct.unpack('>h', gd[0:2])[0] < 0:
+        p = 10
+        while True:
+            flags, comp = struct.unpack('>HH', gd[p:p+4])
+            gd[p+2:p+4] = struct.pack('>H', remap[comp])
+            p += 4 + (4 if flags & 1 else 2)
+            if flags & 8: p += 2
+            elif flags & 0x40: p += 4
+            elif flags & 0x80: p += 8
+            if not flags & 0x20: break
+    gd = bytes(gd)
+    newglyf += gd + b'\0'*((4-len(gd)%4)%4)
+newoffs.append(len(newglyf))
+tables['glyf'] = newglyf
+tables['loca'] = b''.join(struct.pack('>I', o) for o in newoffs)
+tables['head'] = head[:8] + b'\0\0\0\0' + head[12:50] + struct.pack('>h', 1) + head[52:]
+hhea = tables['hhea']; nhm = struct.unpack('>H', hhea[34:36])[0]; hmtx = tables['hmtx']
+def metric(g):
+    if g < nhm: return hmtx[4*g:4*g+4]
+    aw = hmtx[4*(nhm-1):4*(nhm-1)+2]
+    lsb = hmtx[4*nhm+2*(g-nhm):4*nhm+2*(g-nhm)+2]
+    return aw + lsb
+tables['hmtx'] = b''.join(metric(g) for g in order)
+tables['hhea'] = hhea[:34] + struct.pack('>H', len(order))
+tables['maxp'] = maxp[:4] + struct.pack('>H', len(order)) + maxp[6:]
+# cmap format 4 with one segment per character
+pairs = sorted((c, remap[lookup(c)]) for c in chars if lookup(c))
+seg = len(pairs) + 1
+ends = [c for c,_ in pairs] + [0xFFFF]; starts = ends[:]
+deltas = [(g - c) & 0xFFFF for c,g in pairs] + [1]
+es = seg.bit_length()-1; sr = 2*(1<<es)
+f4 = struct.pack('>HHHHHHH', 4, 0, 0, 2*seg, sr, es, 2*seg-sr)
+f4 += b''.join(struct.pack('>H', e) for e in ends) + b'\0\0'
+f4 += b''.join(struct.pack('>H', x) for x in starts)
+f4 += b''.join(struct.pack('>H', d) for d in deltas)
+f4 += b'\0\0'*seg
+f4 = f4[:2] + struct.pack('>H', len(f4)) + f4[4:]
+tables['cmap'] = struct.pack('>HHHHI', 0, 1, 3, 1, 12) + f4
+for t in ['GPOS','GSUB','GDEF','kern','hdmx','VDMX','LTSH','fpgm','prep','cvt ','gasp','FFTM','MATH','post']:
+    tables.pop(t, None)
+# minimal post table (format 3)
+tables['post'] = struct.pack('>Ii', 0x00030000, 0) + struct.pack('>hh', -130, 90) + struct.pack('>IIIII', 0,0,0,0,0)
+def checksum(b):
+    b = b + b'\0'*((4-len(b)%4)%4)
+    return sum(struct.unpack('>%dI' % (len(b)//4), b)) & 0xFFFFFFFF
+tags = sorted(tables)
+nt = len(tags); es = nt.bit_length()-1; sr = (1<<es)*16
+out = struct.pack('>IHHHH', 0x00010000, nt, sr, es, nt*16-sr)
+off = 12 + 16*nt; body = b''
+for t in tags:
+    b = tables[t]
+    out += struct.pack('>4sIII', t.encode(), checksum(b), off+len(body), len(b))
+    body += b + b'\0'*((4-len(b)%4)%4)
+font = out + body
+adj = (0xB1B0AFBA - checksum(font)) & 0xFFFFFFFF
+# locate head offset via directory
+for i,t in enumerate(tags):
+    if t=='head':
+        hoff = struct.unpack('>I', font[12+16*i+8:12+16*i+12])[0]
+font = font[:hoff+8] + struct.pack('>I', adj) + font[hoff+12:]
+open(dst,'wb').write(font)
+print(len(keep), 'glyphs kept of', numGlyphs, '->', len(font), 'bytes')
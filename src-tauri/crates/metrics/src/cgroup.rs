@@ -0,0 +1,151 @@
+//! cgroup v2 limits (Linux)
+//!
+//! With `cgroup_metrics` on, the CPU and memory segments report a cgroup's
+//! usage against its own limits instead of the host's, for the app running in
+//! a container or a workload confined to a slice:
+//!
+//! - CPU: `usage_usec` from `cpu.stat` over the `cpu.max` quota, or over every
+//!   host core when the cgroup has no quota.
+//! - Memory: `memory.current` less `inactive_file` from `memory.stat` (as
+//!   `docker stats` does) over `memory.max`, or over host RAM without a limit.
+//!
+//! The cgroup is the app's own from `/proc/self/cgroup`, or `cgroup_path`
+//! below `/sys/fs/cgroup` (e.g. "system.slice/docker-3f2a.scope"). When it
+//! can't be read the host figures are shown as usual.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+use std::sync::Mutex;
+use std::time::Instant;
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// The cgroup settings
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PATH: Mutex<String> = Mutex::new(String::new());
+
+/// Applies the cgroup settings: whether to report a cgroup instead of the
+/// host, and which one (empty for the process's own).
+pub fn configure(enabled: bool, path: String) {
+    ENABLED.store(enabled, Relaxed);
+    *PATH.lock().unwrap_or_else(|e| e.into_inner()) = path;
+}
+
+/// Whether a unified (v2) hierarchy is mounted.
+pub fn is_available() -> bool {
+    PathBuf::from(CGROUP_ROOT)
+        .join("cgroup.controllers")
+        .exists()
+}
+
+/// Directory of the cgroup to report, while cgroup mode is on.
+fn dir() -> Option<PathBuf> {
+    if !ENABLED.load(Relaxed) {
+        return None;
+    }
+    let path = PATH.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let path = if path.is_empty() {
+        parse_own_cgroup(&fs::read_to_string("/proc/self/cgroup").ok()?)?.to_string()
+    } else {
+        path
+    };
+    Some(PathBuf::from(CGROUP_ROOT).join(path.trim_start_matches('/')))
+}
+
+/// Memory in use and the limit it's measured against, in bytes; `host_total`
+/// stands in for a cgroup without a limit.
+pub fn memory(host_total: u64) -> Option<(u64, u64)> {
+    let dir = dir()?;
+    let current: u64 = fs::read_to_string(dir.join("memory.current"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    let inactive_file = fs::read_to_string(dir.join("memory.stat"))
+        .ok()
+        .and_then(|stat| parse_stat_field(&stat, "inactive_file"))
+        .unwrap_or(0);
+    let limit = fs::read_to_string(dir.join("memory.max"))
+        .ok()
+        .and_then(|max| parse_memory_max(&max))
+        .map_or(host_total, |limit| limit.min(host_total));
+    Some((current.saturating_sub(inactive_file).min(limit), limit))
+}
+
+/// CPU use of the cgroup since the previous sample.
+#[derive(Default)]
+pub struct CpuUsage {
+    /// Directory, `usage_usec` and when it was read
+    prev: Option<(PathBuf, u64, Instant)>,
+}
+
+impl CpuUsage {
+    /// Percent of the quota used, or of `host_cores` without one; `None`
+    /// outside cgroup mode and on the first sample of a cgroup.
+    pub fn sample(&mut self, host_cores: usize) -> Option<f32> {
+        let Some(dir) = dir() else {
+            self.prev = None;
+            return None;
+        };
+        let usage = fs::read_to_string(dir.join("cpu.stat"))
+            .ok()
+            .and_then(|stat| parse_stat_field(&stat, "usage_usec"));
+        let Some(usage) = usage else {
+            self.prev = None;
+            return None;
+        };
+        let now = Instant::now();
+        let cores = fs::read_to_string(dir.join("cpu.max"))
+            .ok()
+            .and_then(|max| parse_cpu_max(&max))
+            .unwrap_or(host_cores as f64);
+        let percent = match self.prev.take() {
+            Some((prev_dir, prev_usage, then)) if prev_dir == dir => cpu_percent(
+                usage.saturating_sub(prev_usage),
+                now.duration_since(then).as_micros() as u64,
+                cores,
+            ),
+            _ => None,
+        };
+        self.prev = Some((dir, usage, now));
+        percent
+    }
+}
+
+/// Share of `cores` busy for `used_usec` of CPU time over `elapsed_usec`,
+/// capped at 100% as the quota can be briefly overrun.
+pub fn cpu_percent(used_usec: u64, elapsed_usec: u64, cores: f64) -> Option<f32> {
+    if elapsed_usec == 0 || cores <= 0.0 {
+        return None;
+    }
+    let percent = used_usec as f64 / (elapsed_usec as f64 * cores) * 100.0;
+    Some(percent.min(100.0) as f32)
+}
+
+/// The v2 entry of `/proc/self/cgroup`, e.g. "/user.slice/app.scope" from
+/// `0::/user.slice/app.scope`.
+pub fn parse_own_cgroup(contents: &str) -> Option<&str> {
+    contents.lines().find_map(|line| line.strip_prefix("0::"))
+}
+
+/// Cores allowed by `cpu.max` ("150000 100000" is 1.5); `None` for "max".
+pub fn parse_cpu_max(contents: &str) -> Option<f64> {
+    let mut fields = contents.split_whitespace();
+    let quota: f64 = fields.next()?.parse().ok()?;
+    let period: f64 = fields.next().map_or(Some(100_000.0), |p| p.parse().ok())?;
+    (period > 0.0).then_some(quota / period)
+}
+
+/// `memory.max` in bytes; `None` for "max".
+pub fn parse_memory_max(contents: &str) -> Option<u64> {
+    contents.trim().parse().ok()
+}
+
+/// A "name value" row of a flat-keyed file such as `cpu.stat`.
+pub fn parse_stat_field(contents: &str, name: &str) -> Option<u64> {
+    contents.lines().find_map(|line| {
+        let (key, value) = line.split_once(' ')?;
+        (key == name).then(|| value.trim().parse().ok())?
+    })
+}
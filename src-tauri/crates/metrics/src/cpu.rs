@@ -0,0 +1,292 @@
+//! CPU samplers: usage, per-core usage, user/system split, load average and
+//! clock frequency
+
+use std::time::Duration;
+
+use better_resource_monitor_types::CoreUsage;
+use sysinfo::{CpuRefreshKind, RefreshKind, System};
+
+use crate::cgroup;
+use crate::sampler::{name, MetricValue, Sampler, Throttle};
+
+/// Global CPU usage across all cores, or of the cgroup's quota in cgroup mode.
+pub struct CpuSampler {
+    sys: System,
+    cgroup: cgroup::CpuUsage,
+}
+
+impl Default for CpuSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CpuSampler {
+    pub fn new() -> Self {
+        let mut sys = System::new();
+        // Usage is a delta between refreshes; prime it so the first sample is valid
+        sys.refresh_cpu_usage();
+        Self {
+            sys,
+            cgroup: cgroup::CpuUsage::default(),
+        }
+    }
+}
+
+impl Sampler for CpuSampler {
+    fn name(&self) -> &'static str {
+        name::CPU
+    }
+
+    fn sample(&mut self) -> MetricValue {
+        self.sys.refresh_cpu_usage();
+        // Host usage stays primed for when cgroup mode is turned off
+        match self.cgroup.sample(self.sys.cpus().len()) {
+            Some(percent) => MetricValue::Percent(percent),
+            None => MetricValue::Percent(self.sys.global_cpu_usage()),
+        }
+    }
+}
+
+/// Usage of each core, for the heatmap CPU display. Kept apart from
+/// [`CpuSampler`] so cgroup mode doesn't change what the cells show.
+pub struct CpuCoresSampler {
+    sys: System,
+}
+
+impl Default for CpuCoresSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CpuCoresSampler {
+    pub fn new() -> Self {
+        let mut sys = System::new();
+        sys.refresh_cpu_usage();
+        Self { sys }
+    }
+}
+
+impl Sampler for CpuCoresSampler {
+    fn name(&self) -> &'static str {
+        name::CPU_CORES
+    }
+
+    fn sample(&mut self) -> MetricValue {
+        self.sys.refresh_cpu_usage();
+        let usage: Vec<f32> = self.sys.cpus().iter().map(|cpu| cpu.cpu_usage()).collect();
+        if usage.is_empty() {
+            return MetricValue::Unavailable;
+        }
+        MetricValue::Cores(CoreUsage::new(&usage))
+    }
+}
+
+/// Cumulative CPU time across all cores by state, in clock ticks.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CpuTimes {
+    /// User time including niced processes
+    pub user: u64,
+    /// Kernel time including interrupt handling
+    pub system: u64,
+    /// Every state, idle included
+    pub total: u64,
+}
+
+impl CpuTimes {
+    /// User and system shares of the time between `prev` and `self`, in percent.
+    pub fn split_since(self, prev: CpuTimes) -> Option<(f32, f32)> {
+        let total = self.total.checked_sub(prev.total).filter(|&t| t > 0)? as f64;
+        let share =
+            |now: u64, before: u64| (now.saturating_sub(before) as f64 / total * 100.0) as f32;
+        Some((share(self.user, prev.user), share(self.system, prev.system)))
+    }
+}
+
+/// Parses the aggregate `cpu` line of `/proc/stat`: user, nice, system, idle,
+/// iowait, irq, softirq and steal ticks. Guest time is already part of user.
+pub fn parse_proc_stat(stat: &str) -> Option<CpuTimes> {
+    let line = stat.lines().find(|line| line.starts_with("cpu "))?;
+    let ticks: Vec<u64> = line
+        .split_whitespace()
+        .skip(1)
+        .take(8)
+        .map(|field| field.parse().ok())
+        .collect::<Option<_>>()?;
+    let [user, nice, system, _idle, _iowait, irq, softirq, _steal] = ticks[..] else {
+        return None;
+    };
+    Some(CpuTimes {
+        user: user + nice,
+        system: system + irq + softirq,
+        total: ticks.iter().sum(),
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn read_cpu_times() -> Option<CpuTimes> {
+    parse_proc_stat(&std::fs::read_to_string("/proc/stat").ok()?)
+}
+
+#[cfg(target_os = "macos")]
+fn read_cpu_times() -> Option<CpuTimes> {
+    mach::cpu_load()
+}
+
+/// User and system CPU time as separate percentages (Linux/macOS), from the
+/// kernel's per-state counters rather than sysinfo's single usage figure.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub struct CpuSplitSampler {
+    prev: Option<CpuTimes>,
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+impl CpuSplitSampler {
+    pub fn new() -> Option<Self> {
+        // Also the baseline, so the first sample is valid
+        let prev = read_cpu_times()?;
+        Some(Self { prev: Some(prev) })
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+impl Sampler for CpuSplitSampler {
+    fn name(&self) -> &'static str {
+        name::CPU_SPLIT
+    }
+
+    fn sample(&mut self) -> MetricValue {
+        let now = read_cpu_times();
+        let split = now
+            .zip(self.prev)
+            .and_then(|(now, prev)| now.split_since(prev));
+        self.prev = now;
+        match split {
+            Some((user, system)) => MetricValue::CpuSplit { user, system },
+            None => MetricValue::Unavailable,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.prev = None;
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod mach {
+    use std::ffi::c_int;
+    use std::sync::OnceLock;
+
+    use super::CpuTimes;
+
+    const HOST_CPU_LOAD_INFO: c_int = 3;
+    const KERN_SUCCESS: c_int = 0;
+
+    /// `host_cpu_load_info` from `<mach/host_info.h>`: ticks indexed by
+    /// `CPU_STATE_USER`, `CPU_STATE_SYSTEM`, `CPU_STATE_IDLE`, `CPU_STATE_NICE`
+    #[repr(C)]
+    #[derive(Default)]
+    struct HostCpuLoadInfo {
+        cpu_ticks: [u32; 4],
+    }
+
+    unsafe extern "C" {
+        fn mach_host_self() -> u32;
+        fn host_statistics(host: u32, flavor: c_int, info: *mut c_int, count: *mut u32) -> c_int;
+    }
+
+    /// Taken once, since every `mach_host_self()` call adds a reference
+    fn host_port() -> u32 {
+        static HOST: OnceLock<u32> = OnceLock::new();
+        *HOST.get_or_init(|| unsafe { mach_host_self() })
+    }
+
+    pub fn cpu_load() -> Option<CpuTimes> {
+        let mut info = HostCpuLoadInfo::default();
+        let mut count = 4;
+        let status = unsafe {
+            host_statistics(
+                host_port(),
+                HOST_CPU_LOAD_INFO,
+                &mut info as *mut HostCpuLoadInfo as *mut c_int,
+                &mut count,
+            )
+        };
+        if status != KERN_SUCCESS {
+            return None;
+        }
+
+        // The counters are 32-bit and wrap; `split_since` treats a wrap like a
+        // missing reading
+        let [user, system, idle, nice] = info.cpu_ticks.map(u64::from);
+        Some(CpuTimes {
+            user: user + nice,
+            system,
+            total: user + system + idle + nice,
+        })
+    }
+}
+
+/// Logical CPUs, the divisor behind [`CpuSampler`]'s global average.
+pub fn logical_cores() -> u32 {
+    let sys =
+        System::new_with_specifics(RefreshKind::nothing().with_cpu(CpuRefreshKind::nothing()));
+    (sys.cpus().len() as u32).max(1)
+}
+
+/// 1-minute load average (Linux/macOS).
+pub struct LoadAverageSampler;
+
+impl Sampler for LoadAverageSampler {
+    fn name(&self) -> &'static str {
+        name::LOAD_AVERAGE
+    }
+
+    fn sample(&mut self) -> MetricValue {
+        MetricValue::Scalar(System::load_average().one as f32)
+    }
+}
+
+/// Average clock frequency across cores, in MHz. sysinfo reports 0 where the
+/// platform doesn't expose it.
+pub struct CpuFrequencySampler {
+    sys: System,
+    throttle: Throttle,
+    mhz: u64,
+}
+
+impl Default for CpuFrequencySampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CpuFrequencySampler {
+    pub fn new() -> Self {
+        Self {
+            sys: System::new(),
+            throttle: Throttle::every(Duration::from_secs(3)),
+            mhz: 0,
+        }
+    }
+}
+
+impl Sampler for CpuFrequencySampler {
+    fn name(&self) -> &'static str {
+        name::CPU_FREQUENCY
+    }
+
+    fn sample(&mut self) -> MetricValue {
+        if self.throttle.due() {
+            self.sys.refresh_cpu_frequency();
+            let cpus = self.sys.cpus();
+            self.mhz = cpus.iter().map(|c| c.frequency()).sum::<u64>() / cpus.len().max(1) as u64;
+        }
+        MetricValue::Megahertz(self.mhz)
+    }
+
+    fn reset(&mut self) {
+        self.throttle.reset();
+    }
+}
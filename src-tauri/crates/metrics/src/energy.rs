@@ -0,0 +1,245 @@
+//! Apple Silicon CPU and Neural Engine power via IOReport
+//!
+//! Subscribes to the IOReport "Energy Model" group and turns the CPU or ANE
+//! energy counters into average power (watts) between two samples. IOReport is
+//! a private but stable framework (`/usr/lib/libIOReport.dylib`) also used by
+//! `powermetrics`; no root privileges are required. Private frameworks are not
+//! allowed in the Mac App Store, so this is only built with the off-by-default
+//! `ioreport` feature.
+//!
+//! The ANE has no utilization counter, so its power draw stands in for load: it
+//! idles at 0 W and climbs with inference work.
+
+use std::ffi::c_void;
+use std::ptr;
+use std::time::Instant;
+
+use core_foundation::array::{CFArrayGetCount, CFArrayGetValueAtIndex, CFArrayRef};
+use core_foundation::base::{kCFAllocatorDefault, CFRelease, CFTypeRef, TCFType};
+use core_foundation::dictionary::{
+    CFDictionaryCreateMutableCopy, CFDictionaryGetCount, CFDictionaryGetValue, CFDictionaryRef,
+    CFMutableDictionaryRef,
+};
+use core_foundation::string::{CFString, CFStringRef};
+
+use crate::sampler::{name, MetricValue, Sampler};
+
+type IOReportSubscriptionRef = *const c_void;
+
+#[link(name = "IOReport", kind = "dylib")]
+unsafe extern "C" {
+    fn IOReportCopyChannelsInGroup(
+        group: CFStringRef,
+        subgroup: CFStringRef,
+        a: u64,
+        b: u64,
+        c: u64,
+    ) -> CFDictionaryRef;
+    fn IOReportCreateSubscription(
+        a: *const c_void,
+        desired_channels: CFMutableDictionaryRef,
+        subscribed_channels: *mut CFMutableDictionaryRef,
+        channel_id: u64,
+        b: CFTypeRef,
+    ) -> IOReportSubscriptionRef;
+    fn IOReportCreateSamples(
+        subscription: IOReportSubscriptionRef,
+        subscribed_channels: CFMutableDictionaryRef,
+        a: CFTypeRef,
+    ) -> CFDictionaryRef;
+    fn IOReportCreateSamplesDelta(
+        previous: CFDictionaryRef,
+        current: CFDictionaryRef,
+        a: CFTypeRef,
+    ) -> CFDictionaryRef;
+    fn IOReportChannelGetChannelName(channel: CFDictionaryRef) -> CFStringRef;
+    fn IOReportChannelGetUnitLabel(channel: CFDictionaryRef) -> CFStringRef;
+    fn IOReportSimpleGetIntegerValue(channel: CFDictionaryRef, index: i32) -> i64;
+}
+
+/// Channel names are "CPU Energy" on M1 and prefixed per die/cluster on later chips.
+const CPU_ENERGY_SUFFIX: &str = "CPU Energy";
+
+/// Neural Engine channels are "ANE", or numbered per engine on larger chips.
+const ANE_ENERGY_PREFIX: &str = "ANE";
+
+pub fn is_cpu_channel(name: &str) -> bool {
+    name.ends_with(CPU_ENERGY_SUFFIX)
+}
+
+pub fn is_ane_channel(name: &str) -> bool {
+    name.starts_with(ANE_ENERGY_PREFIX)
+}
+
+fn cf_string(value: &str) -> CFString {
+    CFString::new(value)
+}
+
+fn to_string(value: CFStringRef) -> Option<String> {
+    if value.is_null() {
+        None
+    } else {
+        Some(unsafe { CFString::wrap_under_get_rule(value) }.to_string())
+    }
+}
+
+/// Joules per unit for an IOReport energy unit label.
+pub fn joules_per_unit(label: &str) -> Option<f64> {
+    match label.trim() {
+        "mJ" => Some(1e-3),
+        "uJ" | "µJ" => Some(1e-6),
+        "nJ" => Some(1e-9),
+        _ => None,
+    }
+}
+
+/// Average power in watts of the channels selected by `matches`, between
+/// consecutive samples.
+pub struct EnergySampler {
+    name: &'static str,
+    matches: fn(&str) -> bool,
+    subscription: IOReportSubscriptionRef,
+    channels: CFMutableDictionaryRef,
+    previous: Option<(CFDictionaryRef, Instant)>,
+}
+
+impl EnergySampler {
+    /// CPU package power. Returns None when IOReport has no energy channels
+    /// (e.g. Intel Macs).
+    pub fn cpu() -> Option<Self> {
+        Self::new(name::CPU_POWER, is_cpu_channel)
+    }
+
+    /// Neural Engine power. Returns None on chips that report no ANE channel.
+    pub fn neural_engine() -> Option<Self> {
+        Self::new(name::ANE_POWER, is_ane_channel)
+    }
+
+    fn new(name: &'static str, matches: fn(&str) -> bool) -> Option<Self> {
+        unsafe {
+            let group = cf_string("Energy Model");
+            let channels =
+                IOReportCopyChannelsInGroup(group.as_concrete_TypeRef(), ptr::null(), 0, 0, 0);
+            if channels.is_null() {
+                return None;
+            }
+
+            let desired = CFDictionaryCreateMutableCopy(
+                kCFAllocatorDefault,
+                CFDictionaryGetCount(channels),
+                channels,
+            );
+            CFRelease(channels as CFTypeRef);
+            if desired.is_null() {
+                return None;
+            }
+
+            let mut subscribed: CFMutableDictionaryRef = ptr::null_mut();
+            let subscription =
+                IOReportCreateSubscription(ptr::null(), desired, &mut subscribed, 0, ptr::null());
+            CFRelease(desired as CFTypeRef);
+            if subscription.is_null() || subscribed.is_null() {
+                return None;
+            }
+
+            let sampler = Self {
+                name,
+                matches,
+                subscription,
+                channels: subscribed,
+                previous: None,
+            };
+            // Dropping the sampler releases the subscription
+            (!sampler.channel_items(sampler.channels).is_empty()).then_some(sampler)
+        }
+    }
+
+    /// The channels of a subscription or sample dictionary selected by `matches`.
+    fn channel_items(&self, dict: CFDictionaryRef) -> Vec<CFDictionaryRef> {
+        unsafe {
+            let key = cf_string("IOReportChannels");
+            let items = CFDictionaryGetValue(dict, key.as_concrete_TypeRef() as *const c_void)
+                as CFArrayRef;
+            if items.is_null() {
+                return Vec::new();
+            }
+
+            (0..CFArrayGetCount(items))
+                .map(|i| CFArrayGetValueAtIndex(items, i) as CFDictionaryRef)
+                .filter(|&item| {
+                    to_string(IOReportChannelGetChannelName(item))
+                        .is_some_and(|name| (self.matches)(&name))
+                })
+                .collect()
+        }
+    }
+
+    /// Sums the selected energy channels in a delta sample, in joules.
+    fn energy_joules(&self, delta: CFDictionaryRef) -> f64 {
+        let mut joules = 0.0;
+        for item in self.channel_items(delta) {
+            unsafe {
+                let Some(scale) =
+                    to_string(IOReportChannelGetUnitLabel(item)).and_then(|l| joules_per_unit(&l))
+                else {
+                    continue;
+                };
+                joules += IOReportSimpleGetIntegerValue(item, 0) as f64 * scale;
+            }
+        }
+        joules
+    }
+}
+
+impl Sampler for EnergySampler {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn sample(&mut self) -> MetricValue {
+        let now = Instant::now();
+        let current =
+            unsafe { IOReportCreateSamples(self.subscription, self.channels, ptr::null()) };
+        if current.is_null() {
+            return MetricValue::Unavailable;
+        }
+
+        let watts = self.previous.take().map(|(previous, at)| unsafe {
+            let delta = IOReportCreateSamplesDelta(previous, current, ptr::null());
+            CFRelease(previous as CFTypeRef);
+            if delta.is_null() {
+                return 0.0;
+            }
+            let joules = self.energy_joules(delta);
+            CFRelease(delta as CFTypeRef);
+            let seconds = now.duration_since(at).as_secs_f64();
+            if seconds > 0.0 {
+                (joules / seconds) as f32
+            } else {
+                0.0
+            }
+        });
+        self.previous = Some((current, now));
+
+        // The first sample only establishes the baseline
+        watts.map_or(MetricValue::Unavailable, MetricValue::Watts)
+    }
+
+    fn reset(&mut self) {
+        if let Some((previous, _)) = self.previous.take() {
+            unsafe { CFRelease(previous as CFTypeRef) };
+        }
+    }
+}
+
+impl Drop for EnergySampler {
+    fn drop(&mut self) {
+        self.reset();
+        unsafe {
+            CFRelease(self.channels as CFTypeRef);
+            CFRelease(self.subscription as CFTypeRef);
+        }
+    }
+}
+
+unsafe impl Send for EnergySampler {}
@@ -0,0 +1,891 @@
+//! GPU utilization and memory monitoring
+//!
+//! Platform-specific implementations:
+//! - macOS: Uses IOAccelerator via public IOKit APIs for device utilization and the GPU's
+//!   share of unified memory on Apple Silicon; on Intel Macs every accelerator (integrated
+//!   Intel, discrete AMD) is read, with VRAM for the discrete ones
+//! - Linux: Uses NVML (NVIDIA Management Library) for NVIDIA GPU utilization, VRAM
+//!   and temperature; where NVML is installed but fails to initialize (containers,
+//!   permissions), `nvidia-smi` is run as a subprocess instead
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::Relaxed;
+use std::time::{Duration, Instant};
+
+use crate::sampler::{name, MetricValue, Sampler, Throttle};
+
+/// Failed utilization reads in a row after which the GPU counts as lost.
+pub const LOST_AFTER_READS: u32 = 3;
+/// Wait after the first failed re-initialization, doubling after each
+/// further one up to [`MAX_REINIT_BACKOFF`]. The first attempt is immediate.
+pub const FIRST_REINIT_BACKOFF: Duration = Duration::from_secs(2);
+pub const MAX_REINIT_BACKOFF: Duration = Duration::from_secs(5 * 60);
+/// How long a lost GPU may stay gone before its segment is hidden.
+pub const HIDE_AFTER: Duration = Duration::from_secs(30);
+/// How often the monitoring loop looks for a GPU when none was found.
+pub const PROBE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Set while the GPU has been gone for [`HIDE_AFTER`].
+static GONE: AtomicBool = AtomicBool::new(false);
+
+/// Whether the GPU went away (eGPU unplugged, driver unloaded) and hasn't come
+/// back; the monitoring loop hides the GPU segment meanwhile.
+pub fn is_gone() -> bool {
+    GONE.load(Relaxed)
+}
+
+/// Where GPU readings come from on this platform.
+#[cfg(target_os = "macos")]
+pub fn backend() -> &'static str {
+    "IOAccelerator"
+}
+
+#[cfg(target_os = "linux")]
+pub fn backend() -> &'static str {
+    linux::backend()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub fn backend() -> &'static str {
+    "none"
+}
+
+/// Encoder utilization from which `gpu_encoder_alert` alerts.
+pub const ENCODER_SATURATED: f32 = 95.0;
+
+/// GPU memory in bytes. On Apple Silicon `total` is the unified memory size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GpuMemory {
+    pub used: u64,
+    pub total: u64,
+}
+
+// ============================================================================
+// macOS Implementation (IOAccelerator)
+// ============================================================================
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::ffi::{c_char, c_int, c_void};
+
+    use core_foundation::base::{kCFAllocatorDefault, CFRelease, CFTypeRef};
+    use core_foundation::dictionary::{
+        CFDictionaryGetValue, CFDictionaryRef, CFMutableDictionaryRef,
+    };
+    use core_foundation::string::{
+        kCFStringEncodingUTF8, CFStringCreateWithBytesNoCopy, CFStringRef,
+    };
+
+    #[allow(non_camel_case_types)]
+    type io_object_t = u32;
+    #[allow(non_camel_case_types)]
+    type io_iterator_t = u32;
+    #[allow(non_camel_case_types)]
+    type io_registry_entry_t = u32;
+    #[allow(non_camel_case_types)]
+    type kern_return_t = i32;
+
+    const KERN_SUCCESS: kern_return_t = 0;
+    const IO_OBJECT_NULL: io_object_t = 0;
+    const CF_NUMBER_SINT64_TYPE: isize = 4;
+
+    #[link(name = "IOKit", kind = "framework")]
+    unsafe extern "C" {
+        fn IOServiceMatching(name: *const i8) -> CFMutableDictionaryRef;
+        fn IOServiceGetMatchingServices(
+            main_port: u32,
+            matching: CFDictionaryRef,
+            existing: *mut io_iterator_t,
+        ) -> kern_return_t;
+        fn IOIteratorNext(iterator: io_iterator_t) -> io_object_t;
+        fn IORegistryEntryCreateCFProperties(
+            entry: io_registry_entry_t,
+            properties: *mut CFMutableDictionaryRef,
+            allocator: *const c_void,
+            options: u32,
+        ) -> kern_return_t;
+        fn IOObjectRelease(object: io_object_t) -> kern_return_t;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    unsafe extern "C" {
+        fn CFNumberGetValue(number: CFTypeRef, the_type: isize, value_ptr: *mut c_void) -> u8;
+    }
+
+    unsafe extern "C" {
+        fn sysctlbyname(
+            name: *const c_char,
+            oldp: *mut c_void,
+            oldlenp: *mut usize,
+            newp: *mut c_void,
+            newlen: usize,
+        ) -> c_int;
+    }
+
+    fn cfstr(val: &str) -> CFStringRef {
+        unsafe {
+            CFStringCreateWithBytesNoCopy(
+                kCFAllocatorDefault,
+                val.as_ptr(),
+                val.len() as isize,
+                kCFStringEncodingUTF8,
+                0,
+                core_foundation::base::kCFAllocatorNull,
+            )
+        }
+    }
+
+    fn cfdict_get_val(dict: CFDictionaryRef, key: &str) -> Option<CFTypeRef> {
+        unsafe {
+            let key = cfstr(key);
+            let val = CFDictionaryGetValue(dict, key as _);
+            CFRelease(key as _);
+
+            if val.is_null() {
+                None
+            } else {
+                Some(val)
+            }
+        }
+    }
+
+    /// Reads an integer entry from the accelerator's `PerformanceStatistics` dictionary.
+    fn read_performance_stat(service: io_registry_entry_t, key: &str) -> Option<i64> {
+        unsafe {
+            let mut props: CFMutableDictionaryRef = std::ptr::null_mut();
+            let kr = IORegistryEntryCreateCFProperties(
+                service,
+                &mut props,
+                kCFAllocatorDefault as *const c_void,
+                0,
+            );
+
+            if kr != KERN_SUCCESS || props.is_null() {
+                return None;
+            }
+
+            let result = cfdict_get_val(props as CFDictionaryRef, "PerformanceStatistics")
+                .and_then(|stats_ptr| {
+                    let value_ref = cfdict_get_val(stats_ptr as CFDictionaryRef, key)?;
+                    let mut value: i64 = 0;
+                    let ok = CFNumberGetValue(
+                        value_ref,
+                        CF_NUMBER_SINT64_TYPE,
+                        &mut value as *mut i64 as *mut c_void,
+                    );
+                    if ok != 0 {
+                        Some(value)
+                    } else {
+                        None
+                    }
+                });
+
+            CFRelease(props as CFTypeRef);
+            result
+        }
+    }
+
+    /// Apple Silicon and current Intel / AMD drivers report `Device Utilization %`;
+    /// older AMD drivers only `GPU Activity(%)`.
+    const UTILIZATION_KEYS: [&str; 2] = ["Device Utilization %", "GPU Activity(%)"];
+
+    fn read_gpu_utilization(service: io_registry_entry_t) -> Option<f32> {
+        UTILIZATION_KEYS
+            .iter()
+            .find_map(|key| read_performance_stat(service, key))
+            .map(|v| v.clamp(0, 100) as f32)
+    }
+
+    /// Dedicated memory of a discrete GPU, as (used, total).
+    fn read_vram(service: io_registry_entry_t) -> Option<(u64, u64)> {
+        let used = read_performance_stat(service, "vramUsedBytes")?.max(0) as u64;
+        let free = read_performance_stat(service, "vramFreeBytes")?.max(0) as u64;
+        Some((used, used + free))
+    }
+
+    fn physical_memory() -> Option<u64> {
+        let mut value: u64 = 0;
+        let mut len = std::mem::size_of::<u64>();
+        let ok = unsafe {
+            sysctlbyname(
+                b"hw.memsize\0".as_ptr().cast(),
+                &mut value as *mut u64 as *mut c_void,
+                &mut len,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        (ok == 0).then_some(value)
+    }
+
+    pub struct GpuDevice {
+        /// Accelerators reporting utilization: one on Apple Silicon, up to two
+        /// (integrated and discrete) on Intel Macs
+        services: Vec<io_registry_entry_t>,
+        total_memory: Option<u64>,
+    }
+
+    impl GpuDevice {
+        pub fn new() -> Option<Self> {
+            unsafe {
+                let matching = IOServiceMatching(b"IOAccelerator\0".as_ptr().cast());
+                if matching.is_null() {
+                    return None;
+                }
+
+                let mut iterator: io_iterator_t = IO_OBJECT_NULL;
+                let kr =
+                    IOServiceGetMatchingServices(0, matching as CFDictionaryRef, &mut iterator);
+                if kr != KERN_SUCCESS || iterator == IO_OBJECT_NULL {
+                    return None;
+                }
+
+                let mut services = Vec::new();
+                loop {
+                    let service = IOIteratorNext(iterator);
+                    if service == IO_OBJECT_NULL {
+                        break;
+                    }
+                    // Only keep services that actually have PerformanceStatistics
+                    if read_gpu_utilization(service).is_some() {
+                        services.push(service);
+                    } else {
+                        IOObjectRelease(service);
+                    }
+                }
+                IOObjectRelease(iterator);
+
+                if services.is_empty() {
+                    return None;
+                }
+
+                Some(Self {
+                    services,
+                    total_memory: physical_memory(),
+                })
+            }
+        }
+
+        /// Busiest accelerator, like the NVIDIA backend across GPUs.
+        pub fn utilization(&mut self) -> Option<f32> {
+            self.services
+                .iter()
+                .filter_map(|&service| read_gpu_utilization(service))
+                .reduce(f32::max)
+        }
+
+        /// VRAM summed over discrete GPUs when there are any, otherwise the
+        /// system (unified) memory in use by the GPU.
+        pub fn memory(&mut self) -> Option<super::GpuMemory> {
+            let vram = self
+                .services
+                .iter()
+                .filter_map(|&service| read_vram(service))
+                .reduce(|a, b| (a.0 + b.0, a.1 + b.1));
+            if let Some((used, total)) = vram {
+                return Some(super::GpuMemory { used, total });
+            }
+
+            let used = self
+                .services
+                .iter()
+                .filter_map(|&service| read_performance_stat(service, "In use system memory"))
+                .max()?;
+            Some(super::GpuMemory {
+                used: used.max(0) as u64,
+                total: self.total_memory?,
+            })
+        }
+    }
+
+    impl Drop for GpuDevice {
+        fn drop(&mut self) {
+            for &service in &self.services {
+                unsafe {
+                    IOObjectRelease(service);
+                }
+            }
+        }
+    }
+
+    unsafe impl Send for GpuDevice {}
+}
+
+// ============================================================================
+// Linux Implementation (NVIDIA via NVML)
+// ============================================================================
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::sync::{Mutex, Once};
+    use std::time::{Duration, Instant};
+
+    use nvml_wrapper::bitmasks::device::ThrottleReasons;
+    use nvml_wrapper::enum_wrappers::device::TemperatureSensor;
+    use nvml_wrapper::enums::device::UsedGpuMemory;
+    use nvml_wrapper::Nvml;
+    use tracing::{info, warn};
+
+    const SMI_FIELDS: &str = "utilization.gpu,memory.used,memory.total,temperature.gpu";
+    const MIB: u64 = 1024 * 1024;
+
+    /// Set once a device has fallen back to `nvidia-smi`.
+    static USING_SMI: AtomicBool = AtomicBool::new(false);
+    /// Every sampler opens its own device; the choice is logged once.
+    static LOGGED: Once = Once::new();
+    /// The last `nvidia-smi` run and when it finished, shared by the samplers.
+    static SMI_READING: Mutex<Option<(Instant, Option<Vec<SmiGpu>>)>> = Mutex::new(None);
+    /// Below the shortest tick, so the samplers of one tick share a run.
+    const SMI_MAX_AGE: Duration = Duration::from_millis(200);
+
+    pub fn backend() -> &'static str {
+        if USING_SMI.load(Relaxed) {
+            "nvidia-smi"
+        } else {
+            "NVML"
+        }
+    }
+
+    pub struct GpuDevice {
+        backend: Backend,
+    }
+
+    enum Backend {
+        Nvml {
+            nvml: Box<Nvml>,
+            device_count: u32,
+        },
+        /// Runs `nvidia-smi` once per tick for all samplers
+        Smi,
+    }
+
+    /// One GPU's line of `nvidia-smi` output; fields it reports as "[N/A]"
+    /// or "[Not Supported]" are `None`.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct SmiGpu {
+        pub utilization: Option<f32>,
+        pub memory: Option<super::GpuMemory>,
+        pub celsius: Option<u32>,
+    }
+
+    impl GpuDevice {
+        /// Opens NVIDIA GPUs via NVML, or via `nvidia-smi` when NVML fails to
+        /// initialize. Returns None without an NVIDIA driver or GPU.
+        pub fn new() -> Option<Self> {
+            let error = match Nvml::init() {
+                Ok(nvml) => {
+                    let device_count = nvml.device_count().ok()?;
+                    if device_count == 0 {
+                        return None;
+                    }
+                    LOGGED.call_once(|| info!("GPU backend: NVML, {device_count} GPU(s)"));
+                    return Some(Self {
+                        backend: Backend::Nvml {
+                            nvml: Box::new(nvml),
+                            device_count,
+                        },
+                    });
+                }
+                Err(e) => e,
+            };
+
+            // Fails quickly where nvidia-smi isn't installed
+            let gpus = shared_smi()?;
+            if gpus.is_empty() {
+                return None;
+            }
+            USING_SMI.store(true, Relaxed);
+            LOGGED.call_once(|| {
+                warn!(
+                    "NVML failed to initialize ({error}); GPU backend: nvidia-smi, {} GPU(s)",
+                    gpus.len()
+                )
+            });
+            Some(Self {
+                backend: Backend::Smi,
+            })
+        }
+
+        /// Samples current GPU utilization percentage (max across all NVIDIA GPUs).
+        pub fn utilization(&mut self) -> Option<f32> {
+            let (nvml, device_count) = match &self.backend {
+                Backend::Nvml { nvml, device_count } => (nvml, *device_count),
+                Backend::Smi => {
+                    return shared_smi()?
+                        .iter()
+                        .filter_map(|gpu| gpu.utilization)
+                        .reduce(f32::max)
+                }
+            };
+            (0..device_count)
+                .filter_map(|i| {
+                    nvml.device_by_index(i)
+                        .ok()
+                        .and_then(|d| d.utilization_rates().ok())
+                        .map(|r| r.gpu as f32)
+                })
+                .reduce(f32::max)
+        }
+
+        /// Samples VRAM usage summed across all NVIDIA GPUs.
+        pub fn memory(&mut self) -> Option<super::GpuMemory> {
+            let memory: Vec<_> = match &self.backend {
+                Backend::Nvml { nvml, device_count } => (0..*device_count)
+                    .filter_map(|i| {
+                        let info = nvml.device_by_index(i).ok()?.memory_info().ok()?;
+                        Some(super::GpuMemory {
+                            used: info.used,
+                            total: info.total,
+                        })
+                    })
+                    .collect(),
+                Backend::Smi => shared_smi()?.iter().filter_map(|gpu| gpu.memory).collect(),
+            };
+            memory.into_iter().reduce(|a, b| super::GpuMemory {
+                used: a.used + b.used,
+                total: a.total + b.total,
+            })
+        }
+
+        /// Hottest GPU core temperature in °C, and whether any GPU is
+        /// currently thermally throttled. `nvidia-smi` readings are never
+        /// flagged as throttled.
+        pub fn temperature(&mut self) -> Option<(u32, bool)> {
+            let (nvml, device_count) = match &self.backend {
+                Backend::Nvml { nvml, device_count } => (nvml, *device_count),
+                Backend::Smi => {
+                    return shared_smi()?
+                        .iter()
+                        .filter_map(|gpu| gpu.celsius)
+                        .max()
+                        .map(|celsius| (celsius, false))
+                }
+            };
+            let thermal =
+                ThrottleReasons::HW_THERMAL_SLOWDOWN | ThrottleReasons::SW_THERMAL_SLOWDOWN;
+            (0..device_count)
+                .filter_map(|i| {
+                    let device = nvml.device_by_index(i).ok()?;
+                    let celsius = device.temperature(TemperatureSensor::Gpu).ok()?;
+                    let throttling = device
+                        .current_throttle_reasons()
+                        .is_ok_and(|reasons| reasons.intersects(thermal));
+                    Some((celsius, throttling))
+                })
+                .reduce(|a, b| (a.0.max(b.0), a.1 || b.1))
+        }
+    }
+
+    impl GpuDevice {
+        /// Busiest video encoder (NVENC) and decoder (NVDEC) in percent, each
+        /// the highest across GPUs. NVML only; GPUs without the engines are
+        /// skipped.
+        pub fn codec(&mut self) -> Option<(f32, f32)> {
+            let Backend::Nvml { nvml, device_count } = &self.backend else {
+                return None;
+            };
+            (0..*device_count)
+                .filter_map(|i| {
+                    let device = nvml.device_by_index(i).ok()?;
+                    let encoder = device.encoder_utilization().ok()?.utilization;
+                    let decoder = device.decoder_utilization().ok()?.utilization;
+                    Some((encoder as f32, decoder as f32))
+                })
+                .reduce(|a, b| (a.0.max(b.0), a.1.max(b.1)))
+        }
+
+        /// Processes with a compute or graphics context on any GPU, with the
+        /// GPU memory they use summed over GPUs where NVML reports it. NVML only.
+        pub fn processes(&mut self) -> Option<Vec<(u32, Option<u64>)>> {
+            let Backend::Nvml { nvml, device_count } = &self.backend else {
+                return None;
+            };
+            let mut processes: Vec<(u32, Option<u64>)> = Vec::new();
+            for i in 0..*device_count {
+                let Ok(device) = nvml.device_by_index(i) else {
+                    continue;
+                };
+                let running = [
+                    device.running_compute_processes(),
+                    device.running_graphics_processes(),
+                ];
+                for info in running.into_iter().flatten().flatten() {
+                    let memory = match info.used_gpu_memory {
+                        UsedGpuMemory::Used(bytes) => Some(bytes),
+                        UsedGpuMemory::Unavailable => None,
+                    };
+                    match processes.iter_mut().find(|(pid, _)| *pid == info.pid) {
+                        Some((_, total)) => {
+                            *total = match (*total, memory) {
+                                (Some(a), Some(b)) => Some(a + b),
+                                (a, b) => a.or(b),
+                            }
+                        }
+                        None => processes.push((info.pid, memory)),
+                    }
+                }
+            }
+            Some(processes)
+        }
+    }
+
+    unsafe impl Send for GpuDevice {}
+
+    /// [`query_smi`], reusing a run from the last [`SMI_MAX_AGE`]: the GPU,
+    /// memory and temperature samplers each read it every tick, and each run
+    /// can take hundreds of milliseconds.
+    fn shared_smi() -> Option<Vec<SmiGpu>> {
+        let mut reading = SMI_READING.lock().unwrap_or_else(|e| e.into_inner());
+        match reading.as_ref() {
+            Some((at, gpus)) if at.elapsed() < SMI_MAX_AGE => gpus.clone(),
+            _ => {
+                let gpus = query_smi();
+                *reading = Some((Instant::now(), gpus.clone()));
+                gpus
+            }
+        }
+    }
+
+    /// Every GPU `nvidia-smi` lists, or `None` if it isn't installed or fails.
+    fn query_smi() -> Option<Vec<SmiGpu>> {
+        let output = std::process::Command::new("nvidia-smi")
+            .arg(format!("--query-gpu={SMI_FIELDS}"))
+            .arg("--format=csv,noheader,nounits")
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| parse_smi_output(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Parses `--query-gpu` output for [`SMI_FIELDS`], one GPU per line:
+    /// "45, 1024, 8192, 61", with memory in MiB.
+    pub fn parse_smi_output(output: &str) -> Vec<SmiGpu> {
+        output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+                let field = |i: usize| fields.get(i).and_then(|value| value.parse::<f64>().ok());
+                let mib = |i: usize| field(i).map(|value| (value * MIB as f64) as u64);
+                SmiGpu {
+                    utilization: field(0).map(|value| value as f32),
+                    memory: mib(1)
+                        .zip(mib(2))
+                        .map(|(used, total)| super::GpuMemory { used, total }),
+                    celsius: field(3).map(|value| value as u32),
+                }
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// Re-export platform-specific implementation
+// ============================================================================
+
+#[cfg(target_os = "macos")]
+use macos::GpuDevice;
+
+#[cfg(target_os = "linux")]
+use linux::GpuDevice;
+#[cfg(target_os = "linux")]
+pub use linux::{parse_smi_output, SmiGpu};
+
+// ============================================================================
+// Samplers
+// ============================================================================
+
+/// Tracks a GPU that stopped answering: when to try opening it again, and
+/// whether it has been gone long enough to hide.
+#[derive(Debug, Default)]
+pub struct Recovery {
+    failed_reads: u32,
+    /// When the device was declared lost
+    lost_at: Option<Instant>,
+    /// Failed re-initializations since then
+    attempts: u32,
+    last_attempt: Option<Instant>,
+}
+
+impl Recovery {
+    /// Records a read. True when this failure makes the device lost, i.e. it
+    /// should be dropped and re-initialized.
+    pub fn record_read(&mut self, ok: bool, now: Instant) -> bool {
+        if ok {
+            self.failed_reads = 0;
+            return false;
+        }
+        self.failed_reads += 1;
+        if self.failed_reads < LOST_AFTER_READS || self.lost_at.is_some() {
+            return false;
+        }
+        self.lost_at = Some(now);
+        true
+    }
+
+    pub fn reinit_due(&self, now: Instant) -> bool {
+        self.last_attempt
+            .is_none_or(|at| now.saturating_duration_since(at) >= reinit_backoff(self.attempts))
+    }
+
+    pub fn reinit_failed(&mut self, now: Instant) {
+        self.attempts += 1;
+        self.last_attempt = Some(now);
+    }
+
+    pub fn recovered(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn is_hidden(&self, now: Instant) -> bool {
+        self.lost_at
+            .is_some_and(|at| now.saturating_duration_since(at) >= HIDE_AFTER)
+    }
+}
+
+/// Wait before the next re-initialization after `attempts` failed ones.
+pub fn reinit_backoff(attempts: u32) -> Duration {
+    match attempts {
+        0 => Duration::ZERO,
+        n => FIRST_REINIT_BACKOFF
+            .saturating_mul(1 << (n - 1).min(16))
+            .min(MAX_REINIT_BACKOFF),
+    }
+}
+
+/// GPU utilization percentage. GPU reads are comparatively expensive, so the
+/// device is only queried every other tick.
+///
+/// On Linux a GPU whose reads keep failing (eGPU unplugged, driver restarted)
+/// is dropped and re-opened with backoff; it reads as 0% for the first
+/// [`HIDE_AFTER`] and unavailable after that, with [`is_gone`] set.
+pub struct GpuSampler {
+    /// `None` while a lost GPU is being re-initialized
+    device: Option<GpuDevice>,
+    throttle: Throttle,
+    last: MetricValue,
+    #[cfg(target_os = "linux")]
+    recovery: Recovery,
+}
+
+impl GpuSampler {
+    /// Returns None when no supported GPU is found.
+    pub fn new() -> Option<Self> {
+        Some(Self {
+            device: Some(GpuDevice::new()?),
+            throttle: Throttle::every(Duration::from_secs(3)),
+            last: MetricValue::Unavailable,
+            #[cfg(target_os = "linux")]
+            recovery: Recovery::default(),
+        })
+    }
+
+    #[cfg(target_os = "linux")]
+    fn read(&mut self) -> MetricValue {
+        use tracing::{info, warn};
+
+        let now = Instant::now();
+        if self.device.is_none() && self.recovery.reinit_due(now) {
+            match GpuDevice::new() {
+                Some(device) => {
+                    info!("GPU is back");
+                    self.device = Some(device);
+                    self.recovery.recovered();
+                }
+                None => self.recovery.reinit_failed(now),
+            }
+        }
+
+        let utilization = self.device.as_mut().and_then(GpuDevice::utilization);
+        if self.device.is_some() && self.recovery.record_read(utilization.is_some(), now) {
+            warn!("GPU stopped answering; re-initializing");
+            self.device = None;
+        }
+        let hidden = self.recovery.is_hidden(now);
+        if GONE.swap(hidden, Relaxed) != hidden && hidden {
+            warn!("GPU still gone; hiding its segment");
+        }
+        match utilization {
+            Some(percent) => MetricValue::Percent(percent),
+            None if hidden => MetricValue::Unavailable,
+            None => MetricValue::Percent(0.0),
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn read(&mut self) -> MetricValue {
+        let utilization = self.device.as_mut().and_then(GpuDevice::utilization);
+        MetricValue::Percent(utilization.unwrap_or(0.0))
+    }
+}
+
+impl Sampler for GpuSampler {
+    fn name(&self) -> &'static str {
+        name::GPU
+    }
+
+    fn sample(&mut self) -> MetricValue {
+        if self.throttle.due() {
+            self.last = self.read();
+        }
+        self.last
+    }
+
+    fn reset(&mut self) {
+        self.throttle.reset();
+    }
+}
+
+/// GPU memory in use, in bytes. Holds its own device handle so it can be
+/// enabled independently of utilization.
+pub struct GpuMemorySampler {
+    device: GpuDevice,
+    throttle: Throttle,
+    last: MetricValue,
+}
+
+impl GpuMemorySampler {
+    pub fn new() -> Option<Self> {
+        Some(Self {
+            device: GpuDevice::new()?,
+            throttle: Throttle::every(Duration::from_secs(3)),
+            last: MetricValue::Unavailable,
+        })
+    }
+}
+
+impl Sampler for GpuMemorySampler {
+    fn name(&self) -> &'static str {
+        name::GPU_MEMORY
+    }
+
+    fn sample(&mut self) -> MetricValue {
+        if self.throttle.due() {
+            self.last = self
+                .device
+                .memory()
+                .map_or(MetricValue::Unavailable, |m| MetricValue::Bytes(m.used));
+        }
+        self.last
+    }
+
+    fn reset(&mut self) {
+        self.throttle.reset();
+    }
+}
+
+/// Hottest NVIDIA GPU temperature, flagged while the GPU is thermally throttled.
+#[cfg(target_os = "linux")]
+pub struct GpuTemperatureSampler {
+    device: GpuDevice,
+    throttle: Throttle,
+    last: MetricValue,
+}
+
+#[cfg(target_os = "linux")]
+impl GpuTemperatureSampler {
+    pub fn new() -> Option<Self> {
+        let mut device = GpuDevice::new()?;
+        // Some devices (e.g. passively reporting datacenter GPUs) expose no sensor
+        device.temperature()?;
+        Some(Self {
+            device,
+            throttle: Throttle::every(Duration::from_secs(3)),
+            last: MetricValue::Unavailable,
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Sampler for GpuTemperatureSampler {
+    fn name(&self) -> &'static str {
+        name::GPU_TEMPERATURE
+    }
+
+    fn sample(&mut self) -> MetricValue {
+        if self.throttle.due() {
+            self.last = self.device.temperature().map_or(
+                MetricValue::Unavailable,
+                |(celsius, throttling)| MetricValue::Temperature {
+                    celsius: celsius as f32,
+                    throttling,
+                },
+            );
+        }
+        self.last
+    }
+
+    fn reset(&mut self) {
+        self.throttle.reset();
+    }
+}
+
+/// NVENC and NVDEC utilization, for streaming and transcoding; the GPU
+/// segment's utilization doesn't include them.
+#[cfg(target_os = "linux")]
+pub struct GpuCodecSampler {
+    device: GpuDevice,
+    throttle: Throttle,
+    last: MetricValue,
+}
+
+#[cfg(target_os = "linux")]
+impl GpuCodecSampler {
+    pub fn new() -> Option<Self> {
+        let mut device = GpuDevice::new()?;
+        // Not on nvidia-smi, nor on GPUs without video engines
+        device.codec()?;
+        Some(Self {
+            device,
+            throttle: Throttle::every(Duration::from_secs(3)),
+            last: MetricValue::Unavailable,
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Sampler for GpuCodecSampler {
+    fn name(&self) -> &'static str {
+        name::GPU_CODEC
+    }
+
+    fn sample(&mut self) -> MetricValue {
+        if self.throttle.due() {
+            self.last = self
+                .device
+                .codec()
+                .map_or(MetricValue::Unavailable, |(encoder, decoder)| {
+                    MetricValue::Codec { encoder, decoder }
+                });
+        }
+        self.last
+    }
+
+    fn reset(&mut self) {
+        self.throttle.reset();
+    }
+}
+
+/// Reads which processes use the GPU, for the GPU processes submenu.
+#[cfg(target_os = "linux")]
+pub struct GpuProcessReader {
+    device: GpuDevice,
+}
+
+#[cfg(target_os = "linux")]
+impl GpuProcessReader {
+    pub fn new() -> Option<Self> {
+        Some(Self {
+            device: GpuDevice::new()?,
+        })
+    }
+
+    /// PIDs with the GPU memory they use, where known.
+    pub fn read(&mut self) -> Vec<(u32, Option<u64>)> {
+        self.device.processes().unwrap_or_default()
+    }
+}
@@ -0,0 +1,41 @@
+//! System metrics for Better Resource Monitor
+//!
+//! CPU, memory, GPU and network samplers behind one [`Sampler`] trait, with a
+//! [`SamplerRegistry`] that refreshes only the metrics a frontend needs each
+//! tick. It has no Tauri dependency, so a CLI or another frontend can drive
+//! the same samplers as the tray app:
+//!
+//! ```no_run
+//! use better_resource_monitor_metrics::{name, SamplerRegistry};
+//!
+//! let mut samplers = SamplerRegistry::with_default_samplers();
+//! samplers.sample(|metric| metric == name::CPU);
+//! println!("{:?}", samplers.get(name::CPU).percent());
+//! ```
+//!
+//! Settings the samplers depend on are applied with [`memory::configure`] and
+//! [`cgroup::configure`].
+
+pub mod cgroup;
+pub mod cpu;
+#[cfg(all(target_os = "macos", feature = "ioreport"))]
+pub mod energy;
+pub mod gpu;
+pub mod memory;
+pub mod network;
+pub mod sampler;
+pub mod thermal;
+
+pub use sampler::{name, MetricValue, Sampler, SamplerRegistry, Throttle};
+
+#[cfg(target_os = "macos")]
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,590 @@
+//! Memory samplers: percent used, bytes used, the breakdown by class and
+//! macOS memory pressure
+
+use std::sync::atomic::{AtomicU8, Ordering::Relaxed};
+use std::time::Duration;
+
+use better_resource_monitor_types::{MemoryBreakdown, MemoryPressure};
+use sysinfo::System;
+
+use crate::cgroup;
+use crate::sampler::{name, MetricValue, Sampler, Throttle};
+
+/// How memory in use is worked out, for the memory segment and alerts.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MemFormula {
+    /// The OS's used figure. On Linux sysinfo already takes this as total
+    /// minus `MemAvailable`; on macOS it is active, wired, compressed and
+    /// speculative pages, which counts some reclaimable file cache.
+    Used,
+    /// Total minus available memory: what could be allocated without
+    /// swapping, so page cache and purgeable memory count as free.
+    Available,
+}
+
+impl MemFormula {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MemFormula::Used => "used",
+            MemFormula::Available => "available",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "used" => Some(MemFormula::Used),
+            "available" => Some(MemFormula::Available),
+            _ => None,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            v if v == MemFormula::Available as u8 => MemFormula::Available,
+            _ => MemFormula::Used,
+        }
+    }
+}
+
+/// Reclaimable memory that `mem_reclaimable` can count as free, for caches
+/// the kernel hands back under pressure but still reports as in use.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MemClass {
+    /// The ZFS ARC above its minimum size, which Linux counts as used even
+    /// with the available formula
+    ZfsArc,
+    /// File-backed and purgeable pages, Activity Monitor's "Cached Files"
+    /// (macOS; Linux already leaves the page cache out of both formulas)
+    FileCache,
+}
+
+impl MemClass {
+    pub const ALL: [MemClass; 2] = [MemClass::ZfsArc, MemClass::FileCache];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MemClass::ZfsArc => "zfs_arc",
+            MemClass::FileCache => "file_cache",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|class| class.as_str() == value)
+    }
+}
+
+/// The `mem_formula` setting
+static FORMULA: AtomicU8 = AtomicU8::new(MemFormula::Used as u8);
+/// The `mem_reclaimable` classes, a bit per [`MemClass`]
+static RECLAIMABLE: AtomicU8 = AtomicU8::new(0);
+
+/// Applies the memory accounting settings: how used memory is worked out and
+/// which reclaimable classes count as free.
+pub fn configure(formula: MemFormula, reclaimable: &[MemClass]) {
+    FORMULA.store(formula as u8, Relaxed);
+    RECLAIMABLE.store(
+        reclaimable
+            .iter()
+            .fold(0, |bits, class| bits | 1 << *class as u8),
+        Relaxed,
+    );
+}
+
+/// "512 MB" or "3.1 GB".
+pub fn format_memory(bytes: u64) -> String {
+    let mb = bytes as f64 / 1_000_000.0;
+    if mb >= 999.5 {
+        format!("{:.1} GB", mb / 1_000.0)
+    } else {
+        format!("{mb:.0} MB")
+    }
+}
+
+/// Memory in use in bytes by the configured formula, less the reclaimable
+/// classes counted as free, from a refreshed `sys`.
+pub fn used(sys: &System) -> u64 {
+    let classes = RECLAIMABLE.load(Relaxed);
+    let reclaimable = MemClass::ALL
+        .into_iter()
+        .filter(|class| classes & 1 << *class as u8 != 0)
+        .map(reclaimable_bytes)
+        .sum();
+    used_bytes(
+        MemFormula::from_u8(FORMULA.load(Relaxed)),
+        sys.total_memory(),
+        sys.used_memory(),
+        sys.available_memory(),
+        reclaimable,
+    )
+}
+
+/// Memory in use and the total it's measured against: the cgroup's usage and
+/// limit in cgroup mode, otherwise [`used`] and host RAM.
+pub fn used_and_total(sys: &System) -> (u64, u64) {
+    let total = sys.total_memory();
+    cgroup::memory(total).unwrap_or_else(|| (used(sys), total))
+}
+
+pub fn used_bytes(
+    formula: MemFormula,
+    total: u64,
+    used: u64,
+    available: u64,
+    reclaimable: u64,
+) -> u64 {
+    let in_use = match formula {
+        MemFormula::Used => used.min(total),
+        MemFormula::Available => total.saturating_sub(available),
+    };
+    in_use.saturating_sub(reclaimable)
+}
+
+/// Bytes of `class` right now; 0 where the platform has no source for it or
+/// it isn't there (no ZFS module loaded).
+fn reclaimable_bytes(class: MemClass) -> u64 {
+    let bytes = match class {
+        MemClass::ZfsArc => zfs_arc_shrinkable(),
+        MemClass::FileCache => file_cache(),
+    };
+    bytes.unwrap_or(0)
+}
+
+#[cfg(target_os = "linux")]
+fn zfs_arc_shrinkable() -> Option<u64> {
+    parse_arcstats(&std::fs::read_to_string("/proc/spl/kstat/zfs/arcstats").ok()?)
+}
+
+/// OpenZFS on macOS publishes the same kstats through sysctl.
+#[cfg(target_os = "macos")]
+fn zfs_arc_shrinkable() -> Option<u64> {
+    let size = mach::sysctl_u64(c"kstat.zfs.misc.arcstats.size")?;
+    let min = mach::sysctl_u64(c"kstat.zfs.misc.arcstats.c_min").unwrap_or(0);
+    Some(size.saturating_sub(min))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn zfs_arc_shrinkable() -> Option<u64> {
+    None
+}
+
+#[cfg(target_os = "macos")]
+fn file_cache() -> Option<u64> {
+    mach::cached_files(mach::host_port())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn file_cache() -> Option<u64> {
+    None
+}
+
+/// The ARC's `size` above `c_min` from the `arcstats` kstat, whose rows are
+/// "name type data" (`size                            4    4294967296`).
+pub fn parse_arcstats(text: &str) -> Option<u64> {
+    let (mut size, mut min) = (None, 0);
+    for line in text.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(name), Some(_), Some(data)) = (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        match name {
+            "size" => size = data.parse::<u64>().ok(),
+            "c_min" => min = data.parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+    Some(size?.saturating_sub(min))
+}
+
+/// Used memory as a percentage of total RAM, by [`MemFormula`], or of the
+/// cgroup's limit. Memory moves slowly, so it is only refreshed every other
+/// tick.
+pub struct MemorySampler {
+    sys: System,
+    throttle: Throttle,
+    /// Bytes in use and total
+    used: (u64, u64),
+}
+
+impl Default for MemorySampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemorySampler {
+    pub fn new() -> Self {
+        Self {
+            sys: System::new(),
+            throttle: Throttle::every(Duration::from_secs(3)),
+            used: (0, 0),
+        }
+    }
+}
+
+impl Sampler for MemorySampler {
+    fn name(&self) -> &'static str {
+        name::MEMORY
+    }
+
+    fn sample(&mut self) -> MetricValue {
+        if self.throttle.due() {
+            self.sys.refresh_memory();
+            self.used = used_and_total(&self.sys);
+        }
+
+        let (used, total) = self.used;
+        if total > 0 {
+            MetricValue::Percent((used as f64 / total as f64 * 100.0) as f32)
+        } else {
+            MetricValue::Percent(0.0)
+        }
+    }
+
+    fn reset(&mut self) {
+        self.throttle.reset();
+    }
+}
+
+/// Used memory in bytes, for the absolute memory display.
+pub struct MemoryUsedSampler {
+    sys: System,
+    throttle: Throttle,
+    used: u64,
+}
+
+impl Default for MemoryUsedSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryUsedSampler {
+    pub fn new() -> Self {
+        Self {
+            sys: System::new(),
+            throttle: Throttle::every(Duration::from_secs(3)),
+            used: 0,
+        }
+    }
+}
+
+impl Sampler for MemoryUsedSampler {
+    fn name(&self) -> &'static str {
+        name::MEMORY_USED
+    }
+
+    fn sample(&mut self) -> MetricValue {
+        if self.throttle.due() {
+            self.sys.refresh_memory();
+            self.used = used_and_total(&self.sys).0;
+        }
+        MetricValue::Bytes(self.used)
+    }
+
+    fn reset(&mut self) {
+        self.throttle.reset();
+    }
+}
+
+/// Apps, wired, cached and free memory for the breakdown memory display
+/// (Linux, macOS). Refreshed every other tick like the other memory samplers.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub struct MemoryBreakdownSampler {
+    throttle: Throttle,
+    last: MetricValue,
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+impl MemoryBreakdownSampler {
+    pub fn new() -> Option<Self> {
+        read_breakdown()?;
+        Some(Self {
+            throttle: Throttle::every(Duration::from_secs(3)),
+            last: MetricValue::Unavailable,
+        })
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+impl Sampler for MemoryBreakdownSampler {
+    fn name(&self) -> &'static str {
+        name::MEMORY_BREAKDOWN
+    }
+
+    fn sample(&mut self) -> MetricValue {
+        if self.throttle.due() {
+            self.last = read_breakdown().map_or(MetricValue::Unavailable, MetricValue::Breakdown);
+        }
+        self.last
+    }
+
+    fn reset(&mut self) {
+        self.throttle.reset();
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_breakdown() -> Option<MemoryBreakdown> {
+    parse_meminfo(&std::fs::read_to_string("/proc/meminfo").ok()?)
+}
+
+#[cfg(target_os = "macos")]
+fn read_breakdown() -> Option<MemoryBreakdown> {
+    mach::breakdown(mach::host_port())
+}
+
+/// Tooltip line for the classes, e.g. "Memory: Apps 8.1 GB · Wired 2.3 GB ·
+/// Cached 4.0 GB · Free 1.6 GB".
+pub fn breakdown_line(classes: &MemoryBreakdown) -> String {
+    format!(
+        "Memory: Apps {} · Wired {} · Cached {} · Free {}",
+        format_memory(classes.apps),
+        format_memory(classes.wired),
+        format_memory(classes.cached),
+        format_memory(classes.free)
+    )
+}
+
+/// Splits `/proc/meminfo` into classes: free is `MemFree`; cached is page
+/// cache, buffers and reclaimable slab less shared memory, which can't be
+/// dropped; wired is unreclaimable slab, kernel stacks and page tables; apps
+/// are the rest.
+pub fn parse_meminfo(text: &str) -> Option<MemoryBreakdown> {
+    let field = |name: &str| {
+        text.lines().find_map(|line| {
+            let rest = line.strip_prefix(name)?.strip_prefix(':')?;
+            let kib: u64 = rest.split_whitespace().next()?.parse().ok()?;
+            Some(kib * 1024)
+        })
+    };
+    let optional = |name: &str| field(name).unwrap_or(0);
+    let total = field("MemTotal")?;
+    let free = field("MemFree")?.min(total);
+    let cached = (field("Cached")? + optional("Buffers") + optional("SReclaimable"))
+        .saturating_sub(optional("Shmem"))
+        .min(total - free);
+    let wired = (optional("SUnreclaim") + optional("KernelStack") + optional("PageTables"))
+        .min(total - free - cached);
+    Some(MemoryBreakdown {
+        apps: total - free - cached - wired,
+        wired,
+        cached,
+        free,
+    })
+}
+
+/// Memory pressure level and Activity Monitor style usage (macOS). Raw
+/// used/total counts file cache and compressed pages, so it sits near 100%
+/// on a healthy Mac; the kernel's pressure level says whether that matters.
+#[cfg(target_os = "macos")]
+pub struct MemoryPressureSampler {
+    sys: System,
+    /// Send right to the host port, taken once since every
+    /// `mach_host_self()` call adds a reference
+    host: u32,
+    throttle: Throttle,
+    last: MetricValue,
+}
+
+#[cfg(target_os = "macos")]
+impl Default for MemoryPressureSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl MemoryPressureSampler {
+    pub fn new() -> Self {
+        Self {
+            sys: System::new(),
+            host: mach::host_port(),
+            throttle: Throttle::every(Duration::from_secs(3)),
+            last: MetricValue::Unavailable,
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Sampler for MemoryPressureSampler {
+    fn name(&self) -> &'static str {
+        name::MEMORY_PRESSURE
+    }
+
+    fn sample(&mut self) -> MetricValue {
+        if self.throttle.due() {
+            self.sys.refresh_memory();
+            let total = self.sys.total_memory();
+            self.last = match (mach::memory_used(self.host), mach::pressure_level()) {
+                (Some(used), Some(level)) if total > 0 => MetricValue::MemoryPressure {
+                    percent: (used as f64 / total as f64 * 100.0) as f32,
+                    level,
+                },
+                _ => MetricValue::Unavailable,
+            };
+        }
+        self.last
+    }
+
+    fn reset(&mut self) {
+        self.throttle.reset();
+    }
+}
+
+/// Maps `kern.memorystatus_vm_pressure_level` (1 normal, 2 warning, 4 critical).
+pub fn pressure_from_level(level: i32) -> Option<MemoryPressure> {
+    match level {
+        1 => Some(MemoryPressure::Normal),
+        2 => Some(MemoryPressure::Warning),
+        4 => Some(MemoryPressure::Critical),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod mach {
+    use std::ffi::{c_char, c_int, c_void, CStr};
+    use std::mem;
+    use std::sync::OnceLock;
+
+    use better_resource_monitor_types::{MemoryBreakdown, MemoryPressure};
+
+    const HOST_VM_INFO64: c_int = 4;
+    const KERN_SUCCESS: c_int = 0;
+
+    /// `vm_statistics64` from `<mach/vm_statistics.h>`
+    #[repr(C)]
+    #[derive(Default)]
+    struct VmStatistics64 {
+        free_count: u32,
+        active_count: u32,
+        inactive_count: u32,
+        wire_count: u32,
+        zero_fill_count: u64,
+        reactivations: u64,
+        pageins: u64,
+        pageouts: u64,
+        faults: u64,
+        cow_faults: u64,
+        lookups: u64,
+        hits: u64,
+        purges: u64,
+        purgeable_count: u32,
+        speculative_count: u32,
+        decompressions: u64,
+        compressions: u64,
+        swapins: u64,
+        swapouts: u64,
+        compressor_page_count: u32,
+        throttled_count: u32,
+        external_page_count: u32,
+        internal_page_count: u32,
+        total_uncompressed_pages_in_compressor: u64,
+    }
+
+    unsafe extern "C" {
+        fn mach_host_self() -> u32;
+        fn host_statistics64(host: u32, flavor: c_int, info: *mut c_int, count: *mut u32) -> c_int;
+        fn sysctlbyname(
+            name: *const c_char,
+            oldp: *mut c_void,
+            oldlenp: *mut usize,
+            newp: *mut c_void,
+            newlen: usize,
+        ) -> c_int;
+    }
+
+    fn sysctl_int(name: &CStr) -> Option<c_int> {
+        let mut value: c_int = 0;
+        let mut len = mem::size_of::<c_int>();
+        let status = unsafe {
+            sysctlbyname(
+                name.as_ptr(),
+                &mut value as *mut c_int as *mut c_void,
+                &mut len,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        (status == 0).then_some(value)
+    }
+
+    pub fn sysctl_u64(name: &CStr) -> Option<u64> {
+        let mut value: u64 = 0;
+        let mut len = mem::size_of::<u64>();
+        let status = unsafe {
+            sysctlbyname(
+                name.as_ptr(),
+                &mut value as *mut u64 as *mut c_void,
+                &mut len,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        (status == 0).then_some(value)
+    }
+
+    pub fn pressure_level() -> Option<MemoryPressure> {
+        super::pressure_from_level(sysctl_int(c"kern.memorystatus_vm_pressure_level")?)
+    }
+
+    /// Taken once and shared, as every `mach_host_self()` call adds a
+    /// reference.
+    pub fn host_port() -> u32 {
+        static HOST: OnceLock<u32> = OnceLock::new();
+        *HOST.get_or_init(|| unsafe { mach_host_self() })
+    }
+
+    /// App memory (anonymous pages that aren't purgeable), wired and
+    /// compressed memory in bytes.
+    pub fn memory_used(host: u32) -> Option<u64> {
+        let (stats, page_size) = vm_statistics(host)?;
+        let app = stats
+            .internal_page_count
+            .saturating_sub(stats.purgeable_count) as u64;
+        let pages = app + stats.wire_count as u64 + stats.compressor_page_count as u64;
+        Some(pages * page_size)
+    }
+
+    /// File-backed and purgeable pages in bytes, Activity Monitor's "Cached
+    /// Files".
+    pub fn cached_files(host: u32) -> Option<u64> {
+        let (stats, page_size) = vm_statistics(host)?;
+        let pages = stats.external_page_count as u64 + stats.purgeable_count as u64;
+        Some(pages * page_size)
+    }
+
+    /// Activity Monitor's classes: app memory, wired plus compressed, cached
+    /// files, and free and speculative pages.
+    pub fn breakdown(host: u32) -> Option<MemoryBreakdown> {
+        let (stats, page_size) = vm_statistics(host)?;
+        let app = stats
+            .internal_page_count
+            .saturating_sub(stats.purgeable_count) as u64;
+        let wired = stats.wire_count as u64 + stats.compressor_page_count as u64;
+        let cached = stats.external_page_count as u64 + stats.purgeable_count as u64;
+        let free = stats.free_count as u64 + stats.speculative_count as u64;
+        Some(MemoryBreakdown {
+            apps: app * page_size,
+            wired: wired * page_size,
+            cached: cached * page_size,
+            free: free * page_size,
+        })
+    }
+
+    fn vm_statistics(host: u32) -> Option<(VmStatistics64, u64)> {
+        let page_size = sysctl_int(c"hw.pagesize")? as u64;
+        let mut stats = VmStatistics64::default();
+        let mut count = (mem::size_of::<VmStatistics64>() / mem::size_of::<c_int>()) as u32;
+        let status = unsafe {
+            host_statistics64(
+                host,
+                HOST_VM_INFO64,
+                &mut stats as *mut VmStatistics64 as *mut c_int,
+                &mut count,
+            )
+        };
+        (status == KERN_SUCCESS).then_some((stats, page_size))
+    }
+}
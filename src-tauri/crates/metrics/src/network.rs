@@ -0,0 +1,67 @@
+//! Network throughput sampler
+
+use std::time::Instant;
+
+use sysinfo::Networks;
+
+use crate::sampler::{name, MetricValue, Sampler};
+
+/// Download/upload rates summed across all interfaces, in bytes per second.
+pub struct NetworkSampler {
+    networks: Networks,
+    /// Totals and time of the previous sample; `None` until the first one
+    baseline: Option<(u64, u64, Instant)>,
+}
+
+impl Default for NetworkSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NetworkSampler {
+    pub fn new() -> Self {
+        Self {
+            networks: Networks::new_with_refreshed_list(),
+            baseline: None,
+        }
+    }
+}
+
+impl Sampler for NetworkSampler {
+    fn name(&self) -> &'static str {
+        name::NETWORK
+    }
+
+    fn sample(&mut self) -> MetricValue {
+        self.networks.refresh(false);
+        let (rx, tx) = sum_network_totals(&self.networks);
+        let now = Instant::now();
+
+        // The first sample only records a baseline to avoid a spike of the
+        // totals accumulated since boot
+        let (down, up) = match self.baseline {
+            Some((prev_rx, prev_tx, at)) => {
+                let dt = now.duration_since(at).as_secs_f64().max(f64::EPSILON);
+                (
+                    rx.saturating_sub(prev_rx) as f64 / dt,
+                    tx.saturating_sub(prev_tx) as f64 / dt,
+                )
+            }
+            None => (0.0, 0.0),
+        };
+        self.baseline = Some((rx, tx, now));
+
+        MetricValue::Rate { down, up }
+    }
+
+    fn reset(&mut self) {
+        self.baseline = None;
+    }
+}
+
+fn sum_network_totals(networks: &Networks) -> (u64, u64) {
+    networks.iter().fold((0, 0), |(rx, tx), (_, data)| {
+        (rx + data.total_received(), tx + data.total_transmitted())
+    })
+}
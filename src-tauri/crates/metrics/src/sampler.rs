@@ -0,0 +1,338 @@
+//! Metric sampler plugin architecture
+//!
+//! Each metric (CPU, memory, GPU, network, ...) lives in its own module and
+//! implements [`Sampler`]. The monitoring loop owns a [`SamplerRegistry`], asks it
+//! to refresh whichever metrics are needed this tick and reads the results by
+//! name, so adding a metric means registering a new sampler rather than editing
+//! `start_monitoring`.
+
+use std::time::{Duration, Instant};
+
+use better_resource_monitor_types::{CoreUsage, MemoryBreakdown, MemoryPressure};
+
+use crate::{cpu, gpu, memory, network, thermal};
+
+/// Registered sampler names.
+pub mod name {
+    pub const CPU: &str = "cpu";
+    pub const CPU_SPLIT: &str = "cpu_split";
+    pub const CPU_CORES: &str = "cpu_cores";
+    pub const LOAD_AVERAGE: &str = "load_average";
+    pub const CPU_FREQUENCY: &str = "cpu_frequency";
+    pub const MEMORY: &str = "memory";
+    pub const MEMORY_USED: &str = "memory_used";
+    pub const MEMORY_PRESSURE: &str = "memory_pressure";
+    pub const MEMORY_BREAKDOWN: &str = "memory_breakdown";
+    pub const GPU: &str = "gpu";
+    pub const GPU_MEMORY: &str = "gpu_memory";
+    pub const NETWORK: &str = "network";
+    pub const CPU_POWER: &str = "cpu_power";
+    pub const ANE_POWER: &str = "ane_power";
+    pub const GPU_TEMPERATURE: &str = "gpu_temperature";
+    pub const GPU_CODEC: &str = "gpu_codec";
+    pub const CPU_THROTTLING: &str = "cpu_throttling";
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum MetricValue {
+    /// Not sampled this tick, or not available on this machine
+    #[default]
+    Unavailable,
+    Percent(f32),
+    /// Unitless value such as a load average
+    Scalar(f32),
+    Bytes(u64),
+    Megahertz(u64),
+    /// Only produced by the macOS CPU and Neural Engine power samplers, with
+    /// the `ioreport` feature
+    Watts(f32),
+    /// Only produced by the NVML GPU temperature sampler
+    Temperature {
+        celsius: f32,
+        /// The device is slowing its clocks to cool down
+        throttling: bool,
+    },
+    /// Only produced by the macOS memory pressure sampler
+    MemoryPressure {
+        /// App, wired and compressed memory as a share of RAM, like
+        /// Activity Monitor's "Memory Used"
+        percent: f32,
+        level: MemoryPressure,
+    },
+    /// Memory by class (Linux and macOS only)
+    Breakdown(MemoryBreakdown),
+    /// User and system CPU time in percent (Linux and macOS only)
+    CpuSplit {
+        user: f32,
+        system: f32,
+    },
+    /// Usage of each core, for the heatmap CPU display
+    Cores(CoreUsage),
+    /// Video encoder and decoder utilization in percent; only produced by the
+    /// NVML codec sampler
+    Codec {
+        encoder: f32,
+        decoder: f32,
+    },
+    /// Whether the CPU is being slowed down to cool off
+    Throttling(bool),
+    /// Bytes per second in each direction
+    Rate {
+        down: f64,
+        up: f64,
+    },
+}
+
+impl MetricValue {
+    pub fn percent(self) -> Option<f32> {
+        match self {
+            MetricValue::Percent(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn scalar(self) -> Option<f32> {
+        match self {
+            MetricValue::Scalar(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn bytes(self) -> Option<u64> {
+        match self {
+            MetricValue::Bytes(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn megahertz(self) -> Option<u64> {
+        match self {
+            MetricValue::Megahertz(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn watts(self) -> Option<f32> {
+        match self {
+            MetricValue::Watts(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn temperature(self) -> Option<(f32, bool)> {
+        match self {
+            MetricValue::Temperature {
+                celsius,
+                throttling,
+            } => Some((celsius, throttling)),
+            _ => None,
+        }
+    }
+
+    pub fn memory_pressure(self) -> Option<(f32, MemoryPressure)> {
+        match self {
+            MetricValue::MemoryPressure { percent, level } => Some((percent, level)),
+            _ => None,
+        }
+    }
+
+    pub fn memory_breakdown(self) -> Option<MemoryBreakdown> {
+        match self {
+            MetricValue::Breakdown(breakdown) => Some(breakdown),
+            _ => None,
+        }
+    }
+
+    pub fn cpu_split(self) -> Option<(f32, f32)> {
+        match self {
+            MetricValue::CpuSplit { user, system } => Some((user, system)),
+            _ => None,
+        }
+    }
+
+    pub fn cores(self) -> Option<CoreUsage> {
+        match self {
+            MetricValue::Cores(cores) => Some(cores),
+            _ => None,
+        }
+    }
+
+    pub fn codec(self) -> Option<(f32, f32)> {
+        match self {
+            MetricValue::Codec { encoder, decoder } => Some((encoder, decoder)),
+            _ => None,
+        }
+    }
+
+    pub fn throttling(self) -> Option<bool> {
+        match self {
+            MetricValue::Throttling(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn rate(self) -> Option<(f64, f64)> {
+        match self {
+            MetricValue::Rate { down, up } => Some((down, up)),
+            _ => None,
+        }
+    }
+}
+
+pub trait Sampler: Send {
+    fn name(&self) -> &'static str;
+
+    /// Reads the current value. Called once per tick while the metric is needed.
+    fn sample(&mut self) -> MetricValue;
+
+    /// Called when the metric stops being needed, so samplers that compute
+    /// deltas (e.g. network rates) start from a fresh baseline next time.
+    fn reset(&mut self) {}
+}
+
+/// Lets a sampler refresh its source at most once per `every` and reuse the
+/// previous value in between, for reads that are comparatively expensive.
+/// Time-based, so a shorter update interval doesn't make the reads more
+/// frequent.
+#[derive(Clone, Copy, Debug)]
+pub struct Throttle {
+    every: Duration,
+    last: Option<Instant>,
+}
+
+impl Throttle {
+    pub const fn every(every: Duration) -> Self {
+        Self { every, last: None }
+    }
+
+    /// True on the first call and then once `every` has passed since the
+    /// last call that returned true.
+    pub fn due(&mut self) -> bool {
+        self.due_at(Instant::now())
+    }
+
+    pub fn due_at(&mut self, now: Instant) -> bool {
+        let due = self
+            .last
+            .is_none_or(|last| now.saturating_duration_since(last) >= self.every);
+        if due {
+            self.last = Some(now);
+        }
+        due
+    }
+
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+struct Entry {
+    sampler: Box<dyn Sampler>,
+    value: MetricValue,
+    active: bool,
+}
+
+#[derive(Default)]
+pub struct SamplerRegistry {
+    entries: Vec<Entry>,
+}
+
+impl SamplerRegistry {
+    /// All samplers supported on this machine. GPU samplers are only registered
+    /// when a supported GPU is found (temperature and encoder/decoder only via
+    /// NVML), CPU power only on Apple Silicon with the `ioreport` feature.
+    pub fn with_default_samplers() -> Self {
+        let mut registry = Self::default();
+        registry.register(cpu::CpuSampler::new());
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        if let Some(sampler) = cpu::CpuSplitSampler::new() {
+            registry.register(sampler);
+        }
+        registry.register(cpu::CpuCoresSampler::new());
+        registry.register(cpu::LoadAverageSampler);
+        registry.register(cpu::CpuFrequencySampler::new());
+        registry.register(memory::MemorySampler::new());
+        registry.register(memory::MemoryUsedSampler::new());
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        if let Some(sampler) = memory::MemoryBreakdownSampler::new() {
+            registry.register(sampler);
+        }
+        #[cfg(target_os = "macos")]
+        registry.register(memory::MemoryPressureSampler::new());
+        registry.register(network::NetworkSampler::new());
+        registry.register_gpu_samplers();
+        if let Some(sampler) = thermal::CpuThrottleSampler::new() {
+            registry.register(sampler);
+        }
+        #[cfg(all(target_os = "macos", feature = "ioreport"))]
+        if let Some(sampler) = crate::energy::EnergySampler::cpu() {
+            registry.register(sampler);
+        }
+        #[cfg(all(target_os = "macos", feature = "ioreport"))]
+        if let Some(sampler) = crate::energy::EnergySampler::neural_engine() {
+            registry.register(sampler);
+        }
+        registry
+    }
+
+    /// Registers the GPU samplers if a supported GPU is found now; true if one
+    /// was. Called again by the monitoring loop to pick up a GPU that appears
+    /// later (eGPU plugged in, driver loaded).
+    pub fn register_gpu_samplers(&mut self) -> bool {
+        let Some(sampler) = gpu::GpuSampler::new() else {
+            return false;
+        };
+        self.register(sampler);
+        if let Some(sampler) = gpu::GpuMemorySampler::new() {
+            self.register(sampler);
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(sampler) = gpu::GpuTemperatureSampler::new() {
+            self.register(sampler);
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(sampler) = gpu::GpuCodecSampler::new() {
+            self.register(sampler);
+        }
+        true
+    }
+
+    /// Adds a sampler, replacing any existing one with the same name.
+    pub fn register(&mut self, sampler: impl Sampler + 'static) {
+        self.entries.retain(|e| e.sampler.name() != sampler.name());
+        self.entries.push(Entry {
+            sampler: Box::new(sampler),
+            value: MetricValue::Unavailable,
+            active: false,
+        });
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.iter().any(|e| e.sampler.name() == name)
+    }
+
+    /// Samples every metric for which `wanted` returns true. The others are
+    /// reset and read as [`MetricValue::Unavailable`] until needed again.
+    pub fn sample(&mut self, wanted: impl Fn(&str) -> bool) {
+        for entry in &mut self.entries {
+            if wanted(entry.sampler.name()) {
+                entry.value = entry.sampler.sample();
+                entry.active = true;
+            } else {
+                if entry.active {
+                    entry.sampler.reset();
+                    entry.active = false;
+                }
+                entry.value = MetricValue::Unavailable;
+            }
+        }
+    }
+
+    /// Latest value for `name`; `Unavailable` if unknown or not sampled this tick.
+    pub fn get(&self, name: &str) -> MetricValue {
+        self.entries
+            .iter()
+            .find(|e| e.sampler.name() == name)
+            .map_or(MetricValue::Unavailable, |e| e.value)
+    }
+}
@@ -0,0 +1,114 @@
+//! CPU thermal throttling detection
+//!
+//! A throttled CPU can look idle while everything is slow, so the monitoring
+//! loop turns the CPU segment the alert color while this reports throttling.
+//!
+//! - Linux: the `thermal_throttle` counters of Intel CPUs
+//!   (`/sys/devices/system/cpu/cpu*/thermal_throttle/*_throttle_count`); the CPU
+//!   counts as throttling while they keep going up
+//! - macOS: `pmset -g therm`, throttling while `CPU_Speed_Limit` is below 100
+//!   (Intel) or a thermal / performance warning level is set
+
+use std::time::Duration;
+
+use crate::sampler::{name, MetricValue, Sampler, Throttle};
+
+pub struct CpuThrottleSampler {
+    /// Counter sum of the previous read; `None` until the first one
+    #[cfg(target_os = "linux")]
+    previous: Option<u64>,
+    throttle: Throttle,
+    last: MetricValue,
+}
+
+impl CpuThrottleSampler {
+    /// `None` where the platform doesn't report throttling (e.g. AMD CPUs on Linux).
+    pub fn new() -> Option<Self> {
+        #[cfg(target_os = "linux")]
+        linux_throttle_count()?;
+        #[cfg(target_os = "macos")]
+        crate::command_stdout("pmset", &["-g", "therm"])?;
+
+        Some(Self {
+            #[cfg(target_os = "linux")]
+            previous: None,
+            // Throttling lasts seconds to minutes; `pmset` needn't run every tick
+            throttle: Throttle::every(Duration::from_secs(5)),
+            last: MetricValue::Unavailable,
+        })
+    }
+
+    #[cfg(target_os = "linux")]
+    fn read(&mut self) -> Option<bool> {
+        let count = linux_throttle_count()?;
+        let previous = self.previous.replace(count);
+        Some(previous.is_some_and(|previous| count > previous))
+    }
+
+    #[cfg(target_os = "macos")]
+    fn read(&mut self) -> Option<bool> {
+        crate::command_stdout("pmset", &["-g", "therm"]).map(|output| parse_pmset_therm(&output))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    fn read(&mut self) -> Option<bool> {
+        None
+    }
+}
+
+impl Sampler for CpuThrottleSampler {
+    fn name(&self) -> &'static str {
+        name::CPU_THROTTLING
+    }
+
+    fn sample(&mut self) -> MetricValue {
+        if self.throttle.due() {
+            self.last = self
+                .read()
+                .map_or(MetricValue::Unavailable, MetricValue::Throttling);
+        }
+        self.last
+    }
+
+    fn reset(&mut self) {
+        self.throttle.reset();
+        #[cfg(target_os = "linux")]
+        {
+            self.previous = None;
+        }
+    }
+}
+
+/// Core and package throttle events summed over all CPUs.
+#[cfg(target_os = "linux")]
+fn linux_throttle_count() -> Option<u64> {
+    let mut total = None;
+    for entry in std::fs::read_dir("/sys/devices/system/cpu").ok()?.flatten() {
+        let dir = entry.path().join("thermal_throttle");
+        for counter in ["core_throttle_count", "package_throttle_count"] {
+            if let Some(count) = std::fs::read_to_string(dir.join(counter))
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+            {
+                *total.get_or_insert(0) += count;
+            }
+        }
+    }
+    total
+}
+
+/// Whether `pmset -g therm` reports the CPU being held back, e.g.
+/// `CPU_Speed_Limit = 80` or `Thermal warning level set to 1.`
+pub fn parse_pmset_therm(output: &str) -> bool {
+    output.lines().any(|line| {
+        let line = line.trim();
+        if let Some((key, value)) = line.split_once('=') {
+            if key.trim() == "CPU_Speed_Limit" {
+                return value.trim().parse::<u32>().is_ok_and(|limit| limit < 100);
+            }
+        }
+        line.split_once("warning level set to")
+            .and_then(|(_, level)| level.trim().trim_end_matches('.').parse::<u32>().ok())
+            .is_some_and(|level| level > 0)
+    })
+}
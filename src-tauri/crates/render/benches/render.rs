@@ -0,0 +1,115 @@
+//! Render path benchmarks: `cargo bench -p better-resource-monitor-render` from `src-tauri/`.
+//!
+//! Each group runs at both sizing presets with the bundled font, so results
+//! don't depend on the fonts installed.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use rusttype::Font;
+
+use better_resource_monitor_render::{
+    self as tray_render, bench::Glyphs, fallback_font, AlertThresholds, IconType, Palette,
+    RenderParams, Sizing, TrayRenderer,
+};
+
+const PRESETS: [(&str, Sizing); 2] = [
+    ("macos", tray_render::SIZING_MACOS),
+    ("linux", tray_render::SIZING_LINUX),
+];
+
+const THRESHOLDS: AlertThresholds = AlertThresholds {
+    cpu: 90.0,
+    mem: 90.0,
+    gpu: 90.0,
+};
+
+/// One frame of the default layout with `cpu` as the only changing value.
+fn render(
+    renderer: &mut TrayRenderer,
+    font: &Font,
+    buffer: &mut Vec<u8>,
+    sizing: Sizing,
+    cpu: f32,
+) {
+    renderer.render_tray_icon_into(
+        font,
+        buffer,
+        &RenderParams {
+            cpu_usage: cpu,
+            mem_percent: 62.0,
+            gpu_usage: 78.0,
+            down_str: "1.5 MB",
+            up_str: "0.2 MB",
+            cpu_cores: 8,
+            thresholds: THRESHOLDS,
+            palette: Palette::for_theme(true),
+            ..RenderParams::new(sizing)
+        },
+    );
+}
+
+fn render_tray_icon(c: &mut Criterion) {
+    let font = fallback_font();
+    let mut group = c.benchmark_group("render_tray_icon_into");
+    for (preset, sizing) in PRESETS {
+        // Steady state: icons and glyphs cached, a new value every frame so
+        // the frame cache misses
+        let mut renderer = TrayRenderer::new();
+        let mut buffer = Vec::new();
+        let mut cpu = 0;
+        group.bench_function(format!("{preset}/warm"), |b| {
+            b.iter(|| {
+                cpu = (cpu + 1) % 100;
+                render(&mut renderer, &font, &mut buffer, sizing, cpu as f32);
+            })
+        });
+
+        // Same frame again: only the frame cache lookup and copy
+        group.bench_function(format!("{preset}/repeat"), |b| {
+            b.iter(|| render(&mut renderer, &font, &mut buffer, sizing, 45.0))
+        });
+
+        // First frame after start or a font change: icons and glyphs rasterized
+        group.bench_function(format!("{preset}/cold"), |b| {
+            b.iter_batched(
+                TrayRenderer::new,
+                |mut renderer| render(&mut renderer, &font, &mut buffer, sizing, 45.0),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn render_svg_icon(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render_svg_icon");
+    for (preset, sizing) in PRESETS {
+        for (name, icon) in [("cpu", IconType::Cpu), ("gpu", IconType::Gpu)] {
+            let svg = tray_render::icon_svg(icon);
+            group.bench_function(format!("{preset}/{name}"), |b| {
+                b.iter(|| tray_render::render_svg_icon(svg, sizing.icon_height, (255, 255, 255)))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn glyph_layout(c: &mut Criterion) {
+    let font = fallback_font();
+    let mut group = c.benchmark_group("glyph_layout");
+    for (preset, sizing) in PRESETS {
+        let mut glyphs = Glyphs::default();
+        group.bench_function(format!("{preset}/cached"), |b| {
+            b.iter(|| glyphs.layout(&font, sizing, "99% 1.5 MB"))
+        });
+        group.bench_function(format!("{preset}/rasterize"), |b| {
+            b.iter(|| {
+                glyphs.clear();
+                glyphs.layout(&font, sizing, "99% 1.5 MB")
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, render_tray_icon, render_svg_icon, glyph_layout);
+criterion_main!(benches);
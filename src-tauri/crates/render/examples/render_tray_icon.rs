@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use better_resource_monitor_render as tray_render;
+use font_kit::family_name::FamilyName;
+use font_kit::handle::Handle;
+use font_kit::properties::{Properties, Weight};
+use font_kit::source::SystemSource;
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageEncoder};
+use rusttype::Font;
+
+fn usage() -> &'static str {
+    "render_tray_icon\n\nUSAGE:\n  cargo run --manifest-path src-tauri/Cargo.toml -p better-resource-monitor-render --example render_tray_icon -- [args]\n\nARGS:\n  --out <path>                 Output PNG path (required)\n  --preset <macos|linux>       Sizing preset (default: host OS)\n  --scale <float>              Uniform scale factor (default: 1.0)\n\n  --cpu <float>                CPU percent (default: 45)\n  --load <float>               Show this load average instead of CPU percent\n  --mem <float>                Memory percent (default: 99)\n  --mem-used <string>          Memory used display instead of percent, e.g. 12.4G\n  --gpu <float>                GPU percent (default: 78)\n  --gpu-mem <string>           GPU memory display, e.g. 6.2G (default: hidden)\n  --power <string>             CPU power display, e.g. 4.3W (default: hidden)\n  --gpu-temp <string>          GPU temperature display, e.g. 72°C (default: hidden)\n  --down <string>              Download display (default: 1.5 MB)\n  --up <string>                Upload display (default: 0.2 MB)\n\n  --show-cpu <true|false>       (default: true)\n  --show-mem <true|false>       (default: true)\n  --show-gpu <true|false>       (default: true)\n  --show-net <true|false>       (default: true)\n  --layout <standard|compact|icons|stacked> (default: standard)\n  --value-style <number|bar|vertical_bar> (default: number)\n  --combine-net <true|false>   Download and upload as one segment (default: false)\n  --auto-width <true|false>    Fit segments to their values (default: false)\n  --tabular <true|false>       Equal-width digits, sized for three (default: false)\n  --order <list>               Segment order, e.g. net,cpu,mem,gpu (default: mem,cpu,gpu,net)\n  --percent-cap <99|100|per_core> (default: 99)\n  --cores <int>                Cores summed by --percent-cap per_core (default: 8)\n  --show-alerts <true|false>   (default: true)\n  --cpu-threshold <float>      CPU alert threshold (default: 90)\n  --mem-threshold <float>      Memory alert threshold (default: 90)\n  --gpu-threshold <float>      GPU alert threshold (default: 90)\n  --use-light-icons <true|false> (default: true)\n  --alert-color <#RRGGBB>      (default: #D14715)\n  --text-color <#RRGGBB>       Overrides the light/dark foreground\n  --font <family>              Font family (default: system sans-serif, else the bundled font)\n  --font-weight <100-900>      (default: 400)\n\n  --bg <transparent|#RRGGBB|#RRGGBBAA> (default: transparent)\n  --help\n"
+}
+
+#[derive(Clone, Copy)]
+enum Preset {
+    Macos,
+    Linux,
+}
+
+fn default_preset() -> Preset {
+    #[cfg(target_os = "macos")]
+    {
+        Preset::Macos
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        Preset::Linux
+    }
+}
+
+fn parse_bool(s: &str, key: &str) -> bool {
+    match s {
+        "true" => true,
+        "false" => false,
+        _ => panic!("{key} must be 'true' or 'false'"),
+    }
+}
+
+fn parse_f32(s: &str, key: &str) -> f32 {
+    s.parse::<f32>()
+        .unwrap_or_else(|_| panic!("{key} must be a number"))
+}
+
+fn parse_bg_hex(s: &str) -> Option<tray_render::Background> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    let bytes = match hex.len() {
+        6 => {
+            let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+            let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+            let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+            (r, g, b, 255)
+        }
+        8 => {
+            let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+            let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+            let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+            let a = u8::from_str_radix(&hex[6..8], 16).ok()?;
+            (r, g, b, a)
+        }
+        _ => return None,
+    };
+
+    Some(tray_render::Background { rgba: bytes })
+}
+
+/// The closest installed match for `family` (system sans-serif when `None`)
+/// at the given CSS weight, or the bundled font when none loads.
+fn load_font(family: Option<&str>, weight: u32) -> Font<'static> {
+    let families: Vec<FamilyName> = family
+        .map(|name| FamilyName::Title(name.to_string()))
+        .into_iter()
+        .chain([FamilyName::SansSerif])
+        .collect();
+    let data = SystemSource::new()
+        .select_best_match(&families, Properties::new().weight(Weight(weight as f32)))
+        .ok()
+        .and_then(|handle| match handle {
+            Handle::Path { path, .. } => std::fs::read(path).ok(),
+            Handle::Memory { bytes, .. } => Some(bytes.to_vec()),
+        });
+    data.and_then(Font::try_from_vec)
+        .unwrap_or_else(tray_render::fallback_font)
+}
+
+fn write_png(path: &Path, rgba: &[u8], width: u32, height: u32) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    PngEncoder::new(BufWriter::new(file))
+        .write_image(rgba, width, height, ColorType::Rgba8)
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn parse_args() -> HashMap<String, String> {
+    let mut args = env::args().skip(1);
+    let mut map = HashMap::new();
+
+    while let Some(arg) = args.next() {
+        if arg == "--help" {
+            print!("{}", usage());
+            std::process::exit(0);
+        }
+
+        if !arg.starts_with("--") {
+            panic!("Unexpected arg '{arg}'. Use --help.");
+        }
+
+        let Some(value) = args.next() else {
+            panic!("Missing value for '{arg}'");
+        };
+
+        if map.insert(arg, value).is_some() {
+            panic!("Duplicate argument");
+        }
+    }
+
+    map
+}
+
+fn main() {
+    let args = parse_args();
+
+    let out = args
+        .get("--out")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| panic!("--out is required"));
+
+    let preset = match args.get("--preset").map(String::as_str) {
+        None => default_preset(),
+        Some("macos") => Preset::Macos,
+        Some("linux") => Preset::Linux,
+        Some(v) => panic!("--preset must be 'macos' or 'linux', got '{v}'"),
+    };
+
+    let scale = args
+        .get("--scale")
+        .map(|v| {
+            v.parse::<f32>()
+                .unwrap_or_else(|_| panic!("--scale must be a number"))
+        })
+        .unwrap_or(1.0);
+
+    if scale.is_nan() || scale <= 0.0 {
+        panic!("--scale must be > 0");
+    }
+
+    let cpu = args
+        .get("--cpu")
+        .map(|v| parse_f32(v, "--cpu"))
+        .unwrap_or(45.0);
+    let cpu_load = args.get("--load").map(|v| parse_f32(v, "--load"));
+    let mem = args
+        .get("--mem")
+        .map(|v| parse_f32(v, "--mem"))
+        .unwrap_or(99.0);
+    let gpu = args
+        .get("--gpu")
+        .map(|v| parse_f32(v, "--gpu"))
+        .unwrap_or(78.0);
+    let mem_used = args.get("--mem-used").cloned();
+    let gpu_mem = args.get("--gpu-mem").cloned();
+    let extra: Vec<tray_render::TextSegment> = [
+        ("--power", tray_render::IconType::Power),
+        ("--gpu-temp", tray_render::IconType::Temperature),
+    ]
+    .into_iter()
+    .filter_map(|(flag, icon)| {
+        args.get(flag).map(|text| tray_render::TextSegment {
+            icon,
+            text: text.clone(),
+            alert: false,
+        })
+    })
+    .collect();
+
+    let down = args
+        .get("--down")
+        .cloned()
+        .unwrap_or_else(|| "1.5 MB".to_string());
+    let up = args
+        .get("--up")
+        .cloned()
+        .unwrap_or_else(|| "0.2 MB".to_string());
+
+    let show_cpu = args
+        .get("--show-cpu")
+        .map(|v| parse_bool(v, "--show-cpu"))
+        .unwrap_or(true);
+    let show_mem = args
+        .get("--show-mem")
+        .map(|v| parse_bool(v, "--show-mem"))
+        .unwrap_or(true);
+    let show_gpu = args
+        .get("--show-gpu")
+        .map(|v| parse_bool(v, "--show-gpu"))
+        .unwrap_or(true);
+    let show_net = args
+        .get("--show-net")
+        .map(|v| parse_bool(v, "--show-net"))
+        .unwrap_or(true);
+    let order: Vec<tray_render::SegmentKind> = match args.get("--order") {
+        Some(list) => list
+            .split(',')
+            .map(|name| {
+                tray_render::SegmentKind::parse(name.trim())
+                    .unwrap_or_else(|| panic!("--order: unknown segment {name}"))
+            })
+            .collect(),
+        None => tray_render::SegmentKind::ALL.to_vec(),
+    };
+    let visible_segments: Vec<tray_render::SegmentKind> = order
+        .into_iter()
+        .filter(|kind| match kind {
+            tray_render::SegmentKind::Memory => show_mem,
+            tray_render::SegmentKind::Cpu => show_cpu,
+            tray_render::SegmentKind::Gpu => show_gpu,
+            tray_render::SegmentKind::Network => show_net,
+        })
+        .collect();
+    let layout = match args.get("--layout") {
+        Some(value) => tray_render::Layout::parse(value)
+            .unwrap_or_else(|| panic!("--layout must be standard, compact, icons or stacked")),
+        None => tray_render::Layout::Standard,
+    };
+    let value_style = match args.get("--value-style") {
+        Some(value) => tray_render::ValueStyle::parse(value)
+            .unwrap_or_else(|| panic!("--value-style must be number, bar or vertical_bar")),
+        None => tray_render::ValueStyle::Number,
+    };
+    let combine_net = args
+        .get("--combine-net")
+        .map(|v| parse_bool(v, "--combine-net"))
+        .unwrap_or(false);
+    let auto_width = args
+        .get("--auto-width")
+        .map(|v| parse_bool(v, "--auto-width"))
+        .unwrap_or(false);
+    let tabular = args
+        .get("--tabular")
+        .map(|v| parse_bool(v, "--tabular"))
+        .unwrap_or(false);
+    let percent_cap = match args.get("--percent-cap") {
+        Some(value) => tray_render::PercentCap::parse(value)
+            .unwrap_or_else(|| panic!("--percent-cap must be 99, 100 or per_core")),
+        None => tray_render::PercentCap::Capped,
+    };
+    let cpu_cores = args
+        .get("--cores")
+        .map(|v| parse_f32(v, "--cores") as u32)
+        .unwrap_or(8);
+    let show_alerts = args
+        .get("--show-alerts")
+        .map(|v| parse_bool(v, "--show-alerts"))
+        .unwrap_or(true);
+    let thresholds = tray_render::AlertThresholds {
+        cpu: args
+            .get("--cpu-threshold")
+            .map(|v| parse_f32(v, "--cpu-threshold"))
+            .unwrap_or(90.0),
+        mem: args
+            .get("--mem-threshold")
+            .map(|v| parse_f32(v, "--mem-threshold"))
+            .unwrap_or(90.0),
+        gpu: args
+            .get("--gpu-threshold")
+            .map(|v| parse_f32(v, "--gpu-threshold"))
+            .unwrap_or(90.0),
+    };
+    let use_light_icons = args
+        .get("--use-light-icons")
+        .map(|v| parse_bool(v, "--use-light-icons"))
+        .unwrap_or(true);
+    let hex_color = |key: &str| {
+        args.get(key).map(|v| {
+            tray_render::parse_hex_color(v).unwrap_or_else(|| panic!("{key} must be #RRGGBB"))
+        })
+    };
+    let mut palette = tray_render::Palette::for_theme(use_light_icons);
+    if let Some(color) = hex_color("--alert-color") {
+        palette.alert = color;
+    }
+    if let Some(color) = hex_color("--text-color") {
+        palette.foreground = color;
+    }
+
+    let background = match args.get("--bg").map(String::as_str) {
+        None => None,
+        Some("transparent") => None,
+        Some(v) => Some(
+            parse_bg_hex(v)
+                .unwrap_or_else(|| panic!("--bg must be 'transparent', '#RRGGBB', or '#RRGGBBAA'")),
+        ),
+    };
+
+    let sizing = match preset {
+        Preset::Macos => tray_render::SIZING_MACOS,
+        Preset::Linux => tray_render::SIZING_LINUX,
+    }
+    .scaled(scale);
+
+    let font_weight = args
+        .get("--font-weight")
+        .map(|v| parse_f32(v, "--font-weight") as u32)
+        .unwrap_or(400);
+    let font = load_font(args.get("--font").map(String::as_str), font_weight);
+    let mut renderer = tray_render::TrayRenderer::new();
+    renderer.set_auto_width(auto_width);
+    renderer.set_tabular_figures(tabular);
+    let mut buffer = Vec::new();
+
+    let (width, height, _has_alert) = renderer.render_tray_icon_into(
+        &font,
+        &mut buffer,
+        &tray_render::RenderParams {
+            cpu_usage: cpu,
+            cpu_load,
+            mem_percent: mem,
+            mem_used_str: mem_used.as_deref(),
+            gpu_usage: gpu,
+            gpu_mem_str: gpu_mem.as_deref(),
+            down_str: &down,
+            up_str: &up,
+            visible_segments: &visible_segments,
+            extra_segments: &extra,
+            layout,
+            value_style,
+            combine_net,
+            percent_cap,
+            cpu_cores,
+            show_alerts,
+            thresholds,
+            palette,
+            background,
+            ..tray_render::RenderParams::new(sizing)
+        },
+    );
+
+    let Some(parent) = out.parent() else {
+        panic!("Invalid output path");
+    };
+    if !parent.as_os_str().is_empty() {
+        std::fs::create_dir_all(parent).expect("failed to create output directory");
+    }
+
+    write_png(&out, &buffer, width, height).expect("failed to write PNG");
+
+    println!("Wrote {} ({}x{})", out.display(), width, height);
+}
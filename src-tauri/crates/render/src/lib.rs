@@ -0,0 +1,2117 @@
+//! Tray icon rendering for Better Resource Monitor
+//!
+//! Draws the menu bar icon (Phosphor fill icons from `assets/icons/` plus the
+//! values beside them) into an RGBA buffer, with the macOS and Linux
+//! [`Sizing`] presets and the text helpers the values go through. It has no
+//! Tauri dependency: hand the buffer from [`TrayRenderer::render_tray_icon_into`]
+//! to any tray library, or write it out as `examples/render_tray_icon.rs` does.
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use image::{ImageBuffer, Rgba};
+use rusttype::{Font, GlyphId, Point, PositionedGlyph, Scale};
+
+pub use better_resource_monitor_types::{
+    CoreUsage, MemoryBreakdown, MemoryPressure, MAX_HEATMAP_CELLS,
+};
+
+const SVG_CPU: &str = include_str!("../assets/icons/cpu-fill.svg");
+const SVG_MEMORY: &str = include_str!("../assets/icons/memory-fill.svg");
+const SVG_GPU: &str = include_str!("../assets/icons/graphics-card-fill.svg");
+const SVG_ARROW_UP: &str = include_str!("../assets/icons/cloud-arrow-up-fill.svg");
+const SVG_ARROW_DOWN: &str = include_str!("../assets/icons/cloud-arrow-down-fill.svg");
+const SVG_LIGHTNING: &str = include_str!("../assets/icons/lightning-fill.svg");
+const SVG_THERMOMETER: &str = include_str!("../assets/icons/thermometer-simple-fill.svg");
+const SVG_SIGNAL: &str = include_str!("../assets/icons/cell-signal-full-fill.svg");
+const SVG_SPARKLE: &str = include_str!("../assets/icons/sparkle-fill.svg");
+const SVG_WIFI: &str = include_str!("../assets/icons/wifi-fill.svg");
+const SVG_STALLED: &str = include_str!("../assets/icons/pause-circle-fill.svg");
+
+/// DejaVu Sans trimmed to Latin-1 (`scripts/subset-font.py`), for systems
+/// without any sans-serif font such as minimal containers.
+const FALLBACK_FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSans-subset.ttf");
+
+pub type Rgb = (u8, u8, u8);
+
+pub const DEFAULT_ALERT_COLOR: Rgb = (209, 71, 21); // #D14715
+/// Memory pressure colors, after Activity Monitor's graph
+pub const PRESSURE_NORMAL_COLOR: Rgb = (52, 199, 89); // #34C759
+pub const PRESSURE_WARNING_COLOR: Rgb = (255, 204, 0); // #FFCC00
+
+/// Colors used for icons and text.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Palette {
+    /// Icons and values when nothing is over its threshold
+    pub foreground: Rgb,
+    pub alert: Rgb,
+    pub text_effect: TextEffect,
+    /// Tints the built-in segments by metric instead of `foreground`
+    pub metric_colors: Option<MetricColors>,
+}
+
+/// One hue per built-in segment, so metrics can be told apart at a glance.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct MetricColors {
+    pub cpu: Rgb,
+    pub mem: Rgb,
+    pub gpu: Rgb,
+    pub net: Rgb,
+}
+
+impl MetricColors {
+    /// Blue, green, purple and gray, readable on light and dark panels.
+    pub const DEFAULT: MetricColors = MetricColors {
+        cpu: (10, 132, 255),  // #0A84FF
+        mem: (48, 209, 88),   // #30D158
+        gpu: (191, 90, 242),  // #BF5AF2
+        net: (142, 142, 147), // #8E8E93
+    };
+
+    pub fn for_segment(&self, kind: SegmentKind) -> Rgb {
+        match kind {
+            SegmentKind::Cpu => self.cpu,
+            SegmentKind::Memory => self.mem,
+            SegmentKind::Gpu => self.gpu,
+            SegmentKind::Network => self.net,
+        }
+    }
+}
+
+/// Contrasting edge drawn behind the values, for panels whose background
+/// doesn't match the theme the icon colors were picked for.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum TextEffect {
+    None,
+    /// Surrounds every glyph
+    Outline,
+    /// Offset down and to the right
+    Shadow,
+}
+
+impl TextEffect {
+    pub const ALL: [TextEffect; 3] = [TextEffect::None, TextEffect::Outline, TextEffect::Shadow];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TextEffect::None => "none",
+            TextEffect::Outline => "outline",
+            TextEffect::Shadow => "shadow",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|effect| effect.as_str() == value)
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|effect| *effect as u8 == value)
+            .unwrap_or(TextEffect::None)
+    }
+
+    /// Where copies of each glyph are drawn in the edge color, `width` pixels out.
+    pub fn offsets(self, width: i32) -> Vec<(i32, i32)> {
+        match self {
+            TextEffect::None => Vec::new(),
+            TextEffect::Outline => (-width..=width)
+                .flat_map(|dx| (-width..=width).map(move |dy| (dx, dy)))
+                .filter(|&offset| offset != (0, 0))
+                .collect(),
+            TextEffect::Shadow => vec![(width, width)],
+        }
+    }
+}
+
+/// Black behind light text, white behind dark text.
+pub fn contrast_color((r, g, b): Rgb) -> Rgb {
+    let luma = 299 * r as u32 + 587 * g as u32 + 114 * b as u32;
+    if luma >= 128_000 {
+        (0, 0, 0)
+    } else {
+        (255, 255, 255)
+    }
+}
+
+impl Palette {
+    /// White or black foreground to contrast with the panel, default alert color.
+    pub fn for_theme(use_light_icons: bool) -> Self {
+        Self {
+            foreground: if use_light_icons {
+                (255, 255, 255)
+            } else {
+                (0, 0, 0)
+            },
+            alert: DEFAULT_ALERT_COLOR,
+            text_effect: TextEffect::None,
+            metric_colors: None,
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::for_theme(true)
+    }
+}
+
+/// Parses "#RRGGBB" (the leading '#' is optional).
+pub fn parse_hex_color(value: &str) -> Option<Rgb> {
+    let hex = value.trim().strip_prefix('#').unwrap_or(value.trim());
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+pub fn format_hex_color((r, g, b): Rgb) -> String {
+    format!("#{r:02X}{g:02X}{b:02X}")
+}
+
+/// Per-metric percentages at or above which a segment is drawn in the alert color.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AlertThresholds {
+    pub cpu: f32,
+    pub mem: f32,
+    pub gpu: f32,
+}
+
+impl Default for AlertThresholds {
+    fn default() -> Self {
+        Self {
+            cpu: 90.0,
+            mem: 90.0,
+            gpu: 90.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sizing {
+    pub segment_width: u32,
+    pub segment_width_net: u32,
+    pub edge_padding: u32,
+    pub segment_gap: u32,
+    pub icon_height: u32,
+    pub font_size: f32,
+}
+
+impl Sizing {
+    pub fn scaled(self, scale: f32) -> Self {
+        if scale.is_nan() || scale <= 0.0 {
+            panic!("scale must be > 0");
+        }
+
+        let scale_u32 = |v: u32| -> u32 { ((v as f32) * scale).round().max(1.0) as u32 };
+        Self {
+            segment_width: scale_u32(self.segment_width),
+            segment_width_net: scale_u32(self.segment_width_net),
+            edge_padding: scale_u32(self.edge_padding),
+            segment_gap: scale_u32(self.segment_gap),
+            icon_height: scale_u32(self.icon_height),
+            font_size: self.font_size * scale,
+        }
+    }
+}
+
+pub const SIZING_MACOS: Sizing = Sizing {
+    segment_width: 180,
+    segment_width_net: 240,
+    edge_padding: 16,
+    segment_gap: 48,
+    icon_height: 64,
+    font_size: 56.0,
+};
+
+pub const SIZING_LINUX: Sizing = Sizing {
+    segment_width: 58,
+    segment_width_net: 75,
+    edge_padding: 5,
+    segment_gap: 18,
+    icon_height: 22,
+    font_size: 19.0,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum IconType {
+    Cpu,
+    Memory,
+    Gpu,
+    ArrowDown,
+    ArrowUp,
+    Power,
+    Temperature,
+    Signal,
+    NeuralEngine,
+    Wifi,
+}
+
+impl IconType {
+    /// Short word drawn for the icon under [`LabelStyle::Text`] and
+    /// [`LabelStyle::Both`].
+    pub fn label(self) -> &'static str {
+        match self {
+            IconType::Cpu => "CPU",
+            IconType::Memory => "MEM",
+            IconType::Gpu => "GPU",
+            IconType::ArrowDown => "DL",
+            IconType::ArrowUp => "UL",
+            IconType::Power => "PWR",
+            IconType::Temperature => "TEMP",
+            IconType::Signal => "PING",
+            IconType::NeuralEngine => "ANE",
+            IconType::Wifi => "WIFI",
+        }
+    }
+}
+
+/// Built-in tray segments. The renderer draws them in the order it is given.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SegmentKind {
+    Memory,
+    Cpu,
+    Gpu,
+    /// Download and upload, always drawn as a pair
+    Network,
+}
+
+impl SegmentKind {
+    /// Every segment, in the default order.
+    pub const ALL: [SegmentKind; 4] = [
+        SegmentKind::Memory,
+        SegmentKind::Cpu,
+        SegmentKind::Gpu,
+        SegmentKind::Network,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SegmentKind::Memory => "mem",
+            SegmentKind::Cpu => "cpu",
+            SegmentKind::Gpu => "gpu",
+            SegmentKind::Network => "net",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == value)
+    }
+}
+
+/// How segments are laid out in the tray icon.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Layout {
+    /// Icon followed by a fixed-width value for every segment
+    Standard,
+    /// Values only, e.g. "34 62 18 | 1.5M↓ 0.2M↑", for crowded menu bars
+    Compact,
+    /// Icons only; a segment turns the alert color when over its threshold
+    IconOnly,
+    /// Standard segments at half height in two rows, memory / CPU / GPU above
+    /// network and the extra segments, for tall panels
+    Stacked,
+}
+
+impl Layout {
+    pub const ALL: [Layout; 4] = [
+        Layout::Standard,
+        Layout::Compact,
+        Layout::IconOnly,
+        Layout::Stacked,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Layout::Standard => "standard",
+            Layout::Compact => "compact",
+            Layout::IconOnly => "icons",
+            Layout::Stacked => "stacked",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Layout::Standard => "Standard",
+            Layout::Compact => "Compact",
+            Layout::IconOnly => "Icons Only",
+            Layout::Stacked => "Two Rows",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|layout| layout.as_str() == value)
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|layout| *layout as u8 == value)
+            .unwrap_or(Layout::Standard)
+    }
+}
+
+/// How percentages are drawn in the standard and stacked layouts.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ValueStyle {
+    Number,
+    /// A bar filled from the left in proportion to usage
+    HorizontalBar,
+    /// A narrow bar filled from the bottom, like a level meter
+    VerticalBar,
+}
+
+impl ValueStyle {
+    pub const ALL: [ValueStyle; 3] = [
+        ValueStyle::Number,
+        ValueStyle::HorizontalBar,
+        ValueStyle::VerticalBar,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ValueStyle::Number => "number",
+            ValueStyle::HorizontalBar => "bar",
+            ValueStyle::VerticalBar => "vertical_bar",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|style| style.as_str() == value)
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|style| *style as u8 == value)
+            .unwrap_or(ValueStyle::Number)
+    }
+}
+
+/// What marks each segment in the standard, icon-only and stacked layouts.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum LabelStyle {
+    /// Pictograms
+    Icons,
+    /// Short words, e.g. "CPU 34%", easier to tell apart at small sizes
+    Text,
+    /// The pictogram followed by the word
+    Both,
+}
+
+impl LabelStyle {
+    pub const ALL: [LabelStyle; 3] = [LabelStyle::Icons, LabelStyle::Text, LabelStyle::Both];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LabelStyle::Icons => "icons",
+            LabelStyle::Text => "text",
+            LabelStyle::Both => "both",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|style| style.as_str() == value)
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|style| *style as u8 == value)
+            .unwrap_or(LabelStyle::Icons)
+    }
+}
+
+/// Upper bound on the percentages drawn in the tray.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PercentCap {
+    /// At most 99%, so every value fits in two digits
+    Capped,
+    /// Up to 100%; the segment widens for the third digit
+    Full,
+    /// CPU usage summed over cores like `top`, up to 100% per core
+    PerCore,
+}
+
+impl PercentCap {
+    pub const ALL: [PercentCap; 3] = [PercentCap::Capped, PercentCap::Full, PercentCap::PerCore];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PercentCap::Capped => "99",
+            PercentCap::Full => "100",
+            PercentCap::PerCore => "per_core",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|cap| cap.as_str() == value)
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|cap| *cap as u8 == value)
+            .unwrap_or(PercentCap::Capped)
+    }
+}
+
+/// Green or yellow for the memory segment; critical pressure is an alert and
+/// takes the alert color instead.
+fn pressure_color(level: MemoryPressure) -> Option<Rgb> {
+    match level {
+        MemoryPressure::Normal => Some(PRESSURE_NORMAL_COLOR),
+        MemoryPressure::Warning => Some(PRESSURE_WARNING_COLOR),
+        MemoryPressure::Critical => None,
+    }
+}
+
+/// Rows, columns and square cell size of a heatmap of `cells` in an icon
+/// `height` pixels tall, with a pixel between cells and at the edges. The grid
+/// is kept about twice as wide as tall; rows never get thinner than a pixel.
+pub fn heatmap_grid(cells: usize, height: u32) -> (u32, u32, u32) {
+    let cells = cells.max(1) as u32;
+    let room = height.saturating_sub(2).max(1);
+    let mut rows = ((cells as f32 / 2.0).sqrt().ceil() as u32).clamp(1, room.div_ceil(2));
+    // Drop rows until each is at least a pixel tall
+    while rows > 1 && (room + 1) / rows < 2 {
+        rows -= 1;
+    }
+    let cell = ((room + 1) / rows).saturating_sub(1).max(1);
+    (rows, cells.div_ceil(rows), cell)
+}
+
+/// Width of a [`heatmap_grid`].
+pub fn heatmap_width(cells: usize, height: u32) -> u32 {
+    let (_, columns, cell) = heatmap_grid(cells, height);
+    columns * (cell + 1) - 1
+}
+
+/// A pre-formatted segment drawn after the built-in ones, for metrics that
+/// don't need percent formatting or thresholds (e.g. CPU power in watts).
+#[derive(Clone, PartialEq, Debug)]
+pub struct TextSegment {
+    pub icon: IconType,
+    pub text: String,
+    pub alert: bool,
+}
+
+pub fn cap_percent(value: f32) -> f32 {
+    value.clamp(0.0, 99.0)
+}
+
+/// "45%" for a segment's usage under `cap`. `value` is always the average over
+/// cores; only the CPU segment is scaled by `cpu_cores` in per-core mode.
+pub fn format_percent(value: f32, cap: PercentCap, is_cpu: bool, cpu_cores: u32) -> String {
+    let shown = match cap {
+        PercentCap::Capped => cap_percent(value),
+        PercentCap::PerCore if is_cpu => {
+            let cores = cpu_cores.max(1) as f32;
+            (value * cores).clamp(0.0, 100.0 * cores)
+        }
+        _ => value.clamp(0.0, 100.0),
+    };
+    format!("{shown:.0}%")
+}
+
+/// "23/8%": user and system CPU time, under the same cap as the usage.
+pub fn format_cpu_split(user: f32, system: f32, cap: PercentCap, cpu_cores: u32) -> String {
+    let user = format_percent(user, cap, true, cpu_cores);
+    let system = format_percent(system, cap, true, cpu_cores);
+    format!("{}/{system}", user.trim_end_matches('%'))
+}
+
+/// Whole-number digits every value is sized for with tabular figures: "100%",
+/// "999 KB" and loads up to 999 fit, so the icon keeps its width as values
+/// change. Only per-core percentages on 10+ cores go beyond.
+const TABULAR_DIGITS: usize = 3;
+
+/// `text` with every digit replaced by the widest one, so a width measured on
+/// it only changes with the number of characters. With `tabular`, whole-number
+/// parts are also padded to [`TABULAR_DIGITS`], so it doesn't change at all.
+pub fn widest_digits(text: &str, tabular: bool) -> String {
+    let mut widest = String::with_capacity(text.len() + TABULAR_DIGITS);
+    let mut run = 0;
+    let mut fraction = false;
+    let pad = |widest: &mut String, run: usize, fraction: bool| {
+        if tabular && run > 0 && !fraction {
+            widest.extend(std::iter::repeat_n('8', TABULAR_DIGITS.saturating_sub(run)));
+        }
+    };
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            widest.push('8');
+            run += 1;
+            continue;
+        }
+        pad(&mut widest, run, fraction);
+        fraction = c == '.';
+        run = 0;
+        widest.push(c);
+    }
+    pad(&mut widest, run, fraction);
+    widest
+}
+
+/// One decimal for typical loads, whole numbers once the value needs three digits.
+pub fn format_load_average(load: f32) -> String {
+    let load = load.max(0.0);
+    if load >= 99.95 {
+        format!("{load:.0}")
+    } else {
+        format!("{load:.1}")
+    }
+}
+
+/// "1.5 MB": a network rate in bytes per second.
+pub fn format_speed(bytes_per_sec: f64) -> String {
+    format_rate(bytes_per_sec, ["KB", "MB", "GB"])
+}
+
+/// Same scale as `format_speed`, but in bits per second (Kb, Mb, Gb).
+pub fn format_bitrate(bytes_per_sec: f64) -> String {
+    format_rate(bytes_per_sec * 8.0, ["Kb", "Mb", "Gb"])
+}
+
+fn format_rate(per_sec: f64, units: [&str; 3]) -> String {
+    const THRESHOLD_K: f64 = 999_500.0;
+    const THRESHOLD_M: f64 = 999_500_000.0;
+
+    let (value, unit) = if per_sec >= THRESHOLD_M {
+        (per_sec / 1_000_000_000.0, units[2])
+    } else if per_sec >= THRESHOLD_K {
+        (per_sec / 1_000_000.0, units[1])
+    } else {
+        (per_sec / 1_000.0, units[0])
+    };
+
+    if value >= 10.0 {
+        format!("{value:.0} {unit}")
+    } else {
+        format!("{value:.1} {unit}")
+    }
+}
+
+/// The bundled DejaVu Sans subset, which renders every value and label.
+pub fn fallback_font() -> Font<'static> {
+    Font::try_from_bytes(FALLBACK_FONT).expect("bundled font is valid")
+}
+
+fn calculate_font_baseline(font: &Font, icon_height: u32, scale: Scale) -> f32 {
+    let reference_text = "0123456789% KMGTP";
+    let mut min_y = i32::MAX;
+    let mut max_y = i32::MIN;
+
+    for glyph in font.layout(reference_text, scale, rusttype::point(0.0, 0.0)) {
+        if let Some(bb) = glyph.pixel_bounding_box() {
+            if bb.min.y < min_y {
+                min_y = bb.min.y;
+            }
+            if bb.max.y > max_y {
+                max_y = bb.max.y;
+            }
+        }
+    }
+
+    if min_y < max_y {
+        (icon_height as f32 / 2.0) - ((min_y + max_y) as f32 / 2.0)
+    } else {
+        (icon_height as f32 / 2.0) + (font.v_metrics(scale).ascent / 2.0)
+    }
+}
+
+/// Rasterizes an icon SVG into a `size`×`size` RGBA square in `color`.
+pub fn render_svg_icon(svg_data: &str, size: u32, color: (u8, u8, u8)) -> Vec<u8> {
+    let color_hex = format!("#{:02x}{:02x}{:02x}", color.0, color.1, color.2);
+
+    let svg_with_color = svg_data
+        .replace("currentColor", &color_hex)
+        .replace("<svg ", &format!("<svg fill=\"{color_hex}\" "));
+
+    let opts = resvg::usvg::Options::default();
+    let tree = resvg::usvg::Tree::from_str(&svg_with_color, &opts).expect("Failed to parse SVG");
+
+    let svg_size = tree.size();
+    let scale = size as f32 / svg_size.width().max(svg_size.height());
+
+    let scaled_width = svg_size.width() * scale;
+    let scaled_height = svg_size.height() * scale;
+
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(size, size).expect("Failed to create pixmap");
+
+    let tx = (size as f32 - scaled_width) / 2.0;
+    let ty = (size as f32 - scaled_height) / 2.0;
+    let transform = resvg::tiny_skia::Transform::from_translate(tx, ty).post_scale(scale, scale);
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+    let mut pixels = pixmap.take();
+    for chunk in pixels.chunks_exact_mut(4) {
+        let alpha = chunk[3];
+        if alpha > 0 && alpha < 255 {
+            let a = alpha as u16;
+            chunk[0] = ((chunk[0] as u16 * 255 / a).min(255)) as u8;
+            chunk[1] = ((chunk[1] as u16 * 255 / a).min(255)) as u8;
+            chunk[2] = ((chunk[2] as u16 * 255 / a).min(255)) as u8;
+        }
+    }
+    pixels
+}
+
+/// Fingerprint of a finished frame and whether it is shown as a macOS
+/// template, so a frame the tray already shows isn't sent again.
+pub fn frame_hash(pixels: &[u8], width: u32, height: u32, template: bool) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (pixels, width, height, template).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Shown in place of the readings while the monitoring loop is stalled: a
+/// pause symbol in the alert color with the usual edge padding. Returns the
+/// pixels, width and height.
+pub fn render_stalled_icon(sizing: Sizing, palette: Palette) -> (Vec<u8>, u32, u32) {
+    let size = sizing.icon_height;
+    let glyph = render_svg_icon(SVG_STALLED, size, palette.alert);
+    let width = size + sizing.edge_padding * 2;
+    let mut pixels = vec![0; (width * size * 4) as usize];
+    let row_bytes = (size * 4) as usize;
+    for (row, glyph_row) in glyph.chunks_exact(row_bytes).enumerate() {
+        let start = (row as u32 * width + sizing.edge_padding) as usize * 4;
+        pixels[start..start + row_bytes].copy_from_slice(glyph_row);
+    }
+    (pixels, width, size)
+}
+
+const ICON_SVGS: [(IconType, &str); 10] = [
+    (IconType::Cpu, SVG_CPU),
+    (IconType::Memory, SVG_MEMORY),
+    (IconType::Gpu, SVG_GPU),
+    (IconType::ArrowDown, SVG_ARROW_DOWN),
+    (IconType::ArrowUp, SVG_ARROW_UP),
+    (IconType::Power, SVG_LIGHTNING),
+    (IconType::Temperature, SVG_THERMOMETER),
+    (IconType::Signal, SVG_SIGNAL),
+    (IconType::NeuralEngine, SVG_SPARKLE),
+    (IconType::Wifi, SVG_WIFI),
+];
+
+/// The SVG drawn for `icon`.
+pub fn icon_svg(icon: IconType) -> &'static str {
+    ICON_SVGS
+        .iter()
+        .find(|(icon_type, _)| *icon_type == icon)
+        .map(|(_, svg)| *svg)
+        .expect("every icon has an SVG")
+}
+
+/// Icons rasterized at one size, per color actually used. Colors are user
+/// configurable, so they are rendered on first use rather than up front.
+struct IconCache {
+    size: u32,
+    icons: HashMap<(IconType, Rgb), Vec<u8>>,
+}
+
+impl IconCache {
+    /// Stop keeping colors that are no longer used after a few changes; room
+    /// for the foreground, alert, pressure and all four metric colors.
+    const MAX_COLORS: usize = 12;
+
+    fn new(size: u32) -> Self {
+        Self {
+            size,
+            icons: HashMap::new(),
+        }
+    }
+
+    fn ensure_colors(&mut self, colors: &[Rgb]) {
+        let cached = |icons: &HashMap<_, _>, color| icons.contains_key(&(IconType::Cpu, color));
+        if colors.iter().all(|&color| cached(&self.icons, color)) {
+            return;
+        }
+        if self.icons.len() >= Self::MAX_COLORS * ICON_SVGS.len() {
+            self.icons.clear();
+        }
+        for &color in colors {
+            if cached(&self.icons, color) {
+                continue;
+            }
+            for (icon_type, svg) in ICON_SVGS {
+                self.icons
+                    .insert((icon_type, color), render_svg_icon(svg, self.size, color));
+            }
+        }
+    }
+
+    fn get(&self, icon_type: IconType, color: Rgb) -> &[u8] {
+        self.icons.get(&(icon_type, color)).expect("icon cached")
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Background {
+    pub rgba: (u8, u8, u8, u8),
+}
+
+/// Everything one frame shows, for [`TrayRenderer::render_tray_icon_into`].
+/// Start from [`RenderParams::new`] and set the fields that differ.
+#[derive(Clone, Copy)]
+pub struct RenderParams<'a> {
+    pub sizing: Sizing,
+    pub cpu_usage: f32,
+    /// Shown instead of `cpu_usage` when set
+    pub cpu_load: Option<f32>,
+    /// User and system CPU percent, drawn as one bar in two tones
+    pub cpu_split: Option<(f32, f32)>,
+    /// Per-core grid drawn instead of the CPU value
+    pub cpu_heatmap: Option<CoreUsage>,
+    pub mem_percent: f32,
+    /// Shown instead of `mem_percent` when set, e.g. "12.4G"
+    pub mem_used_str: Option<&'a str>,
+    /// Colors the memory segment by level (macOS)
+    pub mem_pressure: Option<MemoryPressure>,
+    pub mem_breakdown: Option<MemoryBreakdown>,
+    pub gpu_usage: f32,
+    /// Shown after the GPU value when set
+    pub gpu_mem_str: Option<&'a str>,
+    pub down_str: &'a str,
+    pub up_str: &'a str,
+    /// Built-in segments, in drawing order
+    pub visible_segments: &'a [SegmentKind],
+    /// Drawn after the built-in segments
+    pub extra_segments: &'a [TextSegment],
+    pub layout: Layout,
+    pub value_style: ValueStyle,
+    /// Download and upload as one segment
+    pub combine_net: bool,
+    pub percent_cap: PercentCap,
+    /// Cores summed by [`PercentCap::PerCore`]
+    pub cpu_cores: u32,
+    pub show_alerts: bool,
+    pub thresholds: AlertThresholds,
+    pub palette: Palette,
+    /// Transparent when `None`
+    pub background: Option<Background>,
+}
+
+impl RenderParams<'_> {
+    /// Every built-in segment at zero in the standard layout, with alerts on
+    /// and the default thresholds and palette.
+    pub fn new(sizing: Sizing) -> Self {
+        Self {
+            sizing,
+            cpu_usage: 0.0,
+            cpu_load: None,
+            cpu_split: None,
+            cpu_heatmap: None,
+            mem_percent: 0.0,
+            mem_used_str: None,
+            mem_pressure: None,
+            mem_breakdown: None,
+            gpu_usage: 0.0,
+            gpu_mem_str: None,
+            down_str: "",
+            up_str: "",
+            visible_segments: &SegmentKind::ALL,
+            extra_segments: &[],
+            layout: Layout::Standard,
+            value_style: ValueStyle::Number,
+            combine_net: false,
+            percent_cap: PercentCap::Capped,
+            cpu_cores: 1,
+            show_alerts: true,
+            thresholds: AlertThresholds::default(),
+            palette: Palette::default(),
+            background: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    id: GlyphId,
+    /// Font size as its bits
+    size: u32,
+    /// Position within the pixel as bits; rasterization depends on it
+    x_fraction: u32,
+    y_fraction: u32,
+}
+
+/// Coverage of one rasterized glyph, with its top-left pixel relative to the
+/// whole-pixel part of the glyph's position.
+struct GlyphRaster {
+    left: i32,
+    top: i32,
+    width: u32,
+    coverage: Vec<u8>,
+}
+
+/// Glyph coverage masks, so digits and units are rasterized once and then only
+/// blitted. Coverage doesn't depend on color, which is applied when drawing.
+/// Values are right-aligned and digits share one width in most fonts, so a
+/// glyph keeps landing on the same sub-pixel offset from frame to frame.
+#[derive(Default)]
+struct GlyphCache {
+    rasters: HashMap<GlyphKey, GlyphRaster>,
+}
+
+impl GlyphCache {
+    /// A few sizes of the characters the tray uses fit comfortably; anything
+    /// beyond (many font changes) starts over.
+    const MAX_GLYPHS: usize = 1024;
+
+    /// Lays out `text` like `Font::layout`, or with [`tabular_layout`], and
+    /// returns where each glyph's raster goes, rasterizing the ones not cached
+    /// yet.
+    fn layout(
+        &mut self,
+        font: &Font,
+        scale: Scale,
+        text: &str,
+        start: Point<f32>,
+        tabular: bool,
+    ) -> Vec<(i32, i32, GlyphKey)> {
+        if self.rasters.len() >= Self::MAX_GLYPHS {
+            self.rasters.clear();
+        }
+
+        let glyphs: Vec<_> = if tabular {
+            tabular_layout(font, scale, text, start)
+        } else {
+            font.layout(text, scale, start).collect()
+        };
+        let mut placed = Vec::new();
+        for glyph in glyphs {
+            let position = glyph.position();
+            let (x, y) = (position.x.floor(), position.y.floor());
+            let fraction = rusttype::point(position.x - x, position.y - y);
+            let key = GlyphKey {
+                id: glyph.id(),
+                size: scale.y.to_bits(),
+                x_fraction: fraction.x.to_bits(),
+                y_fraction: fraction.y.to_bits(),
+            };
+
+            self.rasters.entry(key).or_insert_with(|| {
+                let glyph = font.glyph(key.id).scaled(scale).positioned(fraction);
+                let Some(bb) = glyph.pixel_bounding_box() else {
+                    return GlyphRaster {
+                        left: 0,
+                        top: 0,
+                        width: 0,
+                        coverage: Vec::new(),
+                    };
+                };
+                let width = bb.width() as u32;
+                let mut coverage = vec![0; (width * bb.height() as u32) as usize];
+                glyph.draw(|gx, gy, v| {
+                    coverage[(gy * width + gx) as usize] = (v * 255.0) as u8;
+                });
+                GlyphRaster {
+                    left: bb.min.x,
+                    top: bb.min.y,
+                    width,
+                    coverage,
+                }
+            });
+            placed.push((x as i32, y as i32, key));
+        }
+        placed
+    }
+
+    /// Covered pixels of a placed glyph as (x, y, alpha).
+    fn pixels(
+        &self,
+        (x, y, key): (i32, i32, GlyphKey),
+    ) -> impl Iterator<Item = (i32, i32, u8)> + '_ {
+        let raster = &self.rasters[&key];
+        raster
+            .coverage
+            .iter()
+            .enumerate()
+            .filter(|(_, &alpha)| alpha > 0)
+            .map(move |(i, &alpha)| {
+                let gx = (i as u32 % raster.width) as i32;
+                let gy = (i as u32 / raster.width) as i32;
+                (x + raster.left + gx, y + raster.top + gy, alpha)
+            })
+    }
+}
+
+/// Icon, text, width, alert, color, bar fill, solid and medium shares (as
+/// bits) and
+/// per-core heatmap of a segment. A combined network segment's text holds both
+/// rates, one per line.
+type SegmentKey = (
+    IconType,
+    String,
+    u32,
+    bool,
+    Option<Rgb>,
+    Option<(u32, u32, u32)>,
+    Option<CoreUsage>,
+);
+
+/// Everything that determines a frame's pixels apart from the font.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct FrameKey {
+    /// `Sizing` fields, the font size as its bits
+    sizing: [u32; 6],
+    layout: Layout,
+    value_style: ValueStyle,
+    show_alerts: bool,
+    palette: Palette,
+    background: Option<(u8, u8, u8, u8)>,
+    tabular: bool,
+    label_style: LabelStyle,
+    segments: Vec<SegmentKey>,
+}
+
+struct CachedFrame {
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+    alert: bool,
+    last_used: u64,
+}
+
+/// Recently rendered frames, so values that flip back and forth (34% ↔ 35%)
+/// reuse pixels instead of blitting icons and rasterizing glyphs again. Frames
+/// are large on Retina displays, so only a few are kept, evicting the least
+/// recently used.
+#[derive(Default)]
+struct FrameCache {
+    frames: HashMap<FrameKey, CachedFrame>,
+    uses: u64,
+}
+
+impl FrameCache {
+    const CAPACITY: usize = 8;
+
+    fn get(&mut self, key: &FrameKey) -> Option<&CachedFrame> {
+        self.uses += 1;
+        let frame = self.frames.get_mut(key)?;
+        frame.last_used = self.uses;
+        Some(frame)
+    }
+
+    fn insert(&mut self, key: FrameKey, pixels: &[u8], width: u32, height: u32, alert: bool) {
+        if self.frames.len() >= Self::CAPACITY {
+            let oldest = self
+                .frames
+                .iter()
+                .min_by_key(|(_, frame)| frame.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.frames.remove(&oldest);
+            }
+        }
+        self.uses += 1;
+        self.frames.insert(
+            key,
+            CachedFrame {
+                pixels: pixels.to_vec(),
+                width,
+                height,
+                alert,
+                last_used: self.uses,
+            },
+        );
+    }
+}
+
+/// Segment widths in auto-width mode. A segment widens as soon as its value
+/// needs more room but only narrows once it has needed less for
+/// [`Self::SHRINK_AFTER`] frames in a row, so a value hovering around 9-10%
+/// doesn't make the whole icon twitch.
+#[derive(Default)]
+struct FittedWidths {
+    /// Shown width and the frames in a row that needed less
+    widths: HashMap<IconType, (u32, u32)>,
+}
+
+impl FittedWidths {
+    const SHRINK_AFTER: u32 = 5;
+
+    fn fit(&mut self, icon: IconType, needed: u32) -> u32 {
+        let (shown, narrower) = self.widths.entry(icon).or_insert((needed, 0));
+        if needed >= *shown {
+            *shown = needed;
+            *narrower = 0;
+        } else {
+            *narrower += 1;
+            if *narrower >= Self::SHRINK_AFTER {
+                *shown = needed;
+                *narrower = 0;
+            }
+        }
+        *shown
+    }
+}
+
+pub struct TrayRenderer {
+    icon_caches: HashMap<u32, IconCache>,
+    baseline_cache: Option<(u32, u32, f32)>,
+    frames: FrameCache,
+    glyphs: GlyphCache,
+    /// `Some` in auto-width mode
+    fitted: Option<FittedWidths>,
+    tabular: bool,
+    label_style: LabelStyle,
+}
+
+impl Default for TrayRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TrayRenderer {
+    pub fn new() -> Self {
+        Self {
+            icon_caches: HashMap::new(),
+            baseline_cache: None,
+            frames: FrameCache::default(),
+            glyphs: GlyphCache::default(),
+            fitted: None,
+            tabular: false,
+            label_style: LabelStyle::Icons,
+        }
+    }
+
+    /// Sets digits in cells of one width (tabular figures) and sizes values for
+    /// [`TABULAR_DIGITS`], so the icon's width holds as values change; e.g.
+    /// 9% → 10% no longer shifts everything to its left in the menu bar.
+    pub fn set_tabular_figures(&mut self, enabled: bool) {
+        self.tabular = enabled;
+    }
+
+    /// Sizes text segments to their values instead of the preset's fixed
+    /// `segment_width`. Bars, icon-only and compact layouts are unaffected.
+    pub fn set_auto_width(&mut self, enabled: bool) {
+        if enabled != self.fitted.is_some() {
+            self.fitted = enabled.then(FittedWidths::default);
+        }
+    }
+
+    /// Marks segments with icons, text labels or both; segments widen by the
+    /// measured label. The compact layout has no labels and is unaffected.
+    pub fn set_label_style(&mut self, style: LabelStyle) {
+        if style != self.label_style {
+            self.label_style = style;
+            // Fitted widths would otherwise take a few frames to shrink
+            if let Some(fitted) = self.fitted.as_mut() {
+                *fitted = FittedWidths::default();
+            }
+        }
+    }
+
+    /// Frames and glyphs aren't keyed by font, so they must be dropped when it changes.
+    pub fn clear_font_caches(&mut self) {
+        self.frames.frames.clear();
+        self.glyphs.rasters.clear();
+    }
+
+    /// Frames held in the cache, for the app's tests.
+    #[doc(hidden)]
+    pub fn cached_frames(&self) -> usize {
+        self.frames.frames.len()
+    }
+
+    #[doc(hidden)]
+    pub fn cached_glyphs(&self) -> usize {
+        self.glyphs.rasters.len()
+    }
+
+    fn icon_cache_mut(&mut self, size: u32, colors: &[Rgb]) -> &IconCache {
+        let cache = self
+            .icon_caches
+            .entry(size)
+            .or_insert_with(|| IconCache::new(size));
+        cache.ensure_colors(colors);
+        cache
+    }
+
+    fn baseline(&mut self, font: &Font, sizing: Sizing) -> f32 {
+        let font_size_key = (sizing.font_size * 1000.0).round() as u32;
+        if let Some((h, fs, baseline)) = self.baseline_cache {
+            if h == sizing.icon_height && fs == font_size_key {
+                return baseline;
+            }
+        }
+
+        let scale = Scale::uniform(sizing.font_size);
+        let baseline = calculate_font_baseline(font, sizing.icon_height, scale);
+        self.baseline_cache = Some((sizing.icon_height, font_size_key, baseline));
+        baseline
+    }
+
+    /// Draws one frame into `buffer` as RGBA and returns its width, its height
+    /// and whether any segment is in the alert color.
+    pub fn render_tray_icon_into(
+        &mut self,
+        font: &Font,
+        buffer: &mut Vec<u8>,
+        params: &RenderParams,
+    ) -> (u32, u32, bool) {
+        let RenderParams {
+            sizing,
+            cpu_usage,
+            cpu_load,
+            cpu_split,
+            cpu_heatmap,
+            mem_percent,
+            mem_used_str,
+            mem_pressure,
+            mem_breakdown,
+            gpu_usage,
+            gpu_mem_str,
+            down_str,
+            up_str,
+            visible_segments,
+            extra_segments,
+            layout,
+            value_style,
+            combine_net,
+            percent_cap,
+            cpu_cores,
+            show_alerts,
+            thresholds,
+            palette,
+            background,
+        } = *params;
+        struct Segment {
+            icon: IconType,
+            value: String,
+            width: u32,
+            alert: bool,
+            /// Drawn instead of the foreground while no alert is active
+            color: Option<Rgb>,
+            /// Fill fraction of the bar drawn instead of `value`
+            bar: Option<f32>,
+            /// Part of `bar` drawn solid, the rest lighter: user vs system CPU
+            bar_solid: Option<f32>,
+            /// End of a medium tone after `bar_solid`, with the rest of `bar`
+            /// lighter still: wired vs cached memory
+            bar_mid: Option<f32>,
+            /// Upload rate drawn after `value` in a combined network segment
+            up: Option<String>,
+            /// Per-core grid drawn instead of `value`
+            heatmap: Option<CoreUsage>,
+        }
+
+        if layout == Layout::Stacked {
+            let (top, bottom): (Vec<SegmentKind>, Vec<SegmentKind>) = visible_segments
+                .iter()
+                .partition(|&&kind| kind != SegmentKind::Network);
+            // With nothing to put on one of the rows, a single full-height row
+            // is easier to read
+            let stacked = !top.is_empty() && (!bottom.is_empty() || !extra_segments.is_empty());
+            let row_sizing = if stacked {
+                Sizing {
+                    icon_height: sizing.icon_height / 2,
+                    ..sizing.scaled(0.5)
+                }
+            } else {
+                sizing
+            };
+            let render_row = |renderer: &mut Self,
+                              buffer: &mut Vec<u8>,
+                              kinds: &[SegmentKind],
+                              extras: &[TextSegment]| {
+                renderer.render_tray_icon_into(
+                    font,
+                    buffer,
+                    &RenderParams {
+                        sizing: row_sizing,
+                        visible_segments: kinds,
+                        extra_segments: extras,
+                        layout: Layout::Standard,
+                        ..*params
+                    },
+                )
+            };
+            if !stacked {
+                return render_row(self, buffer, visible_segments, extra_segments);
+            }
+
+            let mut top_pixels = Vec::new();
+            let mut bottom_pixels = Vec::new();
+            let top_row = render_row(self, &mut top_pixels, &top, &[]);
+            let bottom_row = render_row(self, &mut bottom_pixels, &bottom, extra_segments);
+            let width = top_row.0.max(bottom_row.0);
+            stack_rows(
+                buffer,
+                width,
+                sizing.icon_height,
+                background,
+                [
+                    (&top_pixels, top_row.0, 0),
+                    (
+                        &bottom_pixels,
+                        bottom_row.0,
+                        sizing.icon_height - bottom_row.1,
+                    ),
+                ],
+            );
+            return (width, sizing.icon_height, top_row.2 || bottom_row.2);
+        }
+
+        let scale = Scale::uniform(sizing.font_size);
+        // Standard segments are sized for "99%"; longer values widen theirs
+        let tabular = self.tabular;
+        let two_digit_width = text_width(font, scale, "88%", tabular);
+        let bar_gap = (sizing.icon_height / 8).max(1);
+        let bar_thickness = (sizing.icon_height * 3 / 8).max(2);
+        // The combined network segment uses half-size arrows and compact rates
+        let small_icon = (sizing.icon_height / 2).max(1);
+        let combine_net = combine_net && layout == Layout::Standard;
+        let rate_width = |rate: &str| {
+            text_width(font, scale, &widest_digits(rate, tabular), tabular)
+                .max(text_width(font, scale, "888K", tabular))
+                .ceil() as u32
+        };
+        let label_style = self.label_style;
+        // Room before a value: the icon, the measured label or both
+        let lead_width = |icon: IconType, icon_size: u32| {
+            let label = || text_width(font, scale, icon.label(), tabular).ceil() as u32;
+            match label_style {
+                LabelStyle::Icons => icon_size,
+                LabelStyle::Text => label(),
+                LabelStyle::Both => icon_size + bar_gap + label(),
+            }
+        };
+        let label_x = |x: u32, icon_size: u32| match label_style {
+            LabelStyle::Both => (x + icon_size + bar_gap) as f32,
+            _ => x as f32,
+        };
+
+        let metric_color = |kind| palette.metric_colors.map(|colors| colors.for_segment(kind));
+
+        let mut segments = Vec::with_capacity(5);
+        for &kind in visible_segments {
+            let (icon, value, threshold, load, suffix) = match kind {
+                SegmentKind::Memory => (IconType::Memory, mem_percent, thresholds.mem, None, None),
+                SegmentKind::Cpu => (IconType::Cpu, cpu_usage, thresholds.cpu, cpu_load, None),
+                SegmentKind::Gpu => (IconType::Gpu, gpu_usage, thresholds.gpu, None, gpu_mem_str),
+                SegmentKind::Network if combine_net => {
+                    let (down, up) = (compact_rate(down_str), compact_rate(up_str));
+                    segments.push(Segment {
+                        icon: IconType::ArrowDown,
+                        width: small_icon * 2
+                            + rate_width(&down)
+                            + sizing.segment_gap / 2
+                            + rate_width(&up),
+                        value: down,
+                        alert: false,
+                        color: metric_color(kind),
+                        bar: None,
+                        bar_solid: None,
+                        bar_mid: None,
+                        up: Some(up),
+                        heatmap: None,
+                    });
+                    continue;
+                }
+                SegmentKind::Network => {
+                    segments.push(Segment {
+                        icon: IconType::ArrowDown,
+                        value: down_str.to_owned(),
+                        width: sizing.segment_width_net,
+                        alert: false,
+                        color: metric_color(kind),
+                        bar: None,
+                        bar_solid: None,
+                        bar_mid: None,
+                        up: None,
+                        heatmap: None,
+                    });
+                    segments.push(Segment {
+                        icon: IconType::ArrowUp,
+                        value: up_str.to_owned(),
+                        width: sizing.segment_width_net,
+                        alert: false,
+                        color: metric_color(kind),
+                        bar: None,
+                        bar_solid: None,
+                        bar_mid: None,
+                        up: None,
+                        heatmap: None,
+                    });
+                    continue;
+                }
+            };
+
+            let used = mem_used_str.filter(|_| kind == SegmentKind::Memory);
+            let split = cpu_split.filter(|_| kind == SegmentKind::Cpu && load.is_none());
+            let heatmap = cpu_heatmap.filter(|_| kind == SegmentKind::Cpu && load.is_none());
+            // A memory breakdown is always a bar, as a number can't show it
+            let breakdown = mem_breakdown
+                .filter(|_| kind == SegmentKind::Memory && suffix.is_none())
+                .map(|breakdown| breakdown.stops());
+            // Only plain percentages become bars; load averages, sizes and
+            // combined segments keep their numbers
+            let bar = (breakdown.is_some()
+                || value_style != ValueStyle::Number
+                    && heatmap.is_none()
+                    && load.is_none()
+                    && used.is_none()
+                    && suffix.is_none())
+            .then(|| match (breakdown, split) {
+                (Some([.., cached]), _) => cached,
+                (None, Some((user, system))) => (user + system).clamp(0.0, 100.0) / 100.0,
+                (None, None) => value.clamp(0.0, 100.0) / 100.0,
+            });
+            let bar_solid = match breakdown {
+                Some([apps, ..]) => Some(apps),
+                None => split
+                    .filter(|_| bar.is_some())
+                    .map(|(user, _)| user.clamp(0.0, 100.0) / 100.0),
+            };
+            let bar_mid = breakdown.map(|[_, wired, _]| wired);
+
+            let (shown, overflow) = match load {
+                Some(load) => (format_load_average(load), 0),
+                None if bar.is_some() => (String::new(), 0),
+                None => {
+                    let shown = match (used, split) {
+                        (Some(used), _) => used.to_owned(),
+                        (None, Some((user, system))) => {
+                            format_cpu_split(user, system, percent_cap, cpu_cores)
+                        }
+                        (None, None) => {
+                            let is_cpu = kind == SegmentKind::Cpu;
+                            format_percent(value, percent_cap, is_cpu, cpu_cores)
+                        }
+                    };
+                    let width = text_width(font, scale, &widest_digits(&shown, tabular), tabular);
+                    (shown, (width - two_digit_width).max(0.0).ceil() as u32)
+                }
+            };
+            // Combined "45% · 6.2G" segments need room for the extra value
+            let (text, width) = match suffix {
+                Some(suffix) => (
+                    format!("{shown} · {suffix}"),
+                    sizing.segment_width + sizing.segment_width_net + overflow,
+                ),
+                None if value_style == ValueStyle::VerticalBar && bar.is_some() => {
+                    (shown, sizing.icon_height + bar_gap + bar_thickness)
+                }
+                None if heatmap.is_some() => {
+                    let cells = heatmap.map_or(1, |cores| cores.cells().len());
+                    let grid = heatmap_width(cells, sizing.icon_height);
+                    (shown, sizing.icon_height + bar_gap + grid)
+                }
+                None => (shown, sizing.segment_width + overflow),
+            };
+            // Under memory pressure mode the level, not the percentage, alerts
+            let pressure = mem_pressure.filter(|_| kind == SegmentKind::Memory);
+            segments.push(Segment {
+                icon,
+                value: text,
+                width,
+                alert: match pressure {
+                    Some(level) => level == MemoryPressure::Critical,
+                    None => value >= threshold,
+                },
+                color: pressure.and_then(pressure_color).or(metric_color(kind)),
+                bar,
+                bar_solid,
+                bar_mid,
+                up: None,
+                heatmap,
+            });
+        }
+
+        for extra in extra_segments {
+            segments.push(Segment {
+                icon: extra.icon,
+                value: extra.text.clone(),
+                width: sizing.segment_width_net,
+                alert: extra.alert,
+                color: None,
+                bar: None,
+                bar_solid: None,
+                bar_mid: None,
+                up: None,
+                heatmap: None,
+            });
+        }
+
+        let has_active_alert = show_alerts && segments.iter().any(|s| s.alert);
+
+        // Compact text is right-aligned in room for its widest digits, so the
+        // icon only changes width when the number of characters does
+        let compact = (layout == Layout::Compact).then(|| {
+            let text = compact_text(segments.iter().map(|s| (s.icon, s.value.as_str())));
+            let width =
+                text_width(font, scale, &widest_digits(&text, tabular), tabular).ceil() as u32;
+            (text, width)
+        });
+
+        // The widths above leave an icon's room before each value
+        if label_style != LabelStyle::Icons {
+            for segment in segments.iter_mut() {
+                segment.width = match segment.up {
+                    Some(_) => (segment.width
+                        + lead_width(IconType::ArrowDown, small_icon)
+                        + lead_width(IconType::ArrowUp, small_icon))
+                    .saturating_sub(small_icon * 2),
+                    None => (segment.width + lead_width(segment.icon, sizing.icon_height))
+                        .saturating_sub(sizing.icon_height),
+                };
+            }
+        }
+
+        // Icon, a small gap, then room for the value's widest digits
+        if let Some(fitted) = self.fitted.as_mut().filter(|_| layout == Layout::Standard) {
+            for segment in segments.iter_mut() {
+                if segment.bar.is_some() || segment.up.is_some() || segment.heatmap.is_some() {
+                    continue;
+                }
+                let text = text_width(
+                    font,
+                    scale,
+                    &widest_digits(&segment.value, tabular),
+                    tabular,
+                )
+                .ceil() as u32;
+                let lead = lead_width(segment.icon, sizing.icon_height);
+                segment.width = fitted.fit(segment.icon, lead + bar_gap + text);
+            }
+        }
+
+        let icon_only = layout == Layout::IconOnly;
+        if icon_only {
+            for segment in &mut segments {
+                segment.width = lead_width(segment.icon, sizing.icon_height);
+            }
+        }
+
+        let total_width = match &compact {
+            Some((_, width)) => sizing.edge_padding * 2 + width,
+            None => {
+                sizing.edge_padding * 2
+                    + segments.iter().map(|s| s.width).sum::<u32>()
+                    + sizing.segment_gap * (segments.len() as u32).saturating_sub(1)
+            }
+        };
+
+        let key = FrameKey {
+            sizing: [
+                sizing.segment_width,
+                sizing.segment_width_net,
+                sizing.edge_padding,
+                sizing.segment_gap,
+                sizing.icon_height,
+                sizing.font_size.to_bits(),
+            ],
+            layout,
+            value_style,
+            show_alerts,
+            palette,
+            background: background.map(|bg| bg.rgba),
+            tabular,
+            label_style,
+            segments: segments
+                .iter()
+                .map(|s| {
+                    let text = match &s.up {
+                        Some(up) => format!("{}\n{up}", s.value),
+                        None => s.value.clone(),
+                    };
+                    let bar = s.bar.map(|fill| {
+                        (
+                            fill.to_bits(),
+                            s.bar_solid.unwrap_or(fill).to_bits(),
+                            s.bar_mid.unwrap_or(fill).to_bits(),
+                        )
+                    });
+                    (s.icon, text, s.width, s.alert, s.color, bar, s.heatmap)
+                })
+                .collect(),
+        };
+        if let Some(frame) = self.frames.get(&key) {
+            buffer.clear();
+            buffer.extend_from_slice(&frame.pixels);
+            return (frame.width, frame.height, frame.alert);
+        }
+
+        let required_size = (total_width * sizing.icon_height * 4) as usize;
+        buffer.clear();
+        buffer.resize(required_size, 0);
+
+        let mut img: ImageBuffer<Rgba<u8>, Vec<u8>> =
+            ImageBuffer::from_raw(total_width, sizing.icon_height, std::mem::take(buffer))
+                .expect("buffer size matches dimensions");
+
+        if let Some(bg) = background {
+            let (r, g, b, a) = bg.rgba;
+            for pixel in img.pixels_mut() {
+                *pixel = Rgba([r, g, b, a]);
+            }
+        }
+
+        let baseline = self.baseline(font, sizing);
+
+        let mut colors = vec![palette.foreground, palette.alert];
+        colors.extend(segments.iter().filter_map(|s| s.color));
+        // Taken out while drawing, as the icon cache keeps `self` borrowed
+        let mut glyph_cache = std::mem::take(&mut self.glyphs);
+        if segments.iter().any(|s| s.up.is_some()) {
+            self.icon_cache_mut(small_icon, &colors);
+        }
+        self.icon_cache_mut(sizing.icon_height, &colors);
+        let icon_cache = &self.icon_caches[&sizing.icon_height];
+        let small_icon_cache = self.icon_caches.get(&small_icon);
+
+        // One logical pixel: 1px on a standard Linux panel, 2px on Retina
+        let edge_offsets = palette
+            .text_effect
+            .offsets((sizing.icon_height / SIZING_LINUX.icon_height).max(1) as i32);
+
+        let mut draw_text = |text: &str,
+                             start_x: f32,
+                             color: (u8, u8, u8),
+                             background: Option<Background>,
+                             img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>| {
+            let glyphs = glyph_cache.layout(
+                font,
+                scale,
+                text,
+                rusttype::point(start_x, baseline),
+                tabular,
+            );
+            let pixel_in_bounds = |x: i32, y: i32| {
+                (x >= 0 && y >= 0 && (x as u32) < total_width && (y as u32) < sizing.icon_height)
+                    .then_some((x as u32, y as u32))
+            };
+
+            // Edge pass first so the text itself is drawn on top
+            let edge = contrast_color(color);
+            for &glyph in &glyphs {
+                for (gx, gy, alpha) in glyph_cache.pixels(glyph) {
+                    for &(dx, dy) in &edge_offsets {
+                        let Some((x, y)) = pixel_in_bounds(gx + dx, gy + dy) else {
+                            continue;
+                        };
+                        let dst = img.get_pixel_mut(x, y);
+                        if background.is_some() {
+                            blend_over(dst, edge, alpha);
+                        } else if dst[3] < alpha {
+                            // Overlapping copies keep the strongest coverage
+                            *dst = Rgba([edge.0, edge.1, edge.2, alpha]);
+                        }
+                    }
+                }
+            }
+
+            for &glyph in &glyphs {
+                for (gx, gy, alpha) in glyph_cache.pixels(glyph) {
+                    let Some((x, y)) = pixel_in_bounds(gx, gy) else {
+                        continue;
+                    };
+                    if background.is_some() || !edge_offsets.is_empty() {
+                        let dst = img.get_pixel_mut(x, y);
+                        blend_over(dst, color, alpha);
+                    } else {
+                        img.put_pixel(x, y, Rgba([color.0, color.1, color.2, alpha]));
+                    }
+                }
+            }
+        };
+
+        let draw_cached_icon =
+            |cache: &IconCache,
+             icon_type: IconType,
+             (start_x, start_y): (u32, u32),
+             color: (u8, u8, u8),
+             background: Option<Background>,
+             img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>| {
+                let icon_pixels = cache.get(icon_type, color);
+                let size = cache.size;
+
+                for y in 0..size {
+                    for x in 0..size {
+                        let src_idx = ((y * size + x) * 4) as usize;
+                        if src_idx + 3 < icon_pixels.len() {
+                            let alpha = icon_pixels[src_idx + 3];
+                            if alpha > 0 {
+                                let dst_x = start_x + x;
+                                let dst_y = start_y + y;
+                                if dst_x < total_width && dst_y < sizing.icon_height {
+                                    if background.is_some() {
+                                        let dst = img.get_pixel_mut(dst_x, dst_y);
+                                        blend_over(
+                                            dst,
+                                            (
+                                                icon_pixels[src_idx],
+                                                icon_pixels[src_idx + 1],
+                                                icon_pixels[src_idx + 2],
+                                            ),
+                                            alpha,
+                                        );
+                                    } else {
+                                        img.put_pixel(
+                                            dst_x,
+                                            dst_y,
+                                            Rgba([
+                                                icon_pixels[src_idx],
+                                                icon_pixels[src_idx + 1],
+                                                icon_pixels[src_idx + 2],
+                                                alpha,
+                                            ]),
+                                        );
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            };
+
+        let base_color = palette.foreground;
+        let segment_color = if has_active_alert {
+            palette.alert
+        } else {
+            base_color
+        };
+
+        if let Some((text, width)) = &compact {
+            let x = (sizing.edge_padding + width) as f32 - text_width(font, scale, text, tabular);
+            draw_text(text, x, segment_color, background, &mut img);
+            self.glyphs = glyph_cache;
+            *buffer = img.into_raw();
+            self.frames.insert(
+                key,
+                buffer,
+                total_width,
+                sizing.icon_height,
+                has_active_alert,
+            );
+            return (total_width, sizing.icon_height, has_active_alert);
+        }
+
+        let mut x_offset = sizing.edge_padding;
+        for (i, segment) in segments.iter().enumerate() {
+            if i > 0 {
+                x_offset += sizing.segment_gap;
+            }
+
+            if icon_only {
+                // Without values, only the offending segment is colored so it
+                // stays clear which metric is over its threshold
+                let color = if show_alerts && segment.alert {
+                    palette.alert
+                } else {
+                    segment.color.unwrap_or(base_color)
+                };
+                if label_style != LabelStyle::Text {
+                    draw_cached_icon(
+                        icon_cache,
+                        segment.icon,
+                        (x_offset, 0),
+                        color,
+                        background,
+                        &mut img,
+                    );
+                }
+                if label_style != LabelStyle::Icons {
+                    let x = label_x(x_offset, sizing.icon_height);
+                    draw_text(segment.icon.label(), x, color, background, &mut img);
+                }
+                x_offset += segment.width;
+                continue;
+            }
+
+            let color = if has_active_alert {
+                segment_color
+            } else {
+                segment.color.unwrap_or(base_color)
+            };
+            if let (Some(up), Some(small_icons)) = (&segment.up, small_icon_cache) {
+                // "↓1.5M ↑0.2M", each rate right-aligned after its arrow
+                let top = (sizing.icon_height - small_icon) / 2;
+                let mut x = x_offset;
+                for (icon, rate) in [
+                    (IconType::ArrowDown, &segment.value),
+                    (IconType::ArrowUp, up),
+                ] {
+                    if label_style != LabelStyle::Text {
+                        draw_cached_icon(small_icons, icon, (x, top), color, background, &mut img);
+                    }
+                    if label_style != LabelStyle::Icons {
+                        let label_x = label_x(x, small_icon);
+                        draw_text(icon.label(), label_x, color, background, &mut img);
+                    }
+                    x += lead_width(icon, small_icon) + rate_width(rate);
+                    let rate_x = x as f32 - text_width(font, scale, rate, tabular);
+                    draw_text(rate, rate_x, color, background, &mut img);
+                    x += sizing.segment_gap / 2;
+                }
+                x_offset += segment.width;
+                continue;
+            }
+            if label_style != LabelStyle::Text {
+                draw_cached_icon(
+                    icon_cache,
+                    segment.icon,
+                    (x_offset, 0),
+                    color,
+                    background,
+                    &mut img,
+                );
+            }
+            if label_style != LabelStyle::Icons {
+                let x = label_x(x_offset, sizing.icon_height);
+                draw_text(segment.icon.label(), x, color, background, &mut img);
+            }
+
+            if let Some(fill) = segment.bar {
+                let start = x_offset + lead_width(segment.icon, sizing.icon_height) + bar_gap;
+                let (x, y, width, height) = match value_style {
+                    ValueStyle::VerticalBar => (
+                        start,
+                        bar_gap,
+                        bar_thickness,
+                        sizing.icon_height - bar_gap * 2,
+                    ),
+                    _ => (
+                        start,
+                        (sizing.icon_height - bar_thickness) / 2,
+                        (x_offset + segment.width).saturating_sub(start),
+                        bar_thickness,
+                    ),
+                };
+                let solid = segment.bar_solid.unwrap_or(fill).min(fill);
+                let stops: &[(f32, u8)] = match segment.bar_mid {
+                    Some(mid) => &[
+                        (solid, 255),
+                        (mid.min(fill), BAR_SECONDARY_ALPHA),
+                        (fill, BAR_TERTIARY_ALPHA),
+                    ],
+                    None => &[(solid, 255), (fill, BAR_SECONDARY_ALPHA)],
+                };
+                draw_bar(
+                    &mut img,
+                    (x, y, width, height),
+                    stops,
+                    value_style,
+                    color,
+                    background,
+                );
+                x_offset += segment.width;
+                continue;
+            }
+
+            if let Some(cores) = segment.heatmap {
+                let x = x_offset + lead_width(segment.icon, sizing.icon_height) + bar_gap;
+                // Cores over the threshold take the alert color on their own
+                let alert_color = show_alerts.then_some(palette.alert);
+                draw_heatmap(
+                    &mut img,
+                    (x, sizing.icon_height),
+                    &cores,
+                    (color, alert_color),
+                    thresholds.cpu,
+                    background,
+                );
+                x_offset += segment.width;
+                continue;
+            }
+
+            let value_width = text_width(font, scale, &segment.value, tabular);
+            let value_x = x_offset as f32 + segment.width as f32 - value_width;
+            draw_text(&segment.value, value_x, color, background, &mut img);
+
+            x_offset += segment.width;
+        }
+
+        self.glyphs = glyph_cache;
+        *buffer = img.into_raw();
+        self.frames.insert(
+            key,
+            buffer,
+            total_width,
+            sizing.icon_height,
+            has_active_alert,
+        );
+        (total_width, sizing.icon_height, has_active_alert)
+    }
+}
+
+/// Alpha of the unfilled part of a bar, enough to show its extent.
+const BAR_TRACK_ALPHA: u8 = 80;
+/// Alpha of the filled part past the solid share, e.g. system CPU time.
+const BAR_SECONDARY_ALPHA: u8 = 160;
+/// Alpha of the third share of a memory breakdown bar, cached memory.
+const BAR_TERTIARY_ALPHA: u8 = 115;
+
+/// Draws a bar in `rect` (x, y, width, height): the track faintly, then each
+/// of `stops` from the left, or from the bottom for vertical bars. A stop is
+/// where a share ends, as a fraction of the bar, and its alpha, so user and
+/// system CPU read as two tones and a memory breakdown as three.
+fn draw_bar(
+    img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>,
+    (x, y, width, height): (u32, u32, u32, u32),
+    stops: &[(f32, u8)],
+    style: ValueStyle,
+    color: Rgb,
+    background: Option<Background>,
+) {
+    let filled = |length: u32, share: f32| (length as f32 * share.clamp(0.0, 1.0)).round() as u32;
+    let is_within = |dx: u32, dy: u32, share: f32| match style {
+        ValueStyle::VerticalBar => height - dy <= filled(height, share),
+        _ => dx < filled(width, share),
+    };
+
+    for dy in 0..height {
+        for dx in 0..width {
+            let (px, py) = (x + dx, y + dy);
+            if px >= img.width() || py >= img.height() {
+                continue;
+            }
+            let alpha = stops
+                .iter()
+                .find(|(end, _)| is_within(dx, dy, *end))
+                .map_or(BAR_TRACK_ALPHA, |(_, alpha)| *alpha);
+            if background.is_some() {
+                blend_over(img.get_pixel_mut(px, py), color, alpha);
+            } else {
+                img.put_pixel(px, py, Rgba([color.0, color.1, color.2, alpha]));
+            }
+        }
+    }
+}
+
+/// Draws `cores` as a [`heatmap_grid`] from `x`, vertically centered in an icon
+/// `height` tall, cells filled row by row. A cell's opacity follows its core's
+/// usage over the faint track of a bar; cores at or past `threshold` are drawn
+/// solid in `alert` when given.
+fn draw_heatmap(
+    img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>,
+    (x, height): (u32, u32),
+    cores: &CoreUsage,
+    (color, alert): (Rgb, Option<Rgb>),
+    threshold: f32,
+    background: Option<Background>,
+) {
+    let cells = cores.cells();
+    let (rows, columns, cell) = heatmap_grid(cells.len(), height);
+    let top = height.saturating_sub(rows * (cell + 1) - 1) / 2;
+    for (index, &usage) in cells.iter().enumerate() {
+        let (row, column) = (index as u32 / columns, index as u32 % columns);
+        let (cell_color, alpha) = match alert {
+            Some(alert) if usage as f32 >= threshold => (alert, 255),
+            _ => {
+                let range = (255 - BAR_TRACK_ALPHA) as u32;
+                (color, BAR_TRACK_ALPHA + (usage as u32 * range / 100) as u8)
+            }
+        };
+        let (left, cell_top) = (x + column * (cell + 1), top + row * (cell + 1));
+        for py in cell_top..cell_top + cell {
+            for px in left..left + cell {
+                if px >= img.width() || py >= img.height() {
+                    continue;
+                }
+                if background.is_some() {
+                    blend_over(img.get_pixel_mut(px, py), cell_color, alpha);
+                } else {
+                    img.put_pixel(
+                        px,
+                        py,
+                        Rgba([cell_color.0, cell_color.1, cell_color.2, alpha]),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Copies rendered rows, each given as pixels, width and top edge, left-aligned
+/// into a `width` × `height` image in `buffer`.
+fn stack_rows(
+    buffer: &mut Vec<u8>,
+    width: u32,
+    height: u32,
+    background: Option<Background>,
+    rows: [(&[u8], u32, u32); 2],
+) {
+    let fill = background.map_or([0; 4], |bg| {
+        let (r, g, b, a) = bg.rgba;
+        [r, g, b, a]
+    });
+    buffer.clear();
+    for _ in 0..width * height {
+        buffer.extend_from_slice(&fill);
+    }
+
+    for (pixels, row_width, top) in rows {
+        let stride = row_width as usize * 4;
+        for (y, line) in pixels.chunks_exact(stride).enumerate() {
+            let start = ((top as usize + y) * width as usize) * 4;
+            buffer[start..start + stride].copy_from_slice(line);
+        }
+    }
+}
+
+/// Glyph layout on its own, for `benches/render.rs`.
+#[doc(hidden)]
+pub mod bench {
+    use rusttype::{Font, Scale};
+
+    use super::{GlyphCache, Sizing};
+
+    #[derive(Default)]
+    pub struct Glyphs(GlyphCache);
+
+    impl Glyphs {
+        /// Lays out `text` at the preset's font size, rasterizing glyphs not
+        /// cached yet, and returns how many were placed.
+        pub fn layout(&mut self, font: &Font, sizing: Sizing, text: &str) -> usize {
+            let scale = Scale::uniform(sizing.font_size);
+            self.0
+                .layout(
+                    font,
+                    scale,
+                    text,
+                    rusttype::point(0.0, sizing.font_size),
+                    false,
+                )
+                .len()
+        }
+
+        pub fn clear(&mut self) {
+            self.0.rasters.clear();
+        }
+    }
+}
+
+fn text_width(font: &Font, scale: Scale, text: &str, tabular: bool) -> f32 {
+    if tabular {
+        let cell = digit_cell(font, scale);
+        return text
+            .chars()
+            .map(|c| {
+                if c.is_ascii_digit() {
+                    cell
+                } else {
+                    font.glyph(c).scaled(scale).h_metrics().advance_width
+                }
+            })
+            .sum();
+    }
+    font.layout(text, scale, rusttype::point(0.0, 0.0))
+        .map(|g| g.unpositioned().h_metrics().advance_width)
+        .sum()
+}
+
+/// Advance of the widest digit, the cell every digit gets with tabular figures.
+fn digit_cell(font: &Font, scale: Scale) -> f32 {
+    ('0'..='9')
+        .map(|c| font.glyph(c).scaled(scale).h_metrics().advance_width)
+        .fold(0.0, f32::max)
+}
+
+/// `text` laid out with each digit centered in a [`digit_cell`], for fonts
+/// whose digits are proportional. Kerning is left out, as pairs involving a
+/// digit would break the fixed cells.
+fn tabular_layout<'f>(
+    font: &'f Font,
+    scale: Scale,
+    text: &str,
+    start: Point<f32>,
+) -> Vec<PositionedGlyph<'f>> {
+    let cell = digit_cell(font, scale);
+    let mut x = start.x;
+    text.chars()
+        .map(|c| {
+            let glyph = font.glyph(c).scaled(scale);
+            let advance = glyph.h_metrics().advance_width;
+            let (offset, step) = if c.is_ascii_digit() {
+                ((cell - advance) / 2.0, cell)
+            } else {
+                (0.0, advance)
+            };
+            let positioned = glyph.positioned(rusttype::point(x + offset, start.y));
+            x += step;
+            positioned
+        })
+        .collect()
+}
+
+/// Joins segment values for the compact layout: percentages lose their "%",
+/// rates become "1.5M↓", and the network pair is set apart with " | ".
+/// "1.5 MB" as "1.5M"; bit rates keep their "b".
+fn compact_rate(rate: &str) -> String {
+    rate.replace(' ', "").trim_end_matches('B').to_string()
+}
+
+pub fn compact_text<'a>(values: impl IntoIterator<Item = (IconType, &'a str)>) -> String {
+    let mut groups: Vec<Vec<String>> = Vec::new();
+    let mut prev_is_net = None;
+    for (icon, value) in values {
+        let is_net = matches!(icon, IconType::ArrowDown | IconType::ArrowUp);
+        let text = match icon {
+            IconType::ArrowDown => format!("{}↓", compact_rate(value)),
+            IconType::ArrowUp => format!("{}↑", compact_rate(value)),
+            _ => value.replace('%', "").replace(" · ", "·"),
+        };
+        match groups.last_mut() {
+            Some(group) if prev_is_net == Some(is_net) => group.push(text),
+            _ => groups.push(vec![text]),
+        }
+        prev_is_net = Some(is_net);
+    }
+
+    groups
+        .iter()
+        .map(|group| group.join(" "))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+fn blend_over(dst: &mut Rgba<u8>, src_rgb: (u8, u8, u8), src_alpha: u8) {
+    let (sr, sg, sb) = src_rgb;
+    let sa = src_alpha as u32;
+
+    let dr = dst[0] as u32;
+    let dg = dst[1] as u32;
+    let db = dst[2] as u32;
+    let da = dst[3] as u32;
+
+    let out_a = sa + (da * (255 - sa) + 127) / 255;
+    if out_a == 0 {
+        *dst = Rgba([0, 0, 0, 0]);
+        return;
+    }
+
+    let src_r_p = (sr as u32 * sa + 127) / 255;
+    let src_g_p = (sg as u32 * sa + 127) / 255;
+    let src_b_p = (sb as u32 * sa + 127) / 255;
+
+    let dst_r_p = (dr * da + 127) / 255;
+    let dst_g_p = (dg * da + 127) / 255;
+    let dst_b_p = (db * da + 127) / 255;
+
+    let out_r_p = src_r_p + (dst_r_p * (255 - sa) + 127) / 255;
+    let out_g_p = src_g_p + (dst_g_p * (255 - sa) + 127) / 255;
+    let out_b_p = src_b_p + (dst_b_p * (255 - sa) + 127) / 255;
+
+    let out_r = (out_r_p * 255 + out_a / 2) / out_a;
+    let out_g = (out_g_p * 255 + out_a / 2) / out_a;
+    let out_b = (out_b_p * 255 + out_a / 2) / out_a;
+
+    *dst = Rgba([
+        out_r.min(255) as u8,
+        out_g.min(255) as u8,
+        out_b.min(255) as u8,
+        out_a.min(255) as u8,
+    ]);
+}
+
+#[cfg(test)]
+mod tests;
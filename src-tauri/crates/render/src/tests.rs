@@ -0,0 +1,1267 @@
+use super::*;
+
+#[cfg(target_os = "macos")]
+const HOST_SIZING: Sizing = SIZING_MACOS;
+#[cfg(not(target_os = "macos"))]
+const HOST_SIZING: Sizing = SIZING_LINUX;
+
+#[test]
+fn test_cap_percent() {
+    assert_eq!(cap_percent(0.0), 0.0);
+    assert_eq!(cap_percent(50.0), 50.0);
+    assert_eq!(cap_percent(99.0), 99.0);
+    assert_eq!(cap_percent(100.0), 99.0);
+    assert_eq!(cap_percent(150.0), 99.0);
+    assert_eq!(cap_percent(-10.0), 0.0);
+}
+
+#[test]
+fn test_format_percent_caps() {
+    assert_eq!(format_percent(100.0, PercentCap::Capped, true, 8), "99%");
+    assert_eq!(format_percent(100.0, PercentCap::Full, true, 8), "100%");
+    assert_eq!(format_percent(130.0, PercentCap::Full, false, 8), "100%");
+    // Per-core scales CPU only: half of 8 cores is 400%
+    assert_eq!(format_percent(50.0, PercentCap::PerCore, true, 8), "400%");
+    assert_eq!(format_percent(50.0, PercentCap::PerCore, false, 8), "50%");
+    assert_eq!(format_percent(120.0, PercentCap::PerCore, true, 2), "200%");
+    assert_eq!(format_percent(-5.0, PercentCap::PerCore, true, 2), "0%");
+}
+
+#[test]
+fn test_render_widens_for_three_digit_percent() {
+    let font = fallback_font();
+    let mut renderer = TrayRenderer::new();
+    let mut width_for = |cpu: f32, cap: PercentCap| {
+        let mut buffer = Vec::new();
+        let (width, _, _) = renderer.render_tray_icon_into(
+            &font,
+            &mut buffer,
+            &RenderParams {
+                cpu_usage: cpu,
+                visible_segments: &[SegmentKind::Cpu],
+                percent_cap: cap,
+                cpu_cores: 8,
+                show_alerts: false,
+                ..RenderParams::new(HOST_SIZING)
+            },
+        );
+        width
+    };
+
+    let capped = width_for(100.0, PercentCap::Capped);
+    assert_eq!(width_for(45.0, PercentCap::Full), capped);
+    assert!(width_for(100.0, PercentCap::Full) > capped);
+    // "400%" of 8 cores takes the same room as "100%"
+    assert_eq!(
+        width_for(50.0, PercentCap::PerCore),
+        width_for(100.0, PercentCap::Full)
+    );
+}
+
+#[test]
+fn test_compact_text() {
+    assert_eq!(
+        compact_text([
+            (IconType::Cpu, "34%"),
+            (IconType::Memory, "62%"),
+            (IconType::Gpu, "18% · 6.2G"),
+            (IconType::ArrowDown, "1.5 MB"),
+            (IconType::ArrowUp, "0.2 MB"),
+        ]),
+        "34 62 18·6.2G | 1.5M↓ 0.2M↑"
+    );
+    // Network first, bits keep their lowercase unit
+    assert_eq!(
+        compact_text([
+            (IconType::ArrowDown, "12 Mb"),
+            (IconType::ArrowUp, "0 Kb"),
+            (IconType::Cpu, "1.3"),
+            (IconType::Power, "4.3W"),
+        ]),
+        "12Mb↓ 0Kb↑ | 1.3 4.3W"
+    );
+    assert_eq!(compact_text([]), "");
+}
+
+#[test]
+fn test_format_load_average() {
+    assert_eq!(format_load_average(0.0), "0.0");
+    assert_eq!(format_load_average(1.26), "1.3");
+    assert_eq!(format_load_average(12.04), "12.0");
+    assert_eq!(format_load_average(128.4), "128");
+    assert_eq!(format_load_average(-1.0), "0.0");
+}
+
+#[test]
+fn test_format_cpu_split() {
+    assert_eq!(format_cpu_split(23.4, 8.2, PercentCap::Capped, 8), "23/8%");
+    assert_eq!(
+        format_cpu_split(25.0, 12.5, PercentCap::PerCore, 8),
+        "200/100%"
+    );
+}
+
+#[test]
+fn test_fallback_font_covers_tray_text() {
+    let font = fallback_font();
+    for c in "0123456789.% KMGTBbit/sW°Cµ<—".chars() {
+        assert_ne!(font.glyph(c).id().0, 0, "missing glyph for {c:?}");
+    }
+
+    let mut buffer: Vec<u8> = Vec::new();
+    let (width, _, _) = TrayRenderer::new().render_tray_icon_into(
+        &font,
+        &mut buffer,
+        &RenderParams {
+            cpu_usage: 45.0,
+            mem_percent: 60.0,
+            down_str: "1.5 MB",
+            up_str: "0.2 MB",
+            ..RenderParams::new(HOST_SIZING)
+        },
+    );
+    assert!(width > 0);
+    assert!(buffer.iter().skip(3).step_by(4).any(|&alpha| alpha > 0));
+}
+
+#[test]
+fn test_alert_thresholds_per_metric() {
+    let font = fallback_font();
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut renderer = TrayRenderer::new();
+    let thresholds = AlertThresholds {
+        cpu: 95.0,
+        mem: 60.0,
+        gpu: 90.0,
+    };
+
+    // Memory at 70% crosses its lowered threshold
+    let (_, _, mem_alert) = renderer.render_tray_icon_into(
+        &font,
+        &mut buffer,
+        &RenderParams {
+            cpu_usage: 50.0,
+            mem_percent: 70.0,
+            down_str: "0 KB",
+            up_str: "0 KB",
+            visible_segments: &[SegmentKind::Memory, SegmentKind::Cpu],
+            thresholds,
+            ..RenderParams::new(HOST_SIZING)
+        },
+    );
+    assert!(mem_alert);
+
+    // CPU at 92% stays below its raised threshold
+    let (_, _, cpu_alert) = renderer.render_tray_icon_into(
+        &font,
+        &mut buffer,
+        &RenderParams {
+            cpu_usage: 92.0,
+            mem_percent: 50.0,
+            down_str: "0 KB",
+            up_str: "0 KB",
+            visible_segments: &[SegmentKind::Memory, SegmentKind::Cpu],
+            thresholds,
+            ..RenderParams::new(HOST_SIZING)
+        },
+    );
+    assert!(!cpu_alert);
+}
+
+#[test]
+fn test_render_uses_custom_alert_color() {
+    let font = fallback_font();
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut renderer = TrayRenderer::new();
+    let palette = Palette {
+        foreground: (255, 255, 255),
+        alert: (0, 255, 0),
+        ..Palette::default()
+    };
+
+    let (_, _, has_alert) = renderer.render_tray_icon_into(
+        &font,
+        &mut buffer,
+        &RenderParams {
+            cpu_usage: 99.0,
+            down_str: "0 KB",
+            up_str: "0 KB",
+            visible_segments: &[SegmentKind::Cpu],
+            palette,
+            ..RenderParams::new(HOST_SIZING)
+        },
+    );
+    assert!(has_alert);
+    assert!(buffer.chunks_exact(4).any(|px| px == [0, 255, 0, 255]));
+    assert!(!buffer
+        .chunks_exact(4)
+        .any(|px| px[3] > 0 && px[..3] == [255, 255, 255]));
+}
+
+#[test]
+fn test_render_metric_colors() {
+    let font = fallback_font();
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut renderer = TrayRenderer::new();
+    let colors = MetricColors::DEFAULT;
+    let mut render = |cpu: f32| {
+        renderer.render_tray_icon_into(
+            &font,
+            &mut buffer,
+            &RenderParams {
+                cpu_usage: cpu,
+                mem_percent: 40.0,
+                down_str: "1.5 MB",
+                up_str: "0.2 MB",
+                visible_segments: &[SegmentKind::Cpu, SegmentKind::Memory, SegmentKind::Network],
+                palette: Palette {
+                    metric_colors: Some(colors),
+                    ..Palette::default()
+                },
+                ..RenderParams::new(HOST_SIZING)
+            },
+        );
+        buffer.clone()
+    };
+    let has_color =
+        |buffer: &[u8], (r, g, b): Rgb| buffer.chunks_exact(4).any(|px| px == [r, g, b, 255]);
+
+    let calm = render(20.0);
+    for color in [colors.cpu, colors.mem, colors.net] {
+        assert!(has_color(&calm, color));
+    }
+    assert!(!calm
+        .chunks_exact(4)
+        .any(|px| px[3] > 0 && px[..3] == [255, 255, 255]));
+
+    // An alert still turns the whole icon the alert color
+    let alert = render(99.0);
+    assert!(has_color(&alert, DEFAULT_ALERT_COLOR));
+    assert!(!has_color(&alert, colors.mem));
+}
+
+#[test]
+fn test_hex_colors() {
+    assert_eq!(parse_hex_color("#D14715"), Some((209, 71, 21)));
+    assert_eq!(parse_hex_color("00ff7f"), Some((0, 255, 127)));
+    assert_eq!(parse_hex_color("#FFF"), None);
+    assert_eq!(parse_hex_color("#GG0000"), None);
+    assert_eq!(format_hex_color((0, 255, 127)), "#00FF7F");
+}
+
+#[test]
+fn test_render_svg_icon_valid() {
+    // Simple valid SVG
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24"><circle cx="12" cy="12" r="10" fill="currentColor"/></svg>"#;
+    let result = render_svg_icon(svg, 16, (255, 255, 255));
+
+    // Should return non-empty pixel data
+    assert!(!result.is_empty());
+
+    // 16x16 RGBA = 1024 bytes
+    assert_eq!(result.len(), 16 * 16 * 4);
+}
+
+#[test]
+#[should_panic(expected = "Failed to parse SVG")]
+fn test_render_svg_icon_invalid_panics() {
+    // Invalid SVG should panic (current behavior uses .expect())
+    render_svg_icon("not valid svg", 16, (255, 255, 255));
+}
+
+#[test]
+fn test_icon_buffer_reuse() {
+    let font = fallback_font();
+
+    let mut renderer = TrayRenderer::new();
+
+    // Create buffer with known capacity
+    let mut buffer: Vec<u8> = Vec::with_capacity(4 * 800 * HOST_SIZING.icon_height as usize);
+    let initial_capacity = buffer.capacity();
+
+    // First render
+    let (width1, height1, _) = renderer.render_tray_icon_into(
+        &font,
+        &mut buffer,
+        &RenderParams {
+            cpu_usage: 50.0,
+            mem_percent: 60.0,
+            down_str: "1.0 KB",
+            up_str: "0.5 KB",
+            visible_segments: &[SegmentKind::Memory, SegmentKind::Cpu, SegmentKind::Network],
+            show_alerts: false,
+            ..RenderParams::new(HOST_SIZING)
+        },
+    );
+    assert!(width1 > 0);
+    assert_eq!(height1, HOST_SIZING.icon_height);
+    assert!(!buffer.is_empty());
+
+    // Capacity should be preserved or grown, never shrunk
+    let capacity_after_first = buffer.capacity();
+    assert!(capacity_after_first >= initial_capacity);
+
+    // Second render with different values - buffer should be reused
+    let (width2, height2, _) = renderer.render_tray_icon_into(
+        &font,
+        &mut buffer,
+        &RenderParams {
+            cpu_usage: 70.0,
+            mem_percent: 80.0,
+            down_str: "2.0 KB",
+            up_str: "1.0 KB",
+            visible_segments: &[SegmentKind::Memory, SegmentKind::Cpu, SegmentKind::Network],
+            show_alerts: false,
+            ..RenderParams::new(HOST_SIZING)
+        },
+    );
+    assert!(width2 > 0);
+    assert_eq!(height2, HOST_SIZING.icon_height);
+
+    // Capacity should still be preserved (key test: no reallocation for same-size renders)
+    assert!(buffer.capacity() >= capacity_after_first);
+}
+
+#[test]
+fn test_alert_colors_all_segments() {
+    let font = fallback_font();
+    let mut buffer: Vec<u8> = Vec::new();
+
+    let mut renderer = TrayRenderer::new();
+
+    // No alerts - has_active_alert should be false
+    let (_, _, has_alert_no) = renderer.render_tray_icon_into(
+        &font,
+        &mut buffer,
+        &RenderParams {
+cpu_usage: 50.0,
+mem_percent: 50.0,
+down_str: "0 KB",
+up_str: "0 KB",
+visible_segments: &[SegmentKind::Memory, SegmentKind::Cpu],
+thresholds: // alerts enabled
+        AlertThresholds::default(),
+..RenderParams::new(HOST_SIZING)
+},
+    );
+    assert!(!has_alert_no);
+
+    // CPU at 95% with alerts enabled - has_active_alert should be true
+    let (_, _, has_alert_yes) = renderer.render_tray_icon_into(
+        &font,
+        &mut buffer,
+        &RenderParams {
+cpu_usage: 95.0,
+mem_percent: 50.0,
+down_str: "0 KB",
+up_str: "0 KB",
+visible_segments: &[SegmentKind::Memory, SegmentKind::Cpu],
+thresholds: // alerts enabled
+        AlertThresholds::default(),
+..RenderParams::new(HOST_SIZING)
+},
+    );
+    assert!(has_alert_yes);
+
+    // CPU at 95% but alerts disabled - has_active_alert should be false
+    let (_, _, has_alert_disabled) = renderer.render_tray_icon_into(
+        &font,
+        &mut buffer,
+        &RenderParams {
+cpu_usage: 95.0,
+mem_percent: 50.0,
+down_str: "0 KB",
+up_str: "0 KB",
+visible_segments: &[SegmentKind::Memory, SegmentKind::Cpu],
+show_alerts: false,
+thresholds: // alerts disabled
+        AlertThresholds::default(),
+..RenderParams::new(HOST_SIZING)
+},
+    );
+    assert!(!has_alert_disabled);
+}
+
+#[test]
+fn test_sizing_scaled_up() {
+    let scaled = SIZING_LINUX.scaled(2.0);
+
+    assert_eq!(scaled.segment_width, 116);
+    assert_eq!(scaled.segment_width_net, 150);
+    assert_eq!(scaled.edge_padding, 10);
+    assert_eq!(scaled.segment_gap, 36);
+    assert_eq!(scaled.icon_height, 44);
+    assert_eq!(scaled.font_size, 38.0);
+}
+
+#[test]
+fn test_sizing_scaled_down() {
+    let scaled = SIZING_LINUX.scaled(0.5);
+
+    assert_eq!(scaled.segment_width, 29);
+    assert_eq!(scaled.segment_width_net, 38);
+    assert_eq!(scaled.edge_padding, 3);
+    assert_eq!(scaled.segment_gap, 9);
+    assert_eq!(scaled.icon_height, 11);
+    assert_eq!(scaled.font_size, 9.5);
+}
+
+#[test]
+fn test_sizing_scaled_rounding() {
+    let scaled = SIZING_LINUX.scaled(0.333);
+
+    assert_eq!(scaled.segment_width, 19);
+    assert_eq!(scaled.segment_width_net, 25);
+    assert_eq!(scaled.edge_padding, 2);
+    assert_eq!(scaled.segment_gap, 6);
+    assert_eq!(scaled.icon_height, 7);
+    assert_eq!(scaled.font_size, 19.0 * 0.333);
+}
+
+#[test]
+#[should_panic(expected = "scale must be > 0")]
+fn test_sizing_scaled_panics_on_zero() {
+    let _ = SIZING_LINUX.scaled(0.0);
+}
+
+#[test]
+fn test_render_with_all_segments_disabled() {
+    let font = fallback_font();
+    let mut buffer = Vec::new();
+    let mut renderer = TrayRenderer::new();
+
+    let (width, height, has_alert) = renderer.render_tray_icon_into(
+        &font,
+        &mut buffer,
+        &RenderParams {
+            cpu_usage: 50.0,
+            mem_percent: 50.0,
+            gpu_usage: 50.0,
+            down_str: "0 KB",
+            up_str: "0 KB",
+            visible_segments: &[],
+            ..RenderParams::new(HOST_SIZING)
+        },
+    );
+
+    assert_eq!(width, HOST_SIZING.edge_padding * 2);
+    assert_eq!(height, HOST_SIZING.icon_height);
+    assert!(!has_alert);
+    assert_eq!(buffer.len(), (width * height * 4) as usize);
+}
+
+#[test]
+fn test_render_follows_segment_order() {
+    let font = fallback_font();
+    let mut renderer = TrayRenderer::new();
+    let mut render = |segments: &[SegmentKind]| {
+        let mut buffer = Vec::new();
+        let (width, _, _) = renderer.render_tray_icon_into(
+            &font,
+            &mut buffer,
+            &RenderParams {
+                cpu_usage: 45.0,
+                mem_percent: 60.0,
+                down_str: "1 KB",
+                up_str: "2 KB",
+                visible_segments: segments,
+                show_alerts: false,
+                ..RenderParams::new(HOST_SIZING)
+            },
+        );
+        (width, buffer)
+    };
+
+    let (default_width, default_pixels) = render(&[SegmentKind::Memory, SegmentKind::Cpu]);
+    let (swapped_width, swapped_pixels) = render(&[SegmentKind::Cpu, SegmentKind::Memory]);
+    assert_eq!(default_width, swapped_width);
+    assert_ne!(default_pixels, swapped_pixels);
+}
+
+#[test]
+fn test_render_compact_layout_is_narrower() {
+    let font = fallback_font();
+    let mut renderer = TrayRenderer::new();
+    let mut width_for = |layout: Layout, cpu: f32| {
+        let mut buffer = Vec::new();
+        let (width, height, _) = renderer.render_tray_icon_into(
+            &font,
+            &mut buffer,
+            &RenderParams {
+                cpu_usage: cpu,
+                mem_percent: 62.0,
+                gpu_usage: 18.0,
+                down_str: "1.5 MB",
+                up_str: "0.2 MB",
+                layout,
+                show_alerts: false,
+                ..RenderParams::new(HOST_SIZING)
+            },
+        );
+        assert_eq!(buffer.len(), (width * height * 4) as usize);
+        width
+    };
+
+    let standard = width_for(Layout::Standard, 34.0);
+    let compact = width_for(Layout::Compact, 34.0);
+    assert!(compact < standard);
+    // Same number of digits, same width
+    assert_eq!(compact, width_for(Layout::Compact, 11.0));
+
+    let icons = width_for(Layout::IconOnly, 34.0);
+    assert_eq!(
+        icons,
+        HOST_SIZING.edge_padding * 2 + HOST_SIZING.icon_height * 5 + HOST_SIZING.segment_gap * 4
+    );
+    assert!(icons < standard);
+}
+
+#[test]
+fn test_render_auto_width() {
+    let font = fallback_font();
+    let mut renderer = TrayRenderer::new();
+    let width_for = |renderer: &mut TrayRenderer, cpu: f32| {
+        let mut buffer = Vec::new();
+        let (width, height, _) = renderer.render_tray_icon_into(
+            &font,
+            &mut buffer,
+            &RenderParams {
+                cpu_usage: cpu,
+                mem_percent: 62.0,
+                gpu_usage: 18.0,
+                down_str: "1.5 MB",
+                up_str: "0.2 MB",
+                visible_segments: &[SegmentKind::Cpu],
+                show_alerts: false,
+                ..RenderParams::new(HOST_SIZING)
+            },
+        );
+        assert_eq!(buffer.len(), (width * height * 4) as usize);
+        width
+    };
+
+    let fixed = width_for(&mut renderer, 5.0);
+    renderer.set_auto_width(true);
+    let single_digit = width_for(&mut renderer, 5.0);
+    assert!(single_digit < fixed);
+
+    // Wider values widen the segment right away
+    let two_digits = width_for(&mut renderer, 10.0);
+    assert!(two_digits > single_digit);
+    // Narrower ones only after a few frames in a row
+    for _ in 0..4 {
+        assert_eq!(width_for(&mut renderer, 7.0), two_digits);
+    }
+    assert_eq!(width_for(&mut renderer, 7.0), single_digit);
+
+    renderer.set_auto_width(false);
+    assert_eq!(width_for(&mut renderer, 5.0), fixed);
+}
+
+#[test]
+fn test_render_tabular_figures() {
+    assert_eq!(widest_digits("9%", false), "8%");
+    assert_eq!(widest_digits("9%", true), "888%");
+    assert_eq!(widest_digits("100%", true), "888%");
+    assert_eq!(widest_digits("1.5 MB", true), "888.8 MB");
+    assert_eq!(widest_digits("23/8%", true), "888/888%");
+    assert_eq!(widest_digits("1250%", true), "8888%");
+
+    let font = fallback_font();
+    let mut renderer = TrayRenderer::new();
+    let render = |renderer: &mut TrayRenderer, layout: Layout, cpu: f32| {
+        let mut buffer = Vec::new();
+        let (width, height, _) = renderer.render_tray_icon_into(
+            &font,
+            &mut buffer,
+            &RenderParams {
+                cpu_usage: cpu,
+                mem_percent: 62.0,
+                gpu_usage: 18.0,
+                down_str: "1.5 MB",
+                up_str: "0.2 MB",
+                layout,
+                percent_cap: PercentCap::Full,
+                show_alerts: false,
+                ..RenderParams::new(HOST_SIZING)
+            },
+        );
+        assert_eq!(buffer.len(), (width * height * 4) as usize);
+        (width, buffer)
+    };
+
+    for layout in [Layout::Standard, Layout::Compact] {
+        assert_ne!(
+            render(&mut renderer, layout, 9.0).0,
+            render(&mut renderer, layout, 100.0).0
+        );
+    }
+    renderer.set_tabular_figures(true);
+    for layout in [Layout::Standard, Layout::Compact] {
+        let (single, pixels) = render(&mut renderer, layout, 9.0);
+        assert_eq!(single, render(&mut renderer, layout, 10.0).0);
+        assert_eq!(single, render(&mut renderer, layout, 100.0).0);
+        assert!(pixels.chunks_exact(4).any(|px| px[3] > 0));
+    }
+}
+
+#[test]
+fn test_render_combined_network_segment() {
+    let font = fallback_font();
+    let mut renderer = TrayRenderer::new();
+    let mut render = |layout: Layout, combine_net: bool, down: &str| {
+        let mut buffer = Vec::new();
+        let (width, height, _) = renderer.render_tray_icon_into(
+            &font,
+            &mut buffer,
+            &RenderParams {
+                cpu_usage: 34.0,
+                mem_percent: 62.0,
+                gpu_usage: 18.0,
+                down_str: down,
+                up_str: "0.2 MB",
+                visible_segments: &[SegmentKind::Network],
+                layout,
+                combine_net,
+                show_alerts: false,
+                ..RenderParams::new(HOST_SIZING)
+            },
+        );
+        assert_eq!(buffer.len(), (width * height * 4) as usize);
+        (width, buffer)
+    };
+
+    let (pair, _) = render(Layout::Standard, false, "1.5 MB");
+    let (combined, pixels) = render(Layout::Standard, true, "1.5 MB");
+    assert!(combined < pair);
+    assert!(pixels.chunks_exact(4).any(|px| px[3] > 0));
+    // Rates are right-aligned in fixed room, so the width holds as they change
+    let (other_rate, other_pixels) = render(Layout::Standard, true, "12 MB");
+    assert_eq!(other_rate, combined);
+    assert_ne!(other_pixels, pixels);
+
+    // The compact layout already joins the rates
+    assert_eq!(
+        render(Layout::Compact, true, "1.5 MB").0,
+        render(Layout::Compact, false, "1.5 MB").0
+    );
+}
+
+#[test]
+fn test_render_stacked_layout() {
+    let font = fallback_font();
+    let mut renderer = TrayRenderer::new();
+    let mut render = |layout: Layout, segments: &[SegmentKind]| {
+        let mut buffer = Vec::new();
+        let (width, height, _) = renderer.render_tray_icon_into(
+            &font,
+            &mut buffer,
+            &RenderParams {
+                cpu_usage: 34.0,
+                mem_percent: 62.0,
+                gpu_usage: 18.0,
+                down_str: "1.5 MB",
+                up_str: "0.2 MB",
+                visible_segments: segments,
+                layout,
+                show_alerts: false,
+                ..RenderParams::new(HOST_SIZING)
+            },
+        );
+        assert_eq!(buffer.len(), (width * height * 4) as usize);
+        (width, height)
+    };
+
+    let (standard, height) = render(Layout::Standard, &SegmentKind::ALL);
+    let (stacked, stacked_height) = render(Layout::Stacked, &SegmentKind::ALL);
+    assert_eq!(stacked_height, height);
+    assert!(stacked < standard / 2);
+
+    // Without network there is no second row, so it draws like standard
+    let no_net = [SegmentKind::Memory, SegmentKind::Cpu];
+    assert_eq!(
+        render(Layout::Stacked, &no_net),
+        render(Layout::Standard, &no_net)
+    );
+}
+
+#[test]
+fn test_render_value_bars() {
+    let font = fallback_font();
+    let mut renderer = TrayRenderer::new();
+    let mut render = |style: ValueStyle, cpu: f32| {
+        let mut buffer = Vec::new();
+        let (width, height, alert) = renderer.render_tray_icon_into(
+            &font,
+            &mut buffer,
+            &RenderParams {
+                cpu_usage: cpu,
+                mem_percent: 62.0,
+                gpu_usage: 18.0,
+                down_str: "1.5 MB",
+                up_str: "0.2 MB",
+                visible_segments: &[SegmentKind::Cpu],
+                value_style: style,
+                ..RenderParams::new(HOST_SIZING)
+            },
+        );
+        let opaque = buffer.chunks_exact(4).filter(|px| px[3] == 255).count();
+        let alert_pixels = buffer
+            .chunks_exact(4)
+            .filter(|px| px[3] == 255 && (px[0], px[1], px[2]) == DEFAULT_ALERT_COLOR)
+            .count();
+        (width, height, alert, opaque, alert_pixels)
+    };
+
+    let (number_width, ..) = render(ValueStyle::Number, 50.0);
+    let (bar_width, _, _, half, _) = render(ValueStyle::HorizontalBar, 50.0);
+    let (_, _, _, full, _) = render(ValueStyle::HorizontalBar, 100.0);
+    let (_, _, _, empty, _) = render(ValueStyle::HorizontalBar, 0.0);
+    assert_eq!(bar_width, number_width);
+    assert!(empty < half && half < full);
+
+    let (vertical_width, height, _, low, _) = render(ValueStyle::VerticalBar, 10.0);
+    let (_, _, _, high, _) = render(ValueStyle::VerticalBar, 80.0);
+    assert!(vertical_width < number_width);
+    assert!(vertical_width > height);
+    assert!(low < high);
+
+    // Over the threshold the bar takes the alert color
+    let (_, _, alert, _, alert_pixels) = render(ValueStyle::HorizontalBar, 95.0);
+    assert!(alert);
+    assert!(alert_pixels > 0);
+}
+
+#[test]
+fn test_core_heatmap() {
+    let cores = CoreUsage::new(&[10.0, 20.4, 99.6, -1.0]);
+    assert_eq!(cores.cells(), &[10, 20, 100, 0]);
+    // Past 64 cores neighbours share a cell
+    let many = CoreUsage::new(&[50.0; 128]);
+    assert_eq!(many.cells().len(), MAX_HEATMAP_CELLS);
+    assert!(many.cells().iter().all(|&cell| cell == 50));
+
+    assert!(!cores.differs_from(&CoreUsage::new(&[11.0, 20.0, 100.0, 0.0]), 2.0));
+    assert!(cores.differs_from(&CoreUsage::new(&[10.0, 25.0, 100.0, 0.0]), 2.0));
+    assert!(cores.differs_from(&CoreUsage::new(&[10.0, 20.0, 100.0]), 2.0));
+
+    assert_eq!(heatmap_grid(4, 22), (2, 2, 9));
+    assert_eq!(heatmap_grid(64, 22), (6, 11, 2));
+    // A short icon keeps a single row of cells
+    assert_eq!(heatmap_grid(16, 4), (1, 16, 2));
+
+    let font = fallback_font();
+    let mut renderer = TrayRenderer::new();
+    let mut render = |heatmap: Option<CoreUsage>| {
+        let mut buffer = Vec::new();
+        let (width, _, _) = renderer.render_tray_icon_into(
+            &font,
+            &mut buffer,
+            &RenderParams {
+                cpu_usage: 40.0,
+                cpu_heatmap: heatmap,
+                mem_percent: 62.0,
+                gpu_usage: 18.0,
+                down_str: "1.5 MB",
+                up_str: "0.2 MB",
+                visible_segments: &[SegmentKind::Cpu],
+                ..RenderParams::new(HOST_SIZING)
+            },
+        );
+        let alert_pixels = buffer
+            .chunks_exact(4)
+            .filter(|px| px[3] == 255 && (px[0], px[1], px[2]) == DEFAULT_ALERT_COLOR)
+            .count();
+        (width, buffer, alert_pixels)
+    };
+
+    let (_, number, _) = render(None);
+    let (width, calm, calm_alerts) = render(Some(CoreUsage::new(&[10.0, 30.0, 50.0, 70.0])));
+    assert_ne!(calm, number);
+    assert!(width > HOST_SIZING.icon_height);
+    assert_eq!(calm_alerts, 0);
+    // A busy core takes the alert color though the average is under it
+    let (_, _, busy_alerts) = render(Some(CoreUsage::new(&[10.0, 30.0, 50.0, 95.0])));
+    assert!(busy_alerts > 0);
+}
+
+#[test]
+fn test_frame_hash() {
+    let frame = vec![255u8; 4 * 6];
+    assert_eq!(
+        frame_hash(&frame, 3, 2, false),
+        frame_hash(&frame.clone(), 3, 2, false)
+    );
+    // Same bytes in another shape, or as a template, still need sending
+    assert_ne!(
+        frame_hash(&frame, 3, 2, false),
+        frame_hash(&frame, 2, 3, false)
+    );
+    assert_ne!(
+        frame_hash(&frame, 3, 2, false),
+        frame_hash(&frame, 3, 2, true)
+    );
+    let mut changed = frame.clone();
+    changed[5] = 0;
+    assert_ne!(
+        frame_hash(&frame, 3, 2, false),
+        frame_hash(&changed, 3, 2, false)
+    );
+}
+
+#[test]
+fn test_render_reuses_cached_frames() {
+    let font = fallback_font();
+    let render = |renderer: &mut TrayRenderer, cpu: f32| {
+        let mut buffer = Vec::new();
+        let size = renderer.render_tray_icon_into(
+            &font,
+            &mut buffer,
+            &RenderParams {
+                cpu_usage: cpu,
+                mem_percent: 62.0,
+                gpu_usage: 18.0,
+                down_str: "1.5 MB",
+                up_str: "0.2 MB",
+                ..RenderParams::new(HOST_SIZING)
+            },
+        );
+        (size, buffer)
+    };
+
+    let mut renderer = TrayRenderer::new();
+    let first = render(&mut renderer, 34.0);
+    render(&mut renderer, 35.0);
+    assert_eq!(renderer.cached_frames(), 2);
+    // Flipping back is served from the cache and matches a fresh render
+    assert_eq!(render(&mut renderer, 34.0), first);
+    assert_eq!(renderer.cached_frames(), 2);
+    assert_eq!(render(&mut TrayRenderer::new(), 34.0), first);
+
+    // Only the most recently used frames are kept
+    for cpu in 0..20 {
+        render(&mut renderer, cpu as f32);
+    }
+    assert!(renderer.cached_frames() < 20);
+    assert_eq!(render(&mut renderer, 34.0), first);
+
+    renderer.clear_font_caches();
+    assert_eq!(renderer.cached_frames(), 0);
+    assert_eq!(renderer.cached_glyphs(), 0);
+}
+
+#[test]
+fn test_glyph_cache_is_shared_between_colors() {
+    let font = fallback_font();
+    let mut renderer = TrayRenderer::new();
+    let render = |renderer: &mut TrayRenderer, palette: Palette| {
+        let mut buffer = Vec::new();
+        renderer.render_tray_icon_into(
+            &font,
+            &mut buffer,
+            &RenderParams {
+                cpu_usage: 34.0,
+                mem_percent: 62.0,
+                gpu_usage: 18.0,
+                down_str: "1.5 MB",
+                up_str: "0.2 MB",
+                palette,
+                ..RenderParams::new(HOST_SIZING)
+            },
+        );
+        buffer
+    };
+
+    let light = render(&mut renderer, Palette::for_theme(true));
+    let glyphs = renderer.cached_glyphs();
+    assert!(glyphs > 0);
+    let dark = render(&mut renderer, Palette::for_theme(false));
+    assert_ne!(light, dark);
+    // Same text in another color is a new frame but no new glyphs
+    assert_eq!(renderer.cached_glyphs(), glyphs);
+}
+
+#[test]
+fn test_render_with_long_network_strings() {
+    let font = fallback_font();
+    let mut buffer = Vec::new();
+    let mut renderer = TrayRenderer::new();
+    let long_down = "9".repeat(512);
+    let long_up = "8".repeat(512);
+
+    let (width, height, has_alert) = renderer.render_tray_icon_into(
+        &font,
+        &mut buffer,
+        &RenderParams {
+            down_str: &long_down,
+            up_str: &long_up,
+            visible_segments: &[SegmentKind::Network],
+            ..RenderParams::new(HOST_SIZING)
+        },
+    );
+
+    let expected_width = HOST_SIZING.edge_padding * 2
+        + (HOST_SIZING.segment_width_net * 2)
+        + HOST_SIZING.segment_gap;
+
+    assert_eq!(width, expected_width);
+    assert_eq!(height, HOST_SIZING.icon_height);
+    assert!(!has_alert);
+    assert_eq!(buffer.len(), (width * height * 4) as usize);
+}
+
+#[test]
+fn test_render_extra_segment_width() {
+    let font = fallback_font();
+    let mut buffer = Vec::new();
+    let mut renderer = TrayRenderer::new();
+    let extra = [TextSegment {
+        icon: IconType::Power,
+        text: "4.3W".to_string(),
+        alert: false,
+    }];
+
+    let (width, _height, has_alert) = renderer.render_tray_icon_into(
+        &font,
+        &mut buffer,
+        &RenderParams {
+            cpu_usage: 50.0,
+            down_str: "0 KB",
+            up_str: "0 KB",
+            visible_segments: &[SegmentKind::Cpu],
+            extra_segments: &extra,
+            ..RenderParams::new(HOST_SIZING)
+        },
+    );
+
+    let expected_width = HOST_SIZING.edge_padding * 2
+        + HOST_SIZING.segment_width
+        + HOST_SIZING.segment_width_net
+        + HOST_SIZING.segment_gap;
+    assert_eq!(width, expected_width);
+    assert!(!has_alert);
+}
+
+#[test]
+fn test_text_effect_draws_contrasting_edge() {
+    assert_eq!(TextEffect::Outline.offsets(1).len(), 8);
+    assert_eq!(TextEffect::Shadow.offsets(2), vec![(2, 2)]);
+    assert!(TextEffect::None.offsets(1).is_empty());
+    assert_eq!(contrast_color((255, 255, 255)), (0, 0, 0));
+    assert_eq!(contrast_color((20, 20, 20)), (255, 255, 255));
+
+    let font = fallback_font();
+    let mut renderer = TrayRenderer::new();
+    let mut render = |text_effect| {
+        let mut buffer: Vec<u8> = Vec::new();
+        renderer.render_tray_icon_into(
+            &font,
+            &mut buffer,
+            &RenderParams {
+                cpu_usage: 42.0,
+                down_str: "0 KB",
+                up_str: "0 KB",
+                visible_segments: &[SegmentKind::Cpu],
+                layout: Layout::Compact,
+                palette: Palette {
+                    text_effect,
+                    ..Palette::default()
+                },
+                ..RenderParams::new(HOST_SIZING)
+            },
+        );
+        buffer
+    };
+    let has_dark_pixels = |buffer: &[u8]| {
+        buffer
+            .chunks_exact(4)
+            .any(|px| px[3] > 0 && px[..3] == [0, 0, 0])
+    };
+
+    assert!(!has_dark_pixels(&render(TextEffect::None)));
+    assert!(has_dark_pixels(&render(TextEffect::Outline)));
+    assert!(has_dark_pixels(&render(TextEffect::Shadow)));
+}
+
+/// Golden images of the tray icon, rendered with the bundled font at Linux
+/// sizing so they come out the same on every machine. After an intentional
+/// rendering change, regenerate them with `UPDATE_SNAPSHOTS=1 cargo test snapshot`
+/// and review the new PNGs before committing.
+const SNAPSHOT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/snapshots");
+
+/// Per-channel difference still counted as the same pixel (antialiasing noise).
+const SNAPSHOT_CHANNEL_TOLERANCE: u8 = 24;
+
+/// Share of pixels allowed to differ beyond the channel tolerance.
+const SNAPSHOT_MAX_DIFF_RATIO: f64 = 0.01;
+
+struct SnapshotCase {
+    name: &'static str,
+    cpu: f32,
+    mem: f32,
+    gpu: f32,
+    gpu_mem: Option<&'static str>,
+    extra: Vec<TextSegment>,
+    layout: Layout,
+    palette: Palette,
+}
+
+fn snapshot_cases() -> Vec<SnapshotCase> {
+    let base = |name, layout| SnapshotCase {
+        name,
+        cpu: 42.0,
+        mem: 63.0,
+        gpu: 17.0,
+        gpu_mem: None,
+        extra: Vec::new(),
+        layout,
+        palette: Palette::default(),
+    };
+    vec![
+        base("standard", Layout::Standard),
+        SnapshotCase {
+            cpu: 95.0,
+            ..base("standard_alert", Layout::Standard)
+        },
+        base("compact", Layout::Compact),
+        SnapshotCase {
+            cpu: 95.0,
+            ..base("icons_only_alert", Layout::IconOnly)
+        },
+        SnapshotCase {
+            gpu_mem: Some("6.2G"),
+            extra: vec![
+                TextSegment {
+                    icon: IconType::Power,
+                    text: "4.3W".to_string(),
+                    alert: false,
+                },
+                TextSegment {
+                    icon: IconType::Temperature,
+                    text: "71°C".to_string(),
+                    alert: false,
+                },
+            ],
+            palette: Palette::for_theme(false),
+            ..base("extras_dark_text", Layout::Standard)
+        },
+    ]
+}
+
+fn render_snapshot(case: &SnapshotCase) -> (Vec<u8>, u32, u32) {
+    let font = fallback_font();
+    let mut buffer = Vec::new();
+    let (width, height, _) = TrayRenderer::new().render_tray_icon_into(
+        &font,
+        &mut buffer,
+        &RenderParams {
+            cpu_usage: case.cpu,
+            mem_percent: case.mem,
+            gpu_usage: case.gpu,
+            gpu_mem_str: case.gpu_mem,
+            down_str: "1.2 MB",
+            up_str: "340 KB",
+            extra_segments: &case.extra,
+            layout: case.layout,
+            palette: case.palette,
+            ..RenderParams::new(SIZING_LINUX)
+        },
+    );
+    (buffer, width, height)
+}
+
+/// Number of pixels differing by more than the channel tolerance.
+fn snapshot_diff(expected: &[u8], actual: &[u8]) -> usize {
+    expected
+        .chunks_exact(4)
+        .zip(actual.chunks_exact(4))
+        .filter(|(a, b)| {
+            a.iter()
+                .zip(b.iter())
+                .any(|(a, b)| a.abs_diff(*b) > SNAPSHOT_CHANNEL_TOLERANCE)
+        })
+        .count()
+}
+
+#[test]
+fn test_snapshot_diff_tolerance() {
+    let expected = [10, 10, 10, 255, 200, 200, 200, 255];
+    assert_eq!(
+        snapshot_diff(&expected, &[30, 0, 10, 255, 200, 200, 200, 255]),
+        0
+    );
+    assert_eq!(
+        snapshot_diff(&expected, &[10, 10, 10, 255, 100, 200, 200, 255]),
+        1
+    );
+}
+
+#[test]
+fn test_tray_render_snapshots() {
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+    let dir = std::path::Path::new(SNAPSHOT_DIR);
+
+    for case in snapshot_cases() {
+        let (rgba, width, height) = render_snapshot(&case);
+        let path = dir.join(format!("{}.png", case.name));
+        if update {
+            image::save_buffer(&path, &rgba, width, height, image::ColorType::Rgba8).unwrap();
+            continue;
+        }
+
+        let expected = image::open(&path)
+            .unwrap_or_else(|e| panic!("{}: {e}; run with UPDATE_SNAPSHOTS=1", path.display()))
+            .to_rgba8();
+        assert_eq!(
+            expected.dimensions(),
+            (width, height),
+            "{}: icon size changed",
+            case.name
+        );
+        let diff = snapshot_diff(expected.as_raw(), &rgba);
+        let allowed = (rgba.len() / 4) as f64 * SNAPSHOT_MAX_DIFF_RATIO;
+        assert!(
+            diff as f64 <= allowed,
+            "{}: {diff} pixels differ from {}",
+            case.name,
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn test_render_label_style() {
+    assert_eq!(LabelStyle::parse("both"), Some(LabelStyle::Both));
+    assert_eq!(LabelStyle::parse("words"), None);
+
+    let font = fallback_font();
+    let mut renderer = TrayRenderer::new();
+    let mut render = |style: LabelStyle, layout: Layout, combine_net: bool| {
+        renderer.set_label_style(style);
+        let mut buffer = Vec::new();
+        let (width, height, _) = renderer.render_tray_icon_into(
+            &font,
+            &mut buffer,
+            &RenderParams {
+                cpu_usage: 34.0,
+                mem_percent: 62.0,
+                gpu_usage: 18.0,
+                down_str: "1.5 MB",
+                up_str: "0.2 MB",
+                layout,
+                combine_net,
+                show_alerts: false,
+                ..RenderParams::new(HOST_SIZING)
+            },
+        );
+        assert_eq!(buffer.len(), (width * height * 4) as usize);
+        (width, buffer)
+    };
+
+    for layout in [Layout::Standard, Layout::IconOnly] {
+        for combine_net in [false, true] {
+            let (icons, icon_pixels) = render(LabelStyle::Icons, layout, combine_net);
+            let (text, text_pixels) = render(LabelStyle::Text, layout, combine_net);
+            let (both, _) = render(LabelStyle::Both, layout, combine_net);
+            // Labels are wider than the icons they replace, and both together
+            // wider still
+            assert!(text > icons, "{layout:?}");
+            assert!(both > text, "{layout:?}");
+            assert_ne!(icon_pixels, text_pixels);
+        }
+    }
+
+    // Compact values carry no labels
+    assert_eq!(
+        render(LabelStyle::Icons, Layout::Compact, false).0,
+        render(LabelStyle::Both, Layout::Compact, false).0
+    );
+}
+
+#[test]
+fn test_render_colors_memory_by_pressure() {
+    let font = fallback_font();
+    let mut renderer = TrayRenderer::new();
+    let mut render = |mem: f32, level: MemoryPressure| {
+        let mut buffer = Vec::new();
+        let (_, _, has_alert) = renderer.render_tray_icon_into(
+            &font,
+            &mut buffer,
+            &RenderParams {
+                mem_percent: mem,
+                mem_pressure: Some(level),
+                visible_segments: &[SegmentKind::Memory],
+                ..RenderParams::new(HOST_SIZING)
+            },
+        );
+        (buffer, has_alert)
+    };
+
+    // A high percentage alone doesn't alert while pressure is low
+    let (buffer, has_alert) = render(95.0, MemoryPressure::Warning);
+    assert!(!has_alert);
+    let (r, g, b) = PRESSURE_WARNING_COLOR;
+    assert!(buffer.chunks_exact(4).any(|px| px == [r, g, b, 255]));
+
+    let (_, has_alert) = render(40.0, MemoryPressure::Critical);
+    assert!(has_alert);
+}
+
+#[test]
+fn test_render_memory_breakdown() {
+    const GIB: u64 = 1 << 30;
+
+    let quarters = MemoryBreakdown {
+        apps: 4 * GIB,
+        wired: 4 * GIB,
+        cached: 4 * GIB,
+        free: 4 * GIB,
+    };
+    let fewer_apps = MemoryBreakdown {
+        apps: 3 * GIB,
+        free: 5 * GIB,
+        ..quarters
+    };
+    assert_eq!(quarters.stops(), [0.25, 0.5, 0.75]);
+    assert!(!quarters.differs_from(&quarters, 2.0));
+    assert!(quarters.differs_from(&fewer_apps, 2.0));
+
+    // Drawn as a bar even with numbers as the value style
+    let font = fallback_font();
+    let mut renderer = TrayRenderer::new();
+    let mut render = |breakdown: Option<MemoryBreakdown>| {
+        let mut buffer = Vec::new();
+        renderer.render_tray_icon_into(
+            &font,
+            &mut buffer,
+            &RenderParams {
+                mem_percent: 75.0,
+                mem_breakdown: breakdown,
+                visible_segments: &[SegmentKind::Memory],
+                ..RenderParams::new(HOST_SIZING)
+            },
+        );
+        buffer
+    };
+    let number = render(None);
+    let bar = render(Some(quarters));
+    assert_ne!(bar, number);
+    // Apps, wired, cached and the free track each have their own tone
+    for alpha in [255, 160, 115, 80] {
+        assert!(bar.chunks_exact(4).any(|px| px[3] == alpha));
+    }
+}
+
+#[test]
+fn test_render_stalled_icon() {
+    let sizing = SIZING_LINUX;
+    let (pixels, width, height) = render_stalled_icon(sizing, Palette::for_theme(false));
+    assert_eq!(height, sizing.icon_height);
+    assert_eq!(width, sizing.icon_height + sizing.edge_padding * 2);
+    assert_eq!(pixels.len(), (width * height * 4) as usize);
+    // Drawn in the alert color
+    let alert = Palette::for_theme(false).alert;
+    assert!(pixels
+        .chunks_exact(4)
+        .any(|p| p[3] == 255 && (p[0], p[1], p[2]) == alert));
+}
@@ -0,0 +1,86 @@
+//! Readings shared by Better Resource Monitor's samplers and its renderer
+//!
+//! The metrics crate produces these and the render crate draws them; neither
+//! depends on the other, and this crate depends on nothing.
+
+/// System memory pressure level (macOS `kern.memorystatus_vm_pressure_level`).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MemoryPressure {
+    Normal,
+    Warning,
+    Critical,
+}
+
+/// Memory by class in bytes for the breakdown memory display: apps, wired
+/// and kernel memory, reclaimable caches and free memory, adding up to RAM.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct MemoryBreakdown {
+    pub apps: u64,
+    pub wired: u64,
+    pub cached: u64,
+    pub free: u64,
+}
+
+impl MemoryBreakdown {
+    pub fn total(&self) -> u64 {
+        self.apps + self.wired + self.cached + self.free
+    }
+
+    /// Where apps, wired and cached memory end along the bar, as fractions.
+    pub fn stops(&self) -> [f32; 3] {
+        let total = self.total().max(1) as f64;
+        let mut end = 0;
+        [self.apps, self.wired, self.cached].map(|bytes| {
+            end += bytes;
+            (end as f64 / total) as f32
+        })
+    }
+
+    /// Whether a class boundary moved by `threshold` percentage points.
+    pub fn differs_from(&self, other: &MemoryBreakdown, threshold: f32) -> bool {
+        self.stops()
+            .iter()
+            .zip(other.stops())
+            .any(|(a, b)| (a - b).abs() * 100.0 >= threshold)
+    }
+}
+
+/// Cells at most in the per-core heatmap; more cores share cells.
+pub const MAX_HEATMAP_CELLS: usize = 64;
+
+/// Per-core CPU usage in whole percent for the heatmap CPU display, one cell
+/// per core. Beyond [`MAX_HEATMAP_CELLS`] cores, neighbours are averaged into
+/// a cell, so the grid stays readable on a 128-thread machine.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct CoreUsage {
+    cells: [u8; MAX_HEATMAP_CELLS],
+    len: u8,
+}
+
+impl CoreUsage {
+    pub fn new(cores: &[f32]) -> Self {
+        let per_cell = cores.len().div_ceil(MAX_HEATMAP_CELLS).max(1);
+        let mut cells = [0; MAX_HEATMAP_CELLS];
+        let mut len = 0;
+        for (cell, group) in cells.iter_mut().zip(cores.chunks(per_cell)) {
+            let average = group.iter().sum::<f32>() / group.len() as f32;
+            *cell = average.round().clamp(0.0, 100.0) as u8;
+            len += 1;
+        }
+        Self { cells, len }
+    }
+
+    pub fn cells(&self) -> &[u8] {
+        &self.cells[..self.len as usize]
+    }
+
+    /// Whether any cell moved by `threshold` points or the core count changed.
+    pub fn differs_from(&self, other: &CoreUsage, threshold: f32) -> bool {
+        self.len != other.len
+            || self
+                .cells()
+                .iter()
+                .zip(other.cells())
+                .any(|(a, b)| (*a as f32 - *b as f32).abs() >= threshold)
+    }
+}
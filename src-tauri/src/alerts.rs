@@ -0,0 +1,430 @@
+//! Recent alerts log
+//!
+//! Tracks when each metric crossed its alert threshold (and when the GPU
+//! throttled) with the peak value, and lists the latest events in a "Recent
+//! Alerts" submenu so spikes that happened while away are still visible. Events
+//! are kept in memory; while history recording is on, finished events are also
+//! appended to `alerts.csv` next to the history files and reloaded at startup,
+//! when the file is cut back to the events kept.
+//!
+//! With `alert_webhook_url` set, every start and end is also POSTed there as
+//! JSON (through `curl`, so HTTPS works without a TLS stack here). The payload
+//! has a `text` and a `content` line for Slack and Discord webhooks next to the
+//! structured fields; failed posts are retried with backoff on a thread of
+//! their own.
+
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::i18n::{tr, tr_args};
+use crate::quiet_hours::{format_time, QuietHours};
+
+use tauri::menu::{MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Wry};
+use tauri_plugin_dialog::DialogExt;
+use tracing::{error, warn};
+
+pub const ALERTS_FILE: &str = "alerts.csv";
+pub const EXPORT_MENU_ID: &str = "export_alerts";
+/// Events kept in memory and reloaded from disk.
+pub const MAX_EVENTS: usize = 100;
+/// Events listed in the submenu.
+const MENU_EVENTS: usize = 10;
+const CSV_HEADER: &str = "metric,peak,started,ended";
+/// Delays before each retry of a failed webhook post.
+const WEBHOOK_RETRY_SECS: [u64; 3] = [5, 30, 120];
+const WEBHOOK_TIMEOUT_SECS: u32 = 10;
+const MAX_WEBHOOK_URL_LEN: usize = 2048;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AlertMetric {
+    Cpu,
+    Memory,
+    Gpu,
+    /// GPU slowing its clocks to cool down; the peak is the temperature
+    GpuThrottling,
+    /// Video encoder (NVENC) at or past [`crate::gpu::ENCODER_SATURATED`]
+    GpuEncoder,
+    /// Monthly traffic past 80% of the data cap; the peak is the share used
+    DataCapNear,
+    /// Monthly traffic past the data cap
+    DataCapReached,
+    /// A watched process over its CPU or memory limit; the peak is the
+    /// highest share of a limit
+    WatchedProcess,
+}
+
+impl AlertMetric {
+    pub const ALL: [AlertMetric; 8] = [
+        AlertMetric::Cpu,
+        AlertMetric::Memory,
+        AlertMetric::Gpu,
+        AlertMetric::GpuThrottling,
+        AlertMetric::GpuEncoder,
+        AlertMetric::DataCapNear,
+        AlertMetric::DataCapReached,
+        AlertMetric::WatchedProcess,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AlertMetric::Cpu => "cpu",
+            AlertMetric::Memory => "mem",
+            AlertMetric::Gpu => "gpu",
+            AlertMetric::GpuThrottling => "gpu_throttling",
+            AlertMetric::GpuEncoder => "gpu_encoder",
+            AlertMetric::DataCapNear => "data_cap_near",
+            AlertMetric::DataCapReached => "data_cap_reached",
+            AlertMetric::WatchedProcess => "watched_process",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|metric| metric.as_str() == value)
+    }
+
+    fn describe(self, peak: f32) -> String {
+        match self {
+            AlertMetric::Cpu => format!("CPU {peak:.0}%"),
+            AlertMetric::Memory => format!("Memory {peak:.0}%"),
+            AlertMetric::Gpu => format!("GPU {peak:.0}%"),
+            AlertMetric::GpuThrottling => {
+                format!("GPU throttling {}", crate::format_temperature(peak))
+            }
+            AlertMetric::GpuEncoder => format!("GPU encoder {peak:.0}%"),
+            AlertMetric::DataCapNear => format!("Data cap {peak:.0}% used"),
+            AlertMetric::DataCapReached => format!("Data cap reached, {peak:.0}% used"),
+            AlertMetric::WatchedProcess => format!("Watched process at {peak:.0}% of its limit"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlertEvent {
+    pub metric: AlertMetric,
+    /// Highest value seen while alerting
+    pub peak: f32,
+    /// Unix seconds
+    pub started: u64,
+    /// Unix seconds; `None` while still alerting
+    pub ended: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AlertChange {
+    Started(AlertEvent),
+    Ended(AlertEvent),
+}
+
+/// Alert events, oldest first.
+#[derive(Debug, Default)]
+pub struct AlertLog {
+    events: VecDeque<AlertEvent>,
+}
+
+pub type SharedAlertLog = Arc<Mutex<AlertLog>>;
+
+impl AlertLog {
+    pub fn with_events(events: impl IntoIterator<Item = AlertEvent>) -> Self {
+        let mut log = Self::default();
+        for event in events {
+            log.push(event);
+        }
+        log
+    }
+
+    /// Feeds one reading. Returns what changed, if an alert started or ended.
+    pub fn observe(
+        &mut self,
+        metric: AlertMetric,
+        value: f32,
+        alerting: bool,
+        now: u64,
+    ) -> Option<AlertChange> {
+        let open = self
+            .events
+            .iter_mut()
+            .rev()
+            .find(|e| e.metric == metric && e.ended.is_none());
+
+        match (open, alerting) {
+            (Some(event), true) => {
+                event.peak = event.peak.max(value);
+                None
+            }
+            (Some(event), false) => {
+                event.ended = Some(now);
+                Some(AlertChange::Ended(event.clone()))
+            }
+            (None, true) => {
+                let event = AlertEvent {
+                    metric,
+                    peak: value,
+                    started: now,
+                    ended: None,
+                };
+                self.push(event.clone());
+                Some(AlertChange::Started(event))
+            }
+            (None, false) => None,
+        }
+    }
+
+    /// Newest first.
+    pub fn recent(&self) -> impl Iterator<Item = &AlertEvent> {
+        self.events.iter().rev()
+    }
+
+    fn push(&mut self, event: AlertEvent) {
+        self.events.push_back(event);
+        while self.events.len() > MAX_EVENTS {
+            self.events.pop_front();
+        }
+    }
+}
+
+/// Whether `url` can be used as `alert_webhook_url`; empty turns webhooks off.
+pub(crate) fn is_valid_webhook_url(url: &str) -> bool {
+    url.is_empty()
+        || ((url.starts_with("https://") || url.starts_with("http://"))
+            && url.len() <= MAX_WEBHOOK_URL_LEN
+            && !url.chars().any(|c| c.is_whitespace() || c.is_control()))
+}
+
+/// JSON body posted to the webhook for an alert starting or ending.
+pub(crate) fn webhook_payload(change: &AlertChange) -> Value {
+    match change {
+        AlertChange::Started(event) => {
+            let text = format!("Alert: {}", event.metric.describe(event.peak));
+            json!({
+                "event": "started",
+                "metric": event.metric.as_str(),
+                "value": event.peak,
+                "started": event.started,
+                "text": text,
+                "content": text,
+            })
+        }
+        AlertChange::Ended(event) => {
+            let ended = event.ended.unwrap_or(event.started);
+            let duration = ended.saturating_sub(event.started);
+            let text = format!(
+                "Alert over: {} peak, lasted {}",
+                event.metric.describe(event.peak),
+                crate::format_uptime(duration)
+            );
+            json!({
+                "event": "ended",
+                "metric": event.metric.as_str(),
+                "peak": event.peak,
+                "started": event.started,
+                "ended": ended,
+                "duration_secs": duration,
+                "text": text,
+                "content": text,
+            })
+        }
+    }
+}
+
+/// Posts `payload` to `url` in the background, retrying with backoff.
+pub fn send_webhook(url: String, payload: Value) {
+    thread::spawn(move || {
+        let body = payload.to_string();
+        let mut delays = WEBHOOK_RETRY_SECS.iter();
+        loop {
+            let error = match post_json(&url, &body) {
+                Ok(()) => return,
+                Err(e) => e,
+            };
+            let Some(delay) = delays.next() else {
+                warn!("Failed to send alert webhook: {error}");
+                return;
+            };
+            thread::sleep(Duration::from_secs(*delay));
+        }
+    });
+}
+
+fn post_json(url: &str, body: &str) -> Result<(), String> {
+    let mut child = Command::new("curl")
+        .args([
+            "-sSf",
+            "--max-time",
+            &WEBHOOK_TIMEOUT_SECS.to_string(),
+            "-X",
+            "POST",
+            "-H",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+            "--",
+            url,
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run curl: {e}"))?;
+    // Dropping stdin closes it; curl is waited on even if the write failed,
+    // e.g. with a broken pipe after it gave up on a bad URL
+    let written = match child.stdin.take() {
+        Some(mut stdin) => stdin.write_all(body.as_bytes()),
+        None => Ok(()),
+    };
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    written.map_err(|e| e.to_string())
+}
+
+pub(crate) fn csv_row(event: &AlertEvent) -> String {
+    format!(
+        "{},{:.1},{},{}",
+        event.metric.as_str(),
+        event.peak,
+        event.started,
+        event.ended.map(|t| t.to_string()).unwrap_or_default()
+    )
+}
+
+pub(crate) fn parse_csv_row(line: &str) -> Option<AlertEvent> {
+    let mut fields = line.trim().split(',');
+    let metric = AlertMetric::parse(fields.next()?)?;
+    let peak = fields.next()?.parse().ok()?;
+    let started = fields.next()?.parse().ok()?;
+    let ended = match fields.next()? {
+        "" => None,
+        value => Some(value.parse().ok()?),
+    };
+    Some(AlertEvent {
+        metric,
+        peak,
+        started,
+        ended,
+    })
+}
+
+/// The last [`MAX_EVENTS`] events saved in `dir`, oldest first. Older ones
+/// are dropped from the file, which would otherwise only ever grow.
+pub fn load(dir: &Path) -> Vec<AlertEvent> {
+    let path = dir.join(ALERTS_FILE);
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    let events: Vec<AlertEvent> = contents.lines().filter_map(parse_csv_row).collect();
+    let kept = events[events.len().saturating_sub(MAX_EVENTS)..].to_vec();
+    if kept.len() < contents.lines().count().saturating_sub(1) {
+        let temp = path.with_extension("tmp");
+        if let Err(e) = fs::write(&temp, export_csv(&kept)).and_then(|_| fs::rename(temp, &path)) {
+            warn!("Failed to trim {}: {e}", path.display());
+        }
+    }
+    kept
+}
+
+pub fn append(dir: &Path, event: &AlertEvent) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(ALERTS_FILE);
+    let is_new = !path.exists();
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if is_new {
+        writeln!(file, "{CSV_HEADER}")?;
+    }
+    writeln!(file, "{}", csv_row(event))
+}
+
+pub(crate) fn export_csv<'a>(events: impl IntoIterator<Item = &'a AlertEvent>) -> String {
+    let mut csv = format!("{CSV_HEADER}\n");
+    for event in events {
+        csv.push_str(&csv_row(event));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// "CPU 97% · 2026-01-05 14:02 UTC · 3m", or "· ongoing" while still alerting.
+pub(crate) fn event_label(event: &AlertEvent) -> String {
+    let duration = match event.ended {
+        Some(ended) => crate::format_uptime(ended.saturating_sub(event.started)),
+        None => "ongoing".to_string(),
+    };
+    format!(
+        "{} · {} · {duration}",
+        event.metric.describe(event.peak),
+        crate::format_utc_timestamp(event.started)
+    )
+}
+
+/// The tray submenu rebuilt whenever an alert starts or ends.
+pub struct AlertMenu(pub Submenu<Wry>);
+
+/// Submenu title, which also says while quiet hours hold back sounds and webhooks.
+pub(crate) fn menu_title(quiet: Option<QuietHours>) -> String {
+    match quiet {
+        Some(hours) => tr_args("Recent Alerts (Quiet until {})", &[&format_time(hours.end)]),
+        None => tr("Recent Alerts").to_string(),
+    }
+}
+
+pub fn update_menu(app: &AppHandle, menu: &Submenu<Wry>, log: &AlertLog) -> tauri::Result<()> {
+    while menu.remove_at(0)?.is_some() {}
+
+    let mut any = false;
+    for event in log.recent().take(MENU_EVENTS) {
+        menu.append(&MenuItem::new(
+            app,
+            event_label(event),
+            false,
+            None::<&str>,
+        )?)?;
+        any = true;
+    }
+    if !any {
+        menu.append(&MenuItem::new(
+            app,
+            tr("No alerts yet"),
+            false,
+            None::<&str>,
+        )?)?;
+    }
+
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        EXPORT_MENU_ID,
+        tr("Export Alerts…"),
+        any,
+        None::<&str>,
+    )?)?;
+    Ok(())
+}
+
+/// Asks where to save and writes every logged event as CSV.
+pub fn export(app: &AppHandle, log: &SharedAlertLog) {
+    let csv = export_csv(&log.lock().unwrap_or_else(|e| e.into_inner()).events);
+
+    app.dialog()
+        .file()
+        .set_file_name("alerts.csv")
+        .add_filter("CSV", &["csv"])
+        .save_file(move |path| {
+            let Some(path) = path.and_then(|p| p.into_path().ok()) else {
+                return;
+            };
+            if let Err(e) = fs::write(&path, csv) {
+                error!("Failed to export alerts to {}: {e}", path.display());
+            }
+        });
+}
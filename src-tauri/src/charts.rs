@@ -0,0 +1,86 @@
+//! Charts window
+//!
+//! "Show Charts…" opens a window (`ui/charts.html`) with a line chart per
+//! metric over the last 10, 30 or 60 minutes, or 24 hours of 1-minute averages
+//! from [`crate::minute_history`]. The monitoring loop records every sample in
+//! an in-memory [`ChartHistory`] and, while the window is open, also sends it
+//! to the page as a [`SAMPLE_EVENT`] (and each finished minute as a
+//! [`MINUTE_EVENT`]); the page loads both histories once when it opens and
+//! appends from the events. Nothing leaves the process.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder};
+use tracing::warn;
+
+use crate::dashboard::push_within_window;
+use crate::metrics::Sample;
+use crate::minute_history::SharedMinuteHistory;
+use crate::status_server::sample_json;
+
+pub const MENU_ID: &str = "show_charts";
+pub const WINDOW_LABEL: &str = "charts";
+/// Carries one sample, shaped like the status server's JSON.
+pub const SAMPLE_EVENT: &str = "chart-sample";
+/// Carries one finished minute's averages, shaped the same way.
+pub const MINUTE_EVENT: &str = "chart-minute";
+
+/// Seconds of samples kept: the longest range drawn from every sample.
+pub const HISTORY_SECS: u64 = 60 * 60;
+
+/// Samples of the last [`HISTORY_SECS`], oldest first.
+pub type ChartHistory = Arc<Mutex<VecDeque<Sample>>>;
+
+/// Keeps `sample` and passes it on to the window if it is open.
+pub fn record(app: &AppHandle, history: &ChartHistory, sample: Sample) {
+    remember(history, sample);
+    send(app, SAMPLE_EVENT, &sample);
+}
+
+/// Passes a finished minute on to the window if it is open.
+pub fn record_minute(app: &AppHandle, minute: Sample) {
+    send(app, MINUTE_EVENT, &minute);
+}
+
+fn send(app: &AppHandle, event: &str, sample: &Sample) {
+    if app.get_webview_window(WINDOW_LABEL).is_some() {
+        if let Err(e) = app.emit_to(WINDOW_LABEL, event, sample_json(sample)) {
+            warn!("Failed to send a sample to the charts window: {e}");
+        }
+    }
+}
+
+pub(crate) fn remember(history: &ChartHistory, sample: Sample) {
+    let mut samples = history.lock().unwrap_or_else(|e| e.into_inner());
+    push_within_window(&mut samples, sample, HISTORY_SECS);
+}
+
+pub fn open(app: &AppHandle) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        window.show()?;
+        window.set_focus()?;
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::App("charts.html".into()))
+        .title("Charts")
+        .inner_size(640.0, 560.0)
+        .min_inner_size(420.0, 360.0)
+        .build()?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_chart_history(
+    history: State<'_, ChartHistory>,
+    minutes: State<'_, SharedMinuteHistory>,
+) -> Value {
+    let samples = history.lock().unwrap_or_else(|e| e.into_inner());
+    let minutes = minutes.lock().unwrap_or_else(|e| e.into_inner());
+    json!({
+        "recent": samples.iter().map(sample_json).collect::<Vec<_>>(),
+        "minutes": minutes.minutes().map(sample_json).collect::<Vec<_>>(),
+    })
+}
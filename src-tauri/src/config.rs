@@ -0,0 +1,1726 @@
+//! Persisted user settings
+//!
+//! Settings are stored as JSON via tauri-plugin-store (`settings.json` in the app
+//! data directory) and mirrored into atomics so the tray menu, the preferences
+//! window and the monitoring thread can share them without locking.
+
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicU8, Ordering::Relaxed};
+use std::sync::Mutex;
+
+use better_resource_monitor_metrics::{cgroup, memory};
+use serde_json::{json, Value};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Notify;
+use tracing::{error, warn};
+
+use crate::history::DEFAULT_RETENTION_DAYS;
+use crate::logging::LogLevel;
+use crate::profiles::{self, Profile};
+use crate::quiet_hours::QuietHours;
+use crate::sound::{self, AlertSound};
+use crate::tray_render::{
+    self, AlertThresholds, LabelStyle, Layout, MetricColors, Palette, PercentCap, Rgb, SegmentKind,
+    TextEffect, ValueStyle,
+};
+use crate::{
+    alerts, data_cap, i18n, idle, latency, remote, smoothing, status_server, watched, wifi,
+};
+
+pub use better_resource_monitor_metrics::memory::{MemClass, MemFormula};
+
+pub const SETTINGS_FILE: &str = "settings.json";
+
+pub mod key {
+    pub const AUTOSTART: &str = "autostart";
+    pub const SHOW_CPU: &str = "show_cpu";
+    pub const SHOW_MEM: &str = "show_mem";
+    pub const SHOW_GPU: &str = "show_gpu";
+    pub const SHOW_GPU_MEMORY: &str = "show_gpu_memory";
+    pub const SHOW_NET: &str = "show_net";
+    pub const SHOW_ALERTS: &str = "show_alerts";
+    pub const CPU_ALERT_THRESHOLD: &str = "cpu_alert_threshold";
+    pub const MEM_ALERT_THRESHOLD: &str = "mem_alert_threshold";
+    pub const GPU_ALERT_THRESHOLD: &str = "gpu_alert_threshold";
+    pub const CPU_SMOOTHING: &str = "cpu_smoothing";
+    pub const MEM_SMOOTHING: &str = "mem_smoothing";
+    pub const GPU_SMOOTHING: &str = "gpu_smoothing";
+    pub const HISTORY_ENABLED: &str = "history_enabled";
+    pub const HISTORY_RETENTION_DAYS: &str = "history_retention_days";
+    pub const UPDATE_INTERVAL_MS: &str = "update_interval_ms";
+    pub const RENDER_INTERVAL_MS: &str = "render_interval_ms";
+    pub const NET_UNIT: &str = "net_unit";
+    pub const NET_HYSTERESIS: &str = "net_hysteresis";
+    pub const NET_HYSTERESIS_TICKS: &str = "net_hysteresis_ticks";
+    pub const CPU_DISPLAY: &str = "cpu_display";
+    pub const MEM_DISPLAY: &str = "mem_display";
+    pub const MEM_FORMULA: &str = "mem_formula";
+    pub const MEM_RECLAIMABLE: &str = "mem_reclaimable";
+    pub const STATUS_SERVER_ENABLED: &str = "status_server_enabled";
+    pub const STATUS_SERVER_PORT: &str = "status_server_port";
+    pub const DBUS_ENABLED: &str = "dbus_enabled";
+    pub const CGROUP_METRICS: &str = "cgroup_metrics";
+    pub const CGROUP_PATH: &str = "cgroup_path";
+    pub const COMBINE_NET: &str = "combine_net";
+    pub const AUTO_WIDTH: &str = "auto_width";
+    pub const TABULAR_FIGURES: &str = "tabular_figures";
+    pub const AUTO_HIDE: &str = "auto_hide";
+    pub const AUTO_HIDE_CYCLES: &str = "auto_hide_cycles";
+    pub const SHOW_PROCESSES: &str = "show_processes";
+    pub const SHOW_CPU_FREQUENCY: &str = "show_cpu_frequency";
+    pub const ADAPTIVE_SAMPLING: &str = "adaptive_sampling";
+    pub const INTERPOLATE_VALUES: &str = "interpolate_values";
+    pub const REDUCE_ON_BATTERY: &str = "reduce_on_battery";
+    pub const PAUSE_WHEN_LOCKED: &str = "pause_when_locked";
+    pub const SHOW_CPU_POWER: &str = "show_cpu_power";
+    pub const SHOW_ANE_POWER: &str = "show_ane_power";
+    pub const SHOW_GPU_TEMPERATURE: &str = "show_gpu_temperature";
+    pub const SHOW_GPU_CODEC: &str = "show_gpu_codec";
+    pub const GPU_ENCODER_ALERT: &str = "gpu_encoder_alert";
+    pub const SEGMENT_ORDER: &str = "segment_order";
+    pub const TRAY_ICONS: &str = "tray_icons";
+    pub const LAYOUT: &str = "layout";
+    pub const VALUE_STYLE: &str = "value_style";
+    pub const LABEL_STYLE: &str = "label_style";
+    pub const ALERT_COLOR: &str = "alert_color";
+    pub const TEXT_COLOR: &str = "text_color";
+    pub const ICON_COLOR: &str = "icon_color";
+    pub const METRIC_COLORS: &str = "metric_colors";
+    pub const CPU_COLOR: &str = "cpu_color";
+    pub const MEM_COLOR: &str = "mem_color";
+    pub const GPU_COLOR: &str = "gpu_color";
+    pub const NET_COLOR: &str = "net_color";
+    pub const SHOW_NETWORK_PROCESSES: &str = "show_network_processes";
+    pub const SHOW_GPU_PROCESSES: &str = "show_gpu_processes";
+    pub const CLICK_ACTION: &str = "click_action";
+    pub const FONT_FAMILY: &str = "font_family";
+    pub const FONT_WEIGHT: &str = "font_weight";
+    pub const TEXT_SCALE: &str = "text_scale";
+    pub const SHOW_LATENCY: &str = "show_latency";
+    pub const LATENCY_HOST: &str = "latency_host";
+    pub const LATENCY_ALERT_MS: &str = "latency_alert_ms";
+    pub const SHOW_WIFI: &str = "show_wifi";
+    pub const WIFI_ALERT_DBM: &str = "wifi_alert_dbm";
+    pub const ALERT_SOUND: &str = "alert_sound";
+    pub const ALERT_SOUND_COOLDOWN_SECS: &str = "alert_sound_cooldown_secs";
+    pub const ALERT_WEBHOOK_URL: &str = "alert_webhook_url";
+    pub const QUIET_HOURS: &str = "quiet_hours";
+    pub const DATA_CAP_GB: &str = "data_cap_gb";
+    pub const WATCHED_PROCESSES: &str = "watched_processes";
+    pub const WATCHED_CPU_LIMIT: &str = "watched_cpu_limit";
+    pub const WATCHED_MEM_LIMIT_MB: &str = "watched_mem_limit_mb";
+    pub const SHOW_VPN: &str = "show_vpn";
+    pub const SHOW_PRESSURE: &str = "show_pressure";
+    pub const SHOW_TASK_COUNTS: &str = "show_task_counts";
+    pub const SHOW_DETAILS: &str = "show_details";
+    pub const SHOW_CONTAINERS: &str = "show_containers";
+    pub const TEXT_EFFECT: &str = "text_effect";
+    pub const PERCENT_CAP: &str = "percent_cap";
+    pub const LANGUAGE: &str = "language";
+    pub const CHECK_UPDATES: &str = "check_updates";
+    pub const COPY_STATS_JSON: &str = "copy_stats_json";
+    pub const LOG_LEVEL: &str = "log_level";
+    pub const LOG_TO_FILE: &str = "log_to_file";
+    pub const PROFILES: &str = "profiles";
+    pub const ACTIVE_PROFILE: &str = "active_profile";
+    pub const REMOTE_HOSTS: &str = "remote_hosts";
+    pub const REMOTE_HOST: &str = "remote_host";
+}
+
+/// Every persisted setting, in the order they are loaded.
+pub const ALL_KEYS: &[&str] = &[
+    key::AUTOSTART,
+    key::SHOW_CPU,
+    key::SHOW_MEM,
+    key::SHOW_GPU,
+    key::SHOW_GPU_MEMORY,
+    key::SHOW_NET,
+    key::SHOW_ALERTS,
+    key::CPU_ALERT_THRESHOLD,
+    key::MEM_ALERT_THRESHOLD,
+    key::GPU_ALERT_THRESHOLD,
+    key::CPU_SMOOTHING,
+    key::MEM_SMOOTHING,
+    key::GPU_SMOOTHING,
+    key::HISTORY_ENABLED,
+    key::HISTORY_RETENTION_DAYS,
+    key::UPDATE_INTERVAL_MS,
+    key::RENDER_INTERVAL_MS,
+    key::NET_UNIT,
+    key::NET_HYSTERESIS,
+    key::NET_HYSTERESIS_TICKS,
+    key::CPU_DISPLAY,
+    key::MEM_DISPLAY,
+    key::MEM_FORMULA,
+    key::MEM_RECLAIMABLE,
+    key::STATUS_SERVER_ENABLED,
+    key::STATUS_SERVER_PORT,
+    key::DBUS_ENABLED,
+    key::CGROUP_METRICS,
+    key::CGROUP_PATH,
+    key::COMBINE_NET,
+    key::AUTO_WIDTH,
+    key::TABULAR_FIGURES,
+    key::AUTO_HIDE,
+    key::AUTO_HIDE_CYCLES,
+    key::SHOW_PROCESSES,
+    key::SHOW_CPU_FREQUENCY,
+    key::ADAPTIVE_SAMPLING,
+    key::INTERPOLATE_VALUES,
+    key::REDUCE_ON_BATTERY,
+    key::PAUSE_WHEN_LOCKED,
+    key::SHOW_CPU_POWER,
+    key::SHOW_ANE_POWER,
+    key::SHOW_GPU_TEMPERATURE,
+    key::SHOW_GPU_CODEC,
+    key::GPU_ENCODER_ALERT,
+    key::SEGMENT_ORDER,
+    key::TRAY_ICONS,
+    key::LAYOUT,
+    key::VALUE_STYLE,
+    key::LABEL_STYLE,
+    key::ALERT_COLOR,
+    key::TEXT_COLOR,
+    key::ICON_COLOR,
+    key::METRIC_COLORS,
+    key::CPU_COLOR,
+    key::MEM_COLOR,
+    key::GPU_COLOR,
+    key::NET_COLOR,
+    key::SHOW_NETWORK_PROCESSES,
+    key::SHOW_GPU_PROCESSES,
+    key::CLICK_ACTION,
+    key::FONT_FAMILY,
+    key::FONT_WEIGHT,
+    key::TEXT_SCALE,
+    key::SHOW_LATENCY,
+    key::LATENCY_HOST,
+    key::LATENCY_ALERT_MS,
+    key::SHOW_WIFI,
+    key::WIFI_ALERT_DBM,
+    key::ALERT_SOUND,
+    key::ALERT_SOUND_COOLDOWN_SECS,
+    key::ALERT_WEBHOOK_URL,
+    key::QUIET_HOURS,
+    key::DATA_CAP_GB,
+    key::WATCHED_PROCESSES,
+    key::WATCHED_CPU_LIMIT,
+    key::WATCHED_MEM_LIMIT_MB,
+    key::SHOW_VPN,
+    key::SHOW_PRESSURE,
+    key::SHOW_TASK_COUNTS,
+    key::SHOW_DETAILS,
+    key::SHOW_CONTAINERS,
+    key::TEXT_EFFECT,
+    key::PERCENT_CAP,
+    key::LANGUAGE,
+    key::CHECK_UPDATES,
+    key::COPY_STATS_JSON,
+    key::LOG_LEVEL,
+    key::LOG_TO_FILE,
+    // After the profile list, which the active profile must be part of
+    key::PROFILES,
+    key::ACTIVE_PROFILE,
+    // Likewise for the selected remote host
+    key::REMOTE_HOSTS,
+    key::REMOTE_HOST,
+];
+
+pub const DEFAULT_ALERT_THRESHOLD: u32 = 90;
+
+const NO_QUIET_HOURS: u32 = u32::MAX;
+
+/// Threshold choices offered in the tray menu. Any value in 1..=100 can be set
+/// from the preferences window or by editing the settings file directly.
+pub const ALERT_THRESHOLD_PRESETS: [u32; 5] = [70, 80, 85, 90, 95];
+
+pub const DEFAULT_UPDATE_INTERVAL_MS: u32 = 2000;
+const MIN_UPDATE_INTERVAL_MS: u64 = 250;
+const MAX_UPDATE_INTERVAL_MS: u64 = 60_000;
+/// Redrawing the tray icon more often than this churns the panel for no
+/// visible gain, however fast the samplers run.
+const MIN_RENDER_INTERVAL_MS: u64 = 1000;
+const MAX_RETENTION_DAYS: u64 = 365;
+/// Unprivileged ports only, so enabling the server never needs elevated rights.
+const MIN_STATUS_PORT: u64 = 1024;
+/// Stored in `text_color` while the foreground follows the panel theme.
+const AUTO_COLOR: u32 = u32::MAX;
+/// CSS-style weight of the tray text; 400 is regular.
+pub const DEFAULT_FONT_WEIGHT: u32 = 400;
+/// Percent the tray icon is enlarged or shrunk by (`text_scale`), 0.8×–1.5×.
+pub const DEFAULT_TEXT_SCALE: u32 = 100;
+const MIN_TEXT_SCALE: u64 = 80;
+const MAX_TEXT_SCALE: u64 = 150;
+const MAX_FONT_FAMILY_LEN: usize = 128;
+const MAX_CGROUP_PATH_LEN: usize = 4096;
+pub const DEFAULT_WATCHED_CPU_LIMIT: u32 = 100;
+pub const DEFAULT_WATCHED_MEM_LIMIT_MB: u32 = 2048;
+/// 64 cores fully busy
+const MAX_WATCHED_CPU_LIMIT: u32 = 6400;
+/// 1 TB
+const MAX_WATCHED_MEM_LIMIT_MB: u32 = 1_000_000;
+pub const DEFAULT_NET_HYSTERESIS: u32 = 10;
+const MAX_NET_HYSTERESIS: u32 = 50;
+pub const DEFAULT_NET_HYSTERESIS_TICKS: u32 = 5;
+const MAX_NET_HYSTERESIS_TICKS: u32 = 60;
+pub const DEFAULT_LATENCY_ALERT_MS: u32 = 100;
+/// Replies slower than the probe timeout never arrive, so higher thresholds can't trigger.
+const MAX_LATENCY_ALERT_MS: u64 = 2000;
+
+/// How network throughput is displayed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NetUnit {
+    /// Bytes per second (KB, MB, GB)
+    Bytes,
+    /// Bits per second (Kb, Mb, Gb)
+    Bits,
+}
+
+impl NetUnit {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NetUnit::Bytes => "bytes",
+            NetUnit::Bits => "bits",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "bytes" => Some(NetUnit::Bytes),
+            "bits" => Some(NetUnit::Bits),
+            _ => None,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        if value == NetUnit::Bits as u8 {
+            NetUnit::Bits
+        } else {
+            NetUnit::Bytes
+        }
+    }
+}
+
+/// What left-clicking the tray icon does. Only macOS and Windows report icon
+/// clicks; Linux tray hosts always open the menu.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ClickAction {
+    Menu,
+    /// Live charts popover (macOS)
+    Dashboard,
+    /// Activity Monitor, or the desktop's system monitor / htop on Linux
+    SystemMonitor,
+    Preferences,
+    /// Switches between the standard and compact layouts
+    ToggleCompact,
+}
+
+impl ClickAction {
+    pub const ALL: [ClickAction; 5] = [
+        ClickAction::Menu,
+        ClickAction::Dashboard,
+        ClickAction::SystemMonitor,
+        ClickAction::Preferences,
+        ClickAction::ToggleCompact,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ClickAction::Menu => "menu",
+            ClickAction::Dashboard => "dashboard",
+            ClickAction::SystemMonitor => "system_monitor",
+            ClickAction::Preferences => "preferences",
+            ClickAction::ToggleCompact => "toggle_compact",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.as_str() == value)
+    }
+
+    fn from_u8(value: u8) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|action| *action as u8 == value)
+            .unwrap_or(ClickAction::Menu)
+    }
+}
+
+/// What the CPU segment shows.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CpuDisplay {
+    /// Instantaneous usage across all cores
+    Percent,
+    /// 1-minute load average
+    LoadAverage,
+    /// User and system time, e.g. "23/8%" (Linux and macOS)
+    UserSystem,
+    /// A grid with a cell per core, shaded by its usage
+    Heatmap,
+}
+
+impl CpuDisplay {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CpuDisplay::Percent => "percent",
+            CpuDisplay::LoadAverage => "load",
+            CpuDisplay::UserSystem => "user_system",
+            CpuDisplay::Heatmap => "heatmap",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "percent" => Some(CpuDisplay::Percent),
+            "load" => Some(CpuDisplay::LoadAverage),
+            "user_system" => Some(CpuDisplay::UserSystem),
+            "heatmap" => Some(CpuDisplay::Heatmap),
+            _ => None,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        [
+            CpuDisplay::LoadAverage,
+            CpuDisplay::UserSystem,
+            CpuDisplay::Heatmap,
+        ]
+        .into_iter()
+        .find(|display| *display as u8 == value)
+        .unwrap_or(CpuDisplay::Percent)
+    }
+}
+
+/// What the memory segment shows.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MemDisplay {
+    /// Share of total RAM in use
+    Percent,
+    /// Bytes in use, e.g. "12.4G"
+    Used,
+    /// Activity Monitor style usage, colored by the pressure level (macOS)
+    Pressure,
+    /// A bar of apps, wired, cached and free memory (Linux and macOS)
+    Breakdown,
+}
+
+impl MemDisplay {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MemDisplay::Percent => "percent",
+            MemDisplay::Used => "used",
+            MemDisplay::Pressure => "pressure",
+            MemDisplay::Breakdown => "breakdown",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "percent" => Some(MemDisplay::Percent),
+            "used" => Some(MemDisplay::Used),
+            "pressure" => Some(MemDisplay::Pressure),
+            "breakdown" => Some(MemDisplay::Breakdown),
+            _ => None,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            v if v == MemDisplay::Used as u8 => MemDisplay::Used,
+            v if v == MemDisplay::Pressure as u8 => MemDisplay::Pressure,
+            v if v == MemDisplay::Breakdown as u8 => MemDisplay::Breakdown,
+            _ => MemDisplay::Percent,
+        }
+    }
+}
+
+/// Whether the icon follows the panel theme or is forced light or dark, for
+/// when theme detection guesses wrong.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IconColor {
+    Auto,
+    /// White, for dark panels
+    Light,
+    /// Black, for light panels
+    Dark,
+}
+
+impl IconColor {
+    pub const ALL: [IconColor; 3] = [IconColor::Auto, IconColor::Light, IconColor::Dark];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IconColor::Auto => "auto",
+            IconColor::Light => "light",
+            IconColor::Dark => "dark",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            IconColor::Auto => "Auto",
+            IconColor::Light => "Light",
+            IconColor::Dark => "Dark",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|color| color.as_str() == value)
+    }
+
+    fn from_u8(value: u8) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|color| *color as u8 == value)
+            .unwrap_or(IconColor::Auto)
+    }
+
+    /// The forced foreground, or `None` to follow the theme.
+    fn foreground(self) -> Option<Rgb> {
+        match self {
+            IconColor::Auto => None,
+            IconColor::Light => Some(Palette::for_theme(true).foreground),
+            IconColor::Dark => Some(Palette::for_theme(false).foreground),
+        }
+    }
+}
+
+pub struct Config {
+    pub show_cpu: AtomicBool,
+    pub show_mem: AtomicBool,
+    pub show_gpu: AtomicBool,
+    pub show_gpu_memory: AtomicBool,
+    pub show_net: AtomicBool,
+    pub show_alerts: AtomicBool,
+    pub autostart: AtomicBool,
+    pub cpu_alert_threshold: AtomicU32,
+    pub mem_alert_threshold: AtomicU32,
+    pub gpu_alert_threshold: AtomicU32,
+    /// Percent of the previous displayed value kept each tick; 0 is off
+    pub cpu_smoothing: AtomicU32,
+    pub mem_smoothing: AtomicU32,
+    pub gpu_smoothing: AtomicU32,
+    pub history_enabled: AtomicBool,
+    pub history_retention_days: AtomicU32,
+    pub update_interval_ms: AtomicU32,
+    /// Minimum time between icon redraws; 0 redraws on every sample
+    pub render_interval_ms: AtomicU32,
+    net_unit: AtomicU8,
+    /// Percent of the shown rate a network change must reach to redraw
+    pub net_hysteresis: AtomicU32,
+    /// Ticks a smaller change is held back before it is drawn anyway
+    pub net_hysteresis_ticks: AtomicU32,
+    cpu_display: AtomicU8,
+    mem_display: AtomicU8,
+    mem_formula: AtomicU8,
+    /// Reclaimable memory counted as free whichever the formula
+    mem_reclaimable: Mutex<Vec<MemClass>>,
+    pub status_server_enabled: AtomicBool,
+    pub status_server_port: AtomicU32,
+    /// Linux only; ignored elsewhere
+    pub dbus_enabled: AtomicBool,
+    /// CPU and memory against a cgroup's limits (Linux)
+    pub cgroup_metrics: AtomicBool,
+    /// Below `/sys/fs/cgroup`; empty for the app's own cgroup
+    cgroup_path: Mutex<String>,
+    /// Download and upload in one narrower segment
+    pub combine_net: AtomicBool,
+    /// Segments only as wide as their values, rather than the fixed widths
+    pub auto_width: AtomicBool,
+    /// Digits of one width and room for three, so the icon width holds
+    pub tabular_figures: AtomicBool,
+    /// Segments hidden while idle
+    auto_hide: Mutex<Vec<SegmentKind>>,
+    /// Idle updates in a row before an `auto_hide` segment is hidden
+    pub auto_hide_cycles: AtomicU32,
+    pub show_processes: AtomicBool,
+    pub show_cpu_frequency: AtomicBool,
+    pub adaptive_sampling: AtomicBool,
+    /// Ease the icon's values from one reading to the next over a few frames
+    pub interpolate_values: AtomicBool,
+    pub reduce_on_battery: AtomicBool,
+    /// Stop sampling and drawing while the screen is locked
+    pub pause_when_locked: AtomicBool,
+    pub show_cpu_power: AtomicBool,
+    pub show_ane_power: AtomicBool,
+    pub show_gpu_temperature: AtomicBool,
+    /// NVENC/NVDEC utilization line in the menu
+    pub show_gpu_codec: AtomicBool,
+    /// Alert while the video encoder is saturated
+    pub gpu_encoder_alert: AtomicBool,
+    pub show_network_processes: AtomicBool,
+    pub show_gpu_processes: AtomicBool,
+    /// One `SegmentKind` index per byte, first segment in the lowest byte
+    segment_order: AtomicU32,
+    /// Segments of each tray icon; empty for a single icon showing them all
+    tray_icons: Mutex<Vec<Vec<SegmentKind>>>,
+    layout: AtomicU8,
+    value_style: AtomicU8,
+    label_style: AtomicU8,
+    click_action: AtomicU8,
+    /// 0xRRGGBB
+    alert_color: AtomicU32,
+    /// 0xRRGGBB, or `AUTO_COLOR`
+    text_color: AtomicU32,
+    icon_color: AtomicU8,
+    pub metric_colors: AtomicBool,
+    /// 0xRRGGBB each, used while `metric_colors` is on
+    cpu_color: AtomicU32,
+    mem_color: AtomicU32,
+    gpu_color: AtomicU32,
+    net_color: AtomicU32,
+    /// Empty for the system sans-serif font
+    font_family: Mutex<String>,
+    font_weight: AtomicU32,
+    /// Bumped on every font change so the monitoring loop knows to reload it
+    font_revision: AtomicU32,
+    /// Percent applied to the panel's sizing, for larger or smaller tray text
+    pub text_scale: AtomicU32,
+    pub show_latency: AtomicBool,
+    /// Empty to ping the default gateway
+    latency_host: Mutex<String>,
+    pub latency_alert_ms: AtomicU32,
+    pub show_wifi: AtomicBool,
+    /// Signal in dBm below which the Wi-Fi segment is alert-colored
+    pub wifi_alert_dbm: AtomicI32,
+    alert_sound: AtomicU8,
+    pub alert_sound_cooldown_secs: AtomicU32,
+    /// Empty while alerts aren't posted anywhere
+    alert_webhook_url: Mutex<String>,
+    /// `QuietHours::pack`, or `NO_QUIET_HOURS`
+    quiet_hours: AtomicU32,
+    /// Monthly data cap in GB (decimal); 0 turns tracking off
+    pub data_cap_gb: AtomicU32,
+    watched_processes: Mutex<Vec<String>>,
+    /// Percent of one core a watched process may use; 0 turns it off
+    pub watched_cpu_limit: AtomicU32,
+    /// Resident memory in MB (decimal) a watched process may use; 0 turns it off
+    pub watched_mem_limit_mb: AtomicU32,
+    pub show_vpn: AtomicBool,
+    pub show_pressure: AtomicBool,
+    pub show_task_counts: AtomicBool,
+    /// Exact readings at the top of the tray menu
+    pub show_details: AtomicBool,
+    pub show_containers: AtomicBool,
+    text_effect: AtomicU8,
+    percent_cap: AtomicU8,
+    /// Code of a bundled language, or empty to follow the system locale
+    language: Mutex<String>,
+    pub check_updates: AtomicBool,
+    /// "Copy Stats" copies JSON instead of a line of text
+    pub copy_stats_json: AtomicBool,
+    log_level: AtomicU8,
+    pub log_to_file: AtomicBool,
+    profiles: Mutex<Vec<Profile>>,
+    /// Name of the profile last chosen, or empty
+    active_profile: Mutex<String>,
+    remote_hosts: Mutex<Vec<String>>,
+    /// Entry of `remote_hosts` shown in the tray, or empty for this machine
+    remote_host: Mutex<String>,
+    /// The pointer is over a tray icon, so its tooltip can be seen
+    pub tray_hovered: AtomicBool,
+    /// Wakes the monitoring loop to redraw before its next tick
+    redraw: Notify,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            show_cpu: AtomicBool::new(true),
+            show_mem: AtomicBool::new(true),
+            show_gpu: AtomicBool::new(true),
+            show_gpu_memory: AtomicBool::new(false),
+            show_net: AtomicBool::new(true),
+            show_alerts: AtomicBool::new(true),
+            autostart: AtomicBool::new(false),
+            cpu_alert_threshold: AtomicU32::new(DEFAULT_ALERT_THRESHOLD),
+            mem_alert_threshold: AtomicU32::new(DEFAULT_ALERT_THRESHOLD),
+            gpu_alert_threshold: AtomicU32::new(DEFAULT_ALERT_THRESHOLD),
+            cpu_smoothing: AtomicU32::new(0),
+            mem_smoothing: AtomicU32::new(0),
+            gpu_smoothing: AtomicU32::new(0),
+            history_enabled: AtomicBool::new(false),
+            history_retention_days: AtomicU32::new(DEFAULT_RETENTION_DAYS),
+            update_interval_ms: AtomicU32::new(DEFAULT_UPDATE_INTERVAL_MS),
+            render_interval_ms: AtomicU32::new(MIN_RENDER_INTERVAL_MS as u32),
+            net_unit: AtomicU8::new(NetUnit::Bytes as u8),
+            net_hysteresis: AtomicU32::new(DEFAULT_NET_HYSTERESIS),
+            net_hysteresis_ticks: AtomicU32::new(DEFAULT_NET_HYSTERESIS_TICKS),
+            cpu_display: AtomicU8::new(CpuDisplay::Percent as u8),
+            mem_display: AtomicU8::new(MemDisplay::Percent as u8),
+            mem_formula: AtomicU8::new(MemFormula::Used as u8),
+            mem_reclaimable: Mutex::new(Vec::new()),
+            status_server_enabled: AtomicBool::new(false),
+            status_server_port: AtomicU32::new(status_server::DEFAULT_PORT as u32),
+            dbus_enabled: AtomicBool::new(false),
+            cgroup_metrics: AtomicBool::new(false),
+            cgroup_path: Mutex::new(String::new()),
+            combine_net: AtomicBool::new(false),
+            auto_width: AtomicBool::new(false),
+            tabular_figures: AtomicBool::new(false),
+            auto_hide: Mutex::new(Vec::new()),
+            auto_hide_cycles: AtomicU32::new(idle::DEFAULT_CYCLES),
+            show_processes: AtomicBool::new(false),
+            show_cpu_frequency: AtomicBool::new(false),
+            adaptive_sampling: AtomicBool::new(true),
+            interpolate_values: AtomicBool::new(false),
+            reduce_on_battery: AtomicBool::new(true),
+            pause_when_locked: AtomicBool::new(true),
+            show_cpu_power: AtomicBool::new(false),
+            show_ane_power: AtomicBool::new(false),
+            show_gpu_temperature: AtomicBool::new(false),
+            show_gpu_codec: AtomicBool::new(false),
+            gpu_encoder_alert: AtomicBool::new(false),
+            show_network_processes: AtomicBool::new(false),
+            show_gpu_processes: AtomicBool::new(false),
+            segment_order: AtomicU32::new(pack_segment_order(SegmentKind::ALL)),
+            tray_icons: Mutex::new(Vec::new()),
+            layout: AtomicU8::new(Layout::Standard as u8),
+            value_style: AtomicU8::new(ValueStyle::Number as u8),
+            label_style: AtomicU8::new(LabelStyle::Icons as u8),
+            click_action: AtomicU8::new(ClickAction::Menu as u8),
+            alert_color: AtomicU32::new(pack_color(tray_render::DEFAULT_ALERT_COLOR)),
+            text_color: AtomicU32::new(AUTO_COLOR),
+            icon_color: AtomicU8::new(IconColor::Auto as u8),
+            metric_colors: AtomicBool::new(false),
+            cpu_color: AtomicU32::new(pack_color(MetricColors::DEFAULT.cpu)),
+            mem_color: AtomicU32::new(pack_color(MetricColors::DEFAULT.mem)),
+            gpu_color: AtomicU32::new(pack_color(MetricColors::DEFAULT.gpu)),
+            net_color: AtomicU32::new(pack_color(MetricColors::DEFAULT.net)),
+            font_family: Mutex::new(String::new()),
+            font_weight: AtomicU32::new(DEFAULT_FONT_WEIGHT),
+            font_revision: AtomicU32::new(0),
+            text_scale: AtomicU32::new(DEFAULT_TEXT_SCALE),
+            show_latency: AtomicBool::new(false),
+            latency_host: Mutex::new(String::new()),
+            latency_alert_ms: AtomicU32::new(DEFAULT_LATENCY_ALERT_MS),
+            show_wifi: AtomicBool::new(false),
+            wifi_alert_dbm: AtomicI32::new(wifi::DEFAULT_ALERT_DBM),
+            alert_sound: AtomicU8::new(AlertSound::Off as u8),
+            alert_sound_cooldown_secs: AtomicU32::new(sound::DEFAULT_COOLDOWN_SECS),
+            alert_webhook_url: Mutex::new(String::new()),
+            quiet_hours: AtomicU32::new(NO_QUIET_HOURS),
+            data_cap_gb: AtomicU32::new(0),
+            watched_processes: Mutex::new(Vec::new()),
+            watched_cpu_limit: AtomicU32::new(DEFAULT_WATCHED_CPU_LIMIT),
+            watched_mem_limit_mb: AtomicU32::new(DEFAULT_WATCHED_MEM_LIMIT_MB),
+            show_vpn: AtomicBool::new(false),
+            show_pressure: AtomicBool::new(false),
+            show_task_counts: AtomicBool::new(false),
+            show_details: AtomicBool::new(false),
+            show_containers: AtomicBool::new(false),
+            text_effect: AtomicU8::new(TextEffect::None as u8),
+            percent_cap: AtomicU8::new(PercentCap::Capped as u8),
+            language: Mutex::new(String::new()),
+            check_updates: AtomicBool::new(false),
+            copy_stats_json: AtomicBool::new(false),
+            log_level: AtomicU8::new(LogLevel::Info as u8),
+            log_to_file: AtomicBool::new(false),
+            profiles: Mutex::new(profiles::built_in()),
+            active_profile: Mutex::new(String::new()),
+            remote_hosts: Mutex::new(Vec::new()),
+            remote_host: Mutex::new(String::new()),
+            tray_hovered: AtomicBool::new(false),
+            redraw: Notify::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Loads persisted settings, falling back to defaults for missing or invalid keys.
+    pub fn load(app: &AppHandle) -> Self {
+        let config = Self::default();
+
+        match app.store(SETTINGS_FILE) {
+            Ok(store) => config.apply(|key| store.get(key)),
+            Err(e) => error!("Failed to load settings store: {e}"),
+        }
+
+        config
+    }
+
+    /// Sets every known key that `lookup` has a value for, skipping invalid ones.
+    pub fn apply(&self, lookup: impl Fn(&str) -> Option<Value>) {
+        for key in ALL_KEYS {
+            if let Some(value) = lookup(key) {
+                if let Err(e) = self.set(key, &value) {
+                    warn!("Ignoring invalid setting: {e}");
+                }
+            }
+        }
+    }
+
+    /// Current value of a setting, or `None` for unknown keys.
+    pub fn get(&self, key: &str) -> Option<Value> {
+        if let Some(flag) = self.flag(key) {
+            return Some(json!(flag.load(Relaxed)));
+        }
+
+        let value = match key {
+            key::CPU_ALERT_THRESHOLD | key::MEM_ALERT_THRESHOLD | key::GPU_ALERT_THRESHOLD => {
+                json!(self.alert_threshold(key)?.load(Relaxed))
+            }
+            key::CPU_SMOOTHING | key::MEM_SMOOTHING | key::GPU_SMOOTHING => {
+                json!(self.smoothing(key)?.load(Relaxed))
+            }
+            key::HISTORY_RETENTION_DAYS => json!(self.history_retention_days.load(Relaxed)),
+            key::UPDATE_INTERVAL_MS => json!(self.update_interval_ms.load(Relaxed)),
+            key::RENDER_INTERVAL_MS => json!(self.render_interval_ms.load(Relaxed)),
+            key::NET_UNIT => json!(self.net_unit().as_str()),
+            key::NET_HYSTERESIS => json!(self.net_hysteresis.load(Relaxed)),
+            key::NET_HYSTERESIS_TICKS => json!(self.net_hysteresis_ticks.load(Relaxed)),
+            key::CPU_DISPLAY => json!(self.cpu_display().as_str()),
+            key::MEM_DISPLAY => json!(self.mem_display().as_str()),
+            key::MEM_FORMULA => json!(self.mem_formula().as_str()),
+            key::MEM_RECLAIMABLE => json!(self
+                .mem_reclaimable()
+                .into_iter()
+                .map(MemClass::as_str)
+                .collect::<Vec<_>>()),
+            key::STATUS_SERVER_PORT => json!(self.status_server_port.load(Relaxed)),
+            key::SEGMENT_ORDER => json!(self.segment_order().map(SegmentKind::as_str)),
+            key::TRAY_ICONS => json!(tray_icons_json(&self.tray_icons())),
+            key::AUTO_HIDE => json!(self
+                .auto_hide()
+                .into_iter()
+                .map(SegmentKind::as_str)
+                .collect::<Vec<_>>()),
+            key::AUTO_HIDE_CYCLES => json!(self.auto_hide_cycles.load(Relaxed)),
+            key::LAYOUT => json!(self.layout().as_str()),
+            key::VALUE_STYLE => json!(self.value_style().as_str()),
+            key::LABEL_STYLE => json!(self.label_style().as_str()),
+            key::CLICK_ACTION => json!(self.click_action().as_str()),
+            key::ALERT_COLOR => json!(tray_render::format_hex_color(self.alert_color())),
+            key::TEXT_COLOR => json!(self
+                .text_color()
+                .map(tray_render::format_hex_color)
+                .unwrap_or_default()),
+            key::ICON_COLOR => json!(self.icon_color().as_str()),
+            key::CPU_COLOR | key::MEM_COLOR | key::GPU_COLOR | key::NET_COLOR => json!(
+                tray_render::format_hex_color(unpack_color(self.metric_color(key)?.load(Relaxed)))
+            ),
+            key::FONT_FAMILY => json!(self.font_family().unwrap_or_default()),
+            key::CGROUP_PATH => json!(self.cgroup_path()),
+            key::FONT_WEIGHT => json!(self.font_weight()),
+            key::TEXT_SCALE => json!(self.text_scale.load(Relaxed)),
+            key::LATENCY_HOST => json!(self.latency_host()),
+            key::LATENCY_ALERT_MS => json!(self.latency_alert_ms.load(Relaxed)),
+            key::WIFI_ALERT_DBM => json!(self.wifi_alert_dbm.load(Relaxed)),
+            key::ALERT_SOUND => json!(self.alert_sound().as_str()),
+            key::ALERT_SOUND_COOLDOWN_SECS => json!(self.alert_sound_cooldown_secs.load(Relaxed)),
+            key::ALERT_WEBHOOK_URL => json!(self.alert_webhook_url()),
+            key::QUIET_HOURS => json!(self
+                .quiet_hours()
+                .map(QuietHours::to_setting)
+                .unwrap_or_default()),
+            key::DATA_CAP_GB => json!(self.data_cap_gb.load(Relaxed)),
+            key::WATCHED_PROCESSES => json!(self.watched_processes()),
+            key::WATCHED_CPU_LIMIT => json!(self.watched_cpu_limit.load(Relaxed)),
+            key::WATCHED_MEM_LIMIT_MB => json!(self.watched_mem_limit_mb.load(Relaxed)),
+            key::TEXT_EFFECT => json!(self.text_effect().as_str()),
+            key::PERCENT_CAP => json!(self.percent_cap().as_str()),
+            key::LANGUAGE => json!(self.language()),
+            key::LOG_LEVEL => json!(self.log_level().as_str()),
+            key::PROFILES => json!(self
+                .profiles()
+                .iter()
+                .map(Profile::to_json)
+                .collect::<Vec<_>>()),
+            key::ACTIVE_PROFILE => json!(self.active_profile()),
+            key::REMOTE_HOSTS => json!(self.remote_hosts()),
+            key::REMOTE_HOST => json!(self.remote_host()),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Validates and applies a setting, returning the sanitized value to persist.
+    pub fn set(&self, key: &str, value: &Value) -> Result<Value, String> {
+        if let Some(flag) = self.flag(key) {
+            let enabled = value
+                .as_bool()
+                .ok_or_else(|| format!("{key} must be true or false"))?;
+            flag.store(enabled, Relaxed);
+            return Ok(json!(enabled));
+        }
+
+        let as_u64 = || {
+            value
+                .as_u64()
+                .ok_or_else(|| format!("{key} must be a positive integer"))
+        };
+
+        match key {
+            key::CPU_ALERT_THRESHOLD | key::MEM_ALERT_THRESHOLD | key::GPU_ALERT_THRESHOLD => {
+                let threshold = sanitize_threshold(as_u64()?);
+                if let Some(atomic) = self.alert_threshold(key) {
+                    atomic.store(threshold, Relaxed);
+                }
+                Ok(json!(threshold))
+            }
+            key::CPU_SMOOTHING | key::MEM_SMOOTHING | key::GPU_SMOOTHING => {
+                let smoothing = as_u64()?.min(smoothing::MAX_SMOOTHING as u64) as u32;
+                if let Some(atomic) = self.smoothing(key) {
+                    atomic.store(smoothing, Relaxed);
+                }
+                Ok(json!(smoothing))
+            }
+            key::HISTORY_RETENTION_DAYS => {
+                let days = as_u64()?.clamp(1, MAX_RETENTION_DAYS) as u32;
+                self.history_retention_days.store(days, Relaxed);
+                Ok(json!(days))
+            }
+            key::UPDATE_INTERVAL_MS => {
+                let interval = sanitize_update_interval(as_u64()?);
+                self.update_interval_ms.store(interval, Relaxed);
+                Ok(json!(interval))
+            }
+            key::RENDER_INTERVAL_MS => {
+                let interval = match as_u64()? {
+                    0 => 0,
+                    ms => ms.clamp(MIN_RENDER_INTERVAL_MS, MAX_UPDATE_INTERVAL_MS) as u32,
+                };
+                self.render_interval_ms.store(interval, Relaxed);
+                Ok(json!(interval))
+            }
+            key::NET_UNIT => {
+                let unit = value
+                    .as_str()
+                    .and_then(NetUnit::parse)
+                    .ok_or_else(|| format!("{key} must be \"bytes\" or \"bits\""))?;
+                self.net_unit.store(unit as u8, Relaxed);
+                Ok(json!(unit.as_str()))
+            }
+            key::CPU_DISPLAY => {
+                let display = value.as_str().and_then(CpuDisplay::parse).ok_or_else(|| {
+                    format!("{key} must be \"percent\", \"load\", \"user_system\" or \"heatmap\"")
+                })?;
+                self.set_cpu_display(display);
+                Ok(json!(display.as_str()))
+            }
+            key::MEM_DISPLAY => {
+                let display = value.as_str().and_then(MemDisplay::parse).ok_or_else(|| {
+                    format!("{key} must be \"percent\", \"used\", \"pressure\" or \"breakdown\"")
+                })?;
+                self.set_mem_display(display);
+                Ok(json!(display.as_str()))
+            }
+            key::MEM_FORMULA => {
+                let formula = value
+                    .as_str()
+                    .and_then(MemFormula::parse)
+                    .ok_or_else(|| format!("{key} must be \"used\" or \"available\""))?;
+                self.set_mem_formula(formula);
+                Ok(json!(formula.as_str()))
+            }
+            key::MEM_RECLAIMABLE => {
+                let classes = parse_mem_classes(value).ok_or_else(|| {
+                    format!("{key} must list any of \"zfs_arc\", \"file_cache\" at most once")
+                })?;
+                let saved = json!(classes
+                    .iter()
+                    .map(|class| class.as_str())
+                    .collect::<Vec<_>>());
+                *self
+                    .mem_reclaimable
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner()) = classes;
+                Ok(saved)
+            }
+            key::STATUS_SERVER_PORT => {
+                let port = as_u64()?;
+                if !(MIN_STATUS_PORT..=u16::MAX as u64).contains(&port) {
+                    return Err(format!(
+                        "{key} must be between {MIN_STATUS_PORT} and {}",
+                        u16::MAX
+                    ));
+                }
+                self.status_server_port.store(port as u32, Relaxed);
+                Ok(json!(port))
+            }
+            key::SEGMENT_ORDER => {
+                let order = parse_segment_order(value).ok_or_else(|| {
+                    format!(
+                        "{key} must list each of \"mem\", \"cpu\", \"gpu\", \"net\" at most once"
+                    )
+                })?;
+                self.segment_order.store(pack_segment_order(order), Relaxed);
+                Ok(json!(order.map(SegmentKind::as_str)))
+            }
+            key::TRAY_ICONS => {
+                let icons = parse_tray_icons(value).ok_or_else(|| {
+                    format!("{key} must be a list of segment lists, each segment in one at most")
+                })?;
+                let saved = json!(tray_icons_json(&icons));
+                *self.tray_icons.lock().unwrap_or_else(|e| e.into_inner()) = icons;
+                Ok(saved)
+            }
+            key::AUTO_HIDE => {
+                let segments = parse_segment_list(value).ok_or_else(|| {
+                    format!(
+                        "{key} must list any of \"mem\", \"cpu\", \"gpu\", \"net\" at most once"
+                    )
+                })?;
+                let saved = json!(segments
+                    .iter()
+                    .map(|kind| kind.as_str())
+                    .collect::<Vec<_>>());
+                *self.auto_hide.lock().unwrap_or_else(|e| e.into_inner()) = segments;
+                Ok(saved)
+            }
+            key::NET_HYSTERESIS => {
+                let percent = as_u64()?.min(MAX_NET_HYSTERESIS as u64) as u32;
+                self.net_hysteresis.store(percent, Relaxed);
+                Ok(json!(percent))
+            }
+            key::NET_HYSTERESIS_TICKS => {
+                let ticks = as_u64()?.clamp(1, MAX_NET_HYSTERESIS_TICKS as u64) as u32;
+                self.net_hysteresis_ticks.store(ticks, Relaxed);
+                Ok(json!(ticks))
+            }
+            key::AUTO_HIDE_CYCLES => {
+                let cycles = as_u64()?.clamp(1, idle::MAX_CYCLES) as u32;
+                self.auto_hide_cycles.store(cycles, Relaxed);
+                Ok(json!(cycles))
+            }
+            key::LAYOUT => {
+                let layout = value.as_str().and_then(Layout::parse).ok_or_else(|| {
+                    format!("{key} must be \"standard\", \"compact\", \"icons\" or \"stacked\"")
+                })?;
+                self.set_layout(layout);
+                Ok(json!(layout.as_str()))
+            }
+            key::VALUE_STYLE => {
+                let style = value.as_str().and_then(ValueStyle::parse).ok_or_else(|| {
+                    format!("{key} must be \"number\", \"bar\" or \"vertical_bar\"")
+                })?;
+                self.value_style.store(style as u8, Relaxed);
+                Ok(json!(style.as_str()))
+            }
+            key::LABEL_STYLE => {
+                let style = value
+                    .as_str()
+                    .and_then(LabelStyle::parse)
+                    .ok_or_else(|| format!("{key} must be \"icons\", \"text\" or \"both\""))?;
+                self.label_style.store(style as u8, Relaxed);
+                Ok(json!(style.as_str()))
+            }
+            key::CLICK_ACTION => {
+                let action = value.as_str().and_then(ClickAction::parse).ok_or_else(|| {
+                    let names: Vec<_> = ClickAction::ALL.map(ClickAction::as_str).into();
+                    format!("{key} must be one of {}", names.join(", "))
+                })?;
+                self.click_action.store(action as u8, Relaxed);
+                Ok(json!(action.as_str()))
+            }
+            key::ALERT_COLOR => {
+                let color = value
+                    .as_str()
+                    .and_then(tray_render::parse_hex_color)
+                    .ok_or_else(|| format!("{key} must be a color like \"#D14715\""))?;
+                self.alert_color.store(pack_color(color), Relaxed);
+                Ok(json!(tray_render::format_hex_color(color)))
+            }
+            key::CPU_COLOR | key::MEM_COLOR | key::GPU_COLOR | key::NET_COLOR => {
+                let color = value
+                    .as_str()
+                    .and_then(tray_render::parse_hex_color)
+                    .ok_or_else(|| format!("{key} must be a color like \"#0A84FF\""))?;
+                if let Some(atomic) = self.metric_color(key) {
+                    atomic.store(pack_color(color), Relaxed);
+                }
+                Ok(json!(tray_render::format_hex_color(color)))
+            }
+            key::TEXT_COLOR => {
+                // An empty string restores the theme-dependent white/black
+                let color = match value.as_str().map(str::trim) {
+                    Some("") => None,
+                    Some(hex) => Some(tray_render::parse_hex_color(hex).ok_or_else(|| {
+                        format!("{key} must be a color like \"#FFFFFF\" or empty")
+                    })?),
+                    None => return Err(format!("{key} must be a string")),
+                };
+                self.text_color
+                    .store(color.map_or(AUTO_COLOR, pack_color), Relaxed);
+                Ok(json!(color
+                    .map(tray_render::format_hex_color)
+                    .unwrap_or_default()))
+            }
+            key::ICON_COLOR => {
+                let color = value.as_str().and_then(IconColor::parse).ok_or_else(|| {
+                    let names: Vec<_> = IconColor::ALL.map(IconColor::as_str).into();
+                    format!("{key} must be one of {}", names.join(", "))
+                })?;
+                self.icon_color.store(color as u8, Relaxed);
+                // Don't leave a wrong guess on screen until the next tick
+                self.request_redraw();
+                Ok(json!(color.as_str()))
+            }
+            key::CGROUP_PATH => {
+                let path = value
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|path| {
+                        path.len() <= MAX_CGROUP_PATH_LEN
+                            && path.split('/').all(|part| part != "..")
+                    })
+                    .ok_or_else(|| format!("{key} must be a cgroup path or empty"))?;
+                *self.cgroup_path.lock().unwrap_or_else(|e| e.into_inner()) = path.to_string();
+                Ok(json!(path))
+            }
+            key::FONT_FAMILY => {
+                // An empty string restores the system sans-serif font
+                let family = value
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|family| family.len() <= MAX_FONT_FAMILY_LEN)
+                    .ok_or_else(|| format!("{key} must be a font family name or empty"))?;
+                *self.font_family.lock().unwrap_or_else(|e| e.into_inner()) = family.to_string();
+                self.font_revision.fetch_add(1, Relaxed);
+                Ok(json!(family))
+            }
+            key::FONT_WEIGHT => {
+                let weight = sanitize_font_weight(as_u64()?);
+                self.font_weight.store(weight, Relaxed);
+                self.font_revision.fetch_add(1, Relaxed);
+                Ok(json!(weight))
+            }
+            key::TEXT_SCALE => {
+                let percent = as_u64()?.clamp(MIN_TEXT_SCALE, MAX_TEXT_SCALE) as u32;
+                self.text_scale.store(percent, Relaxed);
+                Ok(json!(percent))
+            }
+            key::LATENCY_HOST => {
+                // An empty string pings the default gateway
+                let host = value
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|host| latency::is_valid_host(host))
+                    .ok_or_else(|| format!("{key} must be a hostname, an IP address or empty"))?;
+                *self.latency_host.lock().unwrap_or_else(|e| e.into_inner()) = host.to_string();
+                Ok(json!(host))
+            }
+            key::TEXT_EFFECT => {
+                let effect = value
+                    .as_str()
+                    .and_then(TextEffect::parse)
+                    .ok_or_else(|| format!("{key} must be \"none\", \"outline\" or \"shadow\""))?;
+                self.text_effect.store(effect as u8, Relaxed);
+                Ok(json!(effect.as_str()))
+            }
+            key::PERCENT_CAP => {
+                let cap = value
+                    .as_str()
+                    .and_then(PercentCap::parse)
+                    .ok_or_else(|| format!("{key} must be \"99\", \"100\" or \"per_core\""))?;
+                self.percent_cap.store(cap as u8, Relaxed);
+                Ok(json!(cap.as_str()))
+            }
+            key::LOG_LEVEL => {
+                let level = value.as_str().and_then(LogLevel::parse).ok_or_else(|| {
+                    format!("{key} must be \"error\", \"warn\", \"info\", \"debug\" or \"trace\"")
+                })?;
+                self.log_level.store(level as u8, Relaxed);
+                Ok(json!(level.as_str()))
+            }
+            key::LANGUAGE => {
+                // Empty follows the system locale
+                let language = value
+                    .as_str()
+                    .filter(|code| code.is_empty() || i18n::is_supported(code))
+                    .ok_or_else(|| format!("{key} must be a supported language code or empty"))?;
+                *self.language.lock().unwrap_or_else(|e| e.into_inner()) = language.to_string();
+                Ok(json!(language))
+            }
+            key::LATENCY_ALERT_MS => {
+                let ms = as_u64()?.clamp(1, MAX_LATENCY_ALERT_MS) as u32;
+                self.latency_alert_ms.store(ms, Relaxed);
+                Ok(json!(ms))
+            }
+            key::WIFI_ALERT_DBM => {
+                let dbm = value
+                    .as_i64()
+                    .ok_or_else(|| format!("{key} must be an integer"))?
+                    .clamp(wifi::MIN_ALERT_DBM, wifi::MAX_ALERT_DBM)
+                    as i32;
+                self.wifi_alert_dbm.store(dbm, Relaxed);
+                Ok(json!(dbm))
+            }
+            key::ALERT_SOUND => {
+                let sound = value.as_str().and_then(AlertSound::parse).ok_or_else(|| {
+                    let names: Vec<_> = AlertSound::ALL.map(AlertSound::as_str).into();
+                    format!("{key} must be one of {}", names.join(", "))
+                })?;
+                self.alert_sound.store(sound as u8, Relaxed);
+                Ok(json!(sound.as_str()))
+            }
+            key::ALERT_SOUND_COOLDOWN_SECS => {
+                let secs =
+                    as_u64()?.clamp(sound::MIN_COOLDOWN_SECS, sound::MAX_COOLDOWN_SECS) as u32;
+                self.alert_sound_cooldown_secs.store(secs, Relaxed);
+                Ok(json!(secs))
+            }
+            key::ALERT_WEBHOOK_URL => {
+                let url = value
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|url| alerts::is_valid_webhook_url(url))
+                    .ok_or_else(|| format!("{key} must be an http(s) URL or empty"))?;
+                *self
+                    .alert_webhook_url
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner()) = url.to_string();
+                Ok(json!(url))
+            }
+            key::QUIET_HOURS => {
+                // An empty string turns quiet hours off
+                let hours = value
+                    .as_str()
+                    .map(str::trim)
+                    .and_then(|v| match v {
+                        "" => Some(None),
+                        v => QuietHours::parse(v).map(Some),
+                    })
+                    .ok_or_else(|| format!("{key} must look like \"22:00-08:00\" or be empty"))?;
+                self.quiet_hours
+                    .store(hours.map_or(NO_QUIET_HOURS, QuietHours::pack), Relaxed);
+                Ok(json!(hours.map(QuietHours::to_setting).unwrap_or_default()))
+            }
+            key::DATA_CAP_GB => {
+                let cap = as_u64()?.min(data_cap::MAX_CAP_GB) as u32;
+                self.data_cap_gb.store(cap, Relaxed);
+                Ok(json!(cap))
+            }
+            key::WATCHED_PROCESSES => {
+                let names = watched::parse_names(value)?;
+                *self
+                    .watched_processes
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner()) = names.clone();
+                Ok(json!(names))
+            }
+            key::WATCHED_CPU_LIMIT => {
+                let limit = as_u64()?.min(MAX_WATCHED_CPU_LIMIT as u64) as u32;
+                self.watched_cpu_limit.store(limit, Relaxed);
+                Ok(json!(limit))
+            }
+            key::WATCHED_MEM_LIMIT_MB => {
+                let limit = as_u64()?.min(MAX_WATCHED_MEM_LIMIT_MB as u64) as u32;
+                self.watched_mem_limit_mb.store(limit, Relaxed);
+                Ok(json!(limit))
+            }
+            key::PROFILES => {
+                let profiles = profiles::parse_profiles(value)?;
+                let saved = json!(profiles.iter().map(Profile::to_json).collect::<Vec<_>>());
+                let mut active = self
+                    .active_profile
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner());
+                if !profiles.iter().any(|p| p.name == *active) {
+                    active.clear();
+                }
+                *self.profiles.lock().unwrap_or_else(|e| e.into_inner()) = profiles;
+                Ok(saved)
+            }
+            key::ACTIVE_PROFILE => {
+                // An empty string means no profile has been chosen
+                let name = value
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|name| name.is_empty() || self.profile(name).is_some())
+                    .ok_or_else(|| format!("{key} must name a profile or be empty"))?;
+                *self
+                    .active_profile
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner()) = name.to_string();
+                Ok(json!(name))
+            }
+            key::REMOTE_HOSTS => {
+                let hosts = remote::parse_hosts(value)?;
+                let mut selected = self.remote_host.lock().unwrap_or_else(|e| e.into_inner());
+                if !hosts.contains(&*selected) {
+                    selected.clear();
+                }
+                *self.remote_hosts.lock().unwrap_or_else(|e| e.into_inner()) = hosts.clone();
+                Ok(json!(hosts))
+            }
+            key::REMOTE_HOST => {
+                // An empty string means this machine
+                let host = value
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|host| host.is_empty() || self.remote_hosts().iter().any(|h| h == host))
+                    .ok_or_else(|| format!("{key} must be one of remote_hosts or empty"))?;
+                *self.remote_host.lock().unwrap_or_else(|e| e.into_inner()) = host.to_string();
+                Ok(json!(host))
+            }
+            _ => Err(format!("Unknown setting {key}")),
+        }
+    }
+
+    /// All settings as a JSON object keyed by setting name.
+    pub fn to_json(&self) -> Value {
+        let map = ALL_KEYS
+            .iter()
+            .filter_map(|key| Some((key.to_string(), self.get(key)?)))
+            .collect::<serde_json::Map<_, _>>();
+        Value::Object(map)
+    }
+
+    fn flag(&self, key: &str) -> Option<&AtomicBool> {
+        match key {
+            key::AUTOSTART => Some(&self.autostart),
+            key::SHOW_CPU => Some(&self.show_cpu),
+            key::SHOW_MEM => Some(&self.show_mem),
+            key::SHOW_GPU => Some(&self.show_gpu),
+            key::SHOW_GPU_MEMORY => Some(&self.show_gpu_memory),
+            key::SHOW_NET => Some(&self.show_net),
+            key::SHOW_ALERTS => Some(&self.show_alerts),
+            key::HISTORY_ENABLED => Some(&self.history_enabled),
+            key::SHOW_PROCESSES => Some(&self.show_processes),
+            key::SHOW_CPU_FREQUENCY => Some(&self.show_cpu_frequency),
+            key::ADAPTIVE_SAMPLING => Some(&self.adaptive_sampling),
+            key::INTERPOLATE_VALUES => Some(&self.interpolate_values),
+            key::REDUCE_ON_BATTERY => Some(&self.reduce_on_battery),
+            key::PAUSE_WHEN_LOCKED => Some(&self.pause_when_locked),
+            key::SHOW_CPU_POWER => Some(&self.show_cpu_power),
+            key::SHOW_ANE_POWER => Some(&self.show_ane_power),
+            key::SHOW_GPU_TEMPERATURE => Some(&self.show_gpu_temperature),
+            key::SHOW_GPU_CODEC => Some(&self.show_gpu_codec),
+            key::GPU_ENCODER_ALERT => Some(&self.gpu_encoder_alert),
+            key::SHOW_NETWORK_PROCESSES => Some(&self.show_network_processes),
+            key::SHOW_GPU_PROCESSES => Some(&self.show_gpu_processes),
+            key::SHOW_LATENCY => Some(&self.show_latency),
+            key::SHOW_WIFI => Some(&self.show_wifi),
+            key::SHOW_VPN => Some(&self.show_vpn),
+            key::SHOW_PRESSURE => Some(&self.show_pressure),
+            key::SHOW_TASK_COUNTS => Some(&self.show_task_counts),
+            key::SHOW_DETAILS => Some(&self.show_details),
+            key::SHOW_CONTAINERS => Some(&self.show_containers),
+            key::STATUS_SERVER_ENABLED => Some(&self.status_server_enabled),
+            key::DBUS_ENABLED => Some(&self.dbus_enabled),
+            key::CGROUP_METRICS => Some(&self.cgroup_metrics),
+            key::COMBINE_NET => Some(&self.combine_net),
+            key::AUTO_WIDTH => Some(&self.auto_width),
+            key::TABULAR_FIGURES => Some(&self.tabular_figures),
+            key::METRIC_COLORS => Some(&self.metric_colors),
+            key::CHECK_UPDATES => Some(&self.check_updates),
+            key::COPY_STATS_JSON => Some(&self.copy_stats_json),
+            key::LOG_TO_FILE => Some(&self.log_to_file),
+            _ => None,
+        }
+    }
+
+    /// Looks up the threshold atomic backing a settings key.
+    pub fn alert_threshold(&self, key: &str) -> Option<&AtomicU32> {
+        match key {
+            key::CPU_ALERT_THRESHOLD => Some(&self.cpu_alert_threshold),
+            key::MEM_ALERT_THRESHOLD => Some(&self.mem_alert_threshold),
+            key::GPU_ALERT_THRESHOLD => Some(&self.gpu_alert_threshold),
+            _ => None,
+        }
+    }
+
+    /// Looks up the packed color backing a per-metric color key.
+    fn metric_color(&self, key: &str) -> Option<&AtomicU32> {
+        match key {
+            key::CPU_COLOR => Some(&self.cpu_color),
+            key::MEM_COLOR => Some(&self.mem_color),
+            key::GPU_COLOR => Some(&self.gpu_color),
+            key::NET_COLOR => Some(&self.net_color),
+            _ => None,
+        }
+    }
+
+    /// Looks up the smoothing atomic backing a settings key.
+    pub fn smoothing(&self, key: &str) -> Option<&AtomicU32> {
+        match key {
+            key::CPU_SMOOTHING => Some(&self.cpu_smoothing),
+            key::MEM_SMOOTHING => Some(&self.mem_smoothing),
+            key::GPU_SMOOTHING => Some(&self.gpu_smoothing),
+            _ => None,
+        }
+    }
+
+    pub fn alert_thresholds(&self) -> AlertThresholds {
+        AlertThresholds {
+            cpu: self.cpu_alert_threshold.load(Relaxed) as f32,
+            mem: self.mem_alert_threshold.load(Relaxed) as f32,
+            gpu: self.gpu_alert_threshold.load(Relaxed) as f32,
+        }
+    }
+
+    /// Icon colors, using the custom text color or forced icon color if one
+    /// is set.
+    #[cfg_attr(any(target_os = "linux", target_os = "macos"), allow(dead_code))]
+    pub fn palette(&self, use_light_icons: bool) -> Palette {
+        self.palette_on(Palette::for_theme(use_light_icons).foreground)
+    }
+
+    /// [`Self::palette`] for a panel whose theme sets `theme_foreground` as
+    /// its text color.
+    pub fn palette_on(&self, theme_foreground: Rgb) -> Palette {
+        Palette {
+            foreground: self
+                .text_color()
+                .or(self.icon_color().foreground())
+                .unwrap_or(theme_foreground),
+            alert: self.alert_color(),
+            text_effect: self.text_effect(),
+            metric_colors: self.metric_colors.load(Relaxed).then(|| MetricColors {
+                cpu: unpack_color(self.cpu_color.load(Relaxed)),
+                mem: unpack_color(self.mem_color.load(Relaxed)),
+                gpu: unpack_color(self.gpu_color.load(Relaxed)),
+                net: unpack_color(self.net_color.load(Relaxed)),
+            }),
+        }
+    }
+
+    pub fn quiet_hours(&self) -> Option<QuietHours> {
+        QuietHours::unpack(self.quiet_hours.load(Relaxed))
+    }
+
+    pub fn alert_webhook_url(&self) -> String {
+        self.alert_webhook_url
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn alert_sound(&self) -> AlertSound {
+        AlertSound::from_u8(self.alert_sound.load(Relaxed))
+    }
+
+    pub fn text_effect(&self) -> TextEffect {
+        TextEffect::from_u8(self.text_effect.load(Relaxed))
+    }
+
+    pub fn value_style(&self) -> ValueStyle {
+        ValueStyle::from_u8(self.value_style.load(Relaxed))
+    }
+
+    pub fn label_style(&self) -> LabelStyle {
+        LabelStyle::from_u8(self.label_style.load(Relaxed))
+    }
+
+    pub fn percent_cap(&self) -> PercentCap {
+        PercentCap::from_u8(self.percent_cap.load(Relaxed))
+    }
+
+    pub fn log_level(&self) -> LogLevel {
+        LogLevel::from_u8(self.log_level.load(Relaxed))
+    }
+
+    pub fn net_unit(&self) -> NetUnit {
+        NetUnit::from_u8(self.net_unit.load(Relaxed))
+    }
+
+    pub fn cpu_display(&self) -> CpuDisplay {
+        CpuDisplay::from_u8(self.cpu_display.load(Relaxed))
+    }
+
+    pub fn set_cpu_display(&self, display: CpuDisplay) {
+        self.cpu_display.store(display as u8, Relaxed);
+    }
+
+    pub fn mem_display(&self) -> MemDisplay {
+        MemDisplay::from_u8(self.mem_display.load(Relaxed))
+    }
+
+    pub fn set_mem_display(&self, display: MemDisplay) {
+        self.mem_display.store(display as u8, Relaxed);
+    }
+
+    pub fn mem_formula(&self) -> MemFormula {
+        MemFormula::from_u8(self.mem_formula.load(Relaxed))
+    }
+
+    pub fn set_mem_formula(&self, formula: MemFormula) {
+        self.mem_formula.store(formula as u8, Relaxed);
+    }
+
+    pub fn mem_reclaimable(&self) -> Vec<MemClass> {
+        self.mem_reclaimable
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn layout(&self) -> Layout {
+        Layout::from_u8(self.layout.load(Relaxed))
+    }
+
+    pub fn set_layout(&self, layout: Layout) {
+        self.layout.store(layout as u8, Relaxed);
+    }
+
+    pub fn click_action(&self) -> ClickAction {
+        ClickAction::from_u8(self.click_action.load(Relaxed))
+    }
+
+    pub fn alert_color(&self) -> Rgb {
+        unpack_color(self.alert_color.load(Relaxed))
+    }
+
+    /// Custom icon and text color, or `None` to follow the panel theme.
+    pub fn text_color(&self) -> Option<Rgb> {
+        let packed = self.text_color.load(Relaxed);
+        (packed != AUTO_COLOR).then(|| unpack_color(packed))
+    }
+
+    pub fn icon_color(&self) -> IconColor {
+        IconColor::from_u8(self.icon_color.load(Relaxed))
+    }
+
+    /// Whether the icon takes the panel theme's color: no text color, forced
+    /// icon color, metric colors, pressure-colored memory or text effect. On
+    /// macOS this draws it as a template image, which would discard any other
+    /// color and flatten an outline or shadow into the text.
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    pub fn follows_theme(&self) -> bool {
+        self.text_color().is_none()
+            && self.icon_color() == IconColor::Auto
+            && !self.metric_colors.load(Relaxed)
+            && self.mem_display() != MemDisplay::Pressure
+            && self.text_effect() == TextEffect::None
+    }
+
+    /// Has the monitoring loop redraw the icon now rather than on its next tick.
+    pub fn request_redraw(&self) {
+        self.redraw.notify_one();
+    }
+
+    /// Resolves once a redraw is requested; a request made while nobody
+    /// waits is kept for the next call.
+    pub async fn redraw_requested(&self) {
+        self.redraw.notified().await;
+    }
+
+    /// Empty for the app's own cgroup.
+    pub fn cgroup_path(&self) -> String {
+        self.cgroup_path
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Hands the memory accounting and cgroup settings to the samplers, which
+    /// don't see the config.
+    pub fn configure_samplers(&self) {
+        memory::configure(self.mem_formula(), &self.mem_reclaimable());
+        cgroup::configure(self.cgroup_metrics.load(Relaxed), self.cgroup_path());
+    }
+
+    /// Custom tray font family, or `None` for the system sans-serif font.
+    pub fn font_family(&self) -> Option<String> {
+        let family = self.font_family.lock().unwrap_or_else(|e| e.into_inner());
+        (!family.is_empty()).then(|| family.clone())
+    }
+
+    pub fn font_weight(&self) -> u32 {
+        self.font_weight.load(Relaxed)
+    }
+
+    /// `text_scale` as a factor for `Sizing::scaled`, e.g. 1.25.
+    pub fn text_scale(&self) -> f32 {
+        self.text_scale.load(Relaxed) as f32 / 100.0
+    }
+
+    /// Host the latency segment pings; empty for the default gateway.
+    pub fn latency_host(&self) -> String {
+        self.latency_host
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn profiles(&self) -> Vec<Profile> {
+        self.profiles
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn profile(&self, name: &str) -> Option<Profile> {
+        self.profiles
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .find(|profile| profile.name == name)
+            .cloned()
+    }
+
+    pub fn active_profile(&self) -> String {
+        self.active_profile
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn watched_processes(&self) -> Vec<String> {
+        self.watched_processes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn watched_limits(&self) -> watched::Limits {
+        watched::Limits {
+            cpu: self.watched_cpu_limit.load(Relaxed),
+            mem_mb: self.watched_mem_limit_mb.load(Relaxed),
+        }
+    }
+
+    pub fn remote_hosts(&self) -> Vec<String> {
+        self.remote_hosts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn remote_host(&self) -> String {
+        self.remote_host
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn language(&self) -> String {
+        self.language
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Changes whenever the font family or weight is set.
+    pub fn font_revision(&self) -> u32 {
+        self.font_revision.load(Relaxed)
+    }
+
+    pub fn tray_icons(&self) -> Vec<Vec<SegmentKind>> {
+        self.tray_icons
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn auto_hide(&self) -> Vec<SegmentKind> {
+        self.auto_hide
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Order of the built-in tray segments, including hidden ones.
+    pub fn segment_order(&self) -> [SegmentKind; 4] {
+        self.segment_order
+            .load(Relaxed)
+            .to_le_bytes()
+            .map(|i| SegmentKind::ALL[i as usize % SegmentKind::ALL.len()])
+    }
+}
+
+fn pack_color((r, g, b): Rgb) -> u32 {
+    u32::from_be_bytes([0, r, g, b])
+}
+
+fn unpack_color(packed: u32) -> Rgb {
+    let [_, r, g, b] = packed.to_be_bytes();
+    (r, g, b)
+}
+
+fn pack_segment_order(order: [SegmentKind; 4]) -> u32 {
+    u32::from_le_bytes(order.map(|kind| kind as u8))
+}
+
+/// Accepts a JSON array of segment names. Segments left out keep their default
+/// relative order after the listed ones, so older settings files stay valid
+/// when a segment is added.
+pub(crate) fn parse_segment_order(value: &Value) -> Option<[SegmentKind; 4]> {
+    let mut order = parse_segment_list(value)?;
+    for kind in SegmentKind::ALL {
+        if !order.contains(&kind) {
+            order.push(kind);
+        }
+    }
+    order.try_into().ok()
+}
+
+/// Accepts a JSON array of distinct memory class names.
+fn parse_mem_classes(value: &Value) -> Option<Vec<MemClass>> {
+    let mut classes = Vec::with_capacity(MemClass::ALL.len());
+    for item in value.as_array()? {
+        let class = MemClass::parse(item.as_str()?)?;
+        if classes.contains(&class) {
+            return None;
+        }
+        classes.push(class);
+    }
+    Some(classes)
+}
+
+/// Accepts a JSON array of distinct segment names.
+fn parse_segment_list(value: &Value) -> Option<Vec<SegmentKind>> {
+    let mut segments = Vec::with_capacity(SegmentKind::ALL.len());
+    for item in value.as_array()? {
+        let kind = SegmentKind::parse(item.as_str()?)?;
+        if segments.contains(&kind) {
+            return None;
+        }
+        segments.push(kind);
+    }
+    Some(segments)
+}
+
+/// Accepts a JSON array of non-empty segment name arrays, one per tray icon,
+/// e.g. `[["mem", "cpu"], ["net"]]`. Segments no icon lists go on the first.
+pub(crate) fn parse_tray_icons(value: &Value) -> Option<Vec<Vec<SegmentKind>>> {
+    let mut icons: Vec<Vec<SegmentKind>> = Vec::new();
+    for icon in value.as_array()? {
+        let mut segments = Vec::new();
+        for item in icon.as_array()? {
+            let kind = SegmentKind::parse(item.as_str()?)?;
+            if segments.contains(&kind) || icons.iter().any(|other| other.contains(&kind)) {
+                return None;
+            }
+            segments.push(kind);
+        }
+        if segments.is_empty() {
+            return None;
+        }
+        icons.push(segments);
+    }
+    Some(icons)
+}
+
+fn tray_icons_json(icons: &[Vec<SegmentKind>]) -> Vec<Vec<&'static str>> {
+    icons
+        .iter()
+        .map(|segments| segments.iter().map(|kind| kind.as_str()).collect())
+        .collect()
+}
+
+/// Keeps thresholds in a range where an alert can actually trigger.
+pub(crate) fn sanitize_threshold(value: u64) -> u32 {
+    value.clamp(1, 100) as u32
+}
+
+/// Rounds to the nearest hundred within 100..=900, the weights fonts define.
+pub(crate) fn sanitize_font_weight(value: u64) -> u32 {
+    (((value + 50) / 100) * 100).clamp(100, 900) as u32
+}
+
+pub(crate) fn sanitize_update_interval(value: u64) -> u32 {
+    value.clamp(MIN_UPDATE_INTERVAL_MS, MAX_UPDATE_INTERVAL_MS) as u32
+}
+
+pub fn save_setting(app: &AppHandle, key: &str, value: impl Into<Value>) {
+    if let Ok(store) = app.store(SETTINGS_FILE) {
+        store.set(key, value.into());
+        if let Err(e) = store.save() {
+            error!("Failed to save setting {key}: {e}");
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Running containers submenu
+//!
+//! Talks to the Docker Engine API over its Unix socket, which Podman also
+//! serves. The socket is `DOCKER_HOST` when it is a `unix://` URL, otherwise the
+//! first of the usual Docker, Docker Desktop and Podman locations that exists.
+//! Nothing is looked up or polled unless the submenu is enabled.
+//!
+//! Stats are requested with `one-shot=true`, which answers immediately instead
+//! of waiting a second for a second reading, so CPU usage is computed from the
+//! counters of the previous tick.
+//!
+//! With ten containers a poll is eleven requests with a 2 s timeout each, so
+//! it runs on its own thread like the latency probe: a tick that finds the
+//! previous poll still running leaves the submenu as it is.
+
+use std::collections::HashMap;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::Value;
+use tauri::menu::{MenuItem, Submenu};
+use tauri::{AppHandle, Wry};
+
+use crate::http;
+use crate::i18n::tr;
+use crate::processes::format_memory;
+
+/// Containers listed (and queried for stats) per tick.
+pub const MAX_CONTAINERS: usize = 10;
+
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContainerStats {
+    pub name: String,
+    /// Percent of one core, like the top processes list
+    pub cpu: f32,
+    /// Memory in use excluding reclaimable page cache, in bytes
+    pub memory: u64,
+}
+
+/// Managed so the monitoring loop can refill it while the submenu is enabled.
+pub struct ContainerMenu(pub Submenu<Wry>);
+
+#[derive(Default)]
+struct Poll {
+    in_flight: bool,
+    finished: Option<Result<Vec<ContainerStats>, String>>,
+    /// Container CPU and system CPU counters of the previous poll, by container ID
+    previous: HashMap<String, (u64, u64)>,
+}
+
+/// Owned by the monitoring loop while the submenu is enabled.
+#[derive(Default)]
+pub struct ContainerMonitor {
+    poll: Arc<Mutex<Poll>>,
+}
+
+impl ContainerMonitor {
+    /// Starts a poll unless one is still running, and returns the result of a
+    /// poll that finished since the last call. `None` when there is none yet.
+    pub fn sample(&mut self) -> Option<Result<Vec<ContainerStats>, String>> {
+        let mut poll = self.poll.lock().unwrap_or_else(|e| e.into_inner());
+        if !poll.in_flight {
+            poll.in_flight = true;
+            let shared = Arc::clone(&self.poll);
+            let mut previous = std::mem::take(&mut poll.previous);
+            thread::spawn(move || {
+                let result = collect(&mut previous);
+                let mut poll = shared.lock().unwrap_or_else(|e| e.into_inner());
+                poll.finished = Some(result);
+                poll.previous = previous;
+                poll.in_flight = false;
+            });
+        }
+        poll.finished.take()
+    }
+}
+
+/// Stats of the running containers, busiest first, replacing `previous` with
+/// this poll's counters. `Err` when no runtime socket is found or the runtime
+/// doesn't answer.
+fn collect(previous: &mut HashMap<String, (u64, u64)>) -> Result<Vec<ContainerStats>, String> {
+    let socket = find_socket().ok_or("No container runtime found")?;
+    let list = get_json(&socket, "/containers/json")?;
+
+    let mut counters_by_id = HashMap::new();
+    let mut stats = Vec::new();
+    for container in list.as_array().into_iter().flatten().take(MAX_CONTAINERS) {
+        let Some(id) = container["Id"].as_str() else {
+            continue;
+        };
+        let Ok(reading) = get_json(
+            &socket,
+            &format!("/containers/{id}/stats?stream=false&one-shot=true"),
+        ) else {
+            // Stopped between listing and querying
+            continue;
+        };
+        let counters = cpu_counters(&reading);
+        let cpu = match (previous.get(id), counters) {
+            (Some(&prev), Some(current)) => cpu_percent(prev, current, online_cpus(&reading)),
+            _ => 0.0,
+        };
+        if let Some(counters) = counters {
+            counters_by_id.insert(id.to_string(), counters);
+        }
+        stats.push(ContainerStats {
+            name: container_name(container),
+            cpu,
+            memory: memory_in_use(&reading),
+        });
+    }
+    *previous = counters_by_id;
+
+    stats.sort_by(|a, b| b.cpu.total_cmp(&a.cpu).then(a.name.cmp(&b.name)));
+    Ok(stats)
+}
+
+fn find_socket() -> Option<PathBuf> {
+    if let Ok(host) = std::env::var("DOCKER_HOST") {
+        // TCP and SSH hosts are not supported
+        return host.strip_prefix("unix://").map(PathBuf::from);
+    }
+
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+    [
+        Some(PathBuf::from("/var/run/docker.sock")),
+        home.map(|home| home.join(".docker/run/docker.sock")),
+        runtime_dir.map(|dir| dir.join("podman/podman.sock")),
+        Some(PathBuf::from("/run/podman/podman.sock")),
+    ]
+    .into_iter()
+    .flatten()
+    .find(|path| path.exists())
+}
+
+fn get_json(socket: &Path, path: &str) -> Result<Value, String> {
+    let stream = UnixStream::connect(socket).map_err(|e| format!("{}: {e}", socket.display()))?;
+    stream
+        .set_read_timeout(Some(SOCKET_TIMEOUT))
+        .and_then(|_| stream.set_write_timeout(Some(SOCKET_TIMEOUT)))
+        .map_err(|e| e.to_string())?;
+
+    let body = http::get(stream, "localhost", path, "Container runtime")?;
+    serde_json::from_str(&body).map_err(|e| e.to_string())
+}
+
+/// `Names` holds the name with a leading slash, e.g. `["/postgres"]`.
+pub(crate) fn container_name(container: &Value) -> String {
+    container["Names"][0]
+        .as_str()
+        .map(|name| name.trim_start_matches('/').to_string())
+        .or_else(|| {
+            container["Id"]
+                .as_str()
+                .map(|id| id.chars().take(12).collect())
+        })
+        .unwrap_or_default()
+}
+
+/// Cumulative container and system CPU time in nanoseconds.
+pub(crate) fn cpu_counters(stats: &Value) -> Option<(u64, u64)> {
+    let cpu = &stats["cpu_stats"];
+    Some((
+        cpu["cpu_usage"]["total_usage"].as_u64()?,
+        cpu["system_cpu_usage"].as_u64()?,
+    ))
+}
+
+/// CPUs available to the runtime, which on macOS is its VM rather than the host.
+pub(crate) fn online_cpus(stats: &Value) -> u32 {
+    stats["cpu_stats"]["online_cpus"]
+        .as_u64()
+        .map_or(1, |n| n.max(1) as u32)
+}
+
+/// Share of the system's CPU time used by the container, scaled to percent of
+/// one core like `docker stats`. The system counter advances once per core.
+pub(crate) fn cpu_percent(previous: (u64, u64), current: (u64, u64), online_cpus: u32) -> f32 {
+    let container = current.0.saturating_sub(previous.0) as f64;
+    let system = current.1.saturating_sub(previous.1) as f64;
+    if system <= 0.0 {
+        return 0.0;
+    }
+    (container / system * online_cpus as f64 * 100.0) as f32
+}
+
+/// Usage minus page cache, matching `docker stats`: `inactive_file` on
+/// cgroup v2, `cache` on v1.
+pub(crate) fn memory_in_use(stats: &Value) -> u64 {
+    let memory = &stats["memory_stats"];
+    let usage = memory["usage"].as_u64().unwrap_or(0);
+    let cache = memory["stats"]["inactive_file"]
+        .as_u64()
+        .or_else(|| memory["stats"]["cache"].as_u64())
+        .unwrap_or(0);
+    usage.saturating_sub(cache)
+}
+
+/// Replaces the submenu contents with the running containers, or why there are none.
+pub fn update_menu(
+    app: &AppHandle,
+    menu: &Submenu<Wry>,
+    containers: &Result<Vec<ContainerStats>, String>,
+) -> tauri::Result<()> {
+    while menu.remove_at(0)?.is_some() {}
+
+    match containers {
+        Ok(containers) if containers.is_empty() => {
+            menu.append(&MenuItem::new(
+                app,
+                tr("No running containers"),
+                false,
+                None::<&str>,
+            )?)?;
+        }
+        Ok(containers) => {
+            for container in containers {
+                let label = format!(
+                    "{} — {:.1}% · {}",
+                    container.name,
+                    container.cpu,
+                    format_memory(container.memory)
+                );
+                menu.append(&MenuItem::new(app, label, false, None::<&str>)?)?;
+            }
+        }
+        Err(e) => {
+            menu.append(&MenuItem::new(app, e, false, None::<&str>)?)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! Copy stats
+//!
+//! "Copy Stats" puts the last tick's readings on the clipboard, for pasting
+//! into a chat or an issue: one line of text such as
+//! "CPU 34.2% · Mem 41.3% · GPU 18.0% · ↓1.52 MB/s ↑210.00 KB/s", or with
+//! `copy_stats_json` on, the status server's JSON for the same sample. Metrics
+//! that weren't sampled (hidden segments on Linux) are left out.
+
+use crate::config::NetUnit;
+use crate::details::exact_rate;
+use crate::metrics::Sample;
+use crate::status_server::sample_json;
+
+pub const MENU_ID: &str = "copy_stats";
+
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// What goes on the clipboard for `sample`.
+pub fn format(sample: &Sample, json: bool, unit: NetUnit) -> String {
+    if json {
+        sample_json(sample).to_string()
+    } else {
+        line(sample, unit)
+    }
+}
+
+pub(crate) fn line(sample: &Sample, unit: NetUnit) -> String {
+    let mut parts = Vec::new();
+    if let Some(cpu) = sample.cpu {
+        parts.push(format!("CPU {cpu:.1}%"));
+    }
+    if let Some(watts) = sample.cpu_power {
+        parts.push(format!("CPU Power {watts:.1} W"));
+    }
+    if let Some(mem) = sample.mem {
+        parts.push(format!("Mem {mem:.1}%"));
+    }
+    if let Some(gpu) = sample.gpu {
+        parts.push(format!("GPU {gpu:.1}%"));
+    }
+    if let Some(bytes) = sample.gpu_mem {
+        parts.push(format!("GPU Mem {:.2} GB", bytes as f64 / GIB));
+    }
+    if let Some(celsius) = sample.gpu_temp {
+        parts.push(format!("GPU {celsius:.0} °C"));
+    }
+    match (sample.net_down, sample.net_up) {
+        (Some(down), Some(up)) => parts.push(format!(
+            "↓{} ↑{}",
+            exact_rate(down, unit),
+            exact_rate(up, unit)
+        )),
+        (Some(down), None) => parts.push(format!("↓{}", exact_rate(down, unit))),
+        (None, Some(up)) => parts.push(format!("↑{}", exact_rate(up, unit))),
+        (None, None) => {}
+    }
+    parts.join(" · ")
+}
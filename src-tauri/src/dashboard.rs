@@ -0,0 +1,129 @@
+//! Menu bar dashboard popover (macOS)
+//!
+//! An optional borderless window anchored below the tray icon that charts the
+//! last minute of samples (`ui/dashboard.html`). It is one of the tray click
+//! actions: left-clicking the icon toggles it and the menu moves to right-click
+//! only. The window hides as soon as it loses focus, like a native popover.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use tauri::{
+    AppHandle, Manager, PhysicalPosition, Rect, State, WebviewUrl, WebviewWindowBuilder,
+    WindowEvent,
+};
+
+use crate::metrics::Sample;
+use crate::status_server::sample_json;
+
+pub const WINDOW_LABEL: &str = "dashboard";
+
+/// Seconds of samples kept for the charts.
+pub const WINDOW_SECS: u64 = 60;
+
+const WIDTH: f64 = 320.0;
+const HEIGHT: f64 = 380.0;
+
+/// Clicking the icon while the popover is open first blurs (and hides) it; a
+/// click arriving this soon after is that same click and must not reopen it.
+const REOPEN_GUARD: Duration = Duration::from_millis(300);
+
+/// Samples of the last [`WINDOW_SECS`], oldest first.
+pub type RecentSamples = Arc<Mutex<VecDeque<Sample>>>;
+
+/// When the popover was last hidden by losing focus.
+#[derive(Default)]
+pub struct LastHidden(Mutex<Option<Instant>>);
+
+pub fn record(recent: &RecentSamples, sample: Sample) {
+    let mut samples = recent.lock().unwrap_or_else(|e| e.into_inner());
+    push_within_window(&mut samples, sample, WINDOW_SECS);
+}
+
+/// Appends `sample` and drops samples older than `window_secs` before it.
+pub(crate) fn push_within_window(samples: &mut VecDeque<Sample>, sample: Sample, window_secs: u64) {
+    let cutoff = sample.timestamp.saturating_sub(window_secs);
+    samples.push_back(sample);
+    while samples.front().is_some_and(|s| s.timestamp < cutoff) {
+        samples.pop_front();
+    }
+}
+
+pub fn hide(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        let _ = window.hide();
+    }
+}
+
+/// Shows the popover under the tray icon at `anchor`, or hides it if visible.
+pub fn toggle(app: &AppHandle, anchor: Rect) -> tauri::Result<()> {
+    let window = match app.get_webview_window(WINDOW_LABEL) {
+        Some(window) => window,
+        None => build(app)?,
+    };
+
+    if window.is_visible()? {
+        return window.hide();
+    }
+    let recently_hidden = app.try_state::<LastHidden>().is_some_and(|last| {
+        last.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_some_and(|at| at.elapsed() < REOPEN_GUARD)
+    });
+    if recently_hidden {
+        return Ok(());
+    }
+
+    let scale = window.scale_factor()?;
+    let icon = anchor.position.to_physical::<f64>(scale);
+    let icon_size = anchor.size.to_physical::<f64>(scale);
+    let (x, y) = popover_origin(
+        (icon.x, icon.y),
+        (icon_size.width, icon_size.height),
+        WIDTH * scale,
+    );
+    window.set_position(PhysicalPosition::new(x, y))?;
+    window.show()?;
+    window.set_focus()
+}
+
+/// Top-left corner that centers a `width` wide popover under the icon,
+/// without pushing it past the left edge of the screen.
+pub(crate) fn popover_origin(icon: (f64, f64), icon_size: (f64, f64), width: f64) -> (f64, f64) {
+    let x = icon.0 + icon_size.0 / 2.0 - width / 2.0;
+    (x.max(0.0), icon.1 + icon_size.1)
+}
+
+fn build(app: &AppHandle) -> tauri::Result<tauri::WebviewWindow> {
+    let window =
+        WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::App("dashboard.html".into()))
+            .title("Dashboard")
+            .inner_size(WIDTH, HEIGHT)
+            .decorations(false)
+            .resizable(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .visible(false)
+            .build()?;
+
+    let handle = window.clone();
+    let app = app.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Focused(false) = event {
+            let _ = handle.hide();
+            if let Some(last) = app.try_state::<LastHidden>() {
+                *last.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+            }
+        }
+    });
+    Ok(window)
+}
+
+#[tauri::command]
+pub fn get_recent_samples(recent: State<'_, RecentSamples>) -> Value {
+    let samples = recent.lock().unwrap_or_else(|e| e.into_inner());
+    Value::Array(samples.iter().map(sample_json).collect())
+}
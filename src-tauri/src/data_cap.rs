@@ -0,0 +1,138 @@
+//! Monthly data cap
+//!
+//! For metered or tethered connections: with `data_cap_gb` set, traffic in both
+//! directions is added up per calendar month (local time) and kept in
+//! `data_usage.json` under the app data directory, so the count survives
+//! restarts. The "Data Transferred" submenu shows what is left of the cap, and
+//! alerts fire at 80% and again at 100%. The app can't tell which interface
+//! is metered, so every one counts except loopback and virtual ones: container
+//! bridges and VPN tunnels only carry traffic that also crosses a physical
+//! interface, and would count it twice.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::i18n::{tr, tr_args};
+use crate::net_totals::format_total;
+use crate::{history, quiet_hours, vpn};
+
+pub const STATE_FILE: &str = "data_usage.json";
+pub const MAX_CAP_GB: u64 = 100_000;
+/// Share of the cap used when the first alert fires.
+pub const NEAR_PERCENT: f32 = 80.0;
+/// Unsaved traffic is written out at most this often.
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+const BYTES_PER_GB: u64 = 1_000_000_000;
+
+/// Interface name prefixes of loopback, container, VM and macOS-internal
+/// interfaces; VPN tunnels are matched by [`vpn::TUNNEL_PREFIXES`].
+const VIRTUAL_PREFIXES: &[&str] = &[
+    "lo", "docker", "br-", "veth", "virbr", "vnet", "vmnet", "vboxnet", "cni", "flannel", "cali",
+    "podman", "lxc", "lxd", "awdl", "llw", "anpi", "bridge", "gif", "stf",
+];
+
+/// Whether traffic on the interface counts toward the data cap.
+pub(crate) fn counts_interface(name: &str) -> bool {
+    !VIRTUAL_PREFIXES
+        .iter()
+        .chain(vpn::TUNNEL_PREFIXES)
+        .any(|prefix| name.starts_with(prefix))
+}
+
+/// This month's count, owned by the monitoring loop while a cap is set.
+#[derive(Debug, Default)]
+pub struct DataUsage {
+    /// "2026-10"; empty until the first traffic is added
+    pub month: String,
+    pub bytes: u64,
+    /// When the count first changed since it was last saved
+    unsaved_since: Option<Instant>,
+}
+
+impl DataUsage {
+    /// The saved count, or an empty one when there is none yet.
+    pub fn load(dir: &Path) -> Self {
+        fs::read_to_string(dir.join(STATE_FILE))
+            .ok()
+            .and_then(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn parse(contents: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(contents).ok()?;
+        Some(Self {
+            month: value.get("month")?.as_str()?.to_string(),
+            bytes: value.get("bytes")?.as_u64()?,
+            unsaved_since: None,
+        })
+    }
+
+    pub(crate) fn to_json(&self) -> String {
+        json!({ "month": self.month, "bytes": self.bytes }).to_string()
+    }
+
+    /// Adds `bytes` to the count for `month`, starting over when it changed.
+    pub fn add(&mut self, month: &str, bytes: u64) {
+        if self.month != month {
+            self.month = month.to_string();
+            self.bytes = 0;
+            self.unsaved_since.get_or_insert_with(Instant::now);
+        }
+        if bytes > 0 {
+            self.bytes += bytes;
+            self.unsaved_since.get_or_insert_with(Instant::now);
+        }
+    }
+
+    /// Share of `cap_gb` used, in percent.
+    pub fn percent_of(&self, cap_gb: u32) -> f32 {
+        if cap_gb == 0 {
+            return 0.0;
+        }
+        (self.bytes as f64 / (cap_gb as u64 * BYTES_PER_GB) as f64 * 100.0) as f32
+    }
+
+    /// A failed save is retried after the next [`SAVE_INTERVAL`], not every tick.
+    pub fn save(&mut self, dir: &Path) -> io::Result<()> {
+        self.unsaved_since = None;
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join(STATE_FILE), self.to_json())
+    }
+
+    /// Saves once traffic has gone unsaved for a while.
+    pub fn save_if_due(&mut self, dir: &Path) -> io::Result<()> {
+        match self.unsaved_since {
+            Some(since) if since.elapsed() >= SAVE_INTERVAL => self.save(dir),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub fn current_month() -> String {
+    month_key(quiet_hours::local_secs())
+}
+
+/// "2026-10" for a local time in seconds since the epoch.
+pub(crate) fn month_key(local_secs: i64) -> String {
+    let (year, month, _) = history::civil_from_days(local_secs.div_euclid(86_400));
+    format!("{year:04}-{month:02}")
+}
+
+/// "This Month: 42.1 GB of 50 GB · 7.9 GB left", or "· 3.2 GB over".
+pub(crate) fn menu_line(bytes: u64, cap_gb: u32) -> String {
+    let cap = cap_gb as u64 * BYTES_PER_GB;
+    let remaining = if bytes <= cap {
+        tr_args("{} left", &[&format_total(cap - bytes)])
+    } else {
+        tr_args("{} over", &[&format_total(bytes - cap)])
+    };
+    let used = tr_args(
+        "This Month: {} of {}",
+        &[&format_total(bytes), &format!("{cap_gb} {}", tr("GB"))],
+    );
+    format!("{used} · {remaining}")
+}
@@ -0,0 +1,193 @@
+//! Opt-in D-Bus service exposing live metrics (Linux)
+//!
+//! Owns `org.alexx855.ResourceMonitor` on the session bus and serves the
+//! latest sample at `/org/alexx855/ResourceMonitor`, so shell extensions, bars
+//! and scripts can read what the tray already computes:
+//!
+//! ```text
+//! $ busctl --user get-property org.alexx855.ResourceMonitor \
+//!     /org/alexx855/ResourceMonitor org.alexx855.ResourceMonitor Cpu
+//! d 12.5
+//! ```
+//!
+//! Every sample emits one `PropertiesChanged` signal carrying all properties,
+//! so clients can subscribe instead of polling. Readings that aren't available
+//! on this machine are -1. Like the status server, the service thread follows
+//! the config at runtime and releases the name when disabled.
+
+use std::collections::HashMap;
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use tracing::error;
+use zbus::blocking::{connection, Connection};
+use zbus::fdo::Properties;
+use zbus::names::InterfaceName;
+use zbus::zvariant::Value;
+
+use crate::config::Config;
+use crate::metrics::Sample;
+
+pub const BUS_NAME: &str = "org.alexx855.ResourceMonitor";
+pub const OBJECT_PATH: &str = "/org/alexx855/ResourceMonitor";
+pub const INTERFACE: &str = "org.alexx855.ResourceMonitor";
+
+/// How often the idle thread checks for config changes.
+const POLL_INTERVAL_MS: u64 = 200;
+
+/// Value of a reading the platform doesn't provide.
+const UNAVAILABLE: f64 = -1.0;
+
+/// Hands each sample of the monitoring loop to the service thread.
+#[derive(Clone)]
+pub struct Publisher(mpsc::Sender<Sample>);
+
+impl Publisher {
+    pub fn publish(&self, sample: Sample) {
+        // The thread only exits with the app
+        let _ = self.0.send(sample);
+    }
+}
+
+pub fn start(config: Arc<Config>) -> Publisher {
+    let (sender, receiver) = mpsc::channel::<Sample>();
+    thread::spawn(move || {
+        let mut connection: Option<Connection> = None;
+        // So a missing session bus is only logged once per enable
+        let mut failed = false;
+
+        loop {
+            let wanted = config.dbus_enabled.load(Relaxed);
+            if !wanted {
+                connection = None;
+                failed = false;
+            } else if connection.is_none() && !failed {
+                match connect() {
+                    Ok(c) => connection = Some(c),
+                    Err(e) => {
+                        error!("Failed to start D-Bus service: {e}");
+                        failed = true;
+                    }
+                }
+            }
+
+            match receiver.recv_timeout(Duration::from_millis(POLL_INTERVAL_MS)) {
+                Ok(sample) => {
+                    if let Some(connection) = &connection {
+                        if let Err(e) = publish(connection, sample) {
+                            error!("Failed to publish metrics on D-Bus: {e}");
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    });
+    Publisher(sender)
+}
+
+fn connect() -> zbus::Result<Connection> {
+    connection::Builder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, Metrics::default())?
+        .build()
+}
+
+/// Stores the sample for property reads and signals the change.
+fn publish(connection: &Connection, sample: Sample) -> zbus::Result<()> {
+    let object_server = connection.object_server();
+    let iface = object_server.interface::<_, Metrics>(OBJECT_PATH)?;
+    iface.get_mut().sample = sample;
+    zbus::block_on(Properties::properties_changed(
+        iface.signal_emitter(),
+        InterfaceName::from_static_str_unchecked(INTERFACE),
+        properties(&sample),
+        Default::default(),
+    ))
+}
+
+fn reading(value: Option<impl Into<f64>>) -> f64 {
+    value.map_or(UNAVAILABLE, Into::into)
+}
+
+/// Every property of the interface, by D-Bus name.
+pub(crate) fn properties(sample: &Sample) -> HashMap<&'static str, Value<'static>> {
+    let metrics = Metrics { sample: *sample };
+    HashMap::from([
+        ("Timestamp", Value::from(metrics.timestamp())),
+        ("Cpu", metrics.cpu().into()),
+        ("Memory", metrics.memory().into()),
+        ("Gpu", metrics.gpu().into()),
+        ("GpuMemory", metrics.gpu_memory().into()),
+        ("GpuTemperature", metrics.gpu_temperature().into()),
+        ("CpuPower", metrics.cpu_power().into()),
+        ("NetDown", metrics.net_down().into()),
+        ("NetUp", metrics.net_up().into()),
+    ])
+}
+
+#[derive(Default)]
+struct Metrics {
+    sample: Sample,
+}
+
+#[zbus::interface(name = "org.alexx855.ResourceMonitor")]
+impl Metrics {
+    /// Seconds since the Unix epoch of the latest sample; 0 before the first
+    #[zbus(property)]
+    fn timestamp(&self) -> u64 {
+        self.sample.timestamp
+    }
+
+    /// Percent
+    #[zbus(property)]
+    fn cpu(&self) -> f64 {
+        reading(self.sample.cpu)
+    }
+
+    /// Percent
+    #[zbus(property)]
+    fn memory(&self) -> f64 {
+        reading(self.sample.mem)
+    }
+
+    /// Percent
+    #[zbus(property)]
+    fn gpu(&self) -> f64 {
+        reading(self.sample.gpu)
+    }
+
+    /// Bytes
+    #[zbus(property)]
+    fn gpu_memory(&self) -> f64 {
+        reading(self.sample.gpu_mem.map(|bytes| bytes as f64))
+    }
+
+    /// °C
+    #[zbus(property)]
+    fn gpu_temperature(&self) -> f64 {
+        reading(self.sample.gpu_temp)
+    }
+
+    /// Watts
+    #[zbus(property)]
+    fn cpu_power(&self) -> f64 {
+        reading(self.sample.cpu_power)
+    }
+
+    /// Bytes per second
+    #[zbus(property)]
+    fn net_down(&self) -> f64 {
+        reading(self.sample.net_down)
+    }
+
+    /// Bytes per second
+    #[zbus(property)]
+    fn net_up(&self) -> f64 {
+        reading(self.sample.net_up)
+    }
+}
@@ -0,0 +1,215 @@
+//! Details block
+//!
+//! With `show_details` on, the top of the tray menu lists the exact current
+//! readings, e.g. "CPU: 34.2%", "Memory: 12.6 / 32.0 GB", "GPU: 18.0%" and
+//! "Network: ↓1.52 MB/s ↑0.21 MB/s", refreshed every tick. Segments hidden from
+//! the icon are still sampled while it is on, so their values stay readable.
+//! Lines for metrics without a reading (no GPU, a remote host that doesn't
+//! report one) are left out; the other detail lines follow the block.
+//!
+//! Those single lines (CPU frequency, VPN, pressure, ...) come in the fixed
+//! order of [`DetailLine`]; [`DetailLines`] inserts each below the block and
+//! the lines before it that are shown.
+
+use std::sync::Mutex;
+
+use tauri::menu::{Menu, MenuItem};
+use tauri::{AppHandle, Wry};
+
+use crate::config::NetUnit;
+use crate::i18n::{tr, tr_args};
+
+/// CPU, memory, GPU and network.
+pub const MAX_LINES: usize = 4;
+
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Current values for the block; `None` where there is no reading.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Readings {
+    pub cpu: Option<f32>,
+    pub mem: Option<f32>,
+    /// Memory in use and installed, in bytes; local readings only
+    pub mem_bytes: Option<(u64, u64)>,
+    pub gpu: Option<f32>,
+    pub gpu_mem: Option<u64>,
+    /// Download and upload in bytes per second
+    pub net: Option<(f64, f64)>,
+}
+
+/// The block's lines, in menu order.
+pub fn lines(readings: &Readings, unit: NetUnit) -> Vec<String> {
+    let mut lines = Vec::with_capacity(MAX_LINES);
+    if let Some(cpu) = readings.cpu {
+        lines.push(tr_args("CPU: {}", &[&format!("{cpu:.1}%")]));
+    }
+    let gb = tr("GB");
+    match (readings.mem_bytes, readings.mem) {
+        (Some((used, total)), _) => lines.push(tr_args(
+            "Memory: {}",
+            &[&format!(
+                "{:.1} / {:.1} {gb}",
+                used as f64 / GIB,
+                total as f64 / GIB
+            )],
+        )),
+        (None, Some(mem)) => lines.push(tr_args("Memory: {}", &[&format!("{mem:.1}%")])),
+        (None, None) => {}
+    }
+    if let Some(gpu) = readings.gpu {
+        let value = match readings.gpu_mem {
+            Some(bytes) => format!("{gpu:.1}% · {:.2} {gb}", bytes as f64 / GIB),
+            None => format!("{gpu:.1}%"),
+        };
+        lines.push(tr_args("GPU: {}", &[&value]));
+    }
+    if let Some((down, up)) = readings.net {
+        lines.push(tr_args(
+            "Network: ↓{} ↑{}",
+            &[&exact_rate(down, unit), &exact_rate(up, unit)],
+        ));
+    }
+    lines
+}
+
+/// A rate with two decimals from KB/s on: "512 B/s", "1.52 MB/s", "12.16 Mb/s".
+pub(crate) fn exact_rate(bytes_per_sec: f64, unit: NetUnit) -> String {
+    let (mut value, units) = match unit {
+        NetUnit::Bytes => (bytes_per_sec, ["B/s", "KB/s", "MB/s", "GB/s"]),
+        NetUnit::Bits => (bytes_per_sec * 8.0, ["b/s", "Kb/s", "Mb/s", "Gb/s"]),
+    };
+    let mut index = 0;
+    while value >= 1000.0 && index < units.len() - 1 {
+        value /= 1000.0;
+        index += 1;
+    }
+    if index == 0 {
+        format!("{value:.0} {}", units[0])
+    } else {
+        format!("{value:.2} {}", units[index])
+    }
+}
+
+/// Single read-only lines below the details block, in menu order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DetailLine {
+    CpuFrequency,
+    Vpn,
+    Pressure,
+    TaskCounts,
+    Wifi,
+    /// NVENC/NVDEC utilization
+    GpuCodec,
+    /// Present while a watched process is over its limits
+    Watched,
+}
+
+impl DetailLine {
+    pub const ALL: [DetailLine; 7] = [
+        DetailLine::CpuFrequency,
+        DetailLine::Vpn,
+        DetailLine::Pressure,
+        DetailLine::TaskCounts,
+        DetailLine::Wifi,
+        DetailLine::GpuCodec,
+        DetailLine::Watched,
+    ];
+
+    /// Shown until the monitoring loop sets the first reading.
+    fn placeholder(self) -> String {
+        match self {
+            DetailLine::CpuFrequency => tr_args("CPU Frequency: {}", &["…"]),
+            DetailLine::Vpn => tr_args("VPN: {}", &["…"]),
+            DetailLine::Pressure => tr_args("Pressure: {}", &["…"]),
+            DetailLine::TaskCounts => tr_args("Processes: {}", &["…"]),
+            DetailLine::Wifi => tr_args("Wi-Fi: {}", &["…"]),
+            DetailLine::GpuCodec => tr_args("GPU Encoder: {} · Decoder: {}", &["…", "…"]),
+            DetailLine::Watched => tr_args("Over limit: {}", &["…"]),
+        }
+    }
+}
+
+/// Owns the [`DetailLine`] items and where they go in the tray menu.
+pub struct DetailLines {
+    menu: Menu<Wry>,
+    /// In [`DetailLine::ALL`] order
+    items: Vec<MenuItem<Wry>>,
+    shown: Mutex<[bool; DetailLine::ALL.len()]>,
+}
+
+impl DetailLines {
+    pub fn new(app: &AppHandle, menu: &Menu<Wry>) -> tauri::Result<Self> {
+        let items = DetailLine::ALL
+            .iter()
+            .map(|line| MenuItem::new(app, line.placeholder(), false, None::<&str>))
+            .collect::<tauri::Result<_>>()?;
+        Ok(Self {
+            menu: menu.clone(),
+            items,
+            shown: Mutex::new([false; DetailLine::ALL.len()]),
+        })
+    }
+
+    pub fn is_shown(&self, line: DetailLine) -> bool {
+        self.shown.lock().unwrap_or_else(|e| e.into_inner())[line as usize]
+    }
+
+    /// Adds or removes `line`; nothing happens when it already is as asked.
+    /// `block_shown` is how many lines of the details block sit above.
+    pub fn set_shown(&self, line: DetailLine, show: bool, block_shown: usize) -> tauri::Result<()> {
+        let mut shown = self.shown.lock().unwrap_or_else(|e| e.into_inner());
+        let index = line as usize;
+        if shown[index] == show {
+            return Ok(());
+        }
+        if show {
+            let above = shown[..index].iter().filter(|&&shown| shown).count();
+            self.menu.insert(&self.items[index], block_shown + above)?;
+        } else {
+            self.menu.remove(&self.items[index])?;
+        }
+        shown[index] = show;
+        Ok(())
+    }
+
+    pub fn set_text(&self, line: DetailLine, text: &str) -> tauri::Result<()> {
+        self.items[line as usize].set_text(text)
+    }
+}
+
+/// Read-only lines at the very top of the tray menu, present while enabled.
+pub struct DetailsBlock {
+    pub menu: Menu<Wry>,
+    pub items: Vec<MenuItem<Wry>>,
+}
+
+impl DetailsBlock {
+    pub fn new(app: &AppHandle, menu: &Menu<Wry>) -> tauri::Result<Self> {
+        let items = (0..MAX_LINES)
+            .map(|_| MenuItem::new(app, "…", false, None::<&str>))
+            .collect::<tauri::Result<_>>()?;
+        Ok(Self {
+            menu: menu.clone(),
+            items,
+        })
+    }
+
+    /// Shows `lines` where `shown` lines are now, adding or removing items at
+    /// the end of the block. Returns how many are shown afterwards; call it
+    /// only when the lines change.
+    pub fn update(&self, lines: &[String], mut shown: usize) -> tauri::Result<usize> {
+        let count = lines.len().min(self.items.len());
+        while shown > count {
+            shown -= 1;
+            self.menu.remove(&self.items[shown])?;
+        }
+        while shown < count {
+            self.menu.insert(&self.items[shown], shown)?;
+            shown += 1;
+        }
+        for (item, line) in self.items.iter().zip(lines) {
+            item.set_text(line)?;
+        }
+        Ok(shown)
+    }
+}
@@ -0,0 +1,168 @@
+//! Diagnostics report
+//!
+//! "Copy Diagnostics" puts a plain-text summary of what the app found on this
+//! machine on the clipboard, for pasting into bug reports: platform, desktop
+//! and display server, GPU backend, font, icon sizing and the last warnings
+//! and errors. The home folder, user and host names and any URL are redacted,
+//! so the report can be posted publicly as is.
+
+use sysinfo::System;
+
+use crate::preferences::Capabilities;
+use crate::{gpu, logging, tray_render};
+
+pub const MENU_ID: &str = "copy_diagnostics";
+
+/// Identifying strings to take out of the report.
+#[derive(Debug, Default)]
+pub(crate) struct Redactions {
+    pub home: Option<String>,
+    pub user: Option<String>,
+    pub host: Option<String>,
+}
+
+impl Redactions {
+    fn current() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        Self {
+            home: var("HOME"),
+            user: var("USER").or_else(|| var("LOGNAME")),
+            host: System::host_name().filter(|name| !name.is_empty()),
+        }
+    }
+}
+
+/// The report for this run of the app, redacted.
+pub fn report(version: &str, capabilities: &Capabilities, sizing: tray_render::Sizing) -> String {
+    let gpu = if capabilities.gpu {
+        gpu::backend().to_string()
+    } else {
+        format!("{} (no GPU found)", gpu::backend())
+    };
+    let fields = [
+        ("App", version.to_string()),
+        (
+            "OS",
+            format!(
+                "{} ({}, kernel {})",
+                System::long_os_version().unwrap_or_else(|| std::env::consts::OS.to_string()),
+                std::env::consts::ARCH,
+                System::kernel_version().unwrap_or_else(|| "unknown".to_string()),
+            ),
+        ),
+        ("Desktop", desktop()),
+        ("Display server", display_server()),
+        ("GPU backend", gpu),
+        ("Font", crate::loaded_font()),
+        ("Sizing", format_sizing(sizing)),
+    ];
+    redact(
+        &format_report(&fields, &logging::recent_problems()),
+        &Redactions::current(),
+    )
+}
+
+pub(crate) fn format_report(fields: &[(&str, String)], problems: &[String]) -> String {
+    let mut report = String::from("Better Resource Monitor diagnostics\n");
+    for (label, value) in fields {
+        report.push_str(&format!("{label}: {value}\n"));
+    }
+    report.push_str("\nRecent warnings and errors:\n");
+    if problems.is_empty() {
+        report.push_str("(none)\n");
+    }
+    for problem in problems {
+        report.push_str(&format!("{problem}\n"));
+    }
+    report
+}
+
+/// "Linux ×1.5: icon 33 px, font 21.0 px".
+fn format_sizing(sizing: tray_render::Sizing) -> String {
+    let (preset, base) = if cfg!(target_os = "macos") {
+        ("macOS", tray_render::SIZING_MACOS)
+    } else {
+        ("Linux", tray_render::SIZING_LINUX)
+    };
+    format!(
+        "{preset} ×{:.2}: icon {} px, font {:.1} px",
+        sizing.icon_height as f32 / base.icon_height as f32,
+        sizing.icon_height,
+        sizing.font_size
+    )
+}
+
+fn desktop() -> String {
+    if cfg!(target_os = "macos") {
+        return "Aqua".to_string();
+    }
+    [
+        "XDG_CURRENT_DESKTOP",
+        "XDG_SESSION_DESKTOP",
+        "DESKTOP_SESSION",
+    ]
+    .into_iter()
+    .filter_map(|name| std::env::var(name).ok())
+    .find(|value| !value.is_empty())
+    .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn display_server() -> String {
+    if cfg!(target_os = "macos") {
+        return "Quartz".to_string();
+    }
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    let x11 = std::env::var_os("DISPLAY").is_some();
+    let session = std::env::var("XDG_SESSION_TYPE").unwrap_or_default();
+    match (wayland, x11) {
+        (true, true) => "Wayland (XWayland available)".to_string(),
+        (true, false) => "Wayland".to_string(),
+        (false, true) => "X11".to_string(),
+        (false, false) if !session.is_empty() => session,
+        (false, false) => "unknown".to_string(),
+    }
+}
+
+/// `text` with URLs, the home folder, and the user and host names replaced.
+/// Names only match as whole words, so a short user name doesn't eat into
+/// other text.
+pub(crate) fn redact(text: &str, redactions: &Redactions) -> String {
+    let mut text = text
+        .split('\n')
+        .map(|line| {
+            line.split(' ')
+                .map(|word| if word.contains("://") { "<url>" } else { word })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if let Some(home) = redactions.home.as_deref().filter(|home| *home != "/") {
+        text = text.replace(home, "~");
+    }
+    for (name, placeholder) in [(&redactions.host, "<host>"), (&redactions.user, "<user>")] {
+        if let Some(name) = name {
+            text = replace_word(&text, name, placeholder);
+        }
+    }
+    text
+}
+
+fn replace_word(text: &str, word: &str, with: &str) -> String {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    let mut replaced = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (at, _) in text.match_indices(word) {
+        let end = at + word.len();
+        let before = text[..at].chars().next_back();
+        let after = text[end..].chars().next();
+        if before.is_some_and(is_word_char) || after.is_some_and(is_word_char) {
+            continue;
+        }
+        replaced.push_str(&text[copied..at]);
+        replaced.push_str(with);
+        copied = end;
+    }
+    replaced.push_str(&text[copied..]);
+    replaced
+}
@@ -0,0 +1,71 @@
+//! Rendered tray frame dump for debugging
+//!
+//! Set `SILICON_FRAME_DUMP_DIR` to a directory and every icon the monitoring
+//! loop hands to the tray is also written there as a PNG named after the time
+//! it was rendered. Comparing these with what the panel shows tells a rendering
+//! bug apart from a tray host that scales or recolors the icon.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageEncoder};
+use tracing::{error, info, warn};
+
+pub const ENV_VAR: &str = "SILICON_FRAME_DUMP_DIR";
+
+/// Stops dumping after this many frames so a forgotten variable can't fill the disk.
+pub const MAX_FRAMES: u32 = 10_000;
+
+/// Encodes an RGBA buffer as produced by `render_tray_icon_into` to `path`.
+pub fn write_png(path: &Path, rgba: &[u8], width: u32, height: u32) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    PngEncoder::new(BufWriter::new(file))
+        .write_image(rgba, width, height, ColorType::Rgba8)
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+pub struct FrameDump {
+    dir: PathBuf,
+    frames: u32,
+}
+
+impl FrameDump {
+    /// Enabled when [`ENV_VAR`] names a directory that exists or can be created.
+    pub fn from_env() -> Option<Self> {
+        let dir = PathBuf::from(std::env::var_os(ENV_VAR)?);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Frame dump disabled, cannot create {}: {e}", dir.display());
+            return None;
+        }
+        info!("Dumping tray frames to {}", dir.display());
+        Some(Self { dir, frames: 0 })
+    }
+
+    pub fn write(&mut self, rgba: &[u8], width: u32, height: u32) {
+        if self.frames >= MAX_FRAMES {
+            return;
+        }
+        self.frames += 1;
+        if self.frames == MAX_FRAMES {
+            info!("Frame dump reached {MAX_FRAMES} frames, stopping");
+        }
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let path = self.dir.join(frame_file_name(millis, self.frames));
+        if let Err(e) = write_png(&path, rgba, width, height) {
+            error!("Failed to dump tray frame: {e}");
+        }
+    }
+}
+
+/// `frame-<unix millis>-<sequence>.png`; the sequence keeps names unique and
+/// sortable when two frames land in the same millisecond.
+pub(crate) fn frame_file_name(unix_millis: u128, sequence: u32) -> String {
+    format!("frame-{unix_millis}-{sequence:05}.png")
+}
@@ -0,0 +1,168 @@
+//! GPU processes submenu
+//!
+//! Lists the processes using the GPU, heaviest GPU memory first:
+//!
+//! - Linux: NVML's running compute and graphics processes, with the GPU memory
+//!   each uses. Names come from `/proc`; a process in another PID namespace (a
+//!   container) shows as its PID.
+//! - macOS: the owners of IOAccelerator user clients from `ioreg`, i.e. every
+//!   process with a GPU context. macOS doesn't report per-process GPU memory, so
+//!   only names are shown.
+//!
+//! The list is read at most every [`CHECK_INTERVAL`] (`ioreg -l` walks the
+//! whole accelerator registry) and the submenu is only rebuilt when it changes.
+
+use std::time::{Duration, Instant};
+
+use tauri::menu::{MenuItem, Submenu};
+use tauri::{AppHandle, Wry};
+
+use crate::i18n::tr;
+use crate::processes::format_memory;
+
+pub const TOP_COUNT: usize = 5;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GpuProcess {
+    pub pid: u32,
+    pub name: String,
+    /// GPU memory in bytes, where the driver reports it
+    pub memory: Option<u64>,
+}
+
+/// Refilled whenever the list of GPU users changes.
+pub struct GpuProcessMenu(pub Submenu<Wry>);
+
+#[derive(Default)]
+pub struct GpuProcessMonitor {
+    #[cfg(target_os = "linux")]
+    reader: Option<crate::gpu::GpuProcessReader>,
+    last_check: Option<Instant>,
+    /// The list the submenu shows; `None` until the first read
+    shown: Option<Vec<GpuProcess>>,
+}
+
+impl GpuProcessMonitor {
+    #[cfg(target_os = "linux")]
+    pub fn new() -> Self {
+        Self {
+            reader: crate::gpu::GpuProcessReader::new(),
+            ..Self::default()
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The heaviest [`TOP_COUNT`] processes using the GPU, when they differ
+    /// from the last list returned; `None` while unchanged or not yet due.
+    pub fn sample(&mut self) -> Option<Vec<GpuProcess>> {
+        let due = self
+            .last_check
+            .is_none_or(|checked| checked.elapsed() >= CHECK_INTERVAL);
+        if !due {
+            return None;
+        }
+        self.last_check = Some(Instant::now());
+        let top = top_by_memory(self.read(), TOP_COUNT);
+        if self.shown.as_ref() == Some(&top) {
+            return None;
+        }
+        self.shown = Some(top.clone());
+        Some(top)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn read(&mut self) -> Vec<GpuProcess> {
+        let Some(reader) = self.reader.as_mut() else {
+            return Vec::new();
+        };
+        reader
+            .read()
+            .into_iter()
+            .map(|(pid, memory)| GpuProcess {
+                pid,
+                name: std::fs::read_to_string(format!("/proc/{pid}/comm"))
+                    .map(|comm| comm.trim_end().to_string())
+                    .unwrap_or_else(|_| format!("PID {pid}")),
+                memory,
+            })
+            .collect()
+    }
+
+    #[cfg(target_os = "macos")]
+    fn read(&mut self) -> Vec<GpuProcess> {
+        crate::command_stdout("ioreg", &["-r", "-c", "IOAccelerator", "-l", "-w0"])
+            .map(|out| parse_ioreg_clients(&out))
+            .unwrap_or_default()
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    fn read(&mut self) -> Vec<GpuProcess> {
+        Vec::new()
+    }
+}
+
+/// Sorted by GPU memory, unknown amounts last, then by PID.
+pub(crate) fn top_by_memory(mut processes: Vec<GpuProcess>, n: usize) -> Vec<GpuProcess> {
+    processes.sort_by(|a, b| b.memory.cmp(&a.memory).then(a.pid.cmp(&b.pid)));
+    processes.truncate(n);
+    processes
+}
+
+/// Parses the `"IOUserClientCreator" = "pid 412, WindowServer"` lines of
+/// `ioreg -l` output, one entry per process however many clients it holds.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn parse_ioreg_clients(output: &str) -> Vec<GpuProcess> {
+    let mut processes: Vec<GpuProcess> = Vec::new();
+    for line in output.lines() {
+        let Some((_, creator)) = line.split_once("\"IOUserClientCreator\" = \"pid ") else {
+            continue;
+        };
+        let Some((pid, name)) = creator.split_once(", ") else {
+            continue;
+        };
+        let (Ok(pid), Some(name)) = (pid.parse::<u32>(), name.strip_suffix('"')) else {
+            continue;
+        };
+        if processes.iter().all(|process| process.pid != pid) {
+            processes.push(GpuProcess {
+                pid,
+                name: name.to_string(),
+                memory: None,
+            });
+        }
+    }
+    processes
+}
+
+/// Replaces the submenu contents with the current GPU users.
+pub fn update_menu(
+    app: &AppHandle,
+    menu: &Submenu<Wry>,
+    processes: &[GpuProcess],
+) -> tauri::Result<()> {
+    while menu.remove_at(0)?.is_some() {}
+
+    if processes.is_empty() {
+        menu.append(&MenuItem::new(
+            app,
+            tr("No GPU processes"),
+            false,
+            None::<&str>,
+        )?)?;
+    }
+    for process in processes {
+        let label = match process.memory {
+            Some(bytes) => format!("{} — {}", process.name, format_memory(bytes)),
+            None => process.name.clone(),
+        };
+        menu.append(&MenuItem::new(app, label, false, None::<&str>)?)?;
+    }
+
+    Ok(())
+}
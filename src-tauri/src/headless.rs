@@ -0,0 +1,167 @@
+//! Headless mode (`--headless`)
+//!
+//! Runs the samplers and the exporters (status server and CSV history) without
+//! a tray icon or any window, for servers and sessions without a panel. Settings
+//! are read once from the same `settings.json` the tray app writes; command-line
+//! options override them for this run only:
+//!
+//! ```text
+//! better-resource-monitor --headless --status-port 9870 --history
+//! ```
+
+use std::path::PathBuf;
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tracing::{error, info, warn};
+
+use crate::config::{key, Config, SETTINGS_FILE};
+use crate::history::{self, HistoryRecorder};
+use crate::metrics::{self, Sample};
+use crate::minute_history::MinuteHistory;
+use crate::sampler::SamplerRegistry;
+use crate::status_server::{self, LatestSample};
+
+pub const FLAG: &str = "--headless";
+
+/// Must match `identifier` in `tauri.conf.json`, which names the data directory.
+const APP_IDENTIFIER: &str = "dev.alexpedersen.better-resource-monitor";
+
+const USAGE: &str = "Usage: better-resource-monitor --headless [--status-port <port>] [--history]
+
+  --status-port <port>  Serve the latest sample on 127.0.0.1:<port>/status
+  --history             Append samples to the CSV history files";
+
+/// Settings given on the command line after `--headless`, as (key, value) pairs
+/// for `Config::set`.
+pub(crate) fn parse_args(args: &[String]) -> Result<Vec<(&'static str, Value)>, String> {
+    let mut overrides = Vec::new();
+    let mut args = args.iter().filter(|arg| *arg != FLAG);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--status-port" => {
+                let port: u16 = args
+                    .next()
+                    .and_then(|port| port.parse().ok())
+                    .ok_or("--status-port needs a port number")?;
+                overrides.push((key::STATUS_SERVER_ENABLED, json!(true)));
+                overrides.push((key::STATUS_SERVER_PORT, json!(port)));
+            }
+            "--history" => overrides.push((key::HISTORY_ENABLED, json!(true))),
+            other => return Err(format!("Unknown option {other}")),
+        }
+    }
+    Ok(overrides)
+}
+
+/// The directory Tauri resolves as the app data directory.
+pub(crate) fn app_data_dir() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let data_dir = if cfg!(target_os = "macos") {
+        home?.join("Library/Application Support")
+    } else if cfg!(windows) {
+        PathBuf::from(std::env::var_os("APPDATA")?)
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| home.map(|home| home.join(".local/share")))?
+    };
+    Some(data_dir.join(APP_IDENTIFIER))
+}
+
+pub(crate) fn load_config(data_dir: Option<&PathBuf>) -> Config {
+    let settings = data_dir
+        .and_then(|dir| std::fs::read_to_string(dir.join(SETTINGS_FILE)).ok())
+        .and_then(|contents| serde_json::from_str::<Value>(&contents).ok());
+    let config = Config::default();
+    if let Some(Value::Object(settings)) = settings {
+        config.apply(|key| settings.get(key).cloned());
+    }
+    config
+}
+
+pub fn run(args: &[String]) {
+    let overrides = match parse_args(args) {
+        Ok(overrides) => overrides,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    let data_dir = app_data_dir();
+    let config = Arc::new(load_config(data_dir.as_ref()));
+    crate::logging::configure(&config, data_dir.as_deref());
+    for (key, value) in overrides {
+        if let Err(e) = config.set(key, &value) {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    }
+    config.configure_samplers();
+
+    let serve_status = config.status_server_enabled.load(Relaxed);
+    let record_history = config.history_enabled.load(Relaxed);
+    let history_dir = data_dir.as_ref().map(|dir| dir.join(history::HISTORY_DIR));
+    if !serve_status && !record_history {
+        eprintln!("No exporter enabled; nothing will be written.\n\n{USAGE}");
+    }
+    if serve_status {
+        info!(
+            "Serving status on http://127.0.0.1:{}/status",
+            config.status_server_port.load(Relaxed)
+        );
+    }
+
+    let latest = LatestSample::default();
+    let minutes = Arc::new(Mutex::new(
+        data_dir
+            .as_deref()
+            .map(|dir| MinuteHistory::load(dir, metrics::unix_now()))
+            .unwrap_or_default(),
+    ));
+    status_server::start(config.clone(), latest.clone(), minutes.clone());
+
+    let mut history = match (&history_dir, record_history) {
+        (Some(dir), true) => {
+            info!("Recording history to {}", dir.display());
+            Some(HistoryRecorder::new(
+                dir.clone(),
+                config.history_retention_days.load(Relaxed),
+            ))
+        }
+        (None, true) => {
+            warn!("History recording unavailable: no data directory");
+            None
+        }
+        _ => None,
+    };
+
+    let mut samplers = SamplerRegistry::with_default_samplers();
+    loop {
+        samplers.sample(|_| true);
+        let sample = Sample::from_registry(&samplers);
+        *latest.lock().unwrap_or_else(|e| e.into_inner()) = Some(sample);
+        if let Some(recorder) = history.as_mut() {
+            if let Err(e) = recorder.record(&sample) {
+                error!("Failed to record history: {e}");
+            }
+        }
+        {
+            let mut minutes = minutes.lock().unwrap_or_else(|e| e.into_inner());
+            if let (Some(_), Some(dir)) = (minutes.record(sample), data_dir.as_deref()) {
+                if let Err(e) = minutes.save(dir) {
+                    error!("Failed to save minute history: {e}");
+                }
+            }
+        }
+
+        let interval =
+            crate::get_update_interval_ms(config.update_interval_ms.load(Relaxed) as u64);
+        thread::sleep(Duration::from_millis(interval));
+    }
+}
@@ -0,0 +1,156 @@
+//! Opt-in on-disk history of samples
+//!
+//! Appends one CSV row per monitoring tick to `history-YYYY-MM-DD.csv` (one file
+//! per UTC day) under the app data directory. Files older than the retention
+//! window are deleted whenever the recorder rotates to a new day.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use tracing::warn;
+
+use crate::metrics::Sample;
+
+pub const HISTORY_DIR: &str = "history";
+pub const DEFAULT_RETENTION_DAYS: u32 = 7;
+
+const CSV_HEADER: &str =
+    "timestamp,cpu_percent,mem_percent,gpu_percent,net_down_bps,net_up_bps,gpu_mem_bytes,cpu_power_watts,gpu_temp_c";
+const FILE_PREFIX: &str = "history-";
+const FILE_SUFFIX: &str = ".csv";
+const SECS_PER_DAY: u64 = 86_400;
+
+pub struct HistoryRecorder {
+    dir: PathBuf,
+    retention_days: u32,
+    current_day: Option<u64>,
+    file: Option<File>,
+}
+
+impl HistoryRecorder {
+    pub fn new(dir: PathBuf, retention_days: u32) -> Self {
+        Self {
+            dir,
+            retention_days: retention_days.max(1),
+            current_day: None,
+            file: None,
+        }
+    }
+
+    pub fn set_retention_days(&mut self, days: u32) {
+        self.retention_days = days.max(1);
+    }
+
+    pub fn record(&mut self, sample: &Sample) -> io::Result<()> {
+        let day = sample.timestamp / SECS_PER_DAY;
+        if self.current_day != Some(day) || self.file.is_none() {
+            self.rotate(day)?;
+        }
+
+        match self.file.as_mut() {
+            Some(file) => writeln!(file, "{}", csv_row(sample)),
+            None => Ok(()),
+        }
+    }
+
+    fn rotate(&mut self, day: u64) -> io::Result<()> {
+        self.file = None;
+        fs::create_dir_all(&self.dir)?;
+
+        let path = self.dir.join(file_name_for_day(day));
+        let is_new = !path.exists();
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        if is_new {
+            writeln!(file, "{CSV_HEADER}")?;
+        }
+
+        self.file = Some(file);
+        self.current_day = Some(day);
+        self.prune(day);
+        Ok(())
+    }
+
+    fn prune(&self, today: u64) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let Some(day) = name.to_str().and_then(day_from_file_name) else {
+                continue;
+            };
+            if is_expired(day, today, self.retention_days) {
+                if let Err(e) = fs::remove_file(entry.path()) {
+                    warn!("Failed to remove old history file {name:?}: {e}");
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn is_expired(day: u64, today: u64, retention_days: u32) -> bool {
+    today.saturating_sub(day) >= retention_days.max(1) as u64
+}
+
+pub(crate) fn csv_row(sample: &Sample) -> String {
+    fn field<T: std::fmt::Display>(value: Option<T>) -> String {
+        value.map(|v| v.to_string()).unwrap_or_default()
+    }
+
+    format!(
+        "{},{},{},{},{},{},{},{},{}",
+        sample.timestamp,
+        field(sample.cpu.map(|v| format!("{v:.1}"))),
+        field(sample.mem.map(|v| format!("{v:.1}"))),
+        field(sample.gpu.map(|v| format!("{v:.1}"))),
+        field(sample.net_down.map(|v| format!("{v:.0}"))),
+        field(sample.net_up.map(|v| format!("{v:.0}"))),
+        field(sample.gpu_mem),
+        field(sample.cpu_power.map(|v| format!("{v:.2}"))),
+        field(sample.gpu_temp.map(|v| format!("{v:.0}"))),
+    )
+}
+
+pub(crate) fn file_name_for_day(day: u64) -> String {
+    let (y, m, d) = civil_from_days(day as i64);
+    format!("{FILE_PREFIX}{y:04}-{m:02}-{d:02}{FILE_SUFFIX}")
+}
+
+pub(crate) fn day_from_file_name(name: &str) -> Option<u64> {
+    let date = name.strip_prefix(FILE_PREFIX)?.strip_suffix(FILE_SUFFIX)?;
+    let mut parts = date.splitn(3, '-');
+    let y: i64 = parts.next()?.parse().ok()?;
+    let m: u32 = parts.next()?.parse().ok()?;
+    let d: u32 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+        return None;
+    }
+    u64::try_from(days_from_civil(y, m, d)).ok()
+}
+
+/// Converts days since 1970-01-01 into a (year, month, day) civil date.
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Howard Hinnant's algorithm: http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    (y, m, d)
+}
+
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let m = m as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
@@ -0,0 +1,38 @@
+//! Minimal HTTP client for the local APIs the app reads
+//!
+//! One GET per connection, over a stream the caller opened and set timeouts
+//! on: the container runtime's Unix socket or a remote status server's TCP
+//! port. That is all either needs, so there is no HTTP library.
+
+use std::io::{Read, Write};
+
+/// Sends a GET for `path` and returns the response body. `server` names the
+/// other end in errors, e.g. "Container runtime".
+pub(crate) fn get(
+    mut stream: impl Read + Write,
+    host: &str,
+    path: &str,
+    server: &str,
+) -> Result<String, String> {
+    // HTTP/1.0 so the response is neither chunked nor kept alive
+    write!(stream, "GET {path} HTTP/1.0\r\nHost: {host}\r\n\r\n").map_err(|e| e.to_string())?;
+    let mut response = Vec::new();
+    stream
+        .read_to_end(&mut response)
+        .map_err(|e| e.to_string())?;
+
+    let response = String::from_utf8_lossy(&response);
+    response_body(&response, server).map(str::to_string)
+}
+
+/// Body of a raw HTTP response, or an error for a non-2xx status.
+pub(crate) fn response_body<'a>(response: &'a str, server: &str) -> Result<&'a str, String> {
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or("Malformed response")?;
+    let status_line = head.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(body),
+        _ => Err(format!("{server} answered {status_line}")),
+    }
+}
@@ -0,0 +1,139 @@
+//! Menu translations
+//!
+//! Menu labels are looked up by their English text in a table per language,
+//! `locales/<code>.json`, embedded at build time. A table may be partial: any
+//! text without an entry stays in English. `{}` in a text is a placeholder,
+//! filled in order, so a translation can move it but must keep the count.
+//!
+//! The language follows the system locale unless `language` is set, and
+//! applies from the next start, as the tray menu is built once. Reading lines
+//! in the menu and tooltip ("VPN: {}") are translated along with their byte
+//! units (MB, GB, TB); the values filled in, such as rates and interface
+//! names, and the tray icon's own text stay as they are.
+//!
+//! To add a language, copy an existing table to `locales/<code>.json` (the
+//! ISO 639-1 code), translate the values, and list it in [`LANGUAGES`] and in
+//! the preferences window's language menu.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use tracing::warn;
+
+pub struct Language {
+    pub code: &'static str,
+    pub(crate) table: &'static str,
+}
+
+pub const LANGUAGES: &[Language] = &[
+    Language {
+        code: "de",
+        table: include_str!("../locales/de.json"),
+    },
+    Language {
+        code: "es",
+        table: include_str!("../locales/es.json"),
+    },
+    Language {
+        code: "fr",
+        table: include_str!("../locales/fr.json"),
+    },
+    Language {
+        code: "ja",
+        table: include_str!("../locales/ja.json"),
+    },
+];
+
+/// Empty until [`init`] picks a language other than English.
+static TABLE: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Loads the table for `language`, or for the system locale when empty. Only
+/// the first call has an effect.
+pub fn init(language: &str) {
+    let locale = if language.is_empty() {
+        system_locale().unwrap_or_default()
+    } else {
+        language.to_string()
+    };
+    let table = find(&locale)
+        .map(|language| {
+            parse_table(language.table).unwrap_or_else(|e| {
+                warn!("Ignoring translations for {}: {e}", language.code);
+                HashMap::new()
+            })
+        })
+        .unwrap_or_default();
+    let _ = TABLE.set(table);
+}
+
+/// Whether `code` can be set as the `language` setting; English is the
+/// built-in text and needs no table.
+pub fn is_supported(code: &str) -> bool {
+    code == "en" || LANGUAGES.iter().any(|language| language.code == code)
+}
+
+/// The bundled language for a locale such as "de", "de_AT.UTF-8" or "pt-BR".
+pub(crate) fn find(locale: &str) -> Option<&'static Language> {
+    let code = locale
+        .split(['_', '-', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    LANGUAGES.iter().find(|language| language.code == code)
+}
+
+pub(crate) fn parse_table(contents: &str) -> Result<HashMap<String, String>, String> {
+    serde_json::from_str(contents).map_err(|e| e.to_string())
+}
+
+/// `text` in the chosen language, or unchanged without a translation.
+pub fn tr(text: &'static str) -> &'static str {
+    TABLE
+        .get()
+        .and_then(|table| table.get(text))
+        .map_or(text, String::as_str)
+}
+
+/// [`tr`] with each `{}` replaced by the next of `args`.
+pub fn tr_args(text: &'static str, args: &[&str]) -> String {
+    fill(tr(text), args)
+}
+
+pub(crate) fn fill(template: &str, args: &[&str]) -> String {
+    let mut parts = template.split("{}");
+    let mut filled = parts.next().unwrap_or_default().to_string();
+    for (part, arg) in parts.zip(args.iter().chain(std::iter::repeat(&""))) {
+        filled.push_str(arg);
+        filled.push_str(part);
+    }
+    filled
+}
+
+/// The first of `LC_ALL`, `LC_MESSAGES` and `LANG` that is set, as for any
+/// other program's messages.
+#[cfg(not(target_os = "macos"))]
+fn system_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// The first preferred language from System Settings; apps started from
+/// Finder or at login don't get a `LANG`.
+#[cfg(target_os = "macos")]
+fn system_locale() -> Option<String> {
+    use core_foundation::array::{CFArray, CFArrayRef};
+    use core_foundation::base::TCFType;
+    use core_foundation::string::CFString;
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    unsafe extern "C" {
+        fn CFLocaleCopyPreferredLanguages() -> CFArrayRef;
+    }
+
+    // SAFETY: returns a new array of CFStrings (never null), owned here
+    let languages: CFArray<CFString> =
+        unsafe { CFArray::wrap_under_create_rule(CFLocaleCopyPreferredLanguages()) };
+    languages.get(0).map(|language| language.to_string())
+}
@@ -0,0 +1,78 @@
+//! Auto-hiding idle segments
+//!
+//! Segments listed in `auto_hide` drop out of the tray icon once their reading
+//! has stayed under an idle threshold for `auto_hide_cycles` updates in a row,
+//! and come back on the first update with activity. The network arrows are the
+//! usual candidate: most of the time nothing is transferring. Hidden segments
+//! keep being sampled so activity is noticed, and at least one segment always
+//! stays so the icon never disappears.
+
+use crate::tray_render::SegmentKind;
+
+pub const DEFAULT_CYCLES: u32 = 5;
+pub const MAX_CYCLES: u64 = 600;
+
+/// CPU, memory and GPU segments are idle below this percentage.
+const IDLE_PERCENT: f32 = 5.0;
+/// The network segment is idle while both directions are below this rate.
+const IDLE_NET_BPS: f64 = 10_000.0;
+
+/// Readings the idle check looks at; `None` for unavailable ones, which never
+/// count as idle.
+#[derive(Clone, Copy, Default)]
+pub struct Activity {
+    pub cpu: Option<f32>,
+    pub mem: Option<f32>,
+    pub gpu: Option<f32>,
+    pub net: Option<(f64, f64)>,
+}
+
+impl Activity {
+    fn is_idle(&self, kind: SegmentKind) -> bool {
+        let percent_idle = |value: Option<f32>| value.is_some_and(|v| v < IDLE_PERCENT);
+        match kind {
+            SegmentKind::Cpu => percent_idle(self.cpu),
+            SegmentKind::Memory => percent_idle(self.mem),
+            SegmentKind::Gpu => percent_idle(self.gpu),
+            SegmentKind::Network => self
+                .net
+                .is_some_and(|(down, up)| down < IDLE_NET_BPS && up < IDLE_NET_BPS),
+        }
+    }
+}
+
+/// Owned by the monitoring loop: consecutive idle updates per segment.
+#[derive(Default)]
+pub struct IdleSegments {
+    idle_updates: [u32; SegmentKind::ALL.len()],
+}
+
+impl IdleSegments {
+    pub fn observe(&mut self, activity: &Activity) {
+        for kind in SegmentKind::ALL {
+            let count = &mut self.idle_updates[kind as usize];
+            *count = if activity.is_idle(kind) {
+                count.saturating_add(1)
+            } else {
+                0
+            };
+        }
+    }
+
+    /// `visible` without the `auto_hide` segments that have been idle for
+    /// `cycles` updates, keeping the first one if all of them would go.
+    pub fn filter(
+        &self,
+        visible: Vec<SegmentKind>,
+        auto_hide: &[SegmentKind],
+        cycles: u32,
+    ) -> Vec<SegmentKind> {
+        let hidden = |kind: &SegmentKind| {
+            auto_hide.contains(kind) && self.idle_updates[*kind as usize] >= cycles
+        };
+        if visible.iter().all(hidden) {
+            return visible.into_iter().take(1).collect();
+        }
+        visible.into_iter().filter(|kind| !hidden(kind)).collect()
+    }
+}
@@ -0,0 +1,63 @@
+//! Eased transitions between readings
+//!
+//! At a 1 s cadence a busy CPU can jump from 10% to 80% between two frames.
+//! With `interpolate_values` on, a redraw that moves the CPU, memory, GPU or
+//! network values draws [`SUB_FRAMES`] frames a short interval apart instead,
+//! easing out from the values last shown to the new reading, which is always
+//! the last frame. Only the tray icon is eased: alerts, history, the menu and
+//! the other outputs see the readings as sampled. The extra frames are only
+//! drawn on ticks where something changed, so a steady icon costs nothing.
+
+use std::time::Duration;
+
+/// Frames drawn per changed tick, the last being the reading itself.
+pub const SUB_FRAMES: u32 = 3;
+/// Longest gap between frames, so the transition ends well within a tick.
+const MAX_SUB_FRAME_INTERVAL: Duration = Duration::from_millis(80);
+
+/// The values a frame shows.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Shown {
+    pub cpu: f32,
+    pub mem: f32,
+    pub gpu: f32,
+    /// Bytes per second
+    pub down: f64,
+    pub up: f64,
+}
+
+impl Shown {
+    fn towards(self, to: Shown, t: f32) -> Shown {
+        let mix = |from: f32, to: f32| from + (to - from) * t;
+        let mix_rate = |from: f64, to: f64| from + (to - from) * t as f64;
+        Shown {
+            cpu: mix(self.cpu, to.cpu),
+            mem: mix(self.mem, to.mem),
+            gpu: mix(self.gpu, to.gpu),
+            down: mix_rate(self.down, to.down),
+            up: mix_rate(self.up, to.up),
+        }
+    }
+}
+
+/// Cubic ease-out: fast at first, settling onto the reading.
+pub(crate) fn ease_out(t: f32) -> f32 {
+    1.0 - (1.0 - t.clamp(0.0, 1.0)).powi(3)
+}
+
+/// Values of each frame from `from` to `to`; just `to` for a single frame.
+pub fn frames(from: Shown, to: Shown, count: u32) -> Vec<Shown> {
+    let count = count.max(1);
+    (1..=count)
+        .map(|frame| match frame {
+            frame if frame == count => to,
+            frame => from.towards(to, ease_out(frame as f32 / count as f32)),
+        })
+        .collect()
+}
+
+/// Gap between frames for a tick `period` long, leaving most of the tick
+/// before the next reading arrives.
+pub fn sub_frame_interval(period: Duration) -> Duration {
+    (period / (SUB_FRAMES * 2)).min(MAX_SUB_FRAME_INTERVAL)
+}
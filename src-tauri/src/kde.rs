@@ -0,0 +1,97 @@
+//! KDE Plasma panel colors (Linux)
+//!
+//! Plasma doesn't publish its panel colors through gsettings, and the portal's
+//! `color-scheme` only describes applications: "Breeze Twilight" pairs light
+//! windows with a dark panel. So on Plasma the panel's text color is taken from
+//! its own config files instead:
+//!
+//! - `plasmarc` `[Theme] name`: a dark or light Plasma style decides
+//! - otherwise (the default style follows the color scheme) the color scheme's
+//!   `ForegroundNormal` from `kdeglobals`, `[Colors:Header]` where present
+//!   (Plasma 5.23+), else `[Colors:Window]`, used as the exact text color
+
+use std::path::PathBuf;
+
+use crate::tray_render::Rgb;
+
+const WHITE: Rgb = (255, 255, 255);
+const BLACK: Rgb = (0, 0, 0);
+
+/// The panel text color, or `None` outside Plasma or when nothing says.
+pub fn panel_foreground() -> Option<Rgb> {
+    let desktop = std::env::var("XDG_CURRENT_DESKTOP").ok()?;
+    if !desktop.to_ascii_uppercase().contains("KDE") {
+        return None;
+    }
+    let dir = config_dir()?;
+    let read = |name: &str| std::fs::read_to_string(dir.join(name)).ok();
+    if let Some(dark) = read("plasmarc").as_deref().and_then(parse_plasma_theme) {
+        return Some(if dark { WHITE } else { BLACK });
+    }
+    read("kdeglobals")
+        .as_deref()
+        .and_then(parse_color_scheme_foreground)
+}
+
+fn config_dir() -> Option<PathBuf> {
+    match std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")),
+    }
+}
+
+/// Whether the Plasma style in `plasmarc` is dark: `Some(true)` for
+/// "breeze-dark" and the like, `Some(false)` for light ones, `None` for the
+/// default style, which follows the color scheme.
+pub(crate) fn parse_plasma_theme(plasmarc: &str) -> Option<bool> {
+    let name = value(plasmarc, "Theme", "name")?.to_ascii_lowercase();
+    if name.contains("dark") {
+        Some(true)
+    } else if name.contains("light") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// The color scheme's normal text color for the panel.
+pub(crate) fn parse_color_scheme_foreground(kdeglobals: &str) -> Option<Rgb> {
+    ["Colors:Header", "Colors:Window"]
+        .into_iter()
+        .find_map(|group| value(kdeglobals, group, "ForegroundNormal").and_then(parse_rgb))
+}
+
+/// "252,252,252", with an optional alpha that is ignored.
+fn parse_rgb(value: &str) -> Option<Rgb> {
+    let mut channels = value.split(',').map(|channel| channel.trim().parse::<u8>());
+    let rgb = (
+        channels.next()?.ok()?,
+        channels.next()?.ok()?,
+        channels.next()?.ok()?,
+    );
+    match channels.next() {
+        None | Some(Ok(_)) => Some(rgb),
+        Some(Err(_)) => None,
+    }
+}
+
+/// `key` in `[group]` of a KConfig file; keys may carry a locale or flags
+/// ("name[de]", "name[$e]"), which don't count as the plain key.
+fn value<'a>(contents: &'a str, group: &str, key: &str) -> Option<&'a str> {
+    let mut in_group = false;
+    for line in contents.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_group = name == group;
+            continue;
+        }
+        if !in_group {
+            continue;
+        }
+        if let Some((name, value)) = line.split_once('=') {
+            if name.trim() == key {
+                return Some(value.trim());
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,165 @@
+//! Network latency segment
+//!
+//! Pings a host once per tick with the system `ping` command, which needs no
+//! raw-socket privileges, and reports the round-trip time. Each probe runs on
+//! its own thread with a hard timeout, so an unreachable host never stalls the
+//! monitoring loop: a tick that finds the previous probe still in flight just
+//! shows the last result again.
+//!
+//! Without a configured host the default gateway is pinged (read from
+//! `/proc/net/route` on Linux, `route -n get default` on macOS), falling back to
+//! [`FALLBACK_HOST`] when there is none.
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+pub const FALLBACK_HOST: &str = "1.1.1.1";
+
+/// Seconds `ping` waits for a reply before the probe counts as timed out.
+const PROBE_TIMEOUT_SECS: u32 = 2;
+
+/// Round-trip times jitter by a few ms every probe; smaller changes keep the
+/// shown value so the icon isn't redrawn on every tick.
+pub const HYSTERESIS_MS: f32 = 5.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Latency {
+    /// Round-trip time in milliseconds
+    Reply(f32),
+    /// No reply within the probe timeout, or the host could not be resolved
+    Timeout,
+}
+
+#[derive(Default)]
+struct Probe {
+    in_flight: bool,
+    last: Option<Latency>,
+}
+
+/// Owned by the monitoring loop while the segment is shown.
+#[derive(Default)]
+pub struct Pinger {
+    probe: Arc<Mutex<Probe>>,
+}
+
+impl Pinger {
+    /// Starts a probe of `host` (the default gateway when empty) unless one is
+    /// still running, and returns the latest finished result. `None` until the
+    /// first probe completes.
+    pub fn sample(&mut self, host: &str) -> Option<Latency> {
+        let mut probe = self.probe.lock().unwrap_or_else(|e| e.into_inner());
+        if !probe.in_flight {
+            probe.in_flight = true;
+            let shared = Arc::clone(&self.probe);
+            let host = host.to_string();
+            thread::spawn(move || {
+                let result = ping(&host);
+                let mut probe = shared.lock().unwrap_or_else(|e| e.into_inner());
+                probe.last = Some(result);
+                probe.in_flight = false;
+            });
+        }
+        probe.last
+    }
+}
+
+fn ping(host: &str) -> Latency {
+    let host = if host.is_empty() {
+        default_gateway().unwrap_or_else(|| FALLBACK_HOST.to_string())
+    } else {
+        host.to_string()
+    };
+    let timeout = PROBE_TIMEOUT_SECS.to_string();
+    // -W is the reply timeout on Linux; macOS uses -t for the overall timeout
+    #[cfg(target_os = "macos")]
+    let args = ["-n", "-c", "1", "-t", &timeout, &host];
+    #[cfg(not(target_os = "macos"))]
+    let args = ["-n", "-c", "1", "-W", &timeout, &host];
+
+    crate::command_stdout("ping", &args)
+        .as_deref()
+        .and_then(parse_ping_time)
+        .map_or(Latency::Timeout, Latency::Reply)
+}
+
+/// Round-trip time from the reply line of `ping` output, e.g. `time=12.3 ms`.
+pub(crate) fn parse_ping_time(output: &str) -> Option<f32> {
+    let (_, rest) = output.split_once("time=")?;
+    let end = rest
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(rest.len());
+    rest[..end].parse().ok()
+}
+
+#[cfg(target_os = "linux")]
+fn default_gateway() -> Option<String> {
+    parse_proc_net_route(&std::fs::read_to_string("/proc/net/route").ok()?)
+}
+
+#[cfg(target_os = "macos")]
+fn default_gateway() -> Option<String> {
+    parse_route_get(&crate::command_stdout("route", &["-n", "get", "default"])?)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn default_gateway() -> Option<String> {
+    None
+}
+
+/// Gateway of the first default route in `/proc/net/route`, whose addresses are
+/// little-endian hex, e.g. `0101A8C0` for 192.168.1.1.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub(crate) fn parse_proc_net_route(table: &str) -> Option<String> {
+    table.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (destination, gateway) = (fields.get(1)?, fields.get(2)?);
+        if *destination != "00000000" {
+            return None;
+        }
+        let gateway = u32::from_str_radix(gateway, 16).ok()?;
+        (gateway != 0).then(|| std::net::Ipv4Addr::from(gateway.to_le_bytes()).to_string())
+    })
+}
+
+/// The `gateway:` line of `route -n get default`.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn parse_route_get(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let gateway = line.trim().strip_prefix("gateway:")?.trim();
+        (!gateway.is_empty()).then(|| gateway.to_string())
+    })
+}
+
+/// Whether `new` should replace the `shown` result: on the first result, when
+/// replies stop or resume, when crossing `alert_ms`, or on a change of at
+/// least [`HYSTERESIS_MS`].
+pub(crate) fn should_show(shown: Option<Latency>, new: Latency, alert_ms: f32) -> bool {
+    match (shown, new) {
+        (Some(Latency::Reply(prev)), Latency::Reply(ms)) => {
+            (prev >= alert_ms) != (ms >= alert_ms) || (ms - prev).abs() >= HYSTERESIS_MS
+        }
+        (Some(Latency::Timeout), Latency::Timeout) => false,
+        _ => true,
+    }
+}
+
+/// "23ms", "<1ms", or "1.2s" once a reply takes a second or more.
+pub fn format_latency(ms: f32) -> String {
+    if ms < 1.0 {
+        "<1ms".to_string()
+    } else if ms < 999.5 {
+        format!("{ms:.0}ms")
+    } else {
+        format!("{:.1}s", ms / 1000.0)
+    }
+}
+
+/// Hostnames, IPv4 and IPv6 addresses. Anything else, notably a leading `-`
+/// that `ping` would read as an option, is rejected.
+pub(crate) fn is_valid_host(host: &str) -> bool {
+    host.len() <= 253
+        && !host.starts_with('-')
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '%' | '_'))
+}
@@ -1,28 +1,85 @@
-mod gpu;
-pub mod tray_render;
+mod alerts;
+mod charts;
+mod config;
+mod containers;
+mod copy_stats;
+mod dashboard;
+mod data_cap;
+#[cfg(target_os = "linux")]
+mod dbus;
+mod details;
+mod diagnostics;
+pub mod frame_dump;
+mod gpu_processes;
+mod headless;
+mod history;
+mod http;
+mod i18n;
+mod idle;
+mod interpolation;
+#[cfg(target_os = "linux")]
+mod kde;
+mod latency;
+mod logging;
+mod metrics;
+mod minute_history;
+mod net_processes;
+mod net_totals;
+mod power;
+mod preferences;
+mod pressure;
+mod processes;
+mod profiles;
+mod quiet_hours;
+mod remote;
+mod sampling;
+mod save_icon;
+mod screen_lock;
+mod smoothing;
+mod sound;
+mod status_server;
+mod system_monitor;
+mod task_counts;
+#[cfg(target_os = "linux")]
+mod tray_host;
+mod updates;
+mod vpn;
+mod watchdog;
+mod watched;
+mod waybar;
+mod wifi;
+
+pub use better_resource_monitor_render as tray_render;
 
 // std
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
 // external crates
+use better_resource_monitor_metrics::{cgroup, cpu, gpu, memory, sampler};
 use font_kit::family_name::FamilyName;
 use font_kit::handle::Handle;
 use font_kit::properties::{Properties, Weight};
 use font_kit::source::SystemSource;
 use rusttype::Font;
-use serde_json::json;
-use sysinfo::{Networks, System};
+use sysinfo::System;
 use tauri::{
+    async_runtime::JoinHandle,
     image::Image,
-    menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem},
-    tray::TrayIconBuilder,
-    AppHandle,
+    menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Manager, RunEvent,
 };
-use tauri_plugin_store::StoreExt;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
 
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use std::sync::atomic::AtomicU32;
 #[cfg(target_os = "macos")]
 use tauri::ActivationPolicy;
 
@@ -30,10 +87,50 @@ use tauri::ActivationPolicy;
 use tauri_plugin_autostart::{MacosLauncher, ManagerExt};
 
 // internal
-use gpu::GpuSampler;
+use alerts::{AlertChange, AlertLog, AlertMenu, AlertMetric, SharedAlertLog};
+use charts::ChartHistory;
+use config::{
+    save_setting, ClickAction, Config, CpuDisplay, IconColor, MemDisplay, NetUnit,
+    ALERT_THRESHOLD_PRESETS,
+};
+use containers::{ContainerMenu, ContainerMonitor};
+use dashboard::{LastHidden, RecentSamples};
+use data_cap::DataUsage;
+use details::{DetailLine, DetailLines, DetailsBlock};
+use gpu_processes::{GpuProcessMenu, GpuProcessMonitor};
+use history::HistoryRecorder;
+use i18n::{tr, tr_args};
+use idle::{Activity, IdleSegments};
+use interpolation::Shown;
+use latency::{Latency, Pinger};
+use metrics::Sample;
+use minute_history::{MinuteHistory, SharedMinuteHistory};
+use net_processes::{NetworkProcessMenu, NetworkTalkers};
+use net_totals::{NetTotalsMenu, SharedNetTotals};
+use power::PowerMonitor;
+use preferences::{Capabilities, SharedCapabilities};
+use processes::ProcessMenu;
+use quiet_hours::QuietHours;
+use remote::RemoteSource;
+use sampler::SamplerRegistry;
+use sampling::AdaptiveInterval;
+use save_icon::PendingIconSave;
+use smoothing::Ema;
+use sound::AlertSounder;
+use status_server::LatestSample;
+use tray_render::{
+    format_bitrate, format_speed, AlertThresholds, CoreUsage, IconType, Layout, MemoryBreakdown,
+    MemoryPressure, Palette, SegmentKind, TextSegment,
+};
+use updates::{SharedUpdate, UpdateMenu};
+use vpn::TunnelMonitor;
+use watchdog::SharedHeartbeat;
+use wifi::WifiMonitor;
 
-#[cfg(target_os = "linux")]
-static LIGHT_ICONS: AtomicBool = AtomicBool::new(true);
+/// Panel or menu bar text color from the desktop theme as 0xRRGGBB; white
+/// until detected.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+static THEME_FOREGROUND: AtomicU32 = AtomicU32::new(0xFF_FFFF);
 
 #[cfg(target_os = "macos")]
 const APP_SIZING: tray_render::Sizing = tray_render::SIZING_MACOS;
@@ -41,26 +138,123 @@ const APP_SIZING: tray_render::Sizing = tray_render::SIZING_MACOS;
 #[cfg(not(target_os = "macos"))]
 const APP_SIZING: tray_render::Sizing = tray_render::SIZING_LINUX;
 
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "macos"))]
 const THEME_POLL_INTERVAL_SECS: u64 = 5;
 
+/// Set when something other than the monitoring loop replaced the tray
+/// icons (the stalled glyph), so the loop sends its next frames even if they
+/// match what it last sent.
+static ICONS_REPLACED: AtomicBool = AtomicBool::new(false);
+
+/// How long quitting waits for the monitoring task to finish its current tick.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Panel scale factor as `f32` bits; see `start_scale_detection_thread`.
 #[cfg(target_os = "linux")]
-fn detect_light_icons() -> bool {
-    LIGHT_ICONS.load(Relaxed)
+static PANEL_SCALE: AtomicU32 = AtomicU32::new(0x3F80_0000); // 1.0
+
+#[cfg(target_os = "linux")]
+const SCALE_POLL_INTERVAL_SECS: u64 = 5;
+
+/// Tray icon sizing for the current panel at the `text_scale` setting.
+/// `SIZING_LINUX` is drawn for a 1x panel, so HiDPI panels get it scaled up
+/// instead of a blurry upscaled bitmap.
+fn app_sizing(config: &Config) -> tray_render::Sizing {
+    #[cfg(target_os = "linux")]
+    let panel_scale = f32::from_bits(PANEL_SCALE.load(Relaxed));
+    #[cfg(not(target_os = "linux"))]
+    let panel_scale = 1.0;
+    APP_SIZING.scaled(panel_scale * config.text_scale())
 }
 
+/// `GDK_SCALE` (an integer, as GTK reads it) wins over the scale factor the
+/// windowing system reports for the primary monitor. Nonsense values fall back to 1x.
 #[cfg(target_os = "linux")]
+pub(crate) fn effective_panel_scale(gdk_scale: Option<&str>, monitor_scale: Option<f64>) -> f32 {
+    let scale = gdk_scale
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .filter(|&v| v >= 1)
+        .map(|v| v as f64)
+        .or(monitor_scale.filter(|v| v.is_finite() && *v > 0.0))
+        .unwrap_or(1.0);
+    scale.clamp(1.0, 4.0) as f32
+}
+
+#[cfg(target_os = "linux")]
+fn detect_panel_scale(app: &AppHandle) -> f32 {
+    let monitor_scale = app
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .map(|monitor| monitor.scale_factor());
+    effective_panel_scale(std::env::var("GDK_SCALE").ok().as_deref(), monitor_scale)
+}
+
+/// Follows the panel scale as monitors are plugged in or display settings change.
+/// The thread only keeps time: GTK may only be used from the main thread, so
+/// each poll asks the monitor from there.
+#[cfg(target_os = "linux")]
+fn start_scale_detection_thread(app: AppHandle) {
+    PANEL_SCALE.store(detect_panel_scale(&app).to_bits(), Relaxed);
+
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_secs(SCALE_POLL_INTERVAL_SECS));
+        let main_app = app.clone();
+        let polled = app.run_on_main_thread(move || {
+            PANEL_SCALE.store(detect_panel_scale(&main_app).to_bits(), Relaxed);
+        });
+        // The event loop has ended
+        if polled.is_err() {
+            break;
+        }
+    });
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn theme_foreground() -> tray_render::Rgb {
+    let packed = THEME_FOREGROUND.load(Relaxed);
+    ((packed >> 16) as u8, (packed >> 8) as u8, packed as u8)
+}
+
+/// Whether the panel wants light text, i.e. icons drawn in white.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn detect_light_icons() -> bool {
+    tray_render::contrast_color(theme_foreground()) == (0, 0, 0)
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn store_theme_foreground((r, g, b): tray_render::Rgb) {
+    THEME_FOREGROUND.store(u32::from_be_bytes([0, r, g, b]), Relaxed);
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
 fn start_theme_detection_thread() {
     // Initialize with actual value before spawning polling thread to avoid race condition
-    LIGHT_ICONS.store(detect_light_icons_impl(), Relaxed);
+    store_theme_foreground(detect_theme_foreground());
 
     thread::spawn(|| loop {
         thread::sleep(Duration::from_secs(THEME_POLL_INTERVAL_SECS));
-        let detected = detect_light_icons_impl();
-        LIGHT_ICONS.store(detected, Relaxed);
+        store_theme_foreground(detect_theme_foreground());
     });
 }
 
+/// Plasma's own panel colors where they can be read, otherwise white or black
+/// for a dark or light theme.
+#[cfg(target_os = "linux")]
+fn detect_theme_foreground() -> tray_render::Rgb {
+    kde::panel_foreground()
+        .unwrap_or_else(|| Palette::for_theme(detect_light_icons_impl()).foreground)
+}
+
+/// White in dark mode, black in light mode. Only visible while the icon
+/// isn't a template image (alerts, custom colors); macOS tints templates itself.
+#[cfg(target_os = "macos")]
+fn detect_theme_foreground() -> tray_render::Rgb {
+    let dark = command_stdout("defaults", &["read", "-g", "AppleInterfaceStyle"])
+        .is_some_and(|style| style.trim() == "Dark");
+    Palette::for_theme(dark).foreground
+}
+
 #[cfg(target_os = "linux")]
 fn ensure_display_available() -> Result<(), String> {
     let has_x11 = std::env::var("DISPLAY").is_ok();
@@ -73,91 +267,271 @@ fn ensure_display_available() -> Result<(), String> {
     }
 }
 
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Opens a URL or path the way double-clicking it would.
+pub(crate) fn open_with_default_app(target: &std::ffi::OsStr) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    let program = "open";
+    #[cfg(not(target_os = "macos"))]
+    let program = "xdg-open";
+    let mut child = std::process::Command::new(program)
+        .arg(target)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run {program}: {e}"))?;
+    // Reap it in the background so it doesn't linger as a zombie
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Parses `gdbus` output for the portal `color-scheme` setting, e.g. `(<<uint32 1>>,)`.
+/// Returns `Some(true)` for prefer-dark, `Some(false)` for prefer-light and `None`
+/// when the desktop expresses no preference.
+#[cfg(target_os = "linux")]
+pub(crate) fn parse_portal_color_scheme(output: &str) -> Option<bool> {
+    let value = output.split("uint32").nth(1)?;
+    let digits: String = value
+        .trim_start()
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    match digits.parse::<u32>().ok()? {
+        1 => Some(true),
+        2 => Some(false),
+        _ => None,
+    }
+}
+
+/// Parses `gsettings get org.gnome.desktop.interface color-scheme` output.
+#[cfg(target_os = "linux")]
+pub(crate) fn parse_gsettings_color_scheme(output: &str) -> Option<bool> {
+    match output.trim().trim_matches('\'') {
+        "prefer-dark" => Some(true),
+        "prefer-light" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parses an `xfconf-query` boolean property.
+#[cfg(target_os = "linux")]
+pub(crate) fn parse_xfconf_bool(output: &str) -> Option<bool> {
+    match output.trim() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether a GTK theme draws a dark panel: "-dark" variants, plus themes
+/// like Ubuntu MATE's Ambiant that pair light windows with a dark panel.
+#[cfg(target_os = "linux")]
+pub(crate) fn theme_has_dark_panel(theme: &str) -> bool {
+    const DARK_PANEL_THEMES: [&str; 4] = ["dark", "ambiance", "ambiant", "blackmate"];
+    let theme = theme.trim().trim_matches('\'').to_lowercase();
+    DARK_PANEL_THEMES.iter().any(|name| theme.contains(name))
+}
+
+/// Xfce and MATE keep their theme in their own settings stores, which the
+/// portal and the GNOME schema don't reflect.
+#[cfg(target_os = "linux")]
+fn detect_panel_theme(desktop: &str) -> Option<bool> {
+    if desktop.contains("xfce") {
+        // The panel's "Dark mode" switch (Xfce 4.14+) overrides the theme
+        let dark_mode = command_stdout(
+            "xfconf-query",
+            &["-c", "xfce4-panel", "-p", "/panels/dark-mode"],
+        );
+        if dark_mode.as_deref().and_then(parse_xfconf_bool) == Some(true) {
+            return Some(true);
+        }
+        return command_stdout("xfconf-query", &["-c", "xsettings", "-p", "/Net/ThemeName"])
+            .map(|theme| theme_has_dark_panel(&theme));
+    }
+    if desktop.contains("mate") {
+        return command_stdout("gsettings", &["get", "org.mate.interface", "gtk-theme"])
+            .map(|theme| theme_has_dark_panel(&theme));
+    }
+    None
+}
+
 #[cfg(target_os = "linux")]
 fn detect_light_icons_impl() -> bool {
-    // Try gsettings (GNOME/GTK)
-    if let Ok(output) = std::process::Command::new("gsettings")
-        .args([
-            "get",
-            "org.gnome.desktop.interface",
-            "gtk-application-prefer-dark-theme",
-        ])
-        .output()
+    let desktop = std::env::var("XDG_CURRENT_DESKTOP")
+        .unwrap_or_default()
+        .to_lowercase();
+    if let Some(dark_panel) = detect_panel_theme(&desktop) {
+        return dark_panel;
+    }
+
+    // XDG desktop portal: works across GNOME, KDE and most portal-backed desktops
+    if let Some(prefers_dark) = command_stdout(
+        "gdbus",
+        &[
+            "call",
+            "--session",
+            "--timeout",
+            "1",
+            "--dest",
+            "org.freedesktop.portal.Desktop",
+            "--object-path",
+            "/org/freedesktop/portal/desktop",
+            "--method",
+            "org.freedesktop.portal.Settings.Read",
+            "org.freedesktop.appearance",
+            "color-scheme",
+        ],
+    )
+    .as_deref()
+    .and_then(parse_portal_color_scheme)
     {
-        let result = String::from_utf8_lossy(&output.stdout);
-        if result.contains("true") {
-            return true; // Dark theme → light (white) icons
-        }
+        return prefers_dark; // Dark theme → light (white) icons
+    }
+
+    // GNOME 42+ without a running portal
+    if let Some(prefers_dark) = command_stdout(
+        "gsettings",
+        &["get", "org.gnome.desktop.interface", "color-scheme"],
+    )
+    .as_deref()
+    .and_then(parse_gsettings_color_scheme)
+    {
+        return prefers_dark;
     }
 
-    // Check XDG_CURRENT_DESKTOP for common light-themed DEs
-    if let Ok(desktop) = std::env::var("XDG_CURRENT_DESKTOP") {
-        let lower = desktop.to_lowercase();
-        if lower.contains("xfce") || lower.contains("elementary") || lower.contains("kde") {
-            return false; // Often light themes → dark (black) icons
+    // Older GTK desktops only encode the preference in the theme name
+    if let Some(theme) = command_stdout(
+        "gsettings",
+        &["get", "org.gnome.desktop.interface", "gtk-theme"],
+    ) {
+        if theme_has_dark_panel(&theme) {
+            return true;
         }
     }
 
+    // Nothing readable: guess from desktops that usually ship light panels
+    if ["xfce", "elementary", "kde"]
+        .iter()
+        .any(|name| desktop.contains(name))
+    {
+        return false; // Often light themes → dark (black) icons
+    }
+
     // Default: most Linux panels are dark → use light (white) icons
     true
 }
 
-const SETTINGS_FILE: &str = "settings.json";
-
 mod menu_id {
-    pub const AUTOSTART: &str = "autostart";
-    pub const SHOW_CPU: &str = "show_cpu";
-    pub const SHOW_MEM: &str = "show_mem";
-    pub const SHOW_GPU: &str = "show_gpu";
-    pub const SHOW_NET: &str = "show_net";
-    pub const SHOW_ALERTS: &str = "show_alerts";
+    use crate::config::key;
+
+    pub const AUTOSTART: &str = key::AUTOSTART;
+    pub const SHOW_CPU: &str = key::SHOW_CPU;
+    pub const SHOW_MEM: &str = key::SHOW_MEM;
+    pub const SHOW_GPU: &str = key::SHOW_GPU;
+    pub const SHOW_GPU_MEMORY: &str = key::SHOW_GPU_MEMORY;
+    pub const CPU_LOAD_AVERAGE: &str = "cpu_load_average";
+    pub const CPU_USER_SYSTEM: &str = "cpu_user_system";
+    pub const CPU_HEATMAP: &str = "cpu_heatmap";
+    pub const MEM_USED: &str = "mem_used";
+    pub const MEM_PRESSURE: &str = "mem_pressure";
+    pub const MEM_BREAKDOWN: &str = "mem_breakdown";
+    pub const SHOW_CPU_FREQUENCY: &str = key::SHOW_CPU_FREQUENCY;
+    pub const SHOW_CPU_POWER: &str = key::SHOW_CPU_POWER;
+    pub const SHOW_ANE_POWER: &str = key::SHOW_ANE_POWER;
+    pub const SHOW_GPU_TEMPERATURE: &str = key::SHOW_GPU_TEMPERATURE;
+    pub const SHOW_GPU_CODEC: &str = key::SHOW_GPU_CODEC;
+    pub const REDUCE_ON_BATTERY: &str = key::REDUCE_ON_BATTERY;
+    pub const SHOW_NET: &str = key::SHOW_NET;
+    pub const SHOW_LATENCY: &str = key::SHOW_LATENCY;
+    pub const SHOW_WIFI: &str = key::SHOW_WIFI;
+    pub const SHOW_VPN: &str = key::SHOW_VPN;
+    pub const SHOW_PRESSURE: &str = key::SHOW_PRESSURE;
+    pub const SHOW_TASK_COUNTS: &str = key::SHOW_TASK_COUNTS;
+    pub const SHOW_DETAILS: &str = key::SHOW_DETAILS;
+    pub const SHOW_ALERTS: &str = key::SHOW_ALERTS;
+    pub const HISTORY: &str = key::HISTORY_ENABLED;
+    pub const SHOW_PROCESSES: &str = key::SHOW_PROCESSES;
+    pub const SHOW_NETWORK_PROCESSES: &str = key::SHOW_NETWORK_PROCESSES;
+    pub const SHOW_GPU_PROCESSES: &str = key::SHOW_GPU_PROCESSES;
+    pub const SHOW_CONTAINERS: &str = key::SHOW_CONTAINERS;
+    pub const PREFERENCES: &str = "preferences";
+    pub const SYSTEM_MONITOR: &str = "system_monitor";
     pub const QUIT: &str = "quit";
 }
 
 const TRAY_ID: &str = "main";
 
-fn load_settings(app: &AppHandle) -> (bool, bool, bool, bool, bool, bool) {
-    let store = match app.store(SETTINGS_FILE) {
-        Ok(s) => Some(s),
-        Err(e) => {
-            eprintln!("Failed to load settings store: {e}");
-            None
-        }
-    };
+#[cfg(target_os = "macos")]
+const SYSTEM_MONITOR_LABEL: &str = "Open Activity Monitor";
+#[cfg(not(target_os = "macos"))]
+const SYSTEM_MONITOR_LABEL: &str = "Open System Monitor";
+const TRAY_TITLE: &str = "System Monitor";
+
+/// libappindicator has no tooltip support, so Linux skips the extra sampling
+/// the dynamic tooltip needs.
+const TOOLTIP_SUPPORTED: bool = cfg!(not(target_os = "linux"));
+
+/// Metrics with a configurable alert threshold, as (menu label, settings key).
+const THRESHOLD_METRICS: [(&str, &str); 3] = [
+    ("CPU", config::key::CPU_ALERT_THRESHOLD),
+    ("Memory", config::key::MEM_ALERT_THRESHOLD),
+    ("GPU", config::key::GPU_ALERT_THRESHOLD),
+];
+
+/// Threshold preset items are identified as `<settings key>:<percent>`.
+fn threshold_menu_id(key: &str, preset: u32) -> String {
+    format!("{key}:{preset}")
+}
 
-    let get_bool = |key: &str, default: bool| -> bool {
-        store
-            .as_ref()
-            .and_then(|s| s.get(key))
-            .and_then(|v| v.as_bool())
-            .unwrap_or(default)
-    };
+fn parse_threshold_menu_id(id: &str) -> Option<(&str, u32)> {
+    let (key, value) = id.split_once(':')?;
+    if !THRESHOLD_METRICS.iter().any(|(_, k)| *k == key) {
+        return None;
+    }
+    Some((key, value.parse().ok()?))
+}
 
-    (
-        get_bool("show_cpu", true),
-        get_bool("show_mem", true),
-        get_bool("show_gpu", true),
-        get_bool("show_net", true),
-        get_bool("show_alerts", true),
-        get_bool(menu_id::AUTOSTART, false),
-    )
+fn layout_menu_id(layout: Layout) -> String {
+    format!("{}:{}", config::key::LAYOUT, layout.as_str())
 }
 
-fn save_setting(app: &AppHandle, key: &str, value: bool) {
-    if let Ok(store) = app.store(SETTINGS_FILE) {
-        store.set(key, json!(value));
-        if let Err(e) = store.save() {
-            eprintln!("Failed to save setting {key}: {e}");
-        }
+fn parse_layout_menu_id(id: &str) -> Option<Layout> {
+    let (key, value) = id.split_once(':')?;
+    if key != config::key::LAYOUT {
+        return None;
     }
+    Layout::parse(value)
+}
+
+fn icon_color_menu_id(color: IconColor) -> String {
+    format!("{}:{}", config::key::ICON_COLOR, color.as_str())
 }
 
-const UPDATE_INTERVAL_MS: u64 = 2000;
-const CPU_STABILIZE_MS: u64 = 200;
+fn parse_icon_color_menu_id(id: &str) -> Option<IconColor> {
+    let (key, value) = id.split_once(':')?;
+    if key != config::key::ICON_COLOR {
+        return None;
+    }
+    IconColor::parse(value)
+}
 
 /// Minimum change threshold to trigger icon update (prevents compositor leak on Linux)
 const HYSTERESIS_THRESHOLD: f32 = 2.0;
 
+/// Load average is shown with one decimal, so redraw on any visible change.
+const LOAD_HYSTERESIS: f32 = 0.1;
+
 /// Minimum network speed change (bytes/sec) to trigger an update.
 /// Reduces tray icon churn that can accumulate compositor resources on Linux.
 const NET_HYSTERESIS_BPS: f64 = 50_000.0;
@@ -167,67 +541,458 @@ fn should_update(prev: f32, new: f32, threshold: f32) -> bool {
     (new - prev).abs() >= threshold
 }
 
-/// Get update interval from environment variable or use default.
-/// Set SILICON_UPDATE_INTERVAL to override the default cadence.
-fn get_update_interval_ms() -> u64 {
+/// Like [`should_update`] for a network rate: the change must reach
+/// `NET_HYSTERESIS_BPS` and `percent` of the rate shown, so a rate hovering
+/// around 1.45 MB/s doesn't flip between "1.4M" and "1.5M" every tick.
+fn should_update_rate(prev: f64, new: f64, percent: u32) -> bool {
+    let diff = (new - prev).abs();
+    diff >= NET_HYSTERESIS_BPS && diff >= prev.abs() * percent as f64 / 100.0
+}
+
+/// Network redraws held back by `net_hysteresis`. A change too small to
+/// draw at once is drawn after `net_hysteresis_ticks` ticks in a row, so a
+/// lasting one still shows.
+#[derive(Default)]
+struct NetHysteresis {
+    held: u32,
+}
+
+impl NetHysteresis {
+    /// Whether to draw `new` (down, up) over the `shown` rates.
+    fn changed(&mut self, shown: (f64, f64), new: (f64, f64), percent: u32, ticks: u32) -> bool {
+        let rates = [(shown.0, new.0), (shown.1, new.1)];
+        let moved = |percent| {
+            rates
+                .iter()
+                .any(|&(shown, new)| should_update_rate(shown, new, percent))
+        };
+        if moved(percent) {
+            self.held = 0;
+            return true;
+        }
+        let small_change = moved(0);
+        self.held = if small_change { self.held + 1 } else { 0 };
+        if self.held >= ticks {
+            self.held = 0;
+            return true;
+        }
+        false
+    }
+}
+
+/// Memory or GPU memory in use for the tray segments: "850M", "12.4G", or
+/// "128G" from 100 GiB.
+fn format_bytes(bytes: u64) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+    const GIB: f64 = MIB * 1024.0;
+
+    let gib = bytes as f64 / GIB;
+    if gib >= 99.95 {
+        format!("{gib:.0}G")
+    } else if gib >= 0.9995 {
+        format!("{gib:.1}G")
+    } else {
+        format!("{:.0}M", bytes as f64 / MIB)
+    }
+}
+
+/// CPU power for the tray segment: "4.3W" below 10 W, "18W" above.
+fn format_watts(watts: f32) -> String {
+    if watts < 9.95 {
+        format!("{watts:.1}W")
+    } else {
+        format!("{watts:.0}W")
+    }
+}
+
+/// Visible built-in segments in the configured order.
+fn visible_segments(
+    order: [SegmentKind; 4],
+    show_cpu: bool,
+    show_mem: bool,
+    show_gpu: bool,
+    show_net: bool,
+) -> Vec<SegmentKind> {
+    order
+        .into_iter()
+        .filter(|kind| match kind {
+            SegmentKind::Memory => show_mem,
+            SegmentKind::Cpu => show_cpu,
+            SegmentKind::Gpu => show_gpu,
+            SegmentKind::Network => show_net,
+        })
+        .collect()
+}
+
+/// Replaces every tray icon with the stalled glyph; the next frame the
+/// monitoring loop draws puts the readings back.
+fn show_stalled_icon(app: &AppHandle, config: &Config) {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    let palette = config.palette_on(theme_foreground());
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    let palette = config.palette(false);
+    let (pixels, width, height) = tray_render::render_stalled_icon(app_sizing(config), palette);
+    ICONS_REPLACED.store(true, Relaxed);
+    for tray in (0..).map_while(|index| app.tray_by_id(&tray_id(index))) {
+        #[cfg(target_os = "macos")]
+        {
+            // Not a template, so the alert color shows
+            let icon = tray_icon::Icon::from_rgba(pixels.clone(), width, height)
+                .expect("Failed to create icon");
+            let _ = tray.with_inner_tray_icon(move |inner| {
+                inner.set_icon_with_as_template(Some(icon), false)
+            });
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        {
+            let _ = tray.set_icon(Some(Image::new_owned(pixels.clone(), width, height)));
+        }
+    }
+}
+
+/// ID of the tray icon at `index`; the first one carries the tooltip and the
+/// extra segments.
+fn tray_id(index: usize) -> String {
+    match index {
+        0 => TRAY_ID.to_string(),
+        _ => format!("{TRAY_ID}-{}", index + 1),
+    }
+}
+
+/// Splits the visible segments between the tray icons configured in
+/// `tray_icons`, keeping their order. Segments no icon lists go on the first.
+fn icon_segments(icons: &[Vec<SegmentKind>], visible: &[SegmentKind]) -> Vec<Vec<SegmentKind>> {
+    let mut split = vec![Vec::new(); icons.len().max(1)];
+    for &kind in visible {
+        let index = icons
+            .iter()
+            .position(|segments| segments.contains(&kind))
+            .unwrap_or(0);
+        split[index].push(kind);
+    }
+    split
+}
+
+/// GPU temperature for the tray segment, e.g. "72°C".
+fn format_temperature(celsius: f32) -> String {
+    format!("{:.0}°C", celsius.max(0.0))
+}
+
+/// Average core clock for the menu, e.g. "3.20 GHz". sysinfo reports 0 when
+/// the platform doesn't expose it (e.g. some Apple Silicon machines).
+fn format_frequency(mhz: u64) -> String {
+    match mhz {
+        0 => tr("unavailable").to_string(),
+        1..=999 => format!("{mhz} MHz"),
+        _ => format!("{:.2} GHz", mhz as f64 / 1000.0),
+    }
+}
+
+/// Menu line for NVENC/NVDEC utilization.
+fn codec_status_text(encoder: f32, decoder: f32) -> String {
+    tr_args(
+        "GPU Encoder: {} · Decoder: {}",
+        &[&format!("{encoder:.0}%"), &format!("{decoder:.0}%")],
+    )
+}
+
+/// Coarse uptime for the tooltip, e.g. "3d 4h 12m" or "12m".
+fn format_uptime(secs: u64) -> String {
+    let days = secs / 86_400;
+    let hours = secs % 86_400 / 3_600;
+    let minutes = secs % 3_600 / 60;
+    match (days, hours) {
+        (0, 0) => format!("{minutes}m"),
+        (0, _) => format!("{hours}h {minutes}m"),
+        _ => format!("{days}d {hours}h {minutes}m"),
+    }
+}
+
+/// Unix timestamp moved `offset_secs` from UTC, as "YYYY-MM-DD HH:MM".
+fn format_timestamp(secs: u64, offset_secs: i64) -> String {
+    let secs = (secs as i64 + offset_secs).max(0) as u64;
+    let (y, m, d) = history::civil_from_days((secs / 86_400) as i64);
+    let hours = secs % 86_400 / 3_600;
+    let minutes = secs % 3_600 / 60;
+    format!("{y:04}-{m:02}-{d:02} {hours:02}:{minutes:02}")
+}
+
+/// Unix timestamp as "YYYY-MM-DD HH:MM UTC".
+fn format_utc_timestamp(secs: u64) -> String {
+    format!("{} UTC", format_timestamp(secs, 0))
+}
+
+/// Tooltip text: uptime, boot time in local time (`utc_offset_secs` from
+/// UTC) and one line per hidden segment.
+fn tray_tooltip(
+    uptime_secs: u64,
+    boot_time: u64,
+    utc_offset_secs: i64,
+    hidden: &[String],
+) -> String {
+    let mut lines = vec![
+        TRAY_TITLE.to_string(),
+        tr_args("Uptime: {}", &[&format_uptime(uptime_secs)]),
+        tr_args(
+            "Booted: {}",
+            &[&format_timestamp(boot_time, utc_offset_secs)],
+        ),
+    ];
+    lines.extend_from_slice(hidden);
+    lines.join("\n")
+}
+
+/// Tooltip line naming what is thermally throttled, if anything.
+fn throttling_note(cpu: bool, gpu: bool) -> Option<String> {
+    let parts = match (cpu, gpu) {
+        (false, false) => return None,
+        (true, false) => "CPU",
+        (false, true) => "GPU",
+        (true, true) => "CPU, GPU",
+    };
+    Some(tr_args("Thermal throttling: {}", &[parts]))
+}
+
+/// Get update interval from environment variable or use the configured value.
+/// Set SILICON_UPDATE_INTERVAL to override the configured cadence.
+fn get_update_interval_ms(configured: u64) -> u64 {
     std::env::var("SILICON_UPDATE_INTERVAL")
         .ok()
         .and_then(|s| s.parse().ok())
-        .unwrap_or(UPDATE_INTERVAL_MS)
+        .unwrap_or(configured)
+}
+
+/// File name of the font [`load_font`] last picked, for the diagnostics report.
+static LOADED_FONT: Mutex<String> = Mutex::new(String::new());
+
+/// The system sans-serif font, or the bundled fallback when none can be loaded.
+pub fn load_system_font() -> Font<'static> {
+    load_font(None, config::DEFAULT_FONT_WEIGHT)
+}
+
+/// The closest installed match for `family` (system sans-serif when `None` or
+/// not installed) at the given CSS weight, falling back to the bundled font.
+pub fn load_font(family: Option<&str>, weight: u32) -> Font<'static> {
+    let (font, name) = load_font_from_system(family, weight).unwrap_or_else(|e| {
+        warn!("{e}; using the bundled font");
+        (load_fallback_font(), "bundled DejaVu Sans".to_string())
+    });
+    *LOADED_FONT.lock().unwrap_or_else(|e| e.into_inner()) = name;
+    font
+}
+
+fn loaded_font() -> String {
+    LOADED_FONT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
 }
 
-pub fn load_system_font() -> Result<Font<'static>, String> {
+pub fn load_fallback_font() -> Font<'static> {
+    tray_render::fallback_font()
+}
+
+/// The font and its file name.
+fn load_font_from_system(
+    family: Option<&str>,
+    weight: u32,
+) -> Result<(Font<'static>, String), String> {
     let source = SystemSource::new();
+    let families: Vec<FamilyName> = family
+        .map(|name| FamilyName::Title(name.to_string()))
+        .into_iter()
+        .chain([FamilyName::SansSerif])
+        .collect();
 
     let handle = source
-        .select_best_match(
-            &[FamilyName::SansSerif],
-            Properties::new().weight(Weight::NORMAL),
-        )
-        .or_else(|_| source.select_best_match(&[FamilyName::SansSerif], &Properties::new()))
+        .select_best_match(&families, Properties::new().weight(Weight(weight as f32)))
+        .or_else(|_| source.select_best_match(&families, &Properties::new()))
         .map_err(|e| format!("Failed to select a system font: {e}"))?;
 
-    let font_data = match &handle {
-        Handle::Path { path, .. } => {
-            std::fs::read(path).map_err(|e| format!("Failed to read font file: {e}"))?
-        }
-        Handle::Memory { bytes, .. } => bytes.to_vec(),
+    let (font_data, name) = match &handle {
+        Handle::Path { path, .. } => (
+            std::fs::read(path).map_err(|e| format!("Failed to read font file: {e}"))?,
+            path.file_name()
+                .map_or_else(String::new, |name| name.to_string_lossy().into_owned()),
+        ),
+        Handle::Memory { bytes, .. } => (bytes.to_vec(), "in-memory font".to_string()),
     };
 
-    Font::try_from_vec(font_data).ok_or_else(|| "Error constructing font".to_string())
+    let font =
+        Font::try_from_vec(font_data).ok_or_else(|| "Error constructing font".to_string())?;
+    Ok((font, name))
+}
+
+// Rendering is centralized in the better-resource-monitor-render crate
+
+fn format_net(bytes_per_sec: f64, unit: NetUnit) -> String {
+    match unit {
+        NetUnit::Bytes => format_speed(bytes_per_sec),
+        NetUnit::Bits => format_bitrate(bytes_per_sec),
+    }
 }
 
-// Rendering is centralized in tray_render.rs
+#[cfg(test)]
+mod tests;
 
-fn format_speed(bytes_per_sec: f64) -> String {
-    const THRESHOLD_KB: f64 = 999_500.0;
-    const THRESHOLD_MB: f64 = 999_500_000.0;
+// render_tray_icon_into lives in crates/render
 
-    let (value, unit) = if bytes_per_sec >= THRESHOLD_MB {
-        (bytes_per_sec / 1_000_000_000.0, "GB")
-    } else if bytes_per_sec >= THRESHOLD_KB {
-        (bytes_per_sec / 1_000_000.0, "MB")
-    } else {
-        (bytes_per_sec / 1_000.0, "KB")
+/// Enables or disables launching at login through the autostart plugin.
+fn apply_autostart(app: &AppHandle, enabled: bool) {
+    #[cfg(desktop)]
+    {
+        let manager = app.autolaunch();
+        let result = if enabled {
+            manager.enable()
+        } else {
+            manager.disable()
+        };
+        if let Err(e) = result {
+            error!("Failed to update autostart: {e}");
+        }
+    }
+    #[cfg(not(desktop))]
+    let _ = (app, enabled);
+}
+
+/// Any action other than the menu takes over left-click; the menu stays on right-click.
+fn apply_click_action(app: &AppHandle, action: ClickAction) {
+    let trays = (0..).map_while(|index| app.tray_by_id(&tray_id(index)));
+    for tray in trays {
+        if let Err(e) = tray.set_show_menu_on_left_click(action == ClickAction::Menu) {
+            error!("Failed to update tray click behavior: {e}");
+        }
+    }
+    if action != ClickAction::Dashboard {
+        dashboard::hide(app);
+    }
+}
+
+fn run_click_action(app: &AppHandle, config: &Config, anchor: tauri::Rect) {
+    let result = match config.click_action() {
+        ClickAction::Menu => Ok(()),
+        ClickAction::Dashboard => dashboard::toggle(app, anchor).map_err(|e| e.to_string()),
+        ClickAction::SystemMonitor => system_monitor::open(),
+        ClickAction::Preferences => preferences::open(app).map_err(|e| e.to_string()),
+        ClickAction::ToggleCompact => {
+            let layout = toggled_compact_layout(config.layout());
+            config.set_layout(layout);
+            save_setting(app, config::key::LAYOUT, layout.as_str());
+            sync_menu_checks(app);
+            Ok(())
+        }
     };
+    if let Err(e) = result {
+        error!("Failed to run tray click action: {e}");
+    }
+}
 
-    if value >= 10.0 {
-        format!("{value:.0} {unit}")
+/// Compact goes back to standard; any other layout switches to compact.
+fn toggled_compact_layout(layout: Layout) -> Layout {
+    if layout == Layout::Compact {
+        Layout::Standard
     } else {
-        format!("{value:.1} {unit}")
+        Layout::Compact
+    }
+}
+
+/// Check items in the tray menu, kept so settings changed elsewhere (the
+/// preferences window) can be reflected in the menu.
+struct TrayMenuItems(Vec<CheckMenuItem<tauri::Wry>>);
+
+/// Whether the check item with `id` should be checked for the current settings.
+fn menu_item_checked(config: &Config, id: &str) -> Option<bool> {
+    if id == menu_id::CPU_LOAD_AVERAGE {
+        return Some(config.cpu_display() == CpuDisplay::LoadAverage);
+    }
+    if id == menu_id::CPU_USER_SYSTEM {
+        return Some(config.cpu_display() == CpuDisplay::UserSystem);
+    }
+    if id == menu_id::CPU_HEATMAP {
+        return Some(config.cpu_display() == CpuDisplay::Heatmap);
+    }
+    if id == menu_id::MEM_USED {
+        return Some(config.mem_display() == MemDisplay::Used);
+    }
+    if id == menu_id::MEM_PRESSURE {
+        return Some(config.mem_display() == MemDisplay::Pressure);
+    }
+    if id == menu_id::MEM_BREAKDOWN {
+        return Some(config.mem_display() == MemDisplay::Breakdown);
     }
+    if let Some(layout) = parse_layout_menu_id(id) {
+        return Some(config.layout() == layout);
+    }
+    if let Some(color) = parse_icon_color_menu_id(id) {
+        return Some(config.icon_color() == color);
+    }
+    if let Some(name) = profiles::parse_menu_id(id) {
+        return Some(config.active_profile() == name);
+    }
+    if let Some(host) = remote::parse_menu_id(id) {
+        return Some(config.remote_host() == host);
+    }
+    if let Some((key, preset)) = parse_threshold_menu_id(id) {
+        return Some(config.alert_threshold(key)?.load(Relaxed) == preset);
+    }
+    config.get(id)?.as_bool()
 }
 
-fn sum_network_totals(networks: &Networks) -> (u64, u64) {
-    networks.iter().fold((0, 0), |(rx, tx), (_, data)| {
-        (rx + data.total_received(), tx + data.total_transmitted())
-    })
+fn sync_menu_checks(app: &AppHandle) {
+    let (Some(config), Some(items)) = (
+        app.try_state::<Arc<Config>>(),
+        app.try_state::<TrayMenuItems>(),
+    ) else {
+        return;
+    };
+
+    for item in &items.0 {
+        if let Some(checked) = menu_item_checked(&config, item.id().as_ref()) {
+            let _ = item.set_checked(checked);
+        }
+    }
 }
 
-#[cfg(test)]
-mod tests;
+/// GPU items left out of the tray menu because no GPU was found at startup;
+/// the monitoring loop adds them if one turns up.
+struct PendingGpuMenu {
+    menu: Menu<tauri::Wry>,
+    /// The item they go after
+    after: CheckMenuItem<tauri::Wry>,
+    show_gpu: CheckMenuItem<tauri::Wry>,
+    show_gpu_memory: CheckMenuItem<tauri::Wry>,
+    show_gpu_temperature: CheckMenuItem<tauri::Wry>,
+    show_gpu_codec: CheckMenuItem<tauri::Wry>,
+    show_gpu_processes: CheckMenuItem<tauri::Wry>,
+    gpu_processes: Submenu<tauri::Wry>,
+}
 
-// render_tray_icon_into moved to tray_render.rs
+/// Adds the GPU items to the tray menu for a GPU found after startup.
+fn add_gpu_menu(app: &AppHandle, capabilities: &Capabilities) -> tauri::Result<()> {
+    let Some(pending) = app.try_state::<PendingGpuMenu>() else {
+        return Ok(());
+    };
+    let Some(anchor) = pending
+        .menu
+        .items()?
+        .iter()
+        .position(|item| item.id() == pending.after.id())
+    else {
+        return Ok(());
+    };
+    let mut items: Vec<&dyn IsMenuItem<tauri::Wry>> =
+        vec![&pending.show_gpu, &pending.show_gpu_memory];
+    if capabilities.gpu_temperature {
+        items.push(&pending.show_gpu_temperature);
+    }
+    if capabilities.gpu_codec {
+        items.push(&pending.show_gpu_codec);
+    }
+    items.push(&pending.show_gpu_processes);
+    items.push(&pending.gpu_processes);
+    pending.menu.insert_items(&items, anchor + 1)
+}
 
 fn toggle_setting(
     app: &AppHandle,
@@ -249,33 +1014,19 @@ fn toggle_setting(
 fn setup_tray(
     app: &AppHandle,
     font: &Font,
-    show_cpu: Arc<AtomicBool>,
-    show_mem: Arc<AtomicBool>,
-    show_gpu: Arc<AtomicBool>,
-    show_net: Arc<AtomicBool>,
-    show_alerts: Arc<AtomicBool>,
-    gpu_available: bool,
-    is_autostart_enabled: bool,
+    config: Arc<Config>,
+    capabilities: Capabilities,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let gpu_available = capabilities.gpu;
+    let is_autostart_enabled = config.autostart.load(Relaxed);
+
     // Sync the autostart plugin state to match the store value
-    #[cfg(desktop)]
-    {
-        let manager = app.autolaunch();
-        if is_autostart_enabled {
-            if let Err(e) = manager.enable() {
-                eprintln!("Failed to enable autostart: {e}");
-            }
-        } else {
-            if let Err(e) = manager.disable() {
-                eprintln!("Failed to disable autostart: {e}");
-            }
-        }
-    }
+    apply_autostart(app, is_autostart_enabled);
 
     let autostart_item = CheckMenuItem::with_id(
         app,
         menu_id::AUTOSTART,
-        "Start at Login",
+        tr("Start at Login"),
         true,
         is_autostart_enabled,
         None::<&str>,
@@ -286,114 +1037,724 @@ fn setup_tray(
     let show_mem_item = CheckMenuItem::with_id(
         app,
         menu_id::SHOW_MEM,
-        "Show Memory",
+        tr("Show Memory"),
         true,
-        show_mem.load(Relaxed),
+        config.show_mem.load(Relaxed),
         None::<&str>,
     )?;
 
     let show_cpu_item = CheckMenuItem::with_id(
         app,
         menu_id::SHOW_CPU,
-        "Show CPU",
+        tr("Show CPU"),
         true,
-        show_cpu.load(Relaxed),
+        config.show_cpu.load(Relaxed),
         None::<&str>,
     )?;
 
-    let show_net_item = CheckMenuItem::with_id(
+    let mem_used_item = CheckMenuItem::with_id(
         app,
-        menu_id::SHOW_NET,
-        "Show Network",
+        menu_id::MEM_USED,
+        tr("Show Memory Used"),
         true,
-        show_net.load(Relaxed),
+        config.mem_display() == MemDisplay::Used,
         None::<&str>,
     )?;
 
-    let separator2 = PredefinedMenuItem::separator(app)?;
-
-    let show_alerts_item = CheckMenuItem::with_id(
+    let mem_pressure_item = CheckMenuItem::with_id(
         app,
-        menu_id::SHOW_ALERTS,
-        "Show Alert Colors",
+        menu_id::MEM_PRESSURE,
+        tr("Show Memory Pressure"),
         true,
-        show_alerts.load(Relaxed),
+        config.mem_display() == MemDisplay::Pressure,
         None::<&str>,
     )?;
 
-    let separator3 = PredefinedMenuItem::separator(app)?;
-    let quit_item = MenuItem::with_id(app, menu_id::QUIT, "Quit", true, None::<&str>)?;
+    let mem_breakdown_item = CheckMenuItem::with_id(
+        app,
+        menu_id::MEM_BREAKDOWN,
+        tr("Show Memory Breakdown"),
+        true,
+        config.mem_display() == MemDisplay::Breakdown,
+        None::<&str>,
+    )?;
 
-    let show_gpu_item = CheckMenuItem::with_id(
+    let cpu_load_item = CheckMenuItem::with_id(
         app,
-        menu_id::SHOW_GPU,
-        "Show GPU",
+        menu_id::CPU_LOAD_AVERAGE,
+        tr("Show Load Average"),
         true,
-        show_gpu.load(Relaxed),
+        config.cpu_display() == CpuDisplay::LoadAverage,
         None::<&str>,
     )?;
 
-    let menu = Menu::new(app)?;
-    menu.append(&autostart_item)?;
-    menu.append(&separator1)?;
-    menu.append(&show_mem_item)?;
-    menu.append(&show_cpu_item)?;
-    if gpu_available {
-        menu.append(&show_gpu_item)?;
-    }
-    menu.append(&show_net_item)?;
-    menu.append(&separator2)?;
-    menu.append(&show_alerts_item)?;
-    menu.append(&separator3)?;
-    menu.append(&quit_item)?;
+    let cpu_split_item = CheckMenuItem::with_id(
+        app,
+        menu_id::CPU_USER_SYSTEM,
+        tr("Show User / System"),
+        true,
+        config.cpu_display() == CpuDisplay::UserSystem,
+        None::<&str>,
+    )?;
 
-    #[cfg(target_os = "linux")]
-    let use_light_icons = detect_light_icons();
-    #[cfg(not(target_os = "linux"))]
-    let use_light_icons = true;
+    let cpu_heatmap_item = CheckMenuItem::with_id(
+        app,
+        menu_id::CPU_HEATMAP,
+        tr("Show Core Heatmap"),
+        true,
+        config.cpu_display() == CpuDisplay::Heatmap,
+        None::<&str>,
+    )?;
 
-    let mut renderer = tray_render::TrayRenderer::new();
-    let mut initial_buffer = Vec::with_capacity(4 * 800 * APP_SIZING.icon_height as usize);
-    let (width, height, _has_alert) = renderer.render_tray_icon_into(
-        font,
-        &mut initial_buffer,
-        APP_SIZING,
-        0.0,
-        0.0,
-        0.0,
-        "0 KB",
-        "0 KB",
-        show_cpu.load(Relaxed),
-        show_mem.load(Relaxed),
-        show_gpu.load(Relaxed) && gpu_available,
-        show_net.load(Relaxed),
-        show_alerts.load(Relaxed),
-        use_light_icons,
-        None,
-    );
-    let initial_icon = Image::new_owned(initial_buffer, width, height);
+    let cpu_frequency_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_CPU_FREQUENCY,
+        tr("Show CPU Frequency"),
+        true,
+        config.show_cpu_frequency.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let cpu_power_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_CPU_POWER,
+        tr("Show CPU Power"),
+        true,
+        config.show_cpu_power.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let ane_power_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_ANE_POWER,
+        tr("Show Neural Engine Power"),
+        true,
+        config.show_ane_power.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let show_net_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_NET,
+        tr("Show Network"),
+        true,
+        config.show_net.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let show_latency_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_LATENCY,
+        tr("Show Latency"),
+        true,
+        config.show_latency.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let show_wifi_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_WIFI,
+        tr("Show Wi-Fi"),
+        true,
+        config.show_wifi.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let show_vpn_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_VPN,
+        tr("Show VPN Status"),
+        true,
+        config.show_vpn.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let show_pressure_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_PRESSURE,
+        tr("Show Pressure Stall"),
+        true,
+        config.show_pressure.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let show_task_counts_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_TASK_COUNTS,
+        tr("Show Process Count"),
+        true,
+        config.show_task_counts.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let show_details_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_DETAILS,
+        tr("Show Details"),
+        true,
+        config.show_details.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let show_processes_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_PROCESSES,
+        tr("Show Top Processes"),
+        true,
+        config.show_processes.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    // Filled in by the monitoring loop while enabled
+    let processes_menu = Submenu::with_items(
+        app,
+        tr("Top Processes"),
+        config.show_processes.load(Relaxed),
+        &[&MenuItem::new(app, tr("Loading…"), false, None::<&str>)?],
+    )?;
+
+    let show_network_processes_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_NETWORK_PROCESSES,
+        tr("Show Top Network Apps"),
+        true,
+        config.show_network_processes.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let network_processes_menu = Submenu::with_items(
+        app,
+        tr("Top Network Apps"),
+        config.show_network_processes.load(Relaxed),
+        &[&MenuItem::new(app, tr("Loading…"), false, None::<&str>)?],
+    )?;
+
+    let session_item = MenuItem::new(app, format!("{}: …", tr("Session")), false, None::<&str>)?;
+    let since_boot_item =
+        MenuItem::new(app, format!("{}: …", tr("Since Boot")), false, None::<&str>)?;
+    let data_menu = Submenu::with_items(
+        app,
+        tr("Data Transferred"),
+        true,
+        &[
+            &session_item,
+            &since_boot_item,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(
+                app,
+                net_totals::RESET_MENU_ID,
+                tr("Reset Counters"),
+                true,
+                None::<&str>,
+            )?,
+        ],
+    )?;
+
+    let show_containers_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_CONTAINERS,
+        tr("Show Containers"),
+        true,
+        config.show_containers.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let containers_menu = Submenu::with_items(
+        app,
+        tr("Containers"),
+        config.show_containers.load(Relaxed),
+        &[&MenuItem::new(app, tr("Loading…"), false, None::<&str>)?],
+    )?;
+
+    let alerts_menu = Submenu::with_items(
+        app,
+        alerts::menu_title(None),
+        true,
+        &[&MenuItem::new(
+            app,
+            tr("No alerts yet"),
+            false,
+            None::<&str>,
+        )?],
+    )?;
+
+    let separator2 = PredefinedMenuItem::separator(app)?;
+
+    let profile_menu = Submenu::new(app, tr("Profile"), true)?;
+    let mut profile_items = Vec::new();
+    let active_profile = config.active_profile();
+    for profile in config.profiles() {
+        let item = CheckMenuItem::with_id(
+            app,
+            profiles::menu_id(&profile.name),
+            &profile.name,
+            true,
+            profile.name == active_profile,
+            None::<&str>,
+        )?;
+        profile_menu.append(&item)?;
+        profile_items.push(item);
+    }
+
+    // Only offered once remote hosts are set up in the settings file
+    let remote_hosts = config.remote_hosts();
+    let source_menu = Submenu::new(app, tr("Source"), true)?;
+    let mut source_items = Vec::new();
+    let selected_host = config.remote_host();
+    for host in std::iter::once("").chain(remote_hosts.iter().map(String::as_str)) {
+        let item = CheckMenuItem::with_id(
+            app,
+            remote::menu_id(host),
+            if host.is_empty() {
+                tr("This Computer")
+            } else {
+                host
+            },
+            true,
+            host == selected_host,
+            None::<&str>,
+        )?;
+        source_menu.append(&item)?;
+        source_items.push(item);
+    }
+
+    let layout_menu = Submenu::new(app, tr("Layout"), true)?;
+    let mut layout_items = Vec::new();
+    for layout in Layout::ALL {
+        let item = CheckMenuItem::with_id(
+            app,
+            layout_menu_id(layout),
+            tr(layout.label()),
+            true,
+            config.layout() == layout,
+            None::<&str>,
+        )?;
+        layout_menu.append(&item)?;
+        layout_items.push(item);
+    }
+
+    let icon_color_menu = Submenu::new(app, tr("Icon Color"), true)?;
+    let mut icon_color_items = Vec::new();
+    for color in IconColor::ALL {
+        let item = CheckMenuItem::with_id(
+            app,
+            icon_color_menu_id(color),
+            tr(color.label()),
+            true,
+            config.icon_color() == color,
+            None::<&str>,
+        )?;
+        icon_color_menu.append(&item)?;
+        icon_color_items.push(item);
+    }
+
+    let show_alerts_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_ALERTS,
+        tr("Show Alert Colors"),
+        true,
+        config.show_alerts.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let thresholds_menu = Submenu::new(app, tr("Alert Thresholds"), true)?;
+    let mut threshold_items = Vec::new();
+    for (label, key) in THRESHOLD_METRICS {
+        if key == config::key::GPU_ALERT_THRESHOLD && !gpu_available {
+            continue;
+        }
+        let current = config
+            .alert_threshold(key)
+            .map_or(config::DEFAULT_ALERT_THRESHOLD, |t| t.load(Relaxed));
+        let metric_menu = Submenu::new(app, tr(label), true)?;
+        for preset in ALERT_THRESHOLD_PRESETS {
+            let item = CheckMenuItem::with_id(
+                app,
+                threshold_menu_id(key, preset),
+                format!("{preset}%"),
+                true,
+                current == preset,
+                None::<&str>,
+            )?;
+            metric_menu.append(&item)?;
+            threshold_items.push(item);
+        }
+        thresholds_menu.append(&metric_menu)?;
+    }
+
+    let history_item = CheckMenuItem::with_id(
+        app,
+        menu_id::HISTORY,
+        tr("Record History"),
+        true,
+        config.history_enabled.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let reduce_on_battery_item = CheckMenuItem::with_id(
+        app,
+        menu_id::REDUCE_ON_BATTERY,
+        tr("Reduce Updates on Battery"),
+        true,
+        config.reduce_on_battery.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let separator3 = PredefinedMenuItem::separator(app)?;
+    let charts_item =
+        MenuItem::with_id(app, charts::MENU_ID, tr("Show Charts…"), true, None::<&str>)?;
+    let preferences_item = MenuItem::with_id(
+        app,
+        menu_id::PREFERENCES,
+        tr("Preferences…"),
+        true,
+        None::<&str>,
+    )?;
+    let system_monitor_item = MenuItem::with_id(
+        app,
+        menu_id::SYSTEM_MONITOR,
+        tr(SYSTEM_MONITOR_LABEL),
+        true,
+        None::<&str>,
+    )?;
+    let log_folder_item = MenuItem::with_id(
+        app,
+        logging::OPEN_MENU_ID,
+        tr("Open Log Folder"),
+        true,
+        None::<&str>,
+    )?;
+    let copy_stats_item = MenuItem::with_id(
+        app,
+        copy_stats::MENU_ID,
+        tr("Copy Stats"),
+        true,
+        None::<&str>,
+    )?;
+    let save_icon_item = MenuItem::with_id(
+        app,
+        save_icon::MENU_ID,
+        tr("Save Current Icon…"),
+        true,
+        None::<&str>,
+    )?;
+    let diagnostics_item = MenuItem::with_id(
+        app,
+        diagnostics::MENU_ID,
+        tr("Copy Diagnostics"),
+        true,
+        None::<&str>,
+    )?;
+    let quit_item = MenuItem::with_id(app, menu_id::QUIT, tr("Quit"), true, None::<&str>)?;
+
+    let show_gpu_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_GPU,
+        tr("Show GPU"),
+        true,
+        config.show_gpu.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let show_gpu_memory_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_GPU_MEMORY,
+        tr("Show GPU Memory"),
+        true,
+        config.show_gpu_memory.load(Relaxed),
+        None::<&str>,
+    )?;
 
-    let tray_builder = TrayIconBuilder::with_id(TRAY_ID).icon(initial_icon);
+    let show_gpu_temperature_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_GPU_TEMPERATURE,
+        tr("Show GPU Temperature"),
+        true,
+        config.show_gpu_temperature.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let show_gpu_codec_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_GPU_CODEC,
+        tr("Show Encoder/Decoder"),
+        true,
+        config.show_gpu_codec.load(Relaxed),
+        None::<&str>,
+    )?;
+
+    let show_gpu_processes_item = CheckMenuItem::with_id(
+        app,
+        menu_id::SHOW_GPU_PROCESSES,
+        tr("Show GPU Processes"),
+        true,
+        config.show_gpu_processes.load(Relaxed),
+        None::<&str>,
+    )?;
 
-    // Use template mode by default - macOS will handle light/dark adaptation
+    // Filled in by the monitoring loop while enabled
+    let gpu_processes_menu = Submenu::with_items(
+        app,
+        tr("GPU Processes"),
+        config.show_gpu_processes.load(Relaxed),
+        &[&MenuItem::new(app, tr("Loading…"), false, None::<&str>)?],
+    )?;
+
+    let menu = Menu::new(app)?;
+    menu.append(&autostart_item)?;
+    menu.append(&separator1)?;
+    menu.append(&show_mem_item)?;
+    menu.append(&mem_used_item)?;
+    if capabilities.memory_pressure {
+        menu.append(&mem_pressure_item)?;
+    }
+    if capabilities.memory_breakdown {
+        menu.append(&mem_breakdown_item)?;
+    }
+    menu.append(&show_cpu_item)?;
+    menu.append(&cpu_load_item)?;
+    if capabilities.cpu_split {
+        menu.append(&cpu_split_item)?;
+    }
+    menu.append(&cpu_heatmap_item)?;
+    menu.append(&cpu_frequency_item)?;
+    if capabilities.cpu_power {
+        menu.append(&cpu_power_item)?;
+    }
+    if gpu_available {
+        menu.append(&show_gpu_item)?;
+        menu.append(&show_gpu_memory_item)?;
+    }
+    if capabilities.gpu_temperature {
+        menu.append(&show_gpu_temperature_item)?;
+    }
+    if capabilities.gpu_codec {
+        menu.append(&show_gpu_codec_item)?;
+    }
+    if gpu_available {
+        menu.append(&show_gpu_processes_item)?;
+        menu.append(&gpu_processes_menu)?;
+    }
+    if capabilities.ane_power {
+        menu.append(&ane_power_item)?;
+    }
+    menu.append(&show_net_item)?;
+    menu.append(&show_latency_item)?;
+    menu.append(&show_wifi_item)?;
+    menu.append(&show_vpn_item)?;
+    if capabilities.pressure {
+        menu.append(&show_pressure_item)?;
+    }
+    menu.append(&show_task_counts_item)?;
+    menu.append(&show_details_item)?;
+    menu.append(&show_processes_item)?;
+    menu.append(&processes_menu)?;
+    menu.append(&show_network_processes_item)?;
+    menu.append(&network_processes_menu)?;
+    menu.append(&data_menu)?;
+    menu.append(&show_containers_item)?;
+    menu.append(&containers_menu)?;
+    menu.append(&alerts_menu)?;
+    menu.append(&separator2)?;
+    if !remote_hosts.is_empty() {
+        menu.append(&source_menu)?;
+    }
+    menu.append(&profile_menu)?;
+    menu.append(&layout_menu)?;
+    menu.append(&icon_color_menu)?;
+    menu.append(&show_alerts_item)?;
+    menu.append(&thresholds_menu)?;
+    menu.append(&history_item)?;
+    menu.append(&reduce_on_battery_item)?;
+    menu.append(&separator3)?;
+    menu.append(&charts_item)?;
+    menu.append(&preferences_item)?;
+    menu.append(&system_monitor_item)?;
+    menu.append(&log_folder_item)?;
+    menu.append(&copy_stats_item)?;
+    menu.append(&save_icon_item)?;
+    menu.append(&diagnostics_item)?;
+    menu.append(&quit_item)?;
+
+    let mut check_items = vec![
+        autostart_item.clone(),
+        show_mem_item.clone(),
+        show_cpu_item.clone(),
+        cpu_load_item.clone(),
+        mem_used_item.clone(),
+        cpu_frequency_item.clone(),
+        show_net_item.clone(),
+        show_latency_item.clone(),
+        show_wifi_item.clone(),
+        show_vpn_item.clone(),
+        show_task_counts_item.clone(),
+        show_details_item.clone(),
+        show_alerts_item.clone(),
+        history_item.clone(),
+        reduce_on_battery_item.clone(),
+        show_processes_item.clone(),
+        show_network_processes_item.clone(),
+        show_containers_item.clone(),
+    ];
+    if capabilities.cpu_power {
+        check_items.push(cpu_power_item.clone());
+    }
+    if gpu_available {
+        check_items.push(show_gpu_item.clone());
+        check_items.push(show_gpu_memory_item.clone());
+    }
+    if capabilities.gpu_temperature {
+        check_items.push(show_gpu_temperature_item.clone());
+    }
+    if capabilities.gpu_codec {
+        check_items.push(show_gpu_codec_item.clone());
+    }
+    if gpu_available {
+        check_items.push(show_gpu_processes_item.clone());
+    }
+    if capabilities.ane_power {
+        check_items.push(ane_power_item.clone());
+    }
+    if capabilities.pressure {
+        check_items.push(show_pressure_item.clone());
+    }
+    if capabilities.memory_pressure {
+        check_items.push(mem_pressure_item.clone());
+    }
+    if capabilities.memory_breakdown {
+        check_items.push(mem_breakdown_item.clone());
+    }
+    if capabilities.cpu_split {
+        check_items.push(cpu_split_item.clone());
+    }
+    check_items.push(cpu_heatmap_item.clone());
+    check_items.extend(source_items);
+    check_items.extend(profile_items);
+    check_items.extend(layout_items);
+    check_items.extend(icon_color_items);
+    check_items.extend(threshold_items);
+    if !gpu_available {
+        // Kept in sync from the start, so they're right if a GPU turns up
+        check_items.extend([
+            show_gpu_item.clone(),
+            show_gpu_memory_item.clone(),
+            show_gpu_temperature_item.clone(),
+            show_gpu_codec_item.clone(),
+            show_gpu_processes_item.clone(),
+        ]);
+        app.manage(PendingGpuMenu {
+            menu: menu.clone(),
+            after: if capabilities.cpu_power {
+                cpu_power_item.clone()
+            } else {
+                cpu_frequency_item.clone()
+            },
+            show_gpu: show_gpu_item.clone(),
+            show_gpu_memory: show_gpu_memory_item.clone(),
+            show_gpu_temperature: show_gpu_temperature_item.clone(),
+            show_gpu_codec: show_gpu_codec_item.clone(),
+            show_gpu_processes: show_gpu_processes_item.clone(),
+            gpu_processes: gpu_processes_menu.clone(),
+        });
+    }
+    app.manage(TrayMenuItems(check_items));
+    app.manage(ProcessMenu(processes_menu));
+    app.manage(NetworkProcessMenu(network_processes_menu));
+    app.manage(GpuProcessMenu(gpu_processes_menu));
+    app.manage(NetTotalsMenu {
+        menu: data_menu.clone(),
+        session: session_item,
+        since_boot: since_boot_item,
+        month: MenuItem::new(app, "This Month: …", false, None::<&str>)?,
+    });
+    app.manage(ContainerMenu(containers_menu));
+    app.manage(AlertMenu(alerts_menu));
+    app.manage(DetailsBlock::new(app, &menu)?);
+    app.manage(DetailLines::new(app, &menu)?);
+    app.manage(UpdateMenu {
+        menu: menu.clone(),
+        item: MenuItem::with_id(
+            app,
+            updates::MENU_ID,
+            "Update Available",
+            true,
+            None::<&str>,
+        )?,
+    });
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    let palette = config.palette_on(theme_foreground());
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    let palette = config.palette(true);
+
+    let mut renderer = tray_render::TrayRenderer::new();
+    renderer.set_auto_width(config.auto_width.load(Relaxed));
+    renderer.set_tabular_figures(config.tabular_figures.load(Relaxed));
+    renderer.set_label_style(config.label_style());
+    let sizing = app_sizing(&config);
+    let mut initial_icon = |segments: &[SegmentKind]| {
+        let mut buffer = Vec::with_capacity(4 * 800 * sizing.icon_height as usize);
+        let (width, height, _has_alert) = renderer.render_tray_icon_into(
+            font,
+            &mut buffer,
+            &tray_render::RenderParams {
+                down_str: "0 KB",
+                up_str: "0 KB",
+                visible_segments: segments,
+                layout: config.layout(),
+                value_style: config.value_style(),
+                combine_net: config.combine_net.load(Relaxed),
+                percent_cap: config.percent_cap(),
+                cpu_cores: cpu::logical_cores(),
+                show_alerts: config.show_alerts.load(Relaxed),
+                thresholds: config.alert_thresholds(),
+                palette,
+                ..tray_render::RenderParams::new(sizing)
+            },
+        );
+        Image::new_owned(buffer, width, height)
+    };
+    let icons = icon_segments(
+        &config.tray_icons(),
+        &visible_segments(
+            config.segment_order(),
+            config.show_cpu.load(Relaxed),
+            config.show_mem.load(Relaxed),
+            config.show_gpu.load(Relaxed) && gpu_available,
+            config.show_net.load(Relaxed),
+        ),
+    );
+    // Rendered up front, as the first tray's menu handler takes `config`
+    let initial_icons: Vec<Image> = icons
+        .iter()
+        .map(|segments| initial_icon(segments))
+        .collect();
+    let mut initial_icons = initial_icons.into_iter();
+    let tray_builder =
+        TrayIconBuilder::with_id(TRAY_ID).icon(initial_icons.next().expect("at least one icon"));
+
+    // Use template mode by default - macOS will handle light/dark adaptation.
+    // A custom or forced color would be discarded by template mode, so skip it then.
     #[cfg(target_os = "macos")]
-    let tray_builder = tray_builder.icon_as_template(true);
+    let tray_builder = tray_builder.icon_as_template(config.follows_theme());
 
     let cpu_item = show_cpu_item.clone();
     let mem_item = show_mem_item.clone();
     let gpu_item = show_gpu_item.clone();
     let net_item = show_net_item.clone();
+    let click_config = config.clone();
+    let click_config_extra = config.clone();
 
     let _tray = tray_builder
         .menu(&menu)
-        .show_menu_on_left_click(true)
-        .tooltip("System Monitor")
+        .show_menu_on_left_click(config.click_action() == ClickAction::Menu)
+        .tooltip(TRAY_TITLE)
         .on_menu_event(move |app, event| {
             let flags = [
-                show_cpu.as_ref(),
-                show_mem.as_ref(),
-                show_gpu.as_ref(),
-                show_net.as_ref(),
+                &config.show_cpu,
+                &config.show_mem,
+                &config.show_gpu,
+                &config.show_net,
             ];
             match event.id.as_ref() {
                 menu_id::AUTOSTART => {
@@ -403,176 +1764,1405 @@ fn setup_tray(
                         let enabled = manager.is_enabled().unwrap_or(false);
                         if enabled {
                             if let Err(e) = manager.disable() {
-                                eprintln!("Failed to disable autostart: {e}");
+                                error!("Failed to disable autostart: {e}");
                             }
                         } else {
                             if let Err(e) = manager.enable() {
-                                eprintln!("Failed to enable autostart: {e}");
+                                error!("Failed to enable autostart: {e}");
                             }
                         }
+                        config.autostart.store(!enabled, Relaxed);
                         save_setting(app, menu_id::AUTOSTART, !enabled);
                     }
                 }
                 menu_id::SHOW_CPU => {
-                    toggle_setting(app, menu_id::SHOW_CPU, &show_cpu, flags, &cpu_item)
+                    toggle_setting(app, menu_id::SHOW_CPU, &config.show_cpu, flags, &cpu_item)
                 }
                 menu_id::SHOW_MEM => {
-                    toggle_setting(app, menu_id::SHOW_MEM, &show_mem, flags, &mem_item)
+                    toggle_setting(app, menu_id::SHOW_MEM, &config.show_mem, flags, &mem_item)
                 }
                 menu_id::SHOW_GPU => {
-                    toggle_setting(app, menu_id::SHOW_GPU, &show_gpu, flags, &gpu_item)
+                    toggle_setting(app, menu_id::SHOW_GPU, &config.show_gpu, flags, &gpu_item)
                 }
                 menu_id::SHOW_NET => {
-                    toggle_setting(app, menu_id::SHOW_NET, &show_net, flags, &net_item)
+                    toggle_setting(app, menu_id::SHOW_NET, &config.show_net, flags, &net_item)
                 }
                 menu_id::SHOW_ALERTS => {
-                    let new_value = !show_alerts.load(Relaxed);
-                    show_alerts.store(new_value, Relaxed);
+                    let new_value = !config.show_alerts.load(Relaxed);
+                    config.show_alerts.store(new_value, Relaxed);
                     save_setting(app, menu_id::SHOW_ALERTS, new_value);
                 }
+                menu_id::CPU_LOAD_AVERAGE | menu_id::CPU_USER_SYSTEM | menu_id::CPU_HEATMAP => {
+                    let chosen = match event.id.as_ref() {
+                        menu_id::CPU_LOAD_AVERAGE => CpuDisplay::LoadAverage,
+                        menu_id::CPU_USER_SYSTEM => CpuDisplay::UserSystem,
+                        _ => CpuDisplay::Heatmap,
+                    };
+                    let display = if config.cpu_display() == chosen {
+                        CpuDisplay::Percent
+                    } else {
+                        chosen
+                    };
+                    config.set_cpu_display(display);
+                    save_setting(app, config::key::CPU_DISPLAY, display.as_str());
+                    // The modes exclude each other
+                    sync_menu_checks(app);
+                }
+                menu_id::MEM_USED | menu_id::MEM_PRESSURE | menu_id::MEM_BREAKDOWN => {
+                    let chosen = match event.id.as_ref() {
+                        menu_id::MEM_USED => MemDisplay::Used,
+                        menu_id::MEM_PRESSURE => MemDisplay::Pressure,
+                        _ => MemDisplay::Breakdown,
+                    };
+                    let display = if config.mem_display() == chosen {
+                        MemDisplay::Percent
+                    } else {
+                        chosen
+                    };
+                    config.set_mem_display(display);
+                    save_setting(app, config::key::MEM_DISPLAY, display.as_str());
+                    // The modes exclude each other
+                    sync_menu_checks(app);
+                }
+                menu_id::SHOW_CPU_FREQUENCY => {
+                    let new_value = !config.show_cpu_frequency.load(Relaxed);
+                    config.show_cpu_frequency.store(new_value, Relaxed);
+                    save_setting(app, menu_id::SHOW_CPU_FREQUENCY, new_value);
+                }
+                menu_id::SHOW_CPU_POWER => {
+                    let new_value = !config.show_cpu_power.load(Relaxed);
+                    config.show_cpu_power.store(new_value, Relaxed);
+                    save_setting(app, menu_id::SHOW_CPU_POWER, new_value);
+                }
+                menu_id::SHOW_ANE_POWER => {
+                    let new_value = !config.show_ane_power.load(Relaxed);
+                    config.show_ane_power.store(new_value, Relaxed);
+                    save_setting(app, menu_id::SHOW_ANE_POWER, new_value);
+                }
+                menu_id::SHOW_GPU_MEMORY => {
+                    let new_value = !config.show_gpu_memory.load(Relaxed);
+                    config.show_gpu_memory.store(new_value, Relaxed);
+                    save_setting(app, menu_id::SHOW_GPU_MEMORY, new_value);
+                }
+                menu_id::SHOW_GPU_TEMPERATURE => {
+                    let new_value = !config.show_gpu_temperature.load(Relaxed);
+                    config.show_gpu_temperature.store(new_value, Relaxed);
+                    save_setting(app, menu_id::SHOW_GPU_TEMPERATURE, new_value);
+                }
+                menu_id::SHOW_GPU_CODEC => {
+                    let new_value = !config.show_gpu_codec.load(Relaxed);
+                    config.show_gpu_codec.store(new_value, Relaxed);
+                    save_setting(app, menu_id::SHOW_GPU_CODEC, new_value);
+                }
+                menu_id::SHOW_LATENCY => {
+                    let new_value = !config.show_latency.load(Relaxed);
+                    config.show_latency.store(new_value, Relaxed);
+                    save_setting(app, menu_id::SHOW_LATENCY, new_value);
+                }
+                menu_id::SHOW_WIFI => {
+                    let new_value = !config.show_wifi.load(Relaxed);
+                    config.show_wifi.store(new_value, Relaxed);
+                    save_setting(app, menu_id::SHOW_WIFI, new_value);
+                }
+                menu_id::SHOW_VPN => {
+                    let new_value = !config.show_vpn.load(Relaxed);
+                    config.show_vpn.store(new_value, Relaxed);
+                    save_setting(app, menu_id::SHOW_VPN, new_value);
+                }
+                menu_id::SHOW_PRESSURE => {
+                    let new_value = !config.show_pressure.load(Relaxed);
+                    config.show_pressure.store(new_value, Relaxed);
+                    save_setting(app, menu_id::SHOW_PRESSURE, new_value);
+                }
+                menu_id::SHOW_TASK_COUNTS => {
+                    let new_value = !config.show_task_counts.load(Relaxed);
+                    config.show_task_counts.store(new_value, Relaxed);
+                    save_setting(app, menu_id::SHOW_TASK_COUNTS, new_value);
+                }
+                menu_id::SHOW_DETAILS => {
+                    let new_value = !config.show_details.load(Relaxed);
+                    config.show_details.store(new_value, Relaxed);
+                    save_setting(app, menu_id::SHOW_DETAILS, new_value);
+                }
+                menu_id::HISTORY => {
+                    let new_value = !config.history_enabled.load(Relaxed);
+                    config.history_enabled.store(new_value, Relaxed);
+                    save_setting(app, menu_id::HISTORY, new_value);
+                }
+                menu_id::REDUCE_ON_BATTERY => {
+                    let new_value = !config.reduce_on_battery.load(Relaxed);
+                    config.reduce_on_battery.store(new_value, Relaxed);
+                    save_setting(app, menu_id::REDUCE_ON_BATTERY, new_value);
+                }
+                menu_id::SHOW_PROCESSES => {
+                    let new_value = !config.show_processes.load(Relaxed);
+                    config.show_processes.store(new_value, Relaxed);
+                    save_setting(app, menu_id::SHOW_PROCESSES, new_value);
+                }
+                menu_id::SHOW_NETWORK_PROCESSES => {
+                    let new_value = !config.show_network_processes.load(Relaxed);
+                    config.show_network_processes.store(new_value, Relaxed);
+                    save_setting(app, menu_id::SHOW_NETWORK_PROCESSES, new_value);
+                }
+                menu_id::SHOW_GPU_PROCESSES => {
+                    let new_value = !config.show_gpu_processes.load(Relaxed);
+                    config.show_gpu_processes.store(new_value, Relaxed);
+                    save_setting(app, menu_id::SHOW_GPU_PROCESSES, new_value);
+                }
+                menu_id::SHOW_CONTAINERS => {
+                    let new_value = !config.show_containers.load(Relaxed);
+                    config.show_containers.store(new_value, Relaxed);
+                    save_setting(app, menu_id::SHOW_CONTAINERS, new_value);
+                }
+                menu_id::PREFERENCES => {
+                    if let Err(e) = preferences::open(app) {
+                        error!("Failed to open preferences: {e}");
+                    }
+                }
+                alerts::EXPORT_MENU_ID => {
+                    if let Some(log) = app.try_state::<SharedAlertLog>() {
+                        alerts::export(app, &log);
+                    }
+                }
+                net_totals::RESET_MENU_ID => {
+                    if let Some(totals) = app.try_state::<SharedNetTotals>() {
+                        let mut totals = totals.lock().unwrap_or_else(|e| e.into_inner());
+                        totals.reset();
+                        // Shown right away rather than on the next tick
+                        if let Some(menu) = app.try_state::<NetTotalsMenu>() {
+                            let _ = menu.session.set_text(net_totals::menu_lines(&totals).0);
+                        }
+                    }
+                }
+                updates::MENU_ID => {
+                    let release = app.try_state::<SharedUpdate>().and_then(|update| {
+                        update.lock().unwrap_or_else(|e| e.into_inner()).clone()
+                    });
+                    if let Some(release) = release {
+                        if let Err(e) = updates::open(&release) {
+                            error!("Failed to open release page: {e}");
+                        }
+                    }
+                }
+                charts::MENU_ID => {
+                    if let Err(e) = charts::open(app) {
+                        error!("Failed to open charts: {e}");
+                    }
+                }
+                logging::OPEN_MENU_ID => {
+                    if let Err(e) = logging::open_dir() {
+                        error!("Failed to open log folder: {e}");
+                    }
+                }
+                copy_stats::MENU_ID => {
+                    let latest = *app
+                        .state::<LatestSample>()
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner());
+                    // Nothing to copy before the first tick
+                    if let Some(sample) = latest {
+                        let json = config.copy_stats_json.load(Relaxed);
+                        let text = copy_stats::format(&sample, json, config.net_unit());
+                        if let Err(e) = app.clipboard().write_text(text) {
+                            error!("Failed to copy stats: {e}");
+                        }
+                    }
+                }
+                save_icon::MENU_ID => save_icon::choose(app),
+                diagnostics::MENU_ID => {
+                    let capabilities = *app
+                        .state::<SharedCapabilities>()
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner());
+                    let report = diagnostics::report(
+                        &app.package_info().version.to_string(),
+                        &capabilities,
+                        app_sizing(&config),
+                    );
+                    if let Err(e) = app.clipboard().write_text(report) {
+                        error!("Failed to copy diagnostics: {e}");
+                    }
+                }
+                menu_id::SYSTEM_MONITOR => {
+                    if let Err(e) = system_monitor::open() {
+                        error!("Failed to open system monitor: {e}");
+                    }
+                }
                 menu_id::QUIT => app.exit(0),
-                _ => {}
+                id => {
+                    if let Some((key, value)) = parse_threshold_menu_id(id) {
+                        if let Ok(saved) = config.set(key, &value.into()) {
+                            save_setting(app, key, saved);
+                        }
+                        sync_menu_checks(app);
+                    } else if let Some(layout) = parse_layout_menu_id(id) {
+                        config.set_layout(layout);
+                        save_setting(app, config::key::LAYOUT, layout.as_str());
+                        sync_menu_checks(app);
+                    } else if let Some(color) = parse_icon_color_menu_id(id) {
+                        let key = config::key::ICON_COLOR;
+                        if let Ok(saved) = config.set(key, &color.as_str().into()) {
+                            save_setting(app, key, saved);
+                        }
+                        sync_menu_checks(app);
+                    } else if let Some(name) = profiles::parse_menu_id(id) {
+                        profiles::activate(app, &config, name);
+                    } else if let Some(host) = remote::parse_menu_id(id) {
+                        if let Ok(saved) = config.set(config::key::REMOTE_HOST, &host.into()) {
+                            save_setting(app, config::key::REMOTE_HOST, saved);
+                        }
+                        sync_menu_checks(app);
+                    } else if let Some(pid) = processes::parse_kill_menu_id(id) {
+                        processes::confirm_kill(app, pid);
+                    }
+                }
             }
         })
+        .on_tray_icon_event(move |tray, event| handle_tray_click(tray, event, &click_config))
         .build(app)?;
 
+    // Menu events are app-wide, so these share the handler above
+    for (index, icon) in initial_icons.enumerate() {
+        let click_config = click_config_extra.clone();
+        let tray_builder = TrayIconBuilder::with_id(tray_id(index + 1)).icon(icon);
+        #[cfg(target_os = "macos")]
+        let tray_builder = tray_builder.icon_as_template(click_config.follows_theme());
+        tray_builder
+            .menu(&menu)
+            .show_menu_on_left_click(click_config.click_action() == ClickAction::Menu)
+            .tooltip(TRAY_TITLE)
+            .on_tray_icon_event(move |tray, event| handle_tray_click(tray, event, &click_config))
+            .build(app)?;
+    }
+
     Ok(())
 }
 
-fn start_monitoring(
-    app: AppHandle,
-    font: Font<'static>,
-    show_cpu: Arc<AtomicBool>,
-    show_mem: Arc<AtomicBool>,
-    show_gpu: Arc<AtomicBool>,
-    show_net: Arc<AtomicBool>,
-    show_alerts: Arc<AtomicBool>,
-    mut gpu_sampler: Option<GpuSampler>,
-) {
-    thread::spawn(move || {
-        let mut sys = System::new();
-        // Warm up CPU measurement before loop so first render has valid data
-        sys.refresh_cpu_usage();
-        thread::sleep(Duration::from_millis(CPU_STABILIZE_MS));
+fn handle_tray_click(tray: &tauri::tray::TrayIcon, event: TrayIconEvent, config: &Config) {
+    match event {
+        TrayIconEvent::Click {
+            button: MouseButton::Left,
+            button_state: MouseButtonState::Up,
+            rect,
+            ..
+        } => run_click_action(tray.app_handle(), config, rect),
+        // The tooltip's hidden readings are only sampled while it can be seen
+        TrayIconEvent::Enter { .. } => {
+            config.tray_hovered.store(true, Relaxed);
+            config.request_redraw();
+        }
+        TrayIconEvent::Leave { .. } => config.tray_hovered.store(false, Relaxed),
+        _ => {}
+    }
+}
+
+/// The running monitoring task and the token that stops it. Each task also
+/// gets a child token of its own, so the watchdog can stop a stalled one.
+struct Monitoring {
+    shutdown: CancellationToken,
+    task: Mutex<Option<(CancellationToken, JoinHandle<()>)>>,
+}
 
-        let mut networks = Networks::new_with_refreshed_list();
+/// Cancels the monitoring task. Returns its handle the first time, so the caller
+/// can wait for the tick in progress to finish.
+fn stop_monitoring(app: &AppHandle) -> Option<JoinHandle<()>> {
+    let monitoring = app.try_state::<Monitoring>()?;
+    monitoring.shutdown.cancel();
+    let task = monitoring
+        .task
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take();
+    task.map(|(_, task)| task)
+}
 
-        // Initialize network counters from current values to avoid spike on first iteration
-        let (mut prev_rx, mut prev_tx) = sum_network_totals(&networks);
-        let mut gpu_usage: f32 = 0.0;
-        let mut last_update = std::time::Instant::now();
+/// Ticks every `period`, starting one period from now. A tick that runs long
+/// delays the following ones instead of causing a burst to catch up.
+fn monitoring_ticker(period: Duration) -> Interval {
+    let mut ticker = tokio::time::interval_at(Instant::now() + period, period);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker
+}
+
+/// Runs the monitoring loop on a thread of its own until `shutdown` is
+/// cancelled. Each tick samples and renders synchronously; the loop only
+/// yields while waiting for the next tick or between eased frames.
+fn start_monitoring(
+    app: AppHandle,
+    mut font: Font<'static>,
+    config: Arc<Config>,
+    mut samplers: SamplerRegistry,
+    latest_sample: LatestSample,
+    #[cfg(target_os = "linux")] dbus_publisher: dbus::Publisher,
+    recent_samples: RecentSamples,
+    chart_history: ChartHistory,
+    minute_history: SharedMinuteHistory,
+    alert_log: SharedAlertLog,
+    net_totals: SharedNetTotals,
+    shutdown: CancellationToken,
+    heartbeat: SharedHeartbeat,
+) -> JoinHandle<()> {
+    let monitoring = async move {
+        let mut gpu_available = samplers.contains(sampler::name::GPU);
+        let mut last_gpu_probe = Instant::now();
 
         // Track previous values for hysteresis-based updates (prevents compositor leak on Linux)
         let mut prev_cpu: f32 = -100.0; // Force initial update
+        let mut prev_cpu_load: Option<f32> = None;
+        let mut prev_cpu_split: Option<(f32, f32)> = None;
+        let mut prev_cpu_heatmap: Option<CoreUsage> = None;
         let mut prev_mem: f32 = -100.0;
+        let mut prev_mem_level: Option<MemoryPressure> = None;
+        let mut prev_mem_breakdown: Option<MemoryBreakdown> = None;
         let mut prev_gpu: f32 = -100.0;
         let mut prev_down_speed: f64 = -1.0;
         let mut prev_up_speed: f64 = -1.0;
+        let mut prev_gpu_mem_str: Option<String> = None;
+        let mut prev_mem_used_str: Option<String> = None;
+        let mut prev_extra: Vec<TextSegment> = Vec::new();
         let mut prev_flags: (bool, bool, bool, bool, bool, bool) =
             (false, false, false, false, false, false);
-        let update_interval = get_update_interval_ms();
-        let mut tick_count: u32 = 0;
+        let mut prev_thresholds = config.alert_thresholds();
+        let mut prev_throttled = (false, false);
+        let mut prev_icons: Vec<Vec<SegmentKind>> = Vec::new();
+        let mut prev_layout = config.layout();
+        let mut prev_value_style = config.value_style();
+        let mut prev_combine_net = config.combine_net.load(Relaxed);
+        let mut prev_auto_width = config.auto_width.load(Relaxed);
+        let mut prev_tabular = config.tabular_figures.load(Relaxed);
+        let mut prev_label_style = config.label_style();
+        let mut prev_percent_cap = config.percent_cap();
+        let cpu_cores = cpu::logical_cores();
+        let mut prev_palette = Palette::default();
+        let mut prev_sizing = app_sizing(&config);
 
         // Reusable buffer owned by monitoring thread - prevents compositor resource
         // accumulation on Linux that causes cursor slowdown
         let mut renderer = tray_render::TrayRenderer::new();
+        let mut frame_dump = frame_dump::FrameDump::from_env();
         let mut render_buffer: Vec<u8> =
             Vec::with_capacity(4 * 800 * APP_SIZING.icon_height as usize);
 
-        loop {
-            thread::sleep(Duration::from_millis(update_interval));
+        let data_dir = match app.path().app_data_dir() {
+            Ok(dir) => Some(dir),
+            Err(e) => {
+                warn!("History recording and data cap tracking unavailable: {e}");
+                None
+            }
+        };
+        let history_dir = data_dir.as_ref().map(|dir| dir.join(history::HISTORY_DIR));
+        let mut history: Option<HistoryRecorder> = None;
 
-            let now = std::time::Instant::now();
-            let dt = now.duration_since(last_update).as_secs_f64();
-            last_update = now;
-            let full_tick = tick_count % 2 == 0;
-            tick_count = tick_count.wrapping_add(1);
+        if let Some(dir) = &history_dir {
+            *alert_log.lock().unwrap_or_else(|e| e.into_inner()) =
+                AlertLog::with_events(alerts::load(dir));
+        }
+        // Builds the submenu on the first tick, including events loaded from disk
+        let mut alerts_changed = true;
+
+        // Separate System so the process table is only kept while the submenu is enabled
+        let mut process_sys: Option<System> = None;
+        let mut network_talkers: Option<NetworkTalkers> = None;
+        let mut gpu_process_monitor: Option<GpuProcessMonitor> = None;
+        let mut container_monitor: Option<ContainerMonitor> = None;
+        let mut pinger: Option<Pinger> = None;
+        let mut sounder = AlertSounder::default();
+        let mut prev_quiet: Option<QuietHours> = None;
+        let (mut cpu_ema, mut mem_ema, mut gpu_ema) =
+            (Ema::default(), Ema::default(), Ema::default());
+        let mut remote: Option<RemoteSource> = None;
+        let mut shown_latency: Option<Latency> = None;
+        let mut details_shown = 0;
+        let mut prev_details: Vec<String> = Vec::new();
+        // Memory totals for the details block, kept while it is shown
+        let mut details_sys: Option<System> = None;
+        let mut prev_frequency_text = String::new();
+        let mut tunnels: Option<TunnelMonitor> = None;
+        let mut prev_vpn_text = String::new();
+        let mut prev_pressure_text = String::new();
+        let mut task_sys: Option<System> = None;
+        let mut prev_task_text = String::new();
+        let mut wifi_monitor: Option<WifiMonitor> = None;
+        let mut shown_rssi: Option<i32> = None;
+        let mut prev_wifi_text = String::new();
+        let mut prev_codec_text = String::new();
+        // Process list for watched processes, kept while any are named
+        let mut watched_sys: Option<System> = None;
+        let mut prev_watched_text = String::new();
+        let mut shown_update: Option<String> = None;
+        let mut prev_net_totals_lines = (String::new(), String::new());
+        // Loaded while a data cap is set
+        let mut data_usage: Option<DataUsage> = None;
+        let mut prev_month_line: Option<String> = None;
+        let mut prev_tooltip = String::new();
+        let mut adaptive_interval = AdaptiveInterval::default();
+        let mut net_hysteresis = NetHysteresis::default();
+        let mut last_render: Option<Instant> = None;
+        // Values of the last frame drawn, eased from by `interpolate_values`
+        let mut last_shown: Option<Shown> = None;
+        // Hash of the frame each tray icon shows, by index
+        let mut sent_frames: HashMap<usize, u64> = HashMap::new();
+        let mut idle_segments = IdleSegments::default();
+        let mut power_monitor = PowerMonitor::default();
+
+        let mut prev_net_unit = config.net_unit();
+        let mut font_revision = config.font_revision();
+
+        let mut ticker = monitoring_ticker(Duration::from_millis(get_update_interval_ms(
+            config.update_interval_ms.load(Relaxed) as u64,
+        )));
 
-            let sc = show_cpu.load(Relaxed);
-            let sm = show_mem.load(Relaxed);
-            let show_gpu_enabled = show_gpu.load(Relaxed);
-            let sg = show_gpu_enabled && gpu_sampler.is_some();
-            let sn = show_net.load(Relaxed);
-            let sa = show_alerts.load(Relaxed);
+        loop {
+            let update_interval =
+                get_update_interval_ms(config.update_interval_ms.load(Relaxed) as u64);
+            let mut sleep_ms = adaptive_interval.interval_ms(update_interval);
+            if config.reduce_on_battery.load(Relaxed) && power_monitor.is_low_power() {
+                sleep_ms = sleep_ms.saturating_mul(power::BATTERY_INTERVAL_MULTIPLIER);
+            }
+            let period = Duration::from_millis(sleep_ms);
+            if period != ticker.period() {
+                ticker = monitoring_ticker(period);
+            }
+            heartbeat.beat(period);
+            let redraw_requested = tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = ticker.tick() => false,
+                _ = config.redraw_requested() => true,
+            };
+            // Nobody sees the icon; unlocking requests a redraw, which wakes
+            // the loop right away
+            if config.pause_when_locked.load(Relaxed) && screen_lock::is_locked() {
+                continue;
+            }
+            // Set by "Save Current Icon…", which also requests a redraw
+            let mut save_icon_to = app
+                .try_state::<PendingIconSave>()
+                .and_then(|pending| pending.lock().unwrap_or_else(|e| e.into_inner()).take());
+
+            // Picks up an eGPU plugged in or a driver loaded after startup
+            if !gpu_available && last_gpu_probe.elapsed() >= gpu::PROBE_INTERVAL {
+                last_gpu_probe = Instant::now();
+                if samplers.register_gpu_samplers() {
+                    gpu_available = true;
+                    info!("GPU found ({}); adding it to the menu", gpu::backend());
+                    let capabilities = {
+                        let shared = app.state::<SharedCapabilities>();
+                        let mut capabilities = shared.lock().unwrap_or_else(|e| e.into_inner());
+                        capabilities.gpu = true;
+                        capabilities.gpu_temperature =
+                            samplers.contains(sampler::name::GPU_TEMPERATURE);
+                        capabilities.gpu_codec = samplers.contains(sampler::name::GPU_CODEC);
+                        *capabilities
+                    };
+                    if let Err(e) = add_gpu_menu(&app, &capabilities) {
+                        error!("Failed to add the GPU to the menu: {e}");
+                    }
+                }
+            }
 
-            #[cfg(target_os = "linux")]
+            let remote_host = config.remote_host();
+            if remote.as_ref().map_or("", RemoteSource::spec) != remote_host {
+                remote = (!remote_host.is_empty())
+                    .then(|| RemoteSource::new(&remote_host))
+                    .and_then(|source| source.map_err(|e| warn!("{e}")).ok());
+                // Don't blend one machine's readings into another's
+                for ema in [&mut cpu_ema, &mut mem_ema, &mut gpu_ema] {
+                    ema.reset();
+                }
+            }
+            // All readings missing until the selected host first answers
+            let shown_remote = remote
+                .as_mut()
+                .map(|source| source.sample().unwrap_or_default());
+
+            let sc = config.show_cpu.load(Relaxed);
+            let sm = config.show_mem.load(Relaxed);
+            let show_gpu_enabled = config.show_gpu.load(Relaxed);
+            let gpu_present = gpu_available && !gpu::is_gone();
+            let sg =
+                show_gpu_enabled && shown_remote.map_or(gpu_present, |sample| sample.gpu.is_some());
+            let sgm = sg && config.show_gpu_memory.load(Relaxed);
+            let sn = config.show_net.load(Relaxed);
+            let show_power = config.show_cpu_power.load(Relaxed);
+            let show_ane_power = config.show_ane_power.load(Relaxed);
+            let show_gpu_temp = config.show_gpu_temperature.load(Relaxed);
+            let show_codec = config.show_gpu_codec.load(Relaxed);
+            let encoder_alert = config.gpu_encoder_alert.load(Relaxed);
+            let show_latency = config.show_latency.load(Relaxed);
+            let sa = config.show_alerts.load(Relaxed);
+            let thresholds = config.alert_thresholds();
+            let net_unit = config.net_unit();
+            let show_load = config.cpu_display() == CpuDisplay::LoadAverage;
+            let show_split = config.cpu_display() == CpuDisplay::UserSystem;
+            let show_heatmap = config.cpu_display() == CpuDisplay::Heatmap;
+            let show_mem_used = config.mem_display() == MemDisplay::Used;
+            let show_mem_pressure = config.mem_display() == MemDisplay::Pressure;
+            let show_mem_breakdown = config.mem_display() == MemDisplay::Breakdown;
+
+            #[cfg(any(target_os = "linux", target_os = "macos"))]
             let current_flags = (sc, sm, sg, sn, sa, detect_light_icons());
-            #[cfg(not(target_os = "linux"))]
+            #[cfg(not(any(target_os = "linux", target_os = "macos")))]
             let current_flags = (sc, sm, sg, sn, sa, false);
 
-            let flags_changed = prev_flags != current_flags;
-            let net_was_enabled = prev_flags.3;
+            let layout = config.layout();
+            let value_style = config.value_style();
+            let combine_net = config.combine_net.load(Relaxed);
+            let auto_width = config.auto_width.load(Relaxed);
+            renderer.set_auto_width(auto_width);
+            let tabular = config.tabular_figures.load(Relaxed);
+            renderer.set_tabular_figures(tabular);
+            let label_style = config.label_style();
+            renderer.set_label_style(label_style);
+            let percent_cap = config.percent_cap();
+            // The panel's text color unless a text color is set
+            #[cfg(any(target_os = "linux", target_os = "macos"))]
+            let palette = config.palette_on(theme_foreground());
+            #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+            let palette = config.palette(current_flags.5);
+            let sizing = app_sizing(&config);
+
+            let revision = config.font_revision();
+            let font_changed = revision != font_revision;
+            if font_changed {
+                font = load_font(config.font_family().as_deref(), config.font_weight());
+                font_revision = revision;
+                renderer.clear_font_caches();
+            }
 
-            // Refresh only metrics currently visible in the tray
-            if sc {
-                sys.refresh_cpu_usage();
+            // Refresh only metrics currently visible in the tray, unless history
+            // recording, the status server or the D-Bus service needs all of them
+            let record_history = history_dir.is_some() && config.history_enabled.load(Relaxed);
+            let serve_status = config.status_server_enabled.load(Relaxed);
+            let publish_dbus = cfg!(target_os = "linux") && config.dbus_enabled.load(Relaxed);
+            // The tooltip lists hidden segments, so it needs every metric while
+            // it can be seen
+            let tooltip_visible = TOOLTIP_SUPPORTED && config.tray_hovered.load(Relaxed);
+            let sample_all = record_history || serve_status || publish_dbus || tooltip_visible;
+            // The details block shows every metric, hidden segments included
+            let show_details = config.show_details.load(Relaxed);
+            let sample_cpu = sc || sample_all || show_details;
+            let sample_mem = sm || sample_all || show_details;
+            // A GPU that went away is still polled so its segment returns with it
+            let sample_gpu = sg
+                || (gpu_available
+                    && (sample_all
+                        || show_details
+                        || (show_gpu_enabled && shown_remote.is_none())));
+            let sample_gpu_memory = sgm || ((sample_all || show_details) && gpu_available);
+            let sample_net = sn || sample_all || show_details;
+
+            let show_frequency = config.show_cpu_frequency.load(Relaxed);
+            config.configure_samplers();
+            samplers.sample(|metric| match metric {
+                sampler::name::CPU => sample_cpu,
+                sampler::name::LOAD_AVERAGE => sc && show_load,
+                sampler::name::CPU_SPLIT => sc && show_split,
+                sampler::name::CPU_CORES => sc && show_heatmap,
+                sampler::name::CPU_FREQUENCY => show_frequency,
+                sampler::name::MEMORY => sample_mem,
+                sampler::name::MEMORY_USED => sm && show_mem_used,
+                sampler::name::MEMORY_PRESSURE => sm && show_mem_pressure,
+                // The tooltip lists the classes, so it needs them too
+                sampler::name::MEMORY_BREAKDOWN => sm && show_mem_breakdown || tooltip_visible,
+                sampler::name::GPU => sample_gpu,
+                sampler::name::GPU_MEMORY => sample_gpu_memory,
+                sampler::name::NETWORK => sample_net,
+                sampler::name::CPU_POWER => show_power || sample_all,
+                sampler::name::ANE_POWER => show_ane_power || tooltip_visible,
+                // Throttling also turns the CPU and GPU segments the alert color
+                sampler::name::GPU_TEMPERATURE => show_gpu_temp || sg || sample_all,
+                sampler::name::GPU_CODEC => show_codec || encoder_alert,
+                sampler::name::CPU_THROTTLING => sc || sample_all,
+                _ => sample_all,
+            });
+
+            // A remote source replaces the readings the tray shows; readings it
+            // doesn't export are left out rather than mixed in from this machine
+            let cpu =
+                shown_remote.map_or_else(|| samplers.get(sampler::name::CPU).percent(), |s| s.cpu);
+            let cpu_load = samplers
+                .get(sampler::name::LOAD_AVERAGE)
+                .scalar()
+                .filter(|_| shown_remote.is_none());
+            let cpu_split = samplers
+                .get(sampler::name::CPU_SPLIT)
+                .cpu_split()
+                .filter(|_| shown_remote.is_none());
+            let cpu_heatmap = samplers
+                .get(sampler::name::CPU_CORES)
+                .cores()
+                .filter(|_| shown_remote.is_none());
+            let mem = shown_remote
+                .map_or_else(|| samplers.get(sampler::name::MEMORY).percent(), |s| s.mem);
+            let mem_used = samplers
+                .get(sampler::name::MEMORY_USED)
+                .bytes()
+                .filter(|_| shown_remote.is_none());
+            let mem_pressure = samplers
+                .get(sampler::name::MEMORY_PRESSURE)
+                .memory_pressure()
+                .filter(|_| shown_remote.is_none());
+            let mem_classes = samplers
+                .get(sampler::name::MEMORY_BREAKDOWN)
+                .memory_breakdown()
+                .filter(|_| shown_remote.is_none());
+            let mem_breakdown = mem_classes.filter(|_| show_mem_breakdown);
+            let gpu =
+                shown_remote.map_or_else(|| samplers.get(sampler::name::GPU).percent(), |s| s.gpu);
+            let gpu_mem = shown_remote.map_or_else(
+                || samplers.get(sampler::name::GPU_MEMORY).bytes(),
+                |s| s.gpu_mem,
+            );
+            let net = shown_remote.map_or_else(
+                || samplers.get(sampler::name::NETWORK).rate(),
+                |s| s.net_down.zip(s.net_up),
+            );
+            let cpu_power = shown_remote.map_or_else(
+                || samplers.get(sampler::name::CPU_POWER).watts(),
+                |s| s.cpu_power,
+            );
+            let ane_power = samplers
+                .get(sampler::name::ANE_POWER)
+                .watts()
+                .filter(|_| shown_remote.is_none());
+            let gpu_temp = shown_remote.map_or_else(
+                || samplers.get(sampler::name::GPU_TEMPERATURE).temperature(),
+                |s| s.gpu_temp.map(|celsius| (celsius, false)),
+            );
+            let codec = samplers
+                .get(sampler::name::GPU_CODEC)
+                .codec()
+                .filter(|_| shown_remote.is_none());
+            let cpu_throttling = shown_remote.is_none()
+                && samplers
+                    .get(sampler::name::CPU_THROTTLING)
+                    .throttling()
+                    .unwrap_or(false);
+            let gpu_throttling = gpu_temp.is_some_and(|(_, throttling)| throttling);
+
+            // Unsmoothed, for the details block
+            let mut readings = details::Readings {
+                cpu,
+                mem,
+                mem_bytes: None,
+                gpu: gpu.filter(|_| !gpu::is_gone()),
+                gpu_mem,
+                net,
+            };
+
+            let cpu = cpu_ema.update(cpu, config.cpu_smoothing.load(Relaxed));
+            let mem = mem_ema.update(mem, config.mem_smoothing.load(Relaxed));
+            let gpu = gpu_ema.update(gpu, config.gpu_smoothing.load(Relaxed));
+
+            let cpu_usage = cpu.unwrap_or(0.0);
+            let mem_percent = mem.unwrap_or(0.0);
+            let gpu_usage = gpu.unwrap_or(0.0);
+            let (down_speed, up_speed) = net.unwrap_or((0.0, 0.0));
+
+            idle_segments.observe(&Activity { cpu, mem, gpu, net });
+            let segments = idle_segments.filter(
+                visible_segments(config.segment_order(), sc, sm, sg, sn),
+                &config.auto_hide(),
+                config.auto_hide_cycles.load(Relaxed),
+            );
+            let icons = icon_segments(&config.tray_icons(), &segments);
+            // Hidden segments are still sampled, but their changes draw nothing
+            let shown = |kind| segments.contains(&kind);
+
+            let flags_changed = font_changed
+                || prev_flags != current_flags
+                || prev_icons != icons
+                || prev_layout != layout
+                || prev_value_style != value_style
+                || prev_combine_net != combine_net
+                || prev_auto_width != auto_width
+                || prev_tabular != tabular
+                || prev_label_style != label_style
+                || prev_percent_cap != percent_cap
+                || prev_palette != palette
+                || prev_sizing != sizing
+                || prev_thresholds != thresholds
+                || prev_net_unit != net_unit
+                || prev_cpu_load.is_some() != show_load
+                || prev_cpu_split.is_some() != cpu_split.is_some()
+                || prev_cpu_heatmap.is_some() != cpu_heatmap.is_some()
+                || prev_mem_breakdown.is_some() != mem_breakdown.is_some();
+
+            let sample = Sample::from_registry(&samplers);
+            *latest_sample.lock().unwrap_or_else(|e| e.into_inner()) = Some(sample);
+            #[cfg(target_os = "linux")]
+            if publish_dbus {
+                dbus_publisher.publish(sample);
             }
-            if full_tick && sm {
-                sys.refresh_memory();
+            dashboard::record(&recent_samples, sample);
+            charts::record(&app, &chart_history, sample);
+            let finished_minute = {
+                let mut minutes = minute_history.lock().unwrap_or_else(|e| e.into_inner());
+                let finished = minutes.record(sample);
+                if let (Some(_), Some(dir)) = (finished, data_dir.as_deref()) {
+                    if let Err(e) = minutes.save(dir) {
+                        error!("Failed to save minute history: {e}");
+                    }
+                }
+                finished
+            };
+            if let Some(minute) = finished_minute {
+                charts::record_minute(&app, minute);
             }
-            if sn {
-                networks.refresh(false);
+
+            if let Some(dir) = history_dir.as_ref().filter(|_| record_history) {
+                let recorder = history.get_or_insert_with(|| {
+                    HistoryRecorder::new(dir.clone(), config.history_retention_days.load(Relaxed))
+                });
+                recorder.set_retention_days(config.history_retention_days.load(Relaxed));
+                if let Err(e) = recorder.record(&sample) {
+                    error!("Failed to record history: {e}");
+                }
+            } else {
+                // Drop the recorder so the file handle is released while disabled
+                history = None;
             }
 
-            let cpu_usage = if sc { sys.global_cpu_usage() } else { 0.0 };
+            let (net_totals_lines, metered) = {
+                let mut totals = net_totals.lock().unwrap_or_else(|e| e.into_inner());
+                let metered = totals.refresh();
+                (net_totals::menu_lines(&totals), metered)
+            };
+            let data_cap_gb = config.data_cap_gb.load(Relaxed);
+            if (data_cap_gb > 0) != data_usage.is_some() {
+                // Saved before it is dropped, so turning the cap back on resumes the count
+                if let (Some(usage), Some(dir)) = (data_usage.as_mut(), data_dir.as_deref()) {
+                    if let Err(e) = usage.save(dir) {
+                        error!("Failed to save data usage: {e}");
+                    }
+                }
+                data_usage = (data_cap_gb > 0)
+                    .then(|| data_dir.as_deref().map(DataUsage::load).unwrap_or_default());
+            }
+            if let Some(usage) = data_usage.as_mut() {
+                usage.add(&data_cap::current_month(), metered);
+                if let Some(dir) = data_dir.as_deref() {
+                    if let Err(e) = usage.save_if_due(dir) {
+                        error!("Failed to save data usage: {e}");
+                    }
+                }
+            }
+            let data_cap_percent = data_usage
+                .as_ref()
+                .map_or(0.0, |usage| usage.percent_of(data_cap_gb));
 
-            let mem_percent = if sm {
-                let used_mem = sys.used_memory() as f64;
-                let total_mem = sys.total_memory() as f64;
-                if total_mem > 0.0 {
-                    (used_mem / total_mem * 100.0) as f32
-                } else {
-                    0.0
+            let watched_names = config.watched_processes();
+            if watched_names.is_empty() != watched_sys.is_none() {
+                watched_sys = (!watched_names.is_empty()).then(System::new);
+            }
+            let watched_limits = config.watched_limits();
+            let watched_over = match watched_sys.as_mut() {
+                Some(sys) => {
+                    processes::refresh(sys);
+                    let usages = watched::aggregate(&processes::collect(sys), &watched_names);
+                    watched::over_limits(usages, watched_limits)
                 }
-            } else {
-                0.0
+                None => Vec::new(),
             };
 
-            let (down_speed, up_speed) = if sn {
-                let (total_rx, total_tx) = sum_network_totals(&networks);
-                if net_was_enabled {
-                    let down_speed = total_rx.saturating_sub(prev_rx) as f64 / dt;
-                    let up_speed = total_tx.saturating_sub(prev_tx) as f64 / dt;
-                    (prev_rx, prev_tx) = (total_rx, total_tx);
-                    (down_speed, up_speed)
+            let mut alert_changes = Vec::new();
+            {
+                let mut log = alert_log.lock().unwrap_or_else(|e| e.into_inner());
+                let readings = [
+                    (AlertMetric::Cpu, cpu, thresholds.cpu),
+                    (AlertMetric::Memory, mem, thresholds.mem),
+                    (AlertMetric::Gpu, gpu, thresholds.gpu),
+                ];
+                for (metric, value, threshold) in readings {
+                    let value = value.unwrap_or(0.0);
+                    alert_changes.extend(log.observe(
+                        metric,
+                        value,
+                        value >= threshold,
+                        sample.timestamp,
+                    ));
+                }
+                let (celsius, throttling) = gpu_temp.unwrap_or((0.0, false));
+                alert_changes.extend(log.observe(
+                    AlertMetric::GpuThrottling,
+                    celsius,
+                    throttling,
+                    sample.timestamp,
+                ));
+                let encoder = codec.map_or(0.0, |(encoder, _)| encoder);
+                alert_changes.extend(log.observe(
+                    AlertMetric::GpuEncoder,
+                    encoder,
+                    encoder_alert && encoder >= gpu::ENCODER_SATURATED,
+                    sample.timestamp,
+                ));
+                let data_cap_limits = [
+                    (AlertMetric::DataCapNear, data_cap::NEAR_PERCENT),
+                    (AlertMetric::DataCapReached, 100.0),
+                ];
+                for (metric, limit) in data_cap_limits {
+                    alert_changes.extend(log.observe(
+                        metric,
+                        data_cap_percent,
+                        data_cap_percent >= limit,
+                        sample.timestamp,
+                    ));
+                }
+                let watched_peak = watched_over
+                    .iter()
+                    .map(|usage| watched_limits.percent_of(usage))
+                    .fold(0.0, f32::max);
+                alert_changes.extend(log.observe(
+                    AlertMetric::WatchedProcess,
+                    watched_peak,
+                    !watched_over.is_empty(),
+                    sample.timestamp,
+                ));
+            }
+            // Quiet hours hold back sounds and webhooks, never the alert colors
+            let quiet = config
+                .quiet_hours()
+                .filter(|hours| hours.contains(quiet_hours::local_minute_of_day()));
+            if quiet != prev_quiet {
+                if let Some(menu) = app.try_state::<AlertMenu>() {
+                    let _ = menu.0.set_text(alerts::menu_title(quiet));
+                }
+                prev_quiet = quiet;
+            }
+            let webhook_url = config.alert_webhook_url();
+            for change in alert_changes {
+                alerts_changed = true;
+                if quiet.is_none() && !webhook_url.is_empty() {
+                    alerts::send_webhook(webhook_url.clone(), alerts::webhook_payload(&change));
+                }
+                match change {
+                    AlertChange::Started(_) if quiet.is_none() => {
+                        let cooldown = config.alert_sound_cooldown_secs.load(Relaxed);
+                        sounder.alert(config.alert_sound(), Duration::from_secs(cooldown as u64));
+                    }
+                    AlertChange::Ended(event) => {
+                        if let Some(dir) = history_dir.as_ref().filter(|_| record_history) {
+                            if let Err(e) = alerts::append(dir, &event) {
+                                error!("Failed to save alert: {e}");
+                            }
+                        }
+                    }
+                    AlertChange::Started(_) => {}
+                }
+            }
+            if alerts_changed {
+                if let Some(menu) = app.try_state::<AlertMenu>() {
+                    let log = alert_log.lock().unwrap_or_else(|e| e.into_inner());
+                    if let Err(e) = alerts::update_menu(&app, &menu.0, &log) {
+                        error!("Failed to update recent alerts menu: {e}");
+                    }
+                }
+                alerts_changed = false;
+            }
+
+            if tooltip_visible {
+                let mut hidden = Vec::new();
+                if !shown(SegmentKind::Cpu) {
+                    hidden.push(tr_args("CPU: {}", &[&format!("{cpu_usage:.0}%")]));
+                }
+                if !shown(SegmentKind::Memory) {
+                    hidden.push(tr_args("Memory: {}", &[&format!("{mem_percent:.0}%")]));
+                }
+                if let Some(classes) = mem_classes {
+                    hidden.push(memory::breakdown_line(&classes));
+                }
+                if !shown(SegmentKind::Gpu) && gpu_available {
+                    hidden.push(tr_args("GPU: {}", &[&format!("{gpu_usage:.0}%")]));
+                }
+                if let Some(watts) = cpu_power.filter(|_| !show_power) {
+                    hidden.push(tr_args("CPU Power: {}", &[&format_watts(watts)]));
+                }
+                if let Some(watts) = ane_power.filter(|_| !show_ane_power) {
+                    hidden.push(tr_args("Neural Engine Power: {}", &[&format_watts(watts)]));
+                }
+                if let Some((celsius, _)) = gpu_temp.filter(|_| !show_gpu_temp) {
+                    hidden.push(tr_args(
+                        "GPU Temperature: {}",
+                        &[&format_temperature(celsius)],
+                    ));
+                }
+                if !shown(SegmentKind::Network) {
+                    hidden.push(tr_args(
+                        "Network: ↓{} ↑{}",
+                        &[
+                            &format_net(down_speed, net_unit),
+                            &format_net(up_speed, net_unit),
+                        ],
+                    ));
+                }
+                if let Some(note) = throttling_note(cpu_throttling, gpu_throttling) {
+                    hidden.push(note);
+                }
+                let boot_time = System::boot_time();
+                let tooltip = tray_tooltip(
+                    System::uptime(),
+                    boot_time,
+                    quiet_hours::utc_offset_secs(boot_time as i64),
+                    &hidden,
+                );
+                if tooltip != prev_tooltip {
+                    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+                        let _ = tray.set_tooltip(Some(&tooltip));
+                    }
+                    prev_tooltip = tooltip;
+                }
+            }
+
+            if let Some(block) = app.try_state::<DetailsBlock>() {
+                if show_details != details_sys.is_some() {
+                    details_sys = show_details.then(System::new);
+                }
+                readings.mem_bytes =
+                    details_sys
+                        .as_mut()
+                        .filter(|_| shown_remote.is_none())
+                        .map(|sys| {
+                            sys.refresh_memory();
+                            memory::used_and_total(sys)
+                        });
+                let lines = if show_details {
+                    details::lines(&readings, net_unit)
                 } else {
-                    (prev_rx, prev_tx) = (total_rx, total_tx);
-                    (0.0, 0.0)
+                    Vec::new()
+                };
+                if lines != prev_details {
+                    match block.update(&lines, details_shown) {
+                        Ok(shown) => details_shown = shown,
+                        Err(e) => error!("Failed to update details menu items: {e}"),
+                    }
+                    prev_details = lines;
                 }
-            } else {
-                (0.0, 0.0)
-            };
+            }
+
+            let show_vpn = config.show_vpn.load(Relaxed);
+            let show_pressure = config.show_pressure.load(Relaxed);
+            let show_task_counts = config.show_task_counts.load(Relaxed);
+            let show_wifi = config.show_wifi.load(Relaxed);
+            let wifi_alert_dbm = config.wifi_alert_dbm.load(Relaxed);
+            if let Some(lines) = app.try_state::<DetailLines>() {
+                if let Err(e) =
+                    lines.set_shown(DetailLine::CpuFrequency, show_frequency, details_shown)
+                {
+                    error!("Failed to update CPU frequency menu item: {e}");
+                }
+                if let Some(mhz) = samplers.get(sampler::name::CPU_FREQUENCY).megahertz() {
+                    let text = tr_args("CPU Frequency: {}", &[&format_frequency(mhz)]);
+                    if text != prev_frequency_text {
+                        let _ = lines.set_text(DetailLine::CpuFrequency, &text);
+                        prev_frequency_text = text;
+                    }
+                }
+
+                if show_vpn != tunnels.is_some() {
+                    if let Err(e) = lines.set_shown(DetailLine::Vpn, show_vpn, details_shown) {
+                        error!("Failed to update VPN menu item: {e}");
+                    }
+                    tunnels = show_vpn.then(TunnelMonitor::new);
+                    prev_vpn_text.clear();
+                }
+                if let Some(ref mut monitor) = tunnels {
+                    let text = vpn::status_text(&monitor.active());
+                    if text != prev_vpn_text {
+                        let _ = lines.set_text(DetailLine::Vpn, &text);
+                        prev_vpn_text = text;
+                    }
+                }
+
+                if show_pressure != lines.is_shown(DetailLine::Pressure) {
+                    if let Err(e) =
+                        lines.set_shown(DetailLine::Pressure, show_pressure, details_shown)
+                    {
+                        error!("Failed to update pressure menu item: {e}");
+                    }
+                    prev_pressure_text.clear();
+                }
+                if lines.is_shown(DetailLine::Pressure) {
+                    let text = pressure::status_text(&pressure::read());
+                    if text != prev_pressure_text {
+                        let _ = lines.set_text(DetailLine::Pressure, &text);
+                        prev_pressure_text = text;
+                    }
+                }
+
+                if show_task_counts != task_sys.is_some() {
+                    if let Err(e) =
+                        lines.set_shown(DetailLine::TaskCounts, show_task_counts, details_shown)
+                    {
+                        error!("Failed to update process count menu item: {e}");
+                    }
+                    task_sys = show_task_counts.then(System::new);
+                    prev_task_text.clear();
+                }
+                if let Some(ref mut sys) = task_sys {
+                    let text = task_counts::status_text(task_counts::read(sys));
+                    if text != prev_task_text {
+                        let _ = lines.set_text(DetailLine::TaskCounts, &text);
+                        prev_task_text = text;
+                    }
+                }
+
+                if show_wifi != wifi_monitor.is_some() {
+                    if let Err(e) = lines.set_shown(DetailLine::Wifi, show_wifi, details_shown) {
+                        error!("Failed to update Wi-Fi menu item: {e}");
+                    }
+                    wifi_monitor = show_wifi.then(WifiMonitor::default);
+                    shown_rssi = None;
+                    prev_wifi_text.clear();
+                }
+                if let Some(ref mut monitor) = wifi_monitor {
+                    let status = monitor.status();
+                    shown_rssi = match status.map(|status| status.rssi) {
+                        Some(rssi) if wifi::should_show(shown_rssi, rssi, wifi_alert_dbm) => {
+                            Some(rssi)
+                        }
+                        Some(_) => shown_rssi,
+                        None => None,
+                    };
+                    let text = wifi::status_text(status);
+                    if text != prev_wifi_text {
+                        let _ = lines.set_text(DetailLine::Wifi, &text);
+                        prev_wifi_text = text;
+                    }
+                }
+
+                let show = show_codec && codec.is_some();
+                if let Err(e) = lines.set_shown(DetailLine::GpuCodec, show, details_shown) {
+                    error!("Failed to update GPU encoder menu item: {e}");
+                }
+                if let Some((encoder, decoder)) = codec.filter(|_| show) {
+                    let text = codec_status_text(encoder, decoder);
+                    if text != prev_codec_text {
+                        let _ = lines.set_text(DetailLine::GpuCodec, &text);
+                        prev_codec_text = text;
+                    }
+                }
+
+                let show = !watched_over.is_empty();
+                if show != lines.is_shown(DetailLine::Watched) {
+                    if let Err(e) = lines.set_shown(DetailLine::Watched, show, details_shown) {
+                        error!("Failed to update watched processes menu item: {e}");
+                    }
+                    prev_watched_text.clear();
+                }
+                if show {
+                    let text = watched::status_text(&watched_over);
+                    if text != prev_watched_text {
+                        let _ = lines.set_text(DetailLine::Watched, &text);
+                        prev_watched_text = text;
+                    }
+                }
+            }
+
+            if let Some(menu) = app.try_state::<UpdateMenu>() {
+                let version = app
+                    .try_state::<SharedUpdate>()
+                    .filter(|_| config.check_updates.load(Relaxed))
+                    .and_then(|update| {
+                        let update = update.lock().unwrap_or_else(|e| e.into_inner());
+                        update.as_ref().map(|release| release.version.clone())
+                    });
+                if version != shown_update {
+                    let result = match (&version, &shown_update) {
+                        (Some(_), None) => {
+                            // Right above Preferences…
+                            let index = menu.menu.items().map_or(0, |items| {
+                                items
+                                    .iter()
+                                    .position(|item| item.id() == menu_id::PREFERENCES)
+                                    .unwrap_or(items.len())
+                            });
+                            menu.menu.insert(&menu.item, index)
+                        }
+                        (None, Some(_)) => menu.menu.remove(&menu.item),
+                        _ => Ok(()),
+                    };
+                    if let Err(e) = result {
+                        error!("Failed to update the Update Available menu item: {e}");
+                    }
+                    if let Some(version) = &version {
+                        let _ = menu.item.set_text(i18n::tr_args(
+                            "Update Available ({}) → Install…",
+                            &[version],
+                        ));
+                    }
+                    shown_update = version;
+                }
+            }
+
+            if let Some(menu) = app.try_state::<NetTotalsMenu>() {
+                if net_totals_lines.0 != prev_net_totals_lines.0 {
+                    let _ = menu.session.set_text(&net_totals_lines.0);
+                }
+                if net_totals_lines.1 != prev_net_totals_lines.1 {
+                    let _ = menu.since_boot.set_text(&net_totals_lines.1);
+                }
+                prev_net_totals_lines = net_totals_lines;
+
+                let month_line = data_usage
+                    .as_ref()
+                    .map(|usage| data_cap::menu_line(usage.bytes, data_cap_gb));
+                if month_line.is_some() != prev_month_line.is_some() {
+                    // Below the session and since-boot lines
+                    let result = if month_line.is_some() {
+                        menu.menu.insert(&menu.month, 2)
+                    } else {
+                        menu.menu.remove(&menu.month)
+                    };
+                    if let Err(e) = result {
+                        error!("Failed to update data cap menu item: {e}");
+                    }
+                }
+                if let Some(line) = month_line
+                    .as_ref()
+                    .filter(|line| prev_month_line.as_ref() != Some(line))
+                {
+                    let _ = menu.month.set_text(line);
+                }
+                prev_month_line = month_line;
+            }
+
+            let track_processes = config.show_processes.load(Relaxed);
+            if track_processes != process_sys.is_some() {
+                process_sys = track_processes.then(System::new);
+                if let Some(menu) = app.try_state::<ProcessMenu>() {
+                    let _ = menu.0.set_enabled(track_processes);
+                }
+            }
+            if let Some(ref mut psys) = process_sys {
+                processes::refresh(psys);
+                if let Some(menu) = app.try_state::<ProcessMenu>() {
+                    let top = processes::collect(psys);
+                    if let Err(e) = processes::update_menu(&app, &menu.0, &top) {
+                        error!("Failed to update top processes menu: {e}");
+                    }
+                }
+            }
 
-            if sg && full_tick {
-                if let Some(ref mut sampler) = gpu_sampler {
-                    gpu_usage = sampler.sample().unwrap_or(0.0);
+            let track_talkers = config.show_network_processes.load(Relaxed);
+            if track_talkers != network_talkers.is_some() {
+                network_talkers = track_talkers.then(NetworkTalkers::default);
+                if let Some(menu) = app.try_state::<NetworkProcessMenu>() {
+                    let _ = menu.0.set_enabled(track_talkers);
+                }
+            }
+            if let Some(ref mut talkers) = network_talkers {
+                let top = talkers.sample();
+                if let Some(menu) = app.try_state::<NetworkProcessMenu>() {
+                    if let Err(e) = net_processes::update_menu(&app, &menu.0, &top, net_unit) {
+                        error!("Failed to update top network apps menu: {e}");
+                    }
+                }
+            }
+
+            let track_gpu_processes = gpu_available && config.show_gpu_processes.load(Relaxed);
+            if track_gpu_processes != gpu_process_monitor.is_some() {
+                gpu_process_monitor = track_gpu_processes.then(GpuProcessMonitor::new);
+                if let Some(menu) = app.try_state::<GpuProcessMenu>() {
+                    let _ = menu.0.set_enabled(track_gpu_processes);
+                }
+            }
+            if let Some(top) = gpu_process_monitor
+                .as_mut()
+                .and_then(GpuProcessMonitor::sample)
+            {
+                if let Some(menu) = app.try_state::<GpuProcessMenu>() {
+                    if let Err(e) = gpu_processes::update_menu(&app, &menu.0, &top) {
+                        error!("Failed to update GPU processes menu: {e}");
+                    }
+                }
+            }
+
+            let track_containers = config.show_containers.load(Relaxed);
+            if track_containers != container_monitor.is_some() {
+                container_monitor = track_containers.then(ContainerMonitor::default);
+                if let Some(menu) = app.try_state::<ContainerMenu>() {
+                    let _ = menu.0.set_enabled(track_containers);
+                }
+            }
+            if let Some(stats) = container_monitor
+                .as_mut()
+                .and_then(ContainerMonitor::sample)
+            {
+                if let Some(menu) = app.try_state::<ContainerMenu>() {
+                    if let Err(e) = containers::update_menu(&app, &menu.0, &stats) {
+                        error!("Failed to update containers menu: {e}");
+                    }
                 }
-            } else if !sg {
-                gpu_usage = 0.0;
             }
 
             // Hysteresis: only update if values change by meaningful threshold
             // This dramatically reduces icon updates, preventing compositor resource
             // accumulation that causes cursor slowdown on Ubuntu/GNOME
-            let cpu_changed = should_update(prev_cpu, cpu_usage, HYSTERESIS_THRESHOLD);
-            let mem_changed = should_update(prev_mem, mem_percent, HYSTERESIS_THRESHOLD);
-            let gpu_changed = should_update(prev_gpu, gpu_usage, HYSTERESIS_THRESHOLD);
-            let down_diff = (down_speed - prev_down_speed).abs();
-            let up_diff = (up_speed - prev_up_speed).abs();
-            let net_value_changed =
-                down_diff >= NET_HYSTERESIS_BPS || up_diff >= NET_HYSTERESIS_BPS;
-            let net_changed = sn && net_value_changed;
-
-            if cpu_changed || mem_changed || gpu_changed || net_changed || flags_changed {
-                // Defer string formatting to render time only
-                let down_str = format_speed(down_speed);
-                let up_str = format_speed(up_speed);
+            // Hidden metrics may still be sampled for history, so only visible ones count
+            let heatmap_changed = match (cpu_heatmap, prev_cpu_heatmap) {
+                (Some(cores), Some(prev)) => cores.differs_from(&prev, HYSTERESIS_THRESHOLD),
+                _ => false,
+            };
+            let cpu_changed = shown(SegmentKind::Cpu)
+                && match (cpu_load, prev_cpu_load, cpu_split, prev_cpu_split) {
+                    _ if heatmap_changed => true,
+                    (Some(load), Some(prev), ..) => should_update(prev, load, LOAD_HYSTERESIS),
+                    (_, _, Some((user, system)), Some((prev_user, prev_system))) => {
+                        should_update(prev_user, user, HYSTERESIS_THRESHOLD)
+                            || should_update(prev_system, system, HYSTERESIS_THRESHOLD)
+                    }
+                    _ => should_update(prev_cpu, cpu_usage, HYSTERESIS_THRESHOLD),
+                };
+            // Pressure mode shows Activity Monitor's figure, colored by level
+            let (mem_shown, mem_level) = match mem_pressure.filter(|_| show_mem_pressure) {
+                Some((percent, level)) => (percent, Some(level)),
+                None => (mem_percent, None),
+            };
+            let breakdown_changed = match (mem_breakdown, prev_mem_breakdown) {
+                (Some(classes), Some(prev)) => classes.differs_from(&prev, HYSTERESIS_THRESHOLD),
+                _ => false,
+            };
+            let mem_changed = shown(SegmentKind::Memory)
+                && (should_update(prev_mem, mem_shown, HYSTERESIS_THRESHOLD)
+                    || mem_level != prev_mem_level
+                    || breakdown_changed);
+            let gpu_changed =
+                shown(SegmentKind::Gpu) && should_update(prev_gpu, gpu_usage, HYSTERESIS_THRESHOLD);
+            let net_changed = shown(SegmentKind::Network)
+                && net_hysteresis.changed(
+                    (prev_down_speed, prev_up_speed),
+                    (down_speed, up_speed),
+                    config.net_hysteresis.load(Relaxed),
+                    config.net_hysteresis_ticks.load(Relaxed),
+                );
+            // Compare the formatted value so only visible changes trigger a redraw
+            let gpu_mem_str = gpu_mem.filter(|_| sgm).map(format_bytes);
+            let gpu_mem_changed = gpu_mem_str != prev_gpu_mem_str;
+            let mem_used_str = mem_used.filter(|_| sm && show_mem_used).map(format_bytes);
+            let mem_used_changed = mem_used_str != prev_mem_used_str;
+
+            // Optional segments drawn after the built-in ones
+            let mut extra = Vec::new();
+            if let Some(watts) = cpu_power.filter(|_| show_power) {
+                extra.push(TextSegment {
+                    icon: IconType::Power,
+                    text: format_watts(watts),
+                    alert: false,
+                });
+            }
+            if let Some((celsius, throttling)) = gpu_temp.filter(|_| show_gpu_temp) {
+                // Alert on thermal throttling rather than a fixed temperature,
+                // since safe limits differ between GPUs
+                extra.push(TextSegment {
+                    icon: IconType::Temperature,
+                    text: format_temperature(celsius),
+                    alert: throttling,
+                });
+            }
+            if let Some(watts) = ane_power.filter(|_| show_ane_power) {
+                extra.push(TextSegment {
+                    icon: IconType::NeuralEngine,
+                    text: format_watts(watts),
+                    alert: false,
+                });
+            }
+            if show_latency != pinger.is_some() {
+                pinger = show_latency.then(Pinger::default);
+                shown_latency = None;
+            }
+            let latency_alert_ms = config.latency_alert_ms.load(Relaxed) as f32;
+            if let Some(latency) = pinger
+                .as_mut()
+                .and_then(|pinger| pinger.sample(&config.latency_host()))
+            {
+                if latency::should_show(shown_latency, latency, latency_alert_ms) {
+                    shown_latency = Some(latency);
+                }
+            }
+            if let Some(latency) = shown_latency {
+                let (text, alert) = match latency {
+                    Latency::Reply(ms) => (latency::format_latency(ms), ms >= latency_alert_ms),
+                    Latency::Timeout => ("—".to_string(), true),
+                };
+                extra.push(TextSegment {
+                    icon: IconType::Signal,
+                    text,
+                    alert,
+                });
+            }
+            if let Some(rssi) = shown_rssi {
+                extra.push(TextSegment {
+                    icon: IconType::Wifi,
+                    text: wifi::format_rssi(rssi),
+                    alert: rssi < wifi_alert_dbm,
+                });
+            }
+            let extra_changed = extra != prev_extra;
+
+            // Throttling alerts whatever the usage, since a CPU or GPU held
+            // back to cool down can look idle
+            let throttled = (sc && cpu_throttling, sg && gpu_throttling);
+            let throttling_changed = throttled != prev_throttled;
+            let render_thresholds = AlertThresholds {
+                cpu: if throttled.0 { 0.0 } else { thresholds.cpu },
+                gpu: if throttled.1 { 0.0 } else { thresholds.gpu },
+                ..thresholds
+            };
+
+            let changed = cpu_changed
+                || mem_changed
+                || gpu_changed
+                || gpu_mem_changed
+                || mem_used_changed
+                || extra_changed
+                || throttling_changed
+                || net_changed
+                || flags_changed;
+
+            // Back off while the icon is static; any change restores the configured pace
+            if config.adaptive_sampling.load(Relaxed) {
+                adaptive_interval.record(changed);
+            } else {
+                adaptive_interval.reset();
+            }
+
+            // Samplers may run faster than the icon is redrawn; a change that
+            // comes too soon is drawn on the first tick the render interval allows
+            let render_interval =
+                Duration::from_millis(config.render_interval_ms.load(Relaxed) as u64);
+            let render_due = last_render.is_none_or(|at| at.elapsed() >= render_interval);
+            let redraw = (changed && (render_due || redraw_requested)) || save_icon_to.is_some();
+            if redraw {
+                last_render = Some(Instant::now());
+            }
+
+            if redraw {
+                let reading = Shown {
+                    cpu: cpu_usage,
+                    mem: mem_shown,
+                    gpu: gpu_usage,
+                    down: down_speed,
+                    up: up_speed,
+                };
+                // The first frame goes straight to the reading
+                let frames = match last_shown.filter(|_| config.interpolate_values.load(Relaxed)) {
+                    Some(from) if from != reading => {
+                        interpolation::frames(from, reading, interpolation::SUB_FRAMES)
+                    }
+                    _ => vec![reading],
+                };
+                last_shown = Some(reading);
 
                 if sc {
                     prev_cpu = cpu_usage;
                 }
+                prev_cpu_load = cpu_load;
+                prev_cpu_split = cpu_split;
+                prev_cpu_heatmap = cpu_heatmap;
                 if sm {
-                    prev_mem = mem_percent;
+                    prev_mem = mem_shown;
+                    prev_mem_level = mem_level;
+                    prev_mem_breakdown = mem_breakdown;
                 }
                 if sg {
                     prev_gpu = gpu_usage;
@@ -581,76 +3171,228 @@ fn start_monitoring(
                     prev_down_speed = down_speed;
                     prev_up_speed = up_speed;
                 }
+                prev_gpu_mem_str = gpu_mem_str.clone();
+                prev_mem_used_str = mem_used_str.clone();
+                prev_extra = extra.clone();
                 prev_flags = current_flags;
+                prev_icons = icons.clone();
+                prev_layout = layout;
+                prev_value_style = value_style;
+                prev_combine_net = combine_net;
+                prev_auto_width = auto_width;
+                prev_tabular = tabular;
+                prev_label_style = label_style;
+                prev_percent_cap = percent_cap;
+                prev_palette = palette;
+                prev_sizing = sizing;
+                prev_thresholds = thresholds;
+                prev_throttled = throttled;
+                prev_net_unit = net_unit;
+
+                let frame_interval = interpolation::sub_frame_interval(period);
+                for (frame, shown) in frames.iter().enumerate() {
+                    if frame > 0 {
+                        tokio::time::sleep(frame_interval).await;
+                    }
+                    let last_frame = frame + 1 == frames.len();
+                    if ICONS_REPLACED.swap(false, Relaxed) {
+                        sent_frames.clear();
+                    }
+                    // Defer string formatting to render time only
+                    let down_str = format_net(shown.down, net_unit);
+                    let up_str = format_net(shown.up, net_unit);
+
+                    let render = |renderer: &mut tray_render::TrayRenderer,
+                                  buffer: &mut Vec<u8>,
+                                  sizing: tray_render::Sizing,
+                                  segments: &[SegmentKind],
+                                  extra: &[TextSegment]| {
+                        renderer.render_tray_icon_into(
+                            &font,
+                            buffer,
+                            &tray_render::RenderParams {
+                                sizing,
+                                cpu_usage: shown.cpu,
+                                cpu_load,
+                                cpu_split,
+                                cpu_heatmap,
+                                mem_percent: shown.mem,
+                                mem_used_str: mem_used_str.as_deref(),
+                                mem_pressure: mem_level,
+                                mem_breakdown,
+                                gpu_usage: shown.gpu,
+                                gpu_mem_str: gpu_mem_str.as_deref(),
+                                down_str: &down_str,
+                                up_str: &up_str,
+                                visible_segments: segments,
+                                extra_segments: extra,
+                                layout,
+                                value_style,
+                                combine_net,
+                                percent_cap,
+                                cpu_cores,
+                                show_alerts: sa,
+                                thresholds: render_thresholds,
+                                palette,
+                                background: None,
+                            },
+                        )
+                    };
+
+                    for (index, icon_segments) in icons.iter().enumerate() {
+                        let Some(tray) = app.tray_by_id(&tray_id(index)) else {
+                            // Icons added to `tray_icons` appear after a restart
+                            continue;
+                        };
+                        let extra: &[TextSegment] = if index == 0 { &extra } else { &[] };
+                        let (width, height, _has_active_alert) = render(
+                            &mut renderer,
+                            &mut render_buffer,
+                            sizing,
+                            icon_segments,
+                            extra,
+                        );
+
+                        if let Some(dump) = frame_dump.as_mut().filter(|_| index == 0) {
+                            dump.write(&render_buffer, width, height);
+                        }
 
-                let (width, height, _has_active_alert) = renderer.render_tray_icon_into(
-                    &font,
-                    &mut render_buffer,
-                    APP_SIZING,
-                    cpu_usage,
-                    mem_percent,
-                    gpu_usage,
-                    &down_str,
-                    &up_str,
-                    sc,
-                    sm,
-                    sg,
-                    sn,
-                    sa,
-                    current_flags.5, // Pass the detected theme flag
-                    None,
-                );
+                        if let Some(path) = save_icon_to.take_if(|_| index == 0 && last_frame) {
+                            // Its own renderer, so the tray's auto widths stay as they are
+                            let mut export = tray_render::TrayRenderer::new();
+                            export.set_auto_width(auto_width);
+                            export.set_tabular_figures(tabular);
+                            export.set_label_style(label_style);
+                            let frames = save_icon::SCALES.map(|scale| {
+                                let mut buffer = Vec::new();
+                                let (width, height, _) = render(
+                                    &mut export,
+                                    &mut buffer,
+                                    APP_SIZING.scaled(scale),
+                                    icon_segments,
+                                    extra,
+                                );
+                                (buffer, width, height)
+                            });
+                            match save_icon::write(&path, &frames) {
+                                Ok([one, two]) => {
+                                    info!(
+                                        "Saved the icon to {} and {}",
+                                        one.display(),
+                                        two.display()
+                                    )
+                                }
+                                Err(e) => error!("Failed to save the icon: {e}"),
+                            }
+                        }
 
-                if let Some(tray) = app.tray_by_id(TRAY_ID) {
-                    #[cfg(target_os = "macos")]
-                    {
-                        let use_template = !_has_active_alert;
-                        let icon = tray_icon::Icon::from_rgba(render_buffer.clone(), width, height)
-                            .expect("Failed to create icon");
-                        let _ = tray.with_inner_tray_icon(move |inner| {
-                            inner.set_icon_with_as_template(Some(icon), use_template)
-                        });
-                    }
+                        // Template images are tinted by macOS, which would hide the
+                        // alert color; draw in color while an alert is active, in
+                        // the menu bar's text color, and go back to a template after
+                        #[cfg(target_os = "macos")]
+                        let use_template = !_has_active_alert && config.follows_theme();
+                        #[cfg(not(target_os = "macos"))]
+                        let use_template = false;
+                        // A changed key can still draw the same pixels (e.g. a
+                        // threshold no shown value is near); skip resending them
+                        // over D-Bus or to the status item
+                        let hash =
+                            tray_render::frame_hash(&render_buffer, width, height, use_template);
+                        if sent_frames.insert(index, hash) == Some(hash) {
+                            continue;
+                        }
 
-                    #[cfg(not(target_os = "macos"))]
-                    {
-                        let icon = Image::new_owned(render_buffer.clone(), width, height);
-                        let _ = tray.set_icon(Some(icon));
+                        #[cfg(target_os = "macos")]
+                        {
+                            let icon =
+                                tray_icon::Icon::from_rgba(render_buffer.clone(), width, height)
+                                    .expect("Failed to create icon");
+                            let _ = tray.with_inner_tray_icon(move |inner| {
+                                inner.set_icon_with_as_template(Some(icon), use_template)
+                            });
+                        }
+
+                        #[cfg(not(target_os = "macos"))]
+                        {
+                            let icon = Image::new_owned(render_buffer.clone(), width, height);
+                            let _ = tray.set_icon(Some(icon));
+                        }
                     }
                 }
             }
         }
-    });
+
+        if let (Some(usage), Some(dir)) = (data_usage.as_mut(), data_dir.as_deref()) {
+            if let Err(e) = usage.save(dir) {
+                error!("Failed to save data usage: {e}");
+            }
+        }
+    };
+
+    // Sampling runs subprocesses and reads drivers and sockets, which would
+    // hold up Tauri's async workers; the loop gets a thread of its own
+    tauri::async_runtime::spawn_blocking(
+        move || match tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+        {
+            Ok(runtime) => runtime.block_on(monitoring),
+            Err(e) => error!("Failed to start monitoring: {e}"),
+        },
+    )
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    logging::init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == headless::FLAG) {
+        headless::run(&args);
+        return;
+    }
+    if args.iter().any(|arg| arg == waybar::FLAG) {
+        waybar::run();
+        return;
+    }
+
     #[cfg(target_os = "linux")]
     if let Err(e) = ensure_display_available() {
         eprintln!("{e}");
         std::process::exit(1);
     }
 
-    let show_cpu = Arc::new(AtomicBool::new(true));
-    let show_mem = Arc::new(AtomicBool::new(true));
-    let show_gpu = Arc::new(AtomicBool::new(true));
-    let show_net = Arc::new(AtomicBool::new(true));
-    let show_alerts = Arc::new(AtomicBool::new(true));
-
-    let show_cpu_tray = show_cpu.clone();
-    let show_mem_tray = show_mem.clone();
-    let show_gpu_tray = show_gpu.clone();
-    let show_net_tray = show_net.clone();
-    let show_alerts_tray = show_alerts.clone();
-
-    let gpu_sampler = GpuSampler::new();
-    let gpu_available = gpu_sampler.is_some();
+    let samplers = SamplerRegistry::with_default_samplers();
+    let capabilities = Capabilities {
+        gpu: samplers.contains(sampler::name::GPU),
+        cpu_power: samplers.contains(sampler::name::CPU_POWER),
+        ane_power: samplers.contains(sampler::name::ANE_POWER),
+        gpu_temperature: samplers.contains(sampler::name::GPU_TEMPERATURE),
+        gpu_codec: samplers.contains(sampler::name::GPU_CODEC),
+        dashboard: cfg!(target_os = "macos"),
+        tray_clicks: !cfg!(target_os = "linux"),
+        pressure: pressure::is_available(),
+        dbus: cfg!(target_os = "linux"),
+        update_check: !cfg!(target_os = "macos"),
+        memory_pressure: samplers.contains(sampler::name::MEMORY_PRESSURE),
+        memory_breakdown: samplers.contains(sampler::name::MEMORY_BREAKDOWN),
+        cpu_split: samplers.contains(sampler::name::CPU_SPLIT),
+        cgroup: cgroup::is_available(),
+    };
 
     let builder = tauri::Builder::default()
         .plugin(tauri_plugin_single_instance::init(|_app, _args, _cwd| {
             // No-op: tray-only app, nothing to focus
         }))
-        .plugin(tauri_plugin_store::Builder::new().build());
+        .plugin(tauri_plugin_store::Builder::new().build())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .invoke_handler(tauri::generate_handler![
+            preferences::get_settings,
+            preferences::update_setting,
+            preferences::list_font_families,
+            dashboard::get_recent_samples,
+            charts::get_chart_history
+        ]);
 
     builder
         .setup(move |app| {
@@ -665,45 +3407,144 @@ pub fn run() {
                 ))?;
             }
 
-            #[cfg(target_os = "linux")]
+            #[cfg(any(target_os = "linux", target_os = "macos"))]
             start_theme_detection_thread();
+            #[cfg(target_os = "linux")]
+            start_scale_detection_thread(app.handle().clone());
 
             // Load persisted settings
-            let (cpu, mem, gpu, net, alerts, autostart) = load_settings(app.handle());
-            show_cpu_tray.store(cpu, Relaxed);
-            show_mem_tray.store(mem, Relaxed);
-            show_gpu_tray.store(gpu, Relaxed);
-            show_net_tray.store(net, Relaxed);
-            show_alerts_tray.store(alerts, Relaxed);
-
-            let font =
-                load_system_font().map_err(|e| format!("Font required for tray icon: {e}"))?;
-
-            setup_tray(
-                app.handle(),
-                &font,
-                show_cpu_tray,
-                show_mem_tray,
-                show_gpu_tray,
-                show_net_tray,
-                show_alerts_tray,
-                gpu_available,
-                autostart,
-            )?;
+            let config = Arc::new(Config::load(app.handle()));
+            app.manage(config.clone());
+            app.manage(SharedCapabilities::new(Mutex::new(capabilities)));
+            logging::configure(&config, app.path().app_data_dir().ok().as_deref());
+            i18n::init(&config.language());
+            let available_update = SharedUpdate::default();
+            app.manage(available_update.clone());
+            #[cfg(not(target_os = "macos"))]
+            updates::start(
+                config.clone(),
+                app.package_info().version.to_string(),
+                available_update,
+            );
 
-            start_monitoring(
-                app.handle().clone(),
-                font,
-                show_cpu,
-                show_mem,
-                show_gpu,
-                show_net,
-                show_alerts,
-                gpu_sampler,
+            let font = load_font(config.font_family().as_deref(), config.font_weight());
+
+            setup_tray(app.handle(), &font, config.clone(), capabilities)?;
+            #[cfg(target_os = "linux")]
+            tray_host::check_in_background(app.handle().clone());
+
+            let latest_sample = LatestSample::default();
+            app.manage(latest_sample.clone());
+            app.manage(PendingIconSave::default());
+            let recent_samples = RecentSamples::default();
+            app.manage(recent_samples.clone());
+            let chart_history = ChartHistory::default();
+            app.manage(chart_history.clone());
+            let minute_history: SharedMinuteHistory = Arc::new(Mutex::new(
+                app.path()
+                    .app_data_dir()
+                    .map(|dir| MinuteHistory::load(&dir, metrics::unix_now()))
+                    .unwrap_or_default(),
+            ));
+            app.manage(minute_history.clone());
+            let alert_log = SharedAlertLog::default();
+            app.manage(alert_log.clone());
+            let net_totals = SharedNetTotals::default();
+            app.manage(net_totals.clone());
+            app.manage(LastHidden::default());
+            status_server::start(
+                config.clone(),
+                latest_sample.clone(),
+                minute_history.clone(),
+            );
+            #[cfg(target_os = "linux")]
+            let dbus_publisher = dbus::start(config.clone());
+            screen_lock::start(config.clone());
+            let shutdown = CancellationToken::new();
+            let heartbeat = SharedHeartbeat::default();
+            let spawn_monitoring = {
+                let app = app.handle().clone();
+                let config = config.clone();
+                let shutdown = shutdown.clone();
+                let heartbeat = heartbeat.clone();
+                move |samplers, font| {
+                    let stop = shutdown.child_token();
+                    let task = start_monitoring(
+                        app.clone(),
+                        font,
+                        config.clone(),
+                        samplers,
+                        latest_sample.clone(),
+                        #[cfg(target_os = "linux")]
+                        dbus_publisher.clone(),
+                        recent_samples.clone(),
+                        chart_history.clone(),
+                        minute_history.clone(),
+                        alert_log.clone(),
+                        net_totals.clone(),
+                        stop.clone(),
+                        heartbeat.clone(),
+                    );
+                    (stop, task)
+                }
+            };
+            let task = spawn_monitoring(samplers, font);
+            app.manage(Monitoring {
+                shutdown,
+                task: Mutex::new(Some(task)),
+            });
+
+            let stalled_app = app.handle().clone();
+            let stalled_config = config.clone();
+            let restart_app = app.handle().clone();
+            watchdog::start(
+                heartbeat,
+                move || show_stalled_icon(&stalled_app, &stalled_config),
+                move || {
+                    let Some(monitoring) = restart_app.try_state::<Monitoring>() else {
+                        return;
+                    };
+                    let mut task = monitoring.task.lock().unwrap_or_else(|e| e.into_inner());
+                    // Quitting takes the task, and then nothing should start again
+                    let Some((stalled, _)) = task.take() else {
+                        return;
+                    };
+                    // Ends once its stuck call returns, if ever; the new loop
+                    // runs on a thread of its own
+                    stalled.cancel();
+                    let font = load_font(config.font_family().as_deref(), config.font_weight());
+                    *task = Some(spawn_monitoring(
+                        SamplerRegistry::with_default_samplers(),
+                        font,
+                    ));
+                },
             );
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Hold the exit until the monitoring task has finished its tick, so
+            // quitting never interrupts a tray icon update or a history write.
+            // The event loop keeps running meanwhile, as the tick may need it.
+            if let RunEvent::ExitRequested { code, api, .. } = event {
+                // No code means the last window closed; the tray app keeps running
+                // and only quits on an explicit `app.exit`
+                let Some(code) = code else {
+                    api.prevent_exit();
+                    return;
+                };
+                if let Some(task) = stop_monitoring(app) {
+                    api.prevent_exit();
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if tokio::time::timeout(SHUTDOWN_TIMEOUT, task).await.is_err() {
+                            warn!("Monitoring did not stop within {SHUTDOWN_TIMEOUT:?}");
+                        }
+                        app.exit(code);
+                    });
+                }
+            }
+        });
 }
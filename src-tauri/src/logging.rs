@@ -0,0 +1,238 @@
+//! Logging
+//!
+//! Messages go through `tracing` to stderr and, with `log_to_file` on, to
+//! daily files under `logs/` in the app data directory, keeping the last week.
+//! `log_level` filters both and applies right away. The files are what to ask
+//! for when the tray misbehaves on a desktop we can't reproduce: panels
+//! usually start the app without a visible stderr. The last few warnings and
+//! errors are also kept in memory for the diagnostics report.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+use std::sync::{Mutex, OnceLock};
+
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Level, Subscriber};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, Layer, Registry};
+
+use crate::config::Config;
+use crate::quiet_hours;
+
+pub const LOG_DIR: &str = "logs";
+pub const OPEN_MENU_ID: &str = "open_log_folder";
+const FILE_PREFIX: &str = "better-resource-monitor";
+const KEPT_FILES: usize = 7;
+/// Warnings and errors kept for [`recent_problems`].
+const RECENT_PROBLEMS: usize = 10;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Trace,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|level| level.as_str() == value)
+    }
+
+    pub(crate) fn from_u8(value: u8) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|level| *level as u8 == value)
+            .unwrap_or(LogLevel::Info)
+    }
+
+    fn filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+static LEVEL: OnceLock<reload::Handle<LevelFilter, Registry>> = OnceLock::new();
+static TO_FILE: AtomicBool = AtomicBool::new(false);
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static LOG_FILE: LogFile = LogFile {
+    dir: OnceLock::new(),
+    appender: Mutex::new(None),
+};
+
+/// Installs the subscriber at the `info` level. File output waits for
+/// [`configure`], as the app data directory is only known once Tauri is up.
+pub fn init() {
+    let (level, handle) = reload::Layer::new(LogLevel::Info.filter());
+    let _ = LEVEL.set(handle);
+    let stderr = tracing_subscriber::fmt::layer().with_writer(io::stderr);
+    let file = tracing_subscriber::fmt::layer()
+        .with_ansi(false)
+        .with_writer(&LOG_FILE)
+        .with_filter(filter_fn(|_| TO_FILE.load(Relaxed)));
+    if let Err(e) = tracing_subscriber::registry()
+        .with(level)
+        .with(stderr)
+        .with(file)
+        .with(RecentProblems)
+        .try_init()
+    {
+        eprintln!("Failed to set up logging: {e}");
+    }
+}
+
+/// Applies the logging settings; file output goes under `data_dir`.
+pub fn configure(config: &Config, data_dir: Option<&Path>) {
+    if let Some(dir) = data_dir {
+        let _ = LOG_FILE.dir.set(dir.join(LOG_DIR));
+    }
+    set_level(config.log_level());
+    set_to_file(config.log_to_file.load(Relaxed));
+}
+
+pub fn dir() -> Option<&'static Path> {
+    LOG_FILE.dir.get().map(PathBuf::as_path)
+}
+
+pub fn set_level(level: LogLevel) {
+    if let Some(handle) = LEVEL.get() {
+        if let Err(e) = handle.reload(level.filter()) {
+            tracing::error!("Failed to change the log level: {e}");
+        }
+    }
+}
+
+pub fn set_to_file(enabled: bool) {
+    TO_FILE.store(enabled, Relaxed);
+}
+
+/// The last warnings and errors, oldest first: "14:05 WARN Update check
+/// failed: …".
+pub fn recent_problems() -> Vec<String> {
+    RECENT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .cloned()
+        .collect()
+}
+
+/// Opens the log folder in the file manager, creating it if nothing was
+/// logged to a file yet.
+pub fn open_dir() -> Result<(), String> {
+    let dir = dir().ok_or("No app data directory")?;
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    crate::open_with_default_app(dir.as_os_str())
+}
+
+/// The rotating log file, created on the first message written while file
+/// logging is on, so turning it on later needs no restart.
+struct LogFile {
+    dir: OnceLock<PathBuf>,
+    appender: Mutex<Option<RollingFileAppender>>,
+}
+
+impl MakeWriter<'_> for &'static LogFile {
+    type Writer = LogFileWriter;
+
+    fn make_writer(&self) -> Self::Writer {
+        LogFileWriter(self)
+    }
+}
+
+struct LogFileWriter(&'static LogFile);
+
+impl Write for LogFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(dir) = self.0.dir.get() else {
+            return Ok(buf.len());
+        };
+        let mut appender = self.0.appender.lock().unwrap_or_else(|e| e.into_inner());
+        if appender.is_none() {
+            *appender = Some(
+                RollingFileAppender::builder()
+                    .rotation(Rotation::DAILY)
+                    .filename_prefix(FILE_PREFIX)
+                    .filename_suffix("log")
+                    .max_log_files(KEPT_FILES)
+                    .build(dir)
+                    .map_err(io::Error::other)?,
+            );
+        }
+        match appender.as_mut() {
+            Some(appender) => appender.write(buf),
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut appender = self.0.appender.lock().unwrap_or_else(|e| e.into_inner());
+        match appender.as_mut() {
+            Some(appender) => appender.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Records warnings and errors that pass the level filter into [`RECENT`].
+struct RecentProblems;
+
+impl<S: Subscriber> Layer<S> for RecentProblems {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let level = *event.metadata().level();
+        if level > Level::WARN {
+            return;
+        }
+        let mut message = String::new();
+        event.record(&mut MessageVisitor(&mut message));
+        let time = quiet_hours::format_time(quiet_hours::local_minute_of_day());
+        let mut recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+        if recent.len() == RECENT_PROBLEMS {
+            recent.pop_front();
+        }
+        recent.push_back(format!("{time} {level} {message}"));
+    }
+}
+
+struct MessageVisitor<'a>(&'a mut String);
+
+impl Visit for MessageVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            *self.0 = format!("{value:?}");
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Snapshot of the values produced by one monitoring tick
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::sampler::{name, SamplerRegistry};
+
+/// Metrics that were not sampled this tick (hidden and not needed elsewhere,
+/// or unavailable on this machine) are `None`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Sample {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub cpu: Option<f32>,
+    pub mem: Option<f32>,
+    pub gpu: Option<f32>,
+    /// GPU memory in use, in bytes
+    pub gpu_mem: Option<u64>,
+    /// Bytes per second
+    pub net_down: Option<f64>,
+    /// Bytes per second
+    pub net_up: Option<f64>,
+    /// CPU package power in watts (Apple Silicon only)
+    pub cpu_power: Option<f32>,
+    /// Hottest GPU in °C (NVIDIA only)
+    pub gpu_temp: Option<f32>,
+}
+
+impl Sample {
+    /// The values read by the registry's last `sample` call, stamped now.
+    pub fn from_registry(samplers: &SamplerRegistry) -> Self {
+        let net = samplers.get(name::NETWORK).rate();
+        Self {
+            timestamp: unix_now(),
+            cpu: samplers.get(name::CPU).percent(),
+            mem: samplers.get(name::MEMORY).percent(),
+            gpu: samplers.get(name::GPU).percent(),
+            gpu_mem: samplers.get(name::GPU_MEMORY).bytes(),
+            net_down: net.map(|(down, _)| down),
+            net_up: net.map(|(_, up)| up),
+            cpu_power: samplers.get(name::CPU_POWER).watts(),
+            gpu_temp: samplers
+                .get(name::GPU_TEMPERATURE)
+                .temperature()
+                .map(|(celsius, _)| celsius),
+        }
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,217 @@
+//! Last 24 hours as 1-minute averages, kept across restarts
+//!
+//! Every sample is folded into the average of its minute; finished minutes are
+//! kept for a day and saved to `minutes.bin` under the app data directory, so
+//! the charts window and the status server's `/history` still show the day
+//! after a restart. The file is a fixed-size little-endian record per minute
+//! (about 75 KB for a full day) and is rewritten whole, via a temporary file,
+//! each time a minute finishes. Unlike the CSV history it is always on.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::metrics::Sample;
+
+pub const FILE_NAME: &str = "minutes.bin";
+
+/// Minutes kept, and the longest range the charts window offers.
+pub const MINUTES: usize = 24 * 60;
+
+const MAGIC: &[u8; 4] = b"BRM1";
+/// timestamp u64, cpu/mem/gpu f32, gpu_mem u64, net_down/net_up f64,
+/// cpu_power/gpu_temp f32; missing values are NaN (`u64::MAX` for gpu_mem)
+const RECORD_LEN: usize = 8 + 4 * 3 + 8 + 8 * 2 + 4 * 2;
+
+pub type SharedMinuteHistory = Arc<Mutex<MinuteHistory>>;
+
+#[derive(Default)]
+pub struct MinuteHistory {
+    /// Finished minutes, oldest first, stamped with the minute's start
+    minutes: VecDeque<Sample>,
+    current: Option<MinuteSum>,
+}
+
+impl MinuteHistory {
+    /// The saved minutes from `dir` that are still within a day of `now`;
+    /// empty if there are none or the file can't be read.
+    pub fn load(dir: &Path, now: u64) -> Self {
+        let minutes = fs::read(dir.join(FILE_NAME))
+            .ok()
+            .and_then(|bytes| decode(&bytes))
+            .unwrap_or_default();
+        let mut history = Self {
+            minutes,
+            current: None,
+        };
+        history.trim(now);
+        history
+    }
+
+    /// Adds `sample` to its minute's average, returning the previous minute
+    /// once a sample from a later one arrives.
+    pub fn record(&mut self, sample: Sample) -> Option<Sample> {
+        let minute = sample.timestamp / 60 * 60;
+        let finished = match &mut self.current {
+            Some(sum) if sum.minute == minute => {
+                sum.add(&sample);
+                return None;
+            }
+            Some(sum) => Some(sum.average()),
+            None => None,
+        };
+        let mut sum = MinuteSum::new(minute);
+        sum.add(&sample);
+        self.current = Some(sum);
+
+        let finished = finished?;
+        // A clock set back would otherwise put minutes out of order
+        if self
+            .minutes
+            .back()
+            .is_some_and(|last| last.timestamp >= finished.timestamp)
+        {
+            return None;
+        }
+        self.minutes.push_back(finished);
+        self.trim(sample.timestamp);
+        Some(finished)
+    }
+
+    pub fn minutes(&self) -> impl Iterator<Item = &Sample> {
+        self.minutes.iter()
+    }
+
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(FILE_NAME);
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, encode(self.minutes.iter()))?;
+        fs::rename(temp, path)
+    }
+
+    fn trim(&mut self, now: u64) {
+        let cutoff = now.saturating_sub(MINUTES as u64 * 60);
+        while self
+            .minutes
+            .front()
+            .is_some_and(|minute| minute.timestamp < cutoff)
+        {
+            self.minutes.pop_front();
+        }
+        while self.minutes.len() > MINUTES {
+            self.minutes.pop_front();
+        }
+    }
+}
+
+/// Running sums of one minute's samples.
+struct MinuteSum {
+    minute: u64,
+    /// (sum, samples) for cpu, mem, gpu, gpu_mem, net_down, net_up,
+    /// cpu_power and gpu_temp
+    fields: [(f64, u32); 8],
+}
+
+impl MinuteSum {
+    fn new(minute: u64) -> Self {
+        Self {
+            minute,
+            fields: [(0.0, 0); 8],
+        }
+    }
+
+    fn add(&mut self, sample: &Sample) {
+        let values = [
+            sample.cpu.map(f64::from),
+            sample.mem.map(f64::from),
+            sample.gpu.map(f64::from),
+            sample.gpu_mem.map(|bytes| bytes as f64),
+            sample.net_down,
+            sample.net_up,
+            sample.cpu_power.map(f64::from),
+            sample.gpu_temp.map(f64::from),
+        ];
+        for ((sum, count), value) in self.fields.iter_mut().zip(values) {
+            if let Some(value) = value {
+                *sum += value;
+                *count += 1;
+            }
+        }
+    }
+
+    fn average(&self) -> Sample {
+        let [cpu, mem, gpu, gpu_mem, net_down, net_up, cpu_power, gpu_temp] = self
+            .fields
+            .map(|(sum, count)| (count > 0).then(|| sum / count as f64));
+        Sample {
+            timestamp: self.minute,
+            cpu: cpu.map(|v| v as f32),
+            mem: mem.map(|v| v as f32),
+            gpu: gpu.map(|v| v as f32),
+            gpu_mem: gpu_mem.map(|v| v.round() as u64),
+            net_down,
+            net_up,
+            cpu_power: cpu_power.map(|v| v as f32),
+            gpu_temp: gpu_temp.map(|v| v as f32),
+        }
+    }
+}
+
+pub(crate) fn encode<'a>(minutes: impl Iterator<Item = &'a Sample>) -> Vec<u8> {
+    let f32_or_nan = |value: Option<f32>| value.unwrap_or(f32::NAN).to_le_bytes();
+    let f64_or_nan = |value: Option<f64>| value.unwrap_or(f64::NAN).to_le_bytes();
+    let mut bytes = MAGIC.to_vec();
+    for minute in minutes {
+        bytes.extend(minute.timestamp.to_le_bytes());
+        bytes.extend(f32_or_nan(minute.cpu));
+        bytes.extend(f32_or_nan(minute.mem));
+        bytes.extend(f32_or_nan(minute.gpu));
+        bytes.extend(minute.gpu_mem.unwrap_or(u64::MAX).to_le_bytes());
+        bytes.extend(f64_or_nan(minute.net_down));
+        bytes.extend(f64_or_nan(minute.net_up));
+        bytes.extend(f32_or_nan(minute.cpu_power));
+        bytes.extend(f32_or_nan(minute.gpu_temp));
+    }
+    bytes
+}
+
+/// `None` unless `bytes` is a whole file written by [`encode`].
+pub(crate) fn decode(bytes: &[u8]) -> Option<VecDeque<Sample>> {
+    let records = bytes.strip_prefix(MAGIC)?;
+    if records.len() % RECORD_LEN != 0 {
+        return None;
+    }
+    let minutes = records
+        .chunks_exact(RECORD_LEN)
+        .map(|record| {
+            let mut rest = record;
+            let mut take = |len: usize| {
+                let (field, tail) = rest.split_at(len);
+                rest = tail;
+                field
+            };
+            let u64_at = |field: &[u8]| u64::from_le_bytes(field.try_into().expect("8 bytes"));
+            let f32_at = |field: &[u8]| {
+                Some(f32::from_le_bytes(field.try_into().expect("4 bytes"))).filter(|v| !v.is_nan())
+            };
+            let f64_at = |field: &[u8]| {
+                Some(f64::from_le_bytes(field.try_into().expect("8 bytes"))).filter(|v| !v.is_nan())
+            };
+            Sample {
+                timestamp: u64_at(take(8)),
+                cpu: f32_at(take(4)),
+                mem: f32_at(take(4)),
+                gpu: f32_at(take(4)),
+                gpu_mem: Some(u64_at(take(8))).filter(|&bytes| bytes != u64::MAX),
+                net_down: f64_at(take(8)),
+                net_up: f64_at(take(8)),
+                cpu_power: f32_at(take(4)),
+                gpu_temp: f32_at(take(4)),
+            }
+        })
+        .collect();
+    Some(minutes)
+}
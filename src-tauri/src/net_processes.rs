@@ -0,0 +1,230 @@
+//! Top network talkers submenu
+//!
+//! Attributes traffic to processes by diffing cumulative per-process byte
+//! counters between ticks:
+//!
+//! - Linux: `ss -tinpH`, which maps TCP sockets to processes through their
+//!   socket inodes in `/proc/<pid>/fd` and reports `tcp_info` byte counters.
+//!   Without root only the current user's processes can be attributed.
+//! - macOS: `nettop -P -L 1 -x -J bytes_in,bytes_out`
+//!
+//! Counters belong to sockets, so traffic of connections that closed between
+//! two ticks is not counted. This is meant to spot the noisy app, not to bill it.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use tauri::menu::{MenuItem, Submenu};
+use tauri::{AppHandle, Wry};
+
+use crate::config::NetUnit;
+use crate::i18n::tr;
+
+pub const TOP_COUNT: usize = 3;
+
+/// Cumulative bytes received and sent by one process.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Counters {
+    pub pid: u32,
+    pub name: String,
+    pub rx: u64,
+    pub tx: u64,
+}
+
+/// Bytes per second for one process over the last tick.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProcessTraffic {
+    pub pid: u32,
+    pub name: String,
+    pub down: f64,
+    pub up: f64,
+}
+
+/// Top network apps submenu, disabled while `show_network_processes` is off.
+pub struct NetworkProcessMenu(pub Submenu<Wry>);
+
+#[derive(Default)]
+pub struct NetworkTalkers {
+    previous: HashMap<u32, (u64, u64)>,
+    last: Option<Instant>,
+}
+
+impl NetworkTalkers {
+    /// Reads the counters and returns the busiest processes since the last call.
+    /// The first call only establishes the baseline.
+    pub fn sample(&mut self) -> Vec<ProcessTraffic> {
+        let Some(current) = read_counters() else {
+            return Vec::new();
+        };
+        let now = Instant::now();
+
+        let top = self.last.map_or_else(Vec::new, |last| {
+            let secs = now.duration_since(last).as_secs_f64();
+            top_talkers(&self.previous, &current, secs, TOP_COUNT)
+        });
+
+        self.previous = current.iter().map(|c| (c.pid, (c.rx, c.tx))).collect();
+        self.last = Some(now);
+        top
+    }
+}
+
+/// Ranks processes by combined rate. Processes without a previous reading are
+/// skipped so their lifetime totals don't show up as a burst.
+pub(crate) fn top_talkers(
+    previous: &HashMap<u32, (u64, u64)>,
+    current: &[Counters],
+    secs: f64,
+    n: usize,
+) -> Vec<ProcessTraffic> {
+    if secs <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut talkers: Vec<ProcessTraffic> = current
+        .iter()
+        .filter_map(|c| {
+            let (prev_rx, prev_tx) = previous.get(&c.pid)?;
+            let down = c.rx.saturating_sub(*prev_rx) as f64 / secs;
+            let up = c.tx.saturating_sub(*prev_tx) as f64 / secs;
+            (down + up > 0.0).then(|| ProcessTraffic {
+                pid: c.pid,
+                name: c.name.clone(),
+                down,
+                up,
+            })
+        })
+        .collect();
+
+    talkers.sort_by(|a, b| {
+        (b.down + b.up)
+            .total_cmp(&(a.down + a.up))
+            .then(a.pid.cmp(&b.pid))
+    });
+    talkers.truncate(n);
+    talkers
+}
+
+#[cfg(target_os = "linux")]
+fn read_counters() -> Option<Vec<Counters>> {
+    crate::command_stdout("ss", &["-tinpH"]).map(|out| parse_ss_output(&out))
+}
+
+#[cfg(target_os = "macos")]
+fn read_counters() -> Option<Vec<Counters>> {
+    crate::command_stdout(
+        "nettop",
+        &["-P", "-L", "1", "-x", "-J", "bytes_in,bytes_out"],
+    )
+    .map(|out| parse_nettop_output(&out))
+}
+
+fn add_counters(totals: &mut Vec<Counters>, pid: u32, name: &str, rx: u64, tx: u64) {
+    match totals.iter_mut().find(|c| c.pid == pid) {
+        Some(c) => {
+            c.rx += rx;
+            c.tx += tx;
+        }
+        None => totals.push(Counters {
+            pid,
+            name: name.to_string(),
+            rx,
+            tx,
+        }),
+    }
+}
+
+/// Parses `ss -tinpH`: a socket line with `users:(("name",pid=N,fd=M))` followed
+/// by an indented `tcp_info` line with `bytes_acked:`/`bytes_received:`.
+pub(crate) fn parse_ss_output(output: &str) -> Vec<Counters> {
+    let mut totals = Vec::new();
+    let mut owner: Option<(u32, String)> = None;
+
+    for line in output.lines() {
+        if let Some(users) = line.split("users:((").nth(1) {
+            owner = parse_ss_user(users);
+            continue;
+        }
+
+        let Some((pid, name)) = owner.take() else {
+            continue;
+        };
+        let field = |key: &str| {
+            line.split_whitespace()
+                .find_map(|f| f.strip_prefix(key)?.parse::<u64>().ok())
+        };
+        let tx = field("bytes_acked:").or_else(|| field("bytes_sent:"));
+        let rx = field("bytes_received:");
+        if tx.is_some() || rx.is_some() {
+            add_counters(&mut totals, pid, &name, rx.unwrap_or(0), tx.unwrap_or(0));
+        }
+    }
+
+    totals
+}
+
+/// `"firefox",pid=2345,fd=87)),...` → the first owning process.
+fn parse_ss_user(users: &str) -> Option<(u32, String)> {
+    let name = users.strip_prefix('"')?.split('"').next()?;
+    let pid = users
+        .split(',')
+        .find_map(|f| f.strip_prefix("pid="))?
+        .trim_end_matches(')')
+        .parse()
+        .ok()?;
+    Some((pid, name.to_string()))
+}
+
+/// Parses `nettop -P -x -J bytes_in,bytes_out` CSV: a header row, then
+/// `name.pid,bytes_in,bytes_out,` per process.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn parse_nettop_output(output: &str) -> Vec<Counters> {
+    let mut totals = Vec::new();
+
+    for line in output.lines() {
+        let mut fields = line.split(',');
+        let (Some(process), Some(rx), Some(tx)) = (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let Some((name, pid)) = process.rsplit_once('.') else {
+            continue;
+        };
+        let (Ok(pid), Ok(rx), Ok(tx)) = (pid.parse(), rx.parse(), tx.parse()) else {
+            continue;
+        };
+        add_counters(&mut totals, pid, name, rx, tx);
+    }
+
+    totals
+}
+
+/// Replaces the submenu contents with the current top talkers.
+pub fn update_menu(
+    app: &AppHandle,
+    menu: &Submenu<Wry>,
+    talkers: &[ProcessTraffic],
+    unit: NetUnit,
+) -> tauri::Result<()> {
+    while menu.remove_at(0)?.is_some() {}
+
+    if talkers.is_empty() {
+        menu.append(&MenuItem::new(
+            app,
+            tr("No network activity"),
+            false,
+            None::<&str>,
+        )?)?;
+    }
+    for talker in talkers {
+        let label = format!(
+            "{} — ↓ {} ↑ {}",
+            talker.name,
+            crate::format_net(talker.down, unit),
+            crate::format_net(talker.up, unit)
+        );
+        menu.append(&MenuItem::new(app, label, false, None::<&str>)?)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+//! Data transferred submenu
+//!
+//! Lists how much has been downloaded and uploaded this session (since the app
+//! started or the counters were last reset) and since boot, summed across all
+//! interfaces like the network segment. The session totals add up each
+//! refresh's per-interface deltas, so an interface that goes away doesn't take
+//! its share back out; the since-boot totals are the kernel's own counters and
+//! can't be reset.
+
+use std::sync::{Arc, Mutex};
+
+use sysinfo::Networks;
+use tauri::menu::{MenuItem, Submenu};
+use tauri::Wry;
+
+use crate::data_cap;
+use crate::i18n::{tr, tr_args};
+
+pub const RESET_MENU_ID: &str = "reset_net_totals";
+
+/// Owned jointly by the monitoring loop, which refreshes it every tick, and
+/// the menu handler, which resets it.
+pub type SharedNetTotals = Arc<Mutex<NetTotals>>;
+
+/// The submenu's read-only lines, updated in place.
+pub struct NetTotalsMenu {
+    pub menu: Submenu<Wry>,
+    pub session: MenuItem<Wry>,
+    pub since_boot: MenuItem<Wry>,
+    /// Month's usage against the data cap, in the submenu while a cap is set
+    pub month: MenuItem<Wry>,
+}
+
+pub struct NetTotals {
+    networks: Networks,
+    /// Bytes received and transmitted since start or the last reset
+    session: (u64, u64),
+}
+
+impl Default for NetTotals {
+    fn default() -> Self {
+        Self {
+            networks: Networks::new_with_refreshed_list(),
+            session: (0, 0),
+        }
+    }
+}
+
+impl NetTotals {
+    /// Adds what every interface moved since the previous refresh, and
+    /// returns the bytes moved in both directions by the interfaces that count
+    /// toward the data cap.
+    pub fn refresh(&mut self) -> u64 {
+        self.networks.refresh(true);
+        let mut metered = 0;
+        for (name, data) in &self.networks {
+            self.session.0 += data.received();
+            self.session.1 += data.transmitted();
+            if data_cap::counts_interface(name) {
+                metered += data.received() + data.transmitted();
+            }
+        }
+        metered
+    }
+
+    pub fn reset(&mut self) {
+        self.session = (0, 0);
+    }
+
+    pub fn session(&self) -> (u64, u64) {
+        self.session
+    }
+
+    pub fn since_boot(&self) -> (u64, u64) {
+        self.networks.values().fold((0, 0), |(rx, tx), data| {
+            (rx + data.total_received(), tx + data.total_transmitted())
+        })
+    }
+}
+
+/// Decimal units like file managers use: "850 MB", "4.2 GB", "1.3 TB".
+pub(crate) fn format_total(bytes: u64) -> String {
+    let mb = bytes as f64 / 1_000_000.0;
+    if mb >= 999_950.0 {
+        format!("{:.1} {}", mb / 1_000_000.0, tr("TB"))
+    } else if mb >= 999.5 {
+        format!("{:.1} {}", mb / 1_000.0, tr("GB"))
+    } else {
+        format!("{mb:.0} {}", tr("MB"))
+    }
+}
+
+/// "Session: 4.2 GB down / 1.1 GB up".
+pub(crate) fn line(label: &'static str, (down, up): (u64, u64)) -> String {
+    let totals = tr_args("{} down / {} up", &[&format_total(down), &format_total(up)]);
+    format!("{}: {totals}", tr(label))
+}
+
+/// Texts of the session and since-boot lines.
+pub fn menu_lines(totals: &NetTotals) -> (String, String) {
+    (
+        line("Session", totals.session()),
+        line("Since Boot", totals.since_boot()),
+    )
+}
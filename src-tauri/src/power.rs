@@ -0,0 +1,102 @@
+//! Power source detection
+//!
+//! Lets the monitoring loop slow down while the machine runs on battery (or in
+//! macOS Low Power Mode) so the monitor itself costs less energy.
+//!
+//! - Linux: `/sys/class/power_supply/*/{type,online}`
+//! - macOS: `pmset -g batt` and `pmset -g` (for `lowpowermode`)
+
+use std::time::{Duration, Instant};
+
+/// Multiple of the configured interval used while on battery.
+pub const BATTERY_INTERVAL_MULTIPLIER: u64 = 3;
+
+/// Power state changes rarely; re-checking every tick would cost more than it saves.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Default)]
+pub struct PowerMonitor {
+    last_check: Option<Instant>,
+    low_power: bool,
+}
+
+impl PowerMonitor {
+    /// True when running on battery or in a low power mode, cached for `CHECK_INTERVAL`.
+    pub fn is_low_power(&mut self) -> bool {
+        let stale = self
+            .last_check
+            .is_none_or(|checked| checked.elapsed() >= CHECK_INTERVAL);
+        if stale {
+            self.low_power = detect_low_power();
+            self.last_check = Some(Instant::now());
+        }
+        self.low_power
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn detect_low_power() -> bool {
+    let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+
+    let supplies: Vec<(String, bool)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let kind = std::fs::read_to_string(path.join("type")).ok()?;
+            let online = std::fs::read_to_string(path.join("online"))
+                .map(|v| v.trim() == "1")
+                .unwrap_or(false);
+            Some((kind.trim().to_string(), online))
+        })
+        .collect();
+
+    on_battery_from_supplies(supplies.iter().map(|(k, o)| (k.as_str(), *o)))
+}
+
+/// On battery when a battery exists and no mains/USB supply is online.
+/// Desktops without a battery never count as on battery.
+#[cfg(target_os = "linux")]
+pub(crate) fn on_battery_from_supplies<'a>(
+    supplies: impl IntoIterator<Item = (&'a str, bool)>,
+) -> bool {
+    let mut has_battery = false;
+    for (kind, online) in supplies {
+        match kind {
+            "Battery" => has_battery = true,
+            "Mains" | "USB" if online => return false,
+            _ => {}
+        }
+    }
+    has_battery
+}
+
+#[cfg(target_os = "macos")]
+fn detect_low_power() -> bool {
+    let pmset = |args: &[&str]| {
+        std::process::Command::new("pmset")
+            .args(args)
+            .output()
+            .ok()
+            .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+    };
+
+    pmset(&["-g", "batt"]).is_some_and(|out| parse_pmset_on_battery(&out))
+        || pmset(&["-g"]).is_some_and(|out| parse_pmset_low_power_mode(&out))
+}
+
+/// `pmset -g batt` starts with "Now drawing from 'Battery Power'" on battery.
+#[cfg(target_os = "macos")]
+pub(crate) fn parse_pmset_on_battery(output: &str) -> bool {
+    output.contains("'Battery Power'")
+}
+
+/// `pmset -g` lists ` lowpowermode         1` when Low Power Mode is on.
+#[cfg(target_os = "macos")]
+pub(crate) fn parse_pmset_low_power_mode(output: &str) -> bool {
+    output.lines().any(|line| {
+        let mut parts = line.split_whitespace();
+        parts.next() == Some("lowpowermode") && parts.next() == Some("1")
+    })
+}
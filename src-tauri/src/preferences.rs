@@ -0,0 +1,148 @@
+//! Preferences window
+//!
+//! A small webview (`ui/settings.html`) opened from the tray menu. It reads and
+//! writes settings through the commands below, which go through the same
+//! validation as the tray menu and keep its check marks in sync.
+
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindowBuilder};
+
+use crate::config::{key, save_setting, Config};
+use crate::logging;
+
+pub const WINDOW_LABEL: &str = "settings";
+
+/// Optional samplers found at startup, or for the GPU later on; the window
+/// hides options for missing ones.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Capabilities {
+    pub gpu: bool,
+    pub cpu_power: bool,
+    pub ane_power: bool,
+    pub gpu_temperature: bool,
+    /// NVENC/NVDEC utilization can be read (NVML)
+    pub gpu_codec: bool,
+    /// The dashboard popover is implemented on this platform
+    pub dashboard: bool,
+    /// The tray reports icon clicks, so click actions can be offered
+    pub tray_clicks: bool,
+    /// The kernel reports pressure stall information (Linux 4.20+)
+    pub pressure: bool,
+    /// The D-Bus metrics service is implemented on this platform
+    pub dbus: bool,
+    /// The update check is built (not on macOS, where the App Store updates)
+    pub update_check: bool,
+    /// The kernel reports a memory pressure level (macOS)
+    pub memory_pressure: bool,
+    /// Memory can be split into apps, wired, cached and free (Linux, macOS)
+    pub memory_breakdown: bool,
+    /// Per-state CPU time counters can be read (Linux, macOS)
+    pub cpu_split: bool,
+    /// A cgroup v2 hierarchy is mounted (Linux)
+    pub cgroup: bool,
+}
+
+/// Managed as shared state, as a GPU found later turns its capabilities on.
+pub type SharedCapabilities = Arc<Mutex<Capabilities>>;
+
+pub fn open(app: &AppHandle) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        window.show()?;
+        window.set_focus()?;
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::App("settings.html".into()))
+        .title("Preferences")
+        .inner_size(460.0, 420.0)
+        .resizable(false)
+        .maximizable(false)
+        .minimizable(false)
+        .build()?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_settings(
+    config: State<'_, Arc<Config>>,
+    capabilities: State<'_, SharedCapabilities>,
+) -> Value {
+    let capabilities = *capabilities.lock().unwrap_or_else(|e| e.into_inner());
+    json!({
+        "settings": config.to_json(),
+        "gpu_available": capabilities.gpu,
+        "cpu_power_available": capabilities.cpu_power,
+        "ane_power_available": capabilities.ane_power,
+        "gpu_temperature_available": capabilities.gpu_temperature,
+        "gpu_codec_available": capabilities.gpu_codec,
+        "dashboard_available": capabilities.dashboard,
+        "tray_clicks_available": capabilities.tray_clicks,
+        "pressure_available": capabilities.pressure,
+        "dbus_available": capabilities.dbus,
+        "update_check_available": capabilities.update_check,
+        "memory_pressure_available": capabilities.memory_pressure,
+        "memory_breakdown_available": capabilities.memory_breakdown,
+        "cpu_split_available": capabilities.cpu_split,
+        "cgroup_available": capabilities.cgroup,
+    })
+}
+
+/// Installed font families for the font picker, sorted and deduplicated.
+#[tauri::command]
+pub fn list_font_families() -> Vec<String> {
+    let mut families = font_kit::source::SystemSource::new()
+        .all_families()
+        .unwrap_or_default();
+    families.sort_by_key(|family| family.to_lowercase());
+    families.dedup();
+    families
+}
+
+#[tauri::command]
+pub fn update_setting(
+    app: AppHandle,
+    config: State<'_, Arc<Config>>,
+    key: String,
+    value: Value,
+) -> Result<Value, String> {
+    if is_last_visible_segment(&config, &key, &value) {
+        return Err("At least one metric must stay visible".to_string());
+    }
+
+    let saved = config.set(&key, &value)?;
+    if key == key::AUTOSTART {
+        crate::apply_autostart(&app, config.autostart.load(Relaxed));
+    }
+    if key == key::CLICK_ACTION {
+        crate::apply_click_action(&app, config.click_action());
+    }
+    if key == key::LOG_LEVEL {
+        logging::set_level(config.log_level());
+    }
+    if key == key::LOG_TO_FILE {
+        logging::set_to_file(config.log_to_file.load(Relaxed));
+    }
+    save_setting(&app, &key, saved.clone());
+    crate::sync_menu_checks(&app);
+    Ok(saved)
+}
+
+/// Mirrors the tray menu rule that the icon never ends up empty.
+fn is_last_visible_segment(config: &Config, key: &str, value: &Value) -> bool {
+    let segments = [
+        (key::SHOW_CPU, &config.show_cpu),
+        (key::SHOW_MEM, &config.show_mem),
+        (key::SHOW_GPU, &config.show_gpu),
+        (key::SHOW_NET, &config.show_net),
+    ];
+    if value.as_bool() != Some(false) || !segments.iter().any(|(k, _)| *k == key) {
+        return false;
+    }
+
+    segments
+        .iter()
+        .all(|(k, flag)| *k == key || !flag.load(Relaxed))
+}
@@ -0,0 +1,69 @@
+//! Pressure stall information line (Linux)
+//!
+//! Reads `/proc/pressure/{cpu,memory,io}` (kernel 4.20+ with PSI enabled) and
+//! shows the `some avg10` values in the tray menu: the share of the last ten
+//! seconds in which at least one task was stalled waiting for that resource.
+//! Unlike utilization, this only rises when the machine is actually struggling.
+
+use std::fs;
+use std::path::Path;
+
+use crate::i18n::{tr, tr_args};
+
+const PRESSURE_DIR: &str = "/proc/pressure";
+
+/// `some avg10` percentages; `None` for a resource the kernel doesn't report.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pressure {
+    pub cpu: Option<f32>,
+    pub memory: Option<f32>,
+    pub io: Option<f32>,
+}
+
+/// Whether the kernel exposes PSI at all.
+pub fn is_available() -> bool {
+    Path::new(PRESSURE_DIR).join("cpu").exists()
+}
+
+pub fn read() -> Pressure {
+    let avg10 = |resource: &str| {
+        fs::read_to_string(Path::new(PRESSURE_DIR).join(resource))
+            .ok()
+            .and_then(|contents| parse_some_avg10(&contents))
+    };
+    Pressure {
+        cpu: avg10("cpu"),
+        memory: avg10("memory"),
+        io: avg10("io"),
+    }
+}
+
+/// The `avg10` field of the `some` line, e.g.
+/// `some avg10=1.53 avg60=0.87 avg300=0.42 total=1234567`.
+pub(crate) fn parse_some_avg10(contents: &str) -> Option<f32> {
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix("some "))?
+        .split_whitespace()
+        .find_map(|field| field.strip_prefix("avg10="))?
+        .parse()
+        .ok()
+}
+
+/// "Pressure: CPU 2.1% · Memory 0.0% · IO 5.3%".
+pub(crate) fn status_text(pressure: &Pressure) -> String {
+    let parts: Vec<String> = [
+        (tr("CPU"), pressure.cpu),
+        (tr("Memory"), pressure.memory),
+        ("IO", pressure.io),
+    ]
+    .into_iter()
+    .filter_map(|(label, value)| value.map(|v| format!("{label} {v:.1}%")))
+    .collect();
+
+    if parts.is_empty() {
+        tr("Pressure: unavailable").to_string()
+    } else {
+        tr_args("Pressure: {}", &[&parts.join(" · ")])
+    }
+}
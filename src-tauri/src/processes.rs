@@ -0,0 +1,154 @@
+//! Top processes submenu
+//!
+//! When enabled, the monitoring loop refreshes sysinfo's process list every tick
+//! and rebuilds a tray submenu with the heaviest CPU and memory consumers.
+//! Selecting a process asks for confirmation before killing it.
+
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::menu::{MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Wry};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tracing::error;
+
+use crate::i18n::tr;
+
+pub(crate) use better_resource_monitor_metrics::memory::format_memory;
+
+pub const TOP_COUNT: usize = 5;
+const KILL_ID_PREFIX: &str = "kill:";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    /// Percent of one core; can exceed 100 for multi-threaded processes
+    pub cpu: f32,
+    /// Resident memory in bytes
+    pub memory: u64,
+}
+
+/// The top processes submenu, refilled every tick while enabled.
+pub struct ProcessMenu(pub Submenu<Wry>);
+
+/// Refreshes only the CPU and memory figures needed for ranking. Threads are
+/// left out: on Linux sysinfo would otherwise list each one as a process with
+/// its parent's name and memory.
+pub fn refresh(sys: &mut System) {
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing()
+            .with_cpu()
+            .with_memory()
+            .without_tasks(),
+    );
+}
+
+/// Every process, without threads listed by an earlier refresh of `sys`.
+pub fn collect(sys: &System) -> Vec<ProcessInfo> {
+    sys.processes()
+        .iter()
+        .filter(|(_, process)| process.thread_kind().is_none())
+        .map(|(pid, process)| ProcessInfo {
+            pid: pid.as_u32(),
+            name: process.name().to_string_lossy().into_owned(),
+            cpu: process.cpu_usage(),
+            memory: process.memory(),
+        })
+        .collect()
+}
+
+pub fn top_by_cpu(processes: &[ProcessInfo], n: usize) -> Vec<&ProcessInfo> {
+    let mut sorted: Vec<&ProcessInfo> = processes.iter().collect();
+    sorted.sort_by(|a, b| b.cpu.total_cmp(&a.cpu).then(a.pid.cmp(&b.pid)));
+    sorted.truncate(n);
+    sorted
+}
+
+pub fn top_by_memory(processes: &[ProcessInfo], n: usize) -> Vec<&ProcessInfo> {
+    let mut sorted: Vec<&ProcessInfo> = processes.iter().collect();
+    sorted.sort_by(|a, b| b.memory.cmp(&a.memory).then(a.pid.cmp(&b.pid)));
+    sorted.truncate(n);
+    sorted
+}
+
+pub(crate) fn kill_menu_id(pid: u32) -> String {
+    format!("{KILL_ID_PREFIX}{pid}")
+}
+
+pub(crate) fn parse_kill_menu_id(id: &str) -> Option<u32> {
+    id.strip_prefix(KILL_ID_PREFIX)?.parse().ok()
+}
+
+/// Replaces the submenu contents with the current top consumers.
+pub fn update_menu(
+    app: &AppHandle,
+    menu: &Submenu<Wry>,
+    processes: &[ProcessInfo],
+) -> tauri::Result<()> {
+    while menu.remove_at(0)?.is_some() {}
+
+    menu.append(&MenuItem::new(app, tr("By CPU"), false, None::<&str>)?)?;
+    for process in top_by_cpu(processes, TOP_COUNT) {
+        let label = format!("{} — {:.1}%", process.name, process.cpu);
+        menu.append(&process_item(app, process, label)?)?;
+    }
+
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+
+    menu.append(&MenuItem::new(app, tr("By Memory"), false, None::<&str>)?)?;
+    for process in top_by_memory(processes, TOP_COUNT) {
+        let label = format!("{} — {}", process.name, format_memory(process.memory));
+        menu.append(&process_item(app, process, label)?)?;
+    }
+
+    Ok(())
+}
+
+fn process_item(
+    app: &AppHandle,
+    process: &ProcessInfo,
+    label: String,
+) -> tauri::Result<MenuItem<Wry>> {
+    MenuItem::with_id(app, kill_menu_id(process.pid), label, true, None::<&str>)
+}
+
+/// Shows a confirmation dialog and kills the process if the user agrees.
+pub fn confirm_kill(app: &AppHandle, pid: u32) {
+    let pid = Pid::from_u32(pid);
+    let Some(name) = process_name(pid) else {
+        return;
+    };
+
+    app.dialog()
+        .message(format!(
+            "Force quit \"{name}\" (PID {pid})? Any unsaved work in it will be lost."
+        ))
+        .title("Force Quit Process")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Force Quit".to_string(),
+            "Cancel".to_string(),
+        ))
+        .show(move |confirmed| {
+            if confirmed {
+                kill(pid);
+            }
+        });
+}
+
+fn process_name(pid: Pid) -> Option<String> {
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    sys.process(pid)
+        .map(|p| p.name().to_string_lossy().into_owned())
+}
+
+fn kill(pid: Pid) {
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    match sys.process(pid) {
+        Some(process) if !process.kill() => error!("Failed to kill process {pid}"),
+        _ => {}
+    }
+}
@@ -0,0 +1,214 @@
+//! Switchable setting profiles
+//!
+//! A profile is a named set of display settings (which segments are shown,
+//! units, layout, update interval) picked from the "Profile" submenu. Choosing
+//! one writes its settings like any other change, so everything it doesn't
+//! mention is left alone and later tweaks are kept until another profile is
+//! chosen.
+//!
+//! Profiles live in `settings.json` under `profiles`, e.g.
+//! `[{"name": "Minimal", "settings": {"show_cpu": true, "show_net": false}}]`,
+//! and default to the built-in ones below. The submenu is built at startup, so
+//! edits to the list show up after a restart.
+
+use serde_json::{json, Map, Value};
+use tauri::AppHandle;
+use tracing::{error, warn};
+
+use crate::config::{self, key, Config};
+
+const MENU_ID_PREFIX: &str = "profile";
+const MAX_NAME_LEN: usize = 64;
+
+/// Settings a profile may carry. Anything else, such as autostart or the
+/// status server, is not a matter of what the tray shows.
+pub const PROFILE_KEYS: &[&str] = &[
+    key::SHOW_CPU,
+    key::SHOW_MEM,
+    key::SHOW_GPU,
+    key::SHOW_GPU_MEMORY,
+    key::SHOW_NET,
+    key::SHOW_CPU_POWER,
+    key::SHOW_ANE_POWER,
+    key::SHOW_GPU_TEMPERATURE,
+    key::SHOW_GPU_CODEC,
+    key::SHOW_LATENCY,
+    key::SHOW_WIFI,
+    key::SHOW_CPU_FREQUENCY,
+    key::SHOW_VPN,
+    key::SHOW_PRESSURE,
+    key::SHOW_TASK_COUNTS,
+    key::SHOW_DETAILS,
+    key::SEGMENT_ORDER,
+    key::LAYOUT,
+    key::VALUE_STYLE,
+    key::COMBINE_NET,
+    key::AUTO_WIDTH,
+    key::TABULAR_FIGURES,
+    key::NET_UNIT,
+    key::CPU_DISPLAY,
+    key::MEM_DISPLAY,
+    key::PERCENT_CAP,
+    key::UPDATE_INTERVAL_MS,
+];
+
+/// Segments the tray menu always keeps at least one of.
+const SEGMENT_KEYS: [&str; 4] = [key::SHOW_CPU, key::SHOW_MEM, key::SHOW_GPU, key::SHOW_NET];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    /// Sanitized values keyed by setting name, applied in this order
+    pub settings: Map<String, Value>,
+}
+
+impl Profile {
+    pub fn to_json(&self) -> Value {
+        json!({ "name": self.name, "settings": self.settings })
+    }
+}
+
+/// Used until the settings file defines its own list.
+pub fn built_in() -> Vec<Profile> {
+    let profile = |name: &str, settings: Value| Profile {
+        name: name.to_string(),
+        settings: match settings {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        },
+    };
+    vec![
+        profile(
+            "Minimal",
+            json!({
+                "show_cpu": true,
+                "show_mem": true,
+                "show_gpu": false,
+                "show_gpu_memory": false,
+                "show_net": false,
+                "show_latency": false,
+                "layout": "compact",
+                "update_interval_ms": config::DEFAULT_UPDATE_INTERVAL_MS,
+            }),
+        ),
+        profile(
+            "Full",
+            json!({
+                "show_cpu": true,
+                "show_mem": true,
+                "show_gpu": true,
+                "show_gpu_memory": true,
+                "show_net": true,
+                "show_latency": true,
+                "layout": "standard",
+                "update_interval_ms": config::DEFAULT_UPDATE_INTERVAL_MS,
+            }),
+        ),
+        profile(
+            "Network debugging",
+            json!({
+                "show_cpu": false,
+                "show_mem": false,
+                "show_gpu": false,
+                "show_gpu_memory": false,
+                "show_net": true,
+                "show_latency": true,
+                "net_unit": "bits",
+                "layout": "standard",
+                "update_interval_ms": 1000,
+            }),
+        ),
+    ]
+}
+
+/// Validates a `profiles` setting: an array of uniquely named profiles whose
+/// settings are all [`PROFILE_KEYS`] with valid values.
+pub fn parse_profiles(value: &Value) -> Result<Vec<Profile>, String> {
+    let entries = value
+        .as_array()
+        .ok_or_else(|| format!("{} must be an array", key::PROFILES))?;
+
+    let mut profiles: Vec<Profile> = Vec::new();
+    for entry in entries {
+        let name = entry["name"]
+            .as_str()
+            .map(str::trim)
+            .filter(|name| !name.is_empty() && name.len() <= MAX_NAME_LEN)
+            .ok_or("Every profile needs a name")?;
+        if profiles.iter().any(|p| p.name == name) {
+            return Err(format!("Profile {name} is defined twice"));
+        }
+        let settings = entry["settings"]
+            .as_object()
+            .ok_or_else(|| format!("Profile {name} needs a settings object"))?;
+
+        // A scratch config does the validation without touching the live one
+        let scratch = Config::default();
+        let mut sanitized = Map::new();
+        for (setting, value) in settings {
+            if !PROFILE_KEYS.contains(&setting.as_str()) {
+                return Err(format!("Profile {name} can't set {setting}"));
+            }
+            let saved = scratch
+                .set(setting, value)
+                .map_err(|e| format!("Profile {name}: {e}"))?;
+            sanitized.insert(setting.clone(), saved);
+        }
+        // Segments it doesn't mention keep whatever they show when chosen
+        if hides_every_segment(&sanitized, |_| true) {
+            return Err(format!("Profile {name} hides every metric"));
+        }
+        profiles.push(Profile {
+            name: name.to_string(),
+            settings: sanitized,
+        });
+    }
+    Ok(profiles)
+}
+
+/// Whether applying `settings` leaves the icon empty, the rule the tray menu
+/// and preferences enforce. `shown` tells whether a segment `settings`
+/// doesn't set is shown now.
+pub(crate) fn hides_every_segment(
+    settings: &Map<String, Value>,
+    shown: impl Fn(&str) -> bool,
+) -> bool {
+    SEGMENT_KEYS.iter().all(|key| match settings.get(*key) {
+        Some(value) => value.as_bool() == Some(false),
+        None => !shown(key),
+    })
+}
+
+pub fn menu_id(name: &str) -> String {
+    format!("{MENU_ID_PREFIX}:{name}")
+}
+
+pub fn parse_menu_id(id: &str) -> Option<&str> {
+    id.strip_prefix(MENU_ID_PREFIX)?.strip_prefix(':')
+}
+
+/// Applies and persists the settings of the profile called `name`, then marks
+/// it active.
+pub fn activate(app: &AppHandle, config: &Config, name: &str) {
+    let Some(profile) = config.profile(name) else {
+        warn!("Unknown profile {name}");
+        return;
+    };
+    if hides_every_segment(&profile.settings, |key| {
+        config.get(key) == Some(Value::Bool(true))
+    }) {
+        warn!("Not applying profile {name}, as it would hide every metric");
+        return;
+    }
+
+    for (setting, value) in &profile.settings {
+        match config.set(setting, value) {
+            Ok(saved) => config::save_setting(app, setting, saved),
+            Err(e) => error!("Failed to apply profile {name}: {e}"),
+        }
+    }
+    if let Ok(saved) = config.set(key::ACTIVE_PROFILE, &json!(name)) {
+        config::save_setting(app, key::ACTIVE_PROFILE, saved);
+    }
+    crate::sync_menu_checks(app);
+}
@@ -0,0 +1,98 @@
+//! Quiet hours for alerts
+//!
+//! A daily local-time window, e.g. `22:00-08:00`, during which alert sounds and
+//! webhooks are held back. Alert colors and the Recent Alerts log carry on as
+//! usual, and the submenu title says quiet hours are on. A window whose end is
+//! before its start runs past midnight.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuietHours {
+    /// Minutes since local midnight
+    pub start: u16,
+    /// Minutes since local midnight, exclusive
+    pub end: u16,
+}
+
+impl QuietHours {
+    /// Parses `HH:MM-HH:MM`; an en dash works too. Start and end must differ.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (start, end) = value.split_once(['-', '–'])?;
+        let hours = Self {
+            start: parse_time(start.trim())?,
+            end: parse_time(end.trim())?,
+        };
+        (hours.start != hours.end).then_some(hours)
+    }
+
+    pub fn to_setting(self) -> String {
+        format!("{}-{}", format_time(self.start), format_time(self.end))
+    }
+
+    pub fn contains(self, minute: u16) -> bool {
+        if self.start < self.end {
+            (self.start..self.end).contains(&minute)
+        } else {
+            minute >= self.start || minute < self.end
+        }
+    }
+
+    /// Both times in one integer for an atomic.
+    pub(crate) fn pack(self) -> u32 {
+        (self.start as u32) << 16 | self.end as u32
+    }
+
+    pub(crate) fn unpack(value: u32) -> Option<Self> {
+        let hours = Self {
+            start: (value >> 16) as u16,
+            end: value as u16,
+        };
+        (hours.start < MINUTES_PER_DAY && hours.end < MINUTES_PER_DAY).then_some(hours)
+    }
+}
+
+/// `HH:MM` as minutes since midnight.
+fn parse_time(value: &str) -> Option<u16> {
+    let (hours, minutes) = value.split_once(':')?;
+    let (hours, minutes): (u16, u16) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+pub(crate) fn format_time(minute: u16) -> String {
+    format!("{:02}:{:02}", minute / 60, minute % 60)
+}
+
+/// Minutes since midnight in the local time zone (UTC where it can't be read).
+pub fn local_minute_of_day() -> u16 {
+    (local_secs().rem_euclid(86_400) / 60) as u16
+}
+
+/// Seconds since the epoch shifted into the local time zone.
+pub fn local_secs() -> i64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    now as i64 + utc_offset_secs(now as i64)
+}
+
+#[cfg(unix)]
+pub(crate) fn utc_offset_secs(now: i64) -> i64 {
+    let time = now as libc::time_t;
+    // SAFETY: localtime_r only writes to the `tm` it is given
+    unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&time, &mut tm).is_null() {
+            return 0;
+        }
+        tm.tm_gmtoff as i64
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn utc_offset_secs(_now: i64) -> i64 {
+    0
+}
@@ -0,0 +1,259 @@
+//! Remote source: show another machine's metrics in the tray
+//!
+//! The other machine runs the status server (e.g. `--headless --status-port
+//! 9870`) and is listed in `remote_hosts`; picking it from the "Source" submenu
+//! makes the tray show its CPU, memory, GPU and network readings instead of the
+//! local ones. Hosts are written as:
+//!
+//! - `user@server` or `ssh://user@server[:port][?status_port=N]`: runs `curl`
+//!   on the remote over SSH against its status server. As in any `ssh://` URI
+//!   the port is SSH's own; the status server's is `status_port` (default
+//!   9870). The status server only listens on localhost, so this works without
+//!   exposing it. Other connection details come from `~/.ssh/config` and the
+//!   key must not need a passphrase prompt (`BatchMode`). The connection is shared between fetches
+//!   (`ControlMaster`), so a tick starts a local `ssh` but no new handshake.
+//! - `http://server[:port]`: a plain GET of `/status`, for an endpoint made
+//!   reachable by a tunnel or reverse proxy
+//!
+//! Like latency probes, each fetch runs on its own thread so a slow host never
+//! stalls the monitoring loop; a tick shows the latest finished result.
+
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::Value;
+use tracing::warn;
+
+use crate::http;
+use crate::metrics::Sample;
+use crate::status_server;
+
+const MENU_ID_PREFIX: &str = "source";
+const MAX_HOSTS: usize = 16;
+const TIMEOUT_SECS: u64 = 5;
+/// How long the shared SSH connection stays open after the last fetch.
+const CONTROL_PERSIST_SECS: u64 = 60;
+/// `%C` is a hash of the destination, which keeps the socket path short.
+const CONTROL_PATH: &str = "~/.ssh/better-resource-monitor-%C";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RemoteHost {
+    Ssh {
+        destination: String,
+        /// `None` leaves it to `~/.ssh/config`
+        ssh_port: Option<u16>,
+        status_port: u16,
+    },
+    Http {
+        host: String,
+        port: u16,
+    },
+}
+
+/// Parses one `remote_hosts` entry.
+pub fn parse_host(spec: &str) -> Result<RemoteHost, String> {
+    let spec = spec.trim();
+    let (http, rest) = match (spec.strip_prefix("http://"), spec.strip_prefix("ssh://")) {
+        (Some(rest), _) => (true, rest),
+        (_, Some(rest)) => (false, rest),
+        _ => (false, spec),
+    };
+    let parse_port = |port: &str| {
+        port.parse::<u16>()
+            .map_err(|_| format!("Invalid port in remote host {spec}"))
+    };
+    let (rest, status_port) = match rest.split_once("?status_port=") {
+        Some(_) if http => return Err(format!("Invalid remote host {spec}")),
+        Some((rest, port)) => (rest, Some(parse_port(port)?)),
+        None => (rest, None),
+    };
+    let rest = rest.trim_end_matches('/');
+    let (name, port) = match rest.rsplit_once(':') {
+        Some((name, port)) => (name, Some(parse_port(port)?)),
+        None => (rest, None),
+    };
+    // Also keeps a destination from being read as an ssh option
+    let valid = !name.is_empty()
+        && !name.starts_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ".-_@".contains(c));
+    if !valid || (http && name.contains('@')) {
+        return Err(format!("Invalid remote host {spec}"));
+    }
+
+    Ok(if http {
+        RemoteHost::Http {
+            host: name.to_string(),
+            port: port.unwrap_or(status_server::DEFAULT_PORT),
+        }
+    } else {
+        RemoteHost::Ssh {
+            destination: name.to_string(),
+            ssh_port: port,
+            status_port: status_port.unwrap_or(status_server::DEFAULT_PORT),
+        }
+    })
+}
+
+/// Validates a `remote_hosts` setting: an array of distinct host strings.
+pub fn parse_hosts(value: &Value) -> Result<Vec<String>, String> {
+    let entries = value
+        .as_array()
+        .filter(|entries| entries.len() <= MAX_HOSTS)
+        .ok_or_else(|| format!("remote_hosts must be an array of at most {MAX_HOSTS} hosts"))?;
+    let mut hosts: Vec<String> = Vec::new();
+    for entry in entries {
+        let host = entry
+            .as_str()
+            .map(str::trim)
+            .ok_or("Every remote host must be a string")?;
+        parse_host(host)?;
+        if !hosts.iter().any(|h| h == host) {
+            hosts.push(host.to_string());
+        }
+    }
+    Ok(hosts)
+}
+
+/// Latest sample of the host, fetched over SSH or HTTP.
+fn fetch(host: &RemoteHost) -> Result<Sample, String> {
+    let body = match host {
+        RemoteHost::Ssh {
+            destination,
+            ssh_port,
+            status_port,
+        } => {
+            let mut ssh = Command::new("ssh");
+            ssh.args([
+                "-o",
+                "BatchMode=yes",
+                "-o",
+                &format!("ConnectTimeout={TIMEOUT_SECS}"),
+                "-o",
+                "ControlMaster=auto",
+                "-o",
+                &format!("ControlPath={CONTROL_PATH}"),
+                "-o",
+                &format!("ControlPersist={CONTROL_PERSIST_SECS}"),
+            ]);
+            if let Some(port) = ssh_port {
+                ssh.args(["-p", &port.to_string()]);
+            }
+            let output = ssh
+                .args([
+                    "--",
+                    destination,
+                    &format!(
+                        "curl -sf --max-time {TIMEOUT_SECS} http://127.0.0.1:{status_port}/status"
+                    ),
+                ])
+                .output()
+                .map_err(|e| format!("Failed to run ssh: {e}"))?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(match stderr.lines().next() {
+                    Some(line) => format!("{destination}: {line}"),
+                    None => format!("{destination}: no status server on port {status_port}"),
+                });
+            }
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        }
+        RemoteHost::Http { host, port } => http_get_status(host, *port)?,
+    };
+    status_server::parse_sample(&body)
+}
+
+fn http_get_status(host: &str, port: u16) -> Result<String, String> {
+    let timeout = Duration::from_secs(TIMEOUT_SECS);
+    let address = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("{host}: {e}"))?
+        .next()
+        .ok_or_else(|| format!("{host}: no address"))?;
+    let stream =
+        TcpStream::connect_timeout(&address, timeout).map_err(|e| format!("{host}: {e}"))?;
+    stream
+        .set_read_timeout(Some(timeout))
+        .and_then(|_| stream.set_write_timeout(Some(timeout)))
+        .map_err(|e| e.to_string())?;
+
+    http::get(stream, host, "/status", "Status server").map_err(|e| format!("{host}: {e}"))
+}
+
+#[derive(Default)]
+struct Fetch {
+    in_flight: bool,
+    last: Option<Result<Sample, String>>,
+}
+
+/// Owned by the monitoring loop while a remote host is selected.
+pub struct RemoteSource {
+    spec: String,
+    host: RemoteHost,
+    fetch: Arc<Mutex<Fetch>>,
+    /// Last error logged, so a host that stays down is only reported once
+    logged_error: Option<String>,
+}
+
+impl RemoteSource {
+    pub fn new(spec: &str) -> Result<Self, String> {
+        Ok(Self {
+            spec: spec.to_string(),
+            host: parse_host(spec)?,
+            fetch: Arc::default(),
+            logged_error: None,
+        })
+    }
+
+    pub fn spec(&self) -> &str {
+        &self.spec
+    }
+
+    /// Starts a fetch unless one is still running, and returns the latest
+    /// sample. `None` until the first fetch succeeds and after a failed one, so
+    /// stale readings aren't passed off as current.
+    pub fn sample(&mut self) -> Option<Sample> {
+        let last = {
+            let mut fetch = self.fetch.lock().unwrap_or_else(|e| e.into_inner());
+            if !fetch.in_flight {
+                fetch.in_flight = true;
+                let shared = Arc::clone(&self.fetch);
+                let host = self.host.clone();
+                thread::spawn(move || {
+                    let result = self::fetch(&host);
+                    let mut fetch = shared.lock().unwrap_or_else(|e| e.into_inner());
+                    fetch.last = Some(result);
+                    fetch.in_flight = false;
+                });
+            }
+            fetch.last.clone()?
+        };
+
+        match last {
+            Ok(sample) => {
+                self.logged_error = None;
+                Some(sample)
+            }
+            Err(e) => {
+                if self.logged_error.as_ref() != Some(&e) {
+                    warn!("Failed to read remote host {}: {e}", self.spec);
+                    self.logged_error = Some(e);
+                }
+                None
+            }
+        }
+    }
+}
+
+/// `host` is empty for this machine.
+pub fn menu_id(host: &str) -> String {
+    format!("{MENU_ID_PREFIX}:{host}")
+}
+
+pub fn parse_menu_id(id: &str) -> Option<&str> {
+    id.strip_prefix(MENU_ID_PREFIX)?.strip_prefix(':')
+}
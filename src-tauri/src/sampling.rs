@@ -0,0 +1,51 @@
+//! Adaptive sampling cadence
+//!
+//! When the displayed values stop changing, the monitoring loop backs off to a
+//! multiple of the configured interval to save power. Any visible change snaps
+//! it straight back to the configured interval.
+
+/// Unchanged ticks at the current cadence before slowing down one step.
+pub const IDLE_TICKS_BEFORE_BACKOFF: u32 = 5;
+/// Largest multiple of the configured interval used while idle.
+pub const MAX_BACKOFF_MULTIPLIER: u32 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdaptiveInterval {
+    multiplier: u32,
+    idle_ticks: u32,
+}
+
+impl Default for AdaptiveInterval {
+    fn default() -> Self {
+        Self {
+            multiplier: 1,
+            idle_ticks: 0,
+        }
+    }
+}
+
+impl AdaptiveInterval {
+    /// Interval to sleep before the next tick.
+    pub fn interval_ms(&self, base_ms: u64) -> u64 {
+        base_ms.saturating_mul(self.multiplier as u64)
+    }
+
+    /// Feeds the outcome of a tick: `changed` is true when the tray was redrawn.
+    pub fn record(&mut self, changed: bool) {
+        if changed {
+            *self = Self::default();
+            return;
+        }
+
+        self.idle_ticks += 1;
+        if self.idle_ticks >= IDLE_TICKS_BEFORE_BACKOFF && self.multiplier < MAX_BACKOFF_MULTIPLIER
+        {
+            self.multiplier = (self.multiplier * 2).min(MAX_BACKOFF_MULTIPLIER);
+            self.idle_ticks = 0;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
@@ -0,0 +1,70 @@
+//! "Save Current Icon…"
+//!
+//! Writes the tray icon as it is now to PNG files at 1x and 2x, for bug
+//! reports about rendering on a particular panel or theme: the chosen path
+//! gets the 1x frame and a sibling `@2x` file the 2x one ("icon.png" and
+//! "icon@2x.png"). The monitoring loop draws both with a renderer of their
+//! own on its next tick, from the values shown at that moment, so the tray's
+//! own frames and auto-width state are untouched.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::config::Config;
+use crate::frame_dump::write_png;
+
+pub const MENU_ID: &str = "save_icon";
+
+/// Scales the icon is saved at, in file order.
+pub const SCALES: [f32; 2] = [1.0, 2.0];
+
+/// Path chosen in the save dialog, until the monitoring loop writes to it.
+pub type PendingIconSave = Arc<Mutex<Option<PathBuf>>>;
+
+/// Asks where to save, then has the monitoring loop render and write the icon.
+pub fn choose(app: &AppHandle) {
+    let handle = app.clone();
+    app.dialog()
+        .file()
+        .set_title("Save Current Icon")
+        .set_file_name("tray-icon.png")
+        .add_filter("PNG image", &["png"])
+        .save_file(move |path| {
+            let Some(path) = path.and_then(|path| path.into_path().ok()) else {
+                return;
+            };
+            if let Some(pending) = handle.try_state::<PendingIconSave>() {
+                *pending.lock().unwrap_or_else(|e| e.into_inner()) = Some(path);
+            }
+            if let Some(config) = handle.try_state::<Arc<Config>>() {
+                config.request_redraw();
+            }
+        });
+}
+
+/// The files for [`SCALES`]: `path` itself, then `name@2x.ext` beside it.
+pub(crate) fn scaled_paths(path: &Path) -> [PathBuf; 2] {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "tray-icon".to_string());
+    let extension = path
+        .extension()
+        .map_or("png".into(), |extension| extension.to_string_lossy());
+    [
+        path.with_file_name(format!("{stem}.{extension}")),
+        path.with_file_name(format!("{stem}@2x.{extension}")),
+    ]
+}
+
+/// Writes one RGBA frame per scale; returns the files written.
+pub fn write(path: &Path, frames: &[(Vec<u8>, u32, u32); 2]) -> Result<[PathBuf; 2], String> {
+    let paths = scaled_paths(path);
+    for (path, (rgba, width, height)) in paths.iter().zip(frames) {
+        write_png(path, rgba, *width, *height)?;
+    }
+    Ok(paths)
+}
@@ -0,0 +1,197 @@
+//! Screen lock detection
+//!
+//! While the screen is locked nobody sees the tray icon, so with
+//! `pause_when_locked` on the monitoring loop stops sampling, drawing and
+//! feeding the status server, D-Bus service and charts until it is unlocked,
+//! and wakes at once on unlock instead of on its next tick.
+//!
+//! - Linux: logind's session `LockedHint` and `Active` properties on the system
+//!   bus; a session switched away from (another user, a text console) counts as
+//!   locked too
+//! - macOS: the `com.apple.screenIsLocked` / `com.apple.screenIsUnlocked`
+//!   distributed notifications, and the screen saver's start and stop
+//!
+//! Elsewhere, or when these aren't available, the screen never counts as locked.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::{Arc, OnceLock};
+
+use crate::config::Config;
+
+static LOCKED: AtomicBool = AtomicBool::new(false);
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+static SCREEN_SAVER: AtomicBool = AtomicBool::new(false);
+/// For waking the monitoring loop on unlock
+static CONFIG: OnceLock<Arc<Config>> = OnceLock::new();
+
+pub fn is_locked() -> bool {
+    LOCKED.load(Relaxed) || SCREEN_SAVER.load(Relaxed)
+}
+
+/// Starts following the lock state. On macOS this must run on the main thread,
+/// whose run loop delivers the notifications.
+pub fn start(config: Arc<Config>) {
+    let _ = CONFIG.set(config);
+    #[cfg(target_os = "linux")]
+    linux::start();
+    #[cfg(target_os = "macos")]
+    macos::start();
+}
+
+#[cfg_attr(not(any(target_os = "linux", target_os = "macos")), allow(dead_code))]
+fn set(flag: &AtomicBool, value: bool) {
+    let was_locked = is_locked();
+    flag.store(value, Relaxed);
+    if was_locked && !is_locked() {
+        if let Some(config) = CONFIG.get() {
+            config.request_redraw();
+        }
+    }
+}
+
+// ============================================================================
+// Linux Implementation (logind)
+// ============================================================================
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::thread;
+
+    use tracing::{debug, warn};
+    use zbus::blocking::fdo::PropertiesProxy;
+    use zbus::blocking::{Connection, Proxy};
+    use zbus::names::InterfaceName;
+    use zbus::zvariant::OwnedObjectPath;
+
+    const LOGIND: &str = "org.freedesktop.login1";
+    const SESSION_INTERFACE: &str = "org.freedesktop.login1.Session";
+
+    pub fn start() {
+        thread::spawn(|| {
+            if let Err(e) = watch() {
+                warn!("Screen lock detection unavailable: {e}");
+            }
+            super::set(&super::LOCKED, false);
+        });
+    }
+
+    /// Follows this process's logind session until the connection drops.
+    fn watch() -> zbus::Result<()> {
+        let connection = Connection::system()?;
+        let manager = Proxy::new(
+            &connection,
+            LOGIND,
+            "/org/freedesktop/login1",
+            "org.freedesktop.login1.Manager",
+        )?;
+        let session: OwnedObjectPath = manager.call("GetSession", &("auto",))?;
+        debug!("Following the lock state of session {}", session.as_str());
+        let properties = PropertiesProxy::builder(&connection)
+            .destination(LOGIND)?
+            .path(session)?
+            .build()?;
+        // Subscribe before the first read so no change falls in between
+        let changes = properties.receive_properties_changed()?;
+
+        let interface = InterfaceName::from_static_str_unchecked(SESSION_INTERFACE);
+        let read = |name: &str| -> zbus::Result<bool> {
+            Ok(bool::try_from(properties.get(interface.clone(), name)?)?)
+        };
+        let locked = || -> zbus::Result<bool> { Ok(read("LockedHint")? || !read("Active")?) };
+
+        super::set(&super::LOCKED, locked()?);
+        for _ in changes {
+            super::set(&super::LOCKED, locked()?);
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// macOS Implementation (distributed notifications)
+// ============================================================================
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::ffi::c_void;
+
+    use core_foundation::base::TCFType;
+    use core_foundation::dictionary::CFDictionaryRef;
+    use core_foundation::string::{CFString, CFStringRef};
+
+    type CFNotificationCenterRef = *mut c_void;
+    type CFNotificationCallback = extern "C" fn(
+        center: CFNotificationCenterRef,
+        observer: *mut c_void,
+        name: CFStringRef,
+        object: *const c_void,
+        user_info: CFDictionaryRef,
+    );
+
+    /// `CFNotificationSuspensionBehaviorDeliverImmediately`
+    const DELIVER_IMMEDIATELY: isize = 4;
+
+    const SCREEN_LOCKED: &str = "com.apple.screenIsLocked";
+    const SCREEN_UNLOCKED: &str = "com.apple.screenIsUnlocked";
+    const SCREEN_SAVER_STARTED: &str = "com.apple.screensaver.didstart";
+    const SCREEN_SAVER_STOPPED: &str = "com.apple.screensaver.didstop";
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    unsafe extern "C" {
+        fn CFNotificationCenterGetDistributedCenter() -> CFNotificationCenterRef;
+        fn CFNotificationCenterAddObserver(
+            center: CFNotificationCenterRef,
+            observer: *const c_void,
+            callback: CFNotificationCallback,
+            name: CFStringRef,
+            object: *const c_void,
+            suspension_behavior: isize,
+        );
+    }
+
+    pub fn start() {
+        for name in [
+            SCREEN_LOCKED,
+            SCREEN_UNLOCKED,
+            SCREEN_SAVER_STARTED,
+            SCREEN_SAVER_STOPPED,
+        ] {
+            let name = CFString::new(name);
+            // SAFETY: the center copies the name; the callback is a plain
+            // function that lives as long as the process
+            unsafe {
+                CFNotificationCenterAddObserver(
+                    CFNotificationCenterGetDistributedCenter(),
+                    std::ptr::null(),
+                    on_notification,
+                    name.as_concrete_TypeRef(),
+                    std::ptr::null(),
+                    DELIVER_IMMEDIATELY,
+                );
+            }
+        }
+    }
+
+    extern "C" fn on_notification(
+        _center: CFNotificationCenterRef,
+        _observer: *mut c_void,
+        name: CFStringRef,
+        _object: *const c_void,
+        _user_info: CFDictionaryRef,
+    ) {
+        if name.is_null() {
+            return;
+        }
+        // SAFETY: the center passes the name it was registered with, valid
+        // for the duration of the call
+        let name = unsafe { CFString::wrap_under_get_rule(name) }.to_string();
+        match name.as_str() {
+            SCREEN_LOCKED => super::set(&super::LOCKED, true),
+            SCREEN_UNLOCKED => super::set(&super::LOCKED, false),
+            SCREEN_SAVER_STARTED => super::set(&super::SCREEN_SAVER, true),
+            SCREEN_SAVER_STOPPED => super::set(&super::SCREEN_SAVER, false),
+            _ => {}
+        }
+    }
+}
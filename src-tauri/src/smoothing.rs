@@ -0,0 +1,33 @@
+//! Exponential moving average of displayed readings
+//!
+//! GPU residency in particular swings between a few percent and well over half
+//! from one tick to the next on Apple Silicon. Each of CPU, memory and GPU can
+//! be smoothed on its own: `*_smoothing` is the percentage of the previous
+//! displayed value kept each tick, so 0 shows raw readings and 90 settles
+//! slowly. Only what the tray shows and alerts on is smoothed; history, the
+//! status server and D-Bus keep the raw samples.
+
+pub const MAX_SMOOTHING: u32 = 90;
+
+/// One metric's smoothed value, owned by the monitoring loop.
+#[derive(Default)]
+pub struct Ema {
+    value: Option<f32>,
+}
+
+impl Ema {
+    /// Blends `reading` into the running value and returns it. A missing
+    /// reading clears the running value, so the next one starts fresh.
+    pub fn update(&mut self, reading: Option<f32>, smoothing: u32) -> Option<f32> {
+        let weight = smoothing.min(MAX_SMOOTHING) as f32 / 100.0;
+        self.value = reading.map(|current| match self.value {
+            Some(previous) => previous * weight + current * (1.0 - weight),
+            None => current,
+        });
+        self.value
+    }
+
+    pub fn reset(&mut self) {
+        self.value = None;
+    }
+}
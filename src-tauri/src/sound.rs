@@ -0,0 +1,130 @@
+//! Alert sound
+//!
+//! Optionally plays a short system sound when a metric crosses its alert
+//! threshold, for long renders or builds left running while away from the
+//! screen. A cooldown keeps a metric hovering around its threshold from
+//! chiming every few seconds.
+//!
+//! - macOS: `afplay` with one of the sounds in `/System/Library/Sounds`
+//! - Linux: `canberra-gtk-play` with a sound theme event ID, falling back to
+//!   `paplay` and the freedesktop theme files
+
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tracing::error;
+
+pub const DEFAULT_COOLDOWN_SECS: u32 = 300;
+pub const MIN_COOLDOWN_SECS: u64 = 10;
+pub const MAX_COOLDOWN_SECS: u64 = 3600;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AlertSound {
+    Off,
+    /// A soft chime
+    Chime,
+    /// A more insistent warning sound
+    Warning,
+}
+
+impl AlertSound {
+    pub const ALL: [AlertSound; 3] = [AlertSound::Off, AlertSound::Chime, AlertSound::Warning];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AlertSound::Off => "off",
+            AlertSound::Chime => "chime",
+            AlertSound::Warning => "warning",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|sound| sound.as_str() == value)
+    }
+
+    pub(crate) fn from_u8(value: u8) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|sound| *sound as u8 == value)
+            .unwrap_or(AlertSound::Off)
+    }
+
+    /// Name of the system sound: a file in `/System/Library/Sounds` on macOS,
+    /// a sound theme event ID elsewhere.
+    fn system_name(self) -> Option<&'static str> {
+        match self {
+            AlertSound::Off => None,
+            AlertSound::Chime if cfg!(target_os = "macos") => Some("Glass"),
+            AlertSound::Warning if cfg!(target_os = "macos") => Some("Sosumi"),
+            AlertSound::Chime => Some("complete"),
+            AlertSound::Warning => Some("dialog-warning"),
+        }
+    }
+}
+
+/// Owned by the monitoring loop; remembers when the last sound played.
+#[derive(Default)]
+pub struct AlertSounder {
+    last_played: Option<Instant>,
+}
+
+impl AlertSounder {
+    /// Plays `sound` for a newly started alert unless it is off or the last
+    /// one played less than `cooldown` ago. Returns whether it played.
+    pub fn alert(&mut self, sound: AlertSound, cooldown: Duration) -> bool {
+        let Some(name) = sound.system_name() else {
+            return false;
+        };
+        if self
+            .last_played
+            .is_some_and(|last| last.elapsed() < cooldown)
+        {
+            return false;
+        }
+        self.last_played = Some(Instant::now());
+        // Waited for on a thread of its own so the child is reaped without
+        // holding up the tick
+        thread::spawn(move || {
+            if let Err(e) = play(name) {
+                error!("Failed to play alert sound: {e}");
+            }
+        });
+        true
+    }
+}
+
+#[cfg_attr(not(any(target_os = "linux", target_os = "macos")), allow(dead_code))]
+fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let status = Command::new(program)
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("{program}: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{program} exited with {status}"))
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn play(name: &str) -> Result<(), String> {
+    run("afplay", &[&format!("/System/Library/Sounds/{name}.aiff")])
+}
+
+#[cfg(target_os = "linux")]
+fn play(name: &str) -> Result<(), String> {
+    run("canberra-gtk-play", &["-i", name]).or_else(|_| {
+        run(
+            "paplay",
+            &[&format!("/usr/share/sounds/freedesktop/stereo/{name}.oga")],
+        )
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn play(_name: &str) -> Result<(), String> {
+    Err("not supported on this platform".to_string())
+}
@@ -0,0 +1,185 @@
+//! Opt-in local HTTP status endpoint
+//!
+//! Serves the latest monitoring sample as JSON on `127.0.0.1:<port>` so scripts
+//! can read what the tray already computes:
+//!
+//! ```text
+//! $ curl http://127.0.0.1:9870/status
+//! {"cpu":12.5,"cpu_power":null,"gpu":null,"gpu_temp":null,"gpu_mem":null,"mem":48.1,"net_down":1520.0,"net_up":310.0,"timestamp":1700000000}
+//! ```
+//!
+//! `GET /history` returns the last 24 hours as an array of the same objects,
+//! one per minute with its averages (see [`crate::minute_history`]).
+//!
+//! The server thread follows the config at runtime: it binds when enabled,
+//! rebinds when the port changes and releases the port when disabled.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tracing::{error, warn};
+
+use crate::config::Config;
+use crate::metrics::Sample;
+use crate::minute_history::SharedMinuteHistory;
+
+pub const DEFAULT_PORT: u16 = 9870;
+
+/// How often the idle server checks for connections and config changes.
+const POLL_INTERVAL_MS: u64 = 200;
+const READ_TIMEOUT_SECS: u64 = 2;
+
+/// Most recent sample written by the monitoring loop; `None` until the first tick.
+pub type LatestSample = Arc<Mutex<Option<Sample>>>;
+
+pub fn start(config: Arc<Config>, latest: LatestSample, minutes: SharedMinuteHistory) {
+    thread::spawn(move || {
+        let mut listener: Option<(u16, TcpListener)> = None;
+        // Port that failed to bind, so the error is only logged once
+        let mut failed_port: Option<u16> = None;
+
+        loop {
+            let wanted = config
+                .status_server_enabled
+                .load(Relaxed)
+                .then(|| config.status_server_port.load(Relaxed) as u16);
+
+            if listener.as_ref().map(|(port, _)| *port) != wanted {
+                listener = None;
+                if let Some(port) = wanted.filter(|p| failed_port != Some(*p)) {
+                    match bind(port) {
+                        Ok(l) => {
+                            listener = Some((port, l));
+                            failed_port = None;
+                        }
+                        Err(e) => {
+                            error!("Failed to start status server on port {port}: {e}");
+                            failed_port = Some(port);
+                        }
+                    }
+                }
+            }
+
+            let Some((_, ref l)) = listener else {
+                thread::sleep(Duration::from_millis(POLL_INTERVAL_MS));
+                continue;
+            };
+
+            match l.accept() {
+                Ok((stream, _)) => {
+                    if let Err(e) = handle(stream, &latest, &minutes) {
+                        warn!("Status request failed: {e}");
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(POLL_INTERVAL_MS));
+                }
+                Err(e) => warn!("Status server accept failed: {e}"),
+            }
+        }
+    });
+}
+
+fn bind(port: u16) -> io::Result<TcpListener> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    // Non-blocking so the thread can notice config changes between requests
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+fn handle(
+    stream: TcpStream,
+    latest: &LatestSample,
+    minutes: &SharedMinuteHistory,
+) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(READ_TIMEOUT_SECS)))?;
+
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let sample = *latest.lock().unwrap_or_else(|e| e.into_inner());
+    // The loop saves the history under the same lock, so only `/history` takes it
+    let history = || {
+        minutes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .minutes()
+            .copied()
+            .collect()
+    };
+    let response = response_for(&request_line, sample.as_ref(), history);
+    (&stream).write_all(response.as_bytes())
+}
+
+/// Builds the full HTTP response for a request line such as `GET /status HTTP/1.1`.
+/// `history` is only called for `/history`.
+pub(crate) fn response_for(
+    request_line: &str,
+    sample: Option<&Sample>,
+    history: impl FnOnce() -> Vec<Sample>,
+) -> String {
+    let mut parts = request_line.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/status")) => match sample {
+            Some(sample) => ("200 OK", sample_json(sample)),
+            None => (
+                "503 Service Unavailable",
+                json!({ "error": "no sample yet" }),
+            ),
+        },
+        (Some("GET"), Some("/history")) => (
+            "200 OK",
+            Value::Array(history().iter().map(sample_json).collect()),
+        ),
+        (Some("GET"), _) => ("404 Not Found", json!({ "error": "not found" })),
+        _ => (
+            "405 Method Not Allowed",
+            json!({ "error": "method not allowed" }),
+        ),
+    };
+
+    let body = body.to_string();
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// The inverse of [`sample_json`], for reading another machine's endpoint.
+pub(crate) fn parse_sample(body: &str) -> Result<Sample, String> {
+    let json: Value = serde_json::from_str(body).map_err(|e| format!("Invalid status: {e}"))?;
+    let number = |field: &str| json[field].as_f64();
+    Ok(Sample {
+        timestamp: json["timestamp"]
+            .as_u64()
+            .ok_or("Invalid status: no timestamp")?,
+        cpu: number("cpu").map(|v| v as f32),
+        cpu_power: number("cpu_power").map(|v| v as f32),
+        mem: number("mem").map(|v| v as f32),
+        gpu: number("gpu").map(|v| v as f32),
+        gpu_mem: json["gpu_mem"].as_u64(),
+        gpu_temp: number("gpu_temp").map(|v| v as f32),
+        net_down: number("net_down"),
+        net_up: number("net_up"),
+    })
+}
+
+pub(crate) fn sample_json(sample: &Sample) -> Value {
+    json!({
+        "timestamp": sample.timestamp,
+        "cpu": sample.cpu,
+        "cpu_power": sample.cpu_power,
+        "mem": sample.mem,
+        "gpu": sample.gpu,
+        "gpu_mem": sample.gpu_mem,
+        "gpu_temp": sample.gpu_temp,
+        "net_down": sample.net_down,
+        "net_up": sample.net_up,
+    })
+}
@@ -0,0 +1,51 @@
+//! Opens the platform's own process viewer
+//!
+//! - macOS: Activity Monitor
+//! - Linux: the first installed desktop system monitor, else `htop` (or `top`)
+//!   in a terminal
+
+use std::process::{Command, Stdio};
+
+#[cfg(target_os = "macos")]
+pub(crate) const CANDIDATES: &[&[&str]] = &[&["open", "-a", "Activity Monitor"]];
+
+/// Tried in order; a candidate that isn't installed fails to spawn and the next
+/// one is tried.
+#[cfg(not(target_os = "macos"))]
+pub(crate) const CANDIDATES: &[&[&str]] = &[
+    &["gnome-system-monitor"],
+    &["plasma-systemmonitor"],
+    &["ksysguard"],
+    &["xfce4-taskmanager"],
+    &["mate-system-monitor"],
+    &["lxtask"],
+    &["missioncenter"],
+    &["x-terminal-emulator", "-e", "htop"],
+    &["gnome-terminal", "--", "htop"],
+    &["konsole", "-e", "htop"],
+    &["xfce4-terminal", "-e", "htop"],
+    &["kitty", "htop"],
+    &["alacritty", "-e", "htop"],
+    &["xterm", "-e", "htop"],
+    &["xterm", "-e", "top"],
+];
+
+pub fn open() -> Result<(), String> {
+    for command in CANDIDATES {
+        let [program, args @ ..] = *command else {
+            continue;
+        };
+        let spawned = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        if let Ok(mut child) = spawned {
+            // Reap it in the background so it doesn't linger as a zombie
+            std::thread::spawn(move || child.wait());
+            return Ok(());
+        }
+    }
+    Err("No system monitor found".to_string())
+}
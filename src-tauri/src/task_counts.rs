@@ -0,0 +1,70 @@
+//! Process and thread count line
+//!
+//! Shows how many processes are running and how many threads they have in
+//! total, e.g. to spot a leak that keeps spawning workers. Processes come from
+//! sysinfo's process list refreshed with nothing but the list itself, without
+//! each process's threads (`/proc/<pid>/task`); the thread total comes from
+//! `/proc/loadavg` on Linux, which is far cheaper than walking every process's
+//! tasks. macOS doesn't expose a thread total to sandboxed apps, so only
+//! processes are shown there.
+
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+
+use crate::i18n::tr_args;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TaskCounts {
+    pub processes: usize,
+    pub threads: Option<usize>,
+}
+
+/// Updates the process list without reading any per-process figures or
+/// threads, which are counted separately.
+pub fn read(sys: &mut System) -> TaskCounts {
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().without_tasks(),
+    );
+    TaskCounts {
+        processes: sys
+            .processes()
+            .values()
+            .filter(|process| process.thread_kind().is_none())
+            .count(),
+        threads: read_threads(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_threads() -> Option<usize> {
+    parse_loadavg_threads(&std::fs::read_to_string("/proc/loadavg").ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_threads() -> Option<usize> {
+    None
+}
+
+/// The total after the slash in the fourth field, e.g. `1873` in
+/// `0.52 0.58 0.59 2/1873 41237`: every thread the kernel schedules.
+pub(crate) fn parse_loadavg_threads(contents: &str) -> Option<usize> {
+    contents
+        .split_whitespace()
+        .nth(3)?
+        .split_once('/')?
+        .1
+        .parse()
+        .ok()
+}
+
+/// "Processes: 412 · Threads: 1873".
+pub(crate) fn status_text(counts: TaskCounts) -> String {
+    match counts.threads {
+        Some(threads) => tr_args(
+            "Processes: {} · Threads: {}",
+            &[&counts.processes.to_string(), &threads.to_string()],
+        ),
+        None => tr_args("Processes: {}", &[&counts.processes.to_string()]),
+    }
+}
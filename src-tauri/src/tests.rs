@@ -1,5 +1,9 @@
 use super::*;
+use better_resource_monitor_metrics::thermal;
 use std::sync::{Mutex, OnceLock};
+use tray_render::{Layout, Palette, SegmentKind};
+
+const UPDATE_INTERVAL_MS: u64 = config::DEFAULT_UPDATE_INTERVAL_MS as u64;
 
 fn env_lock() -> &'static Mutex<()> {
     static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
@@ -7,13 +11,179 @@ fn env_lock() -> &'static Mutex<()> {
 }
 
 #[test]
-fn test_cap_percent() {
-    assert_eq!(tray_render::cap_percent(0.0), 0.0);
-    assert_eq!(tray_render::cap_percent(50.0), 50.0);
-    assert_eq!(tray_render::cap_percent(99.0), 99.0);
-    assert_eq!(tray_render::cap_percent(100.0), 99.0);
-    assert_eq!(tray_render::cap_percent(150.0), 99.0);
-    assert_eq!(tray_render::cap_percent(-10.0), 0.0);
+fn test_cpu_split() {
+    use cpu::{parse_proc_stat, CpuTimes};
+
+    let stat = "cpu  100 20 50 800 10 5 15 0 0 0\ncpu0 50 10 25 400 5 2 8 0 0 0\n";
+    let before = parse_proc_stat(stat).unwrap();
+    assert_eq!(
+        before,
+        CpuTimes {
+            user: 120,
+            system: 70,
+            total: 1000,
+        }
+    );
+    assert_eq!(parse_proc_stat("cpu0 1 2 3\n"), None);
+
+    let after = CpuTimes {
+        user: 150,
+        system: 80,
+        total: 1100,
+    };
+    let (user, system) = after.split_since(before).unwrap();
+    assert!((user - 30.0).abs() < 0.01);
+    assert!((system - 10.0).abs() < 0.01);
+    // No time passed, or the counters went backwards
+    assert_eq!(before.split_since(before), None);
+    assert_eq!(before.split_since(after), None);
+}
+
+#[test]
+fn test_adaptive_interval_backs_off_when_idle() {
+    use sampling::{AdaptiveInterval, IDLE_TICKS_BEFORE_BACKOFF, MAX_BACKOFF_MULTIPLIER};
+
+    let mut adaptive = AdaptiveInterval::default();
+    assert_eq!(adaptive.interval_ms(2_000), 2_000);
+
+    for _ in 0..IDLE_TICKS_BEFORE_BACKOFF - 1 {
+        adaptive.record(false);
+    }
+    assert_eq!(adaptive.interval_ms(2_000), 2_000);
+    adaptive.record(false);
+    assert_eq!(adaptive.interval_ms(2_000), 4_000);
+
+    // Keeps slowing down until the cap
+    for _ in 0..IDLE_TICKS_BEFORE_BACKOFF * 10 {
+        adaptive.record(false);
+    }
+    assert_eq!(
+        adaptive.interval_ms(2_000),
+        2_000 * MAX_BACKOFF_MULTIPLIER as u64
+    );
+
+    // Activity ramps straight back to the configured interval
+    adaptive.record(true);
+    assert_eq!(adaptive.interval_ms(2_000), 2_000);
+
+    // A change part-way through an idle streak restarts the count
+    for _ in 0..IDLE_TICKS_BEFORE_BACKOFF - 1 {
+        adaptive.record(false);
+    }
+    adaptive.record(true);
+    adaptive.record(false);
+    assert_eq!(adaptive.interval_ms(2_000), 2_000);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_on_battery_from_supplies() {
+    assert!(power::on_battery_from_supplies([
+        ("Mains", false),
+        ("Battery", false)
+    ]));
+    assert!(!power::on_battery_from_supplies([
+        ("Mains", true),
+        ("Battery", false)
+    ]));
+    assert!(!power::on_battery_from_supplies([
+        ("USB", true),
+        ("Battery", true)
+    ]));
+    // Desktops without a battery are never on battery
+    assert!(!power::on_battery_from_supplies([("Mains", false)]));
+    assert!(!power::on_battery_from_supplies([]));
+}
+
+#[cfg(target_os = "macos")]
+#[test]
+fn test_parse_pmset() {
+    assert!(power::parse_pmset_on_battery(
+        "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=1)\t80%; discharging"
+    ));
+    assert!(!power::parse_pmset_on_battery(
+        "Now drawing from 'AC Power'\n"
+    ));
+    assert!(power::parse_pmset_low_power_mode(
+        "System-wide power settings:\n lowpowermode         1\n"
+    ));
+    assert!(!power::parse_pmset_low_power_mode(
+        " lowpowermode         0\n"
+    ));
+}
+
+struct CountingSampler {
+    name: &'static str,
+    samples: u32,
+    resets: Arc<std::sync::atomic::AtomicU32>,
+}
+
+impl sampler::Sampler for CountingSampler {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn sample(&mut self) -> sampler::MetricValue {
+        self.samples += 1;
+        sampler::MetricValue::Scalar(self.samples as f32)
+    }
+
+    fn reset(&mut self) {
+        self.resets.fetch_add(1, Relaxed);
+    }
+}
+
+#[test]
+fn test_sampler_registry() {
+    use sampler::{MetricValue, SamplerRegistry};
+
+    let resets = Arc::new(std::sync::atomic::AtomicU32::new(0));
+    let counting = |name| CountingSampler {
+        name,
+        samples: 0,
+        resets: resets.clone(),
+    };
+
+    let mut registry = SamplerRegistry::default();
+    registry.register(counting("a"));
+    registry.register(counting("b"));
+    assert!(registry.contains("a"));
+    assert!(!registry.contains("missing"));
+
+    registry.sample(|_| true);
+    registry.sample(|name| name == "a");
+    assert_eq!(registry.get("a"), MetricValue::Scalar(2.0));
+    // Unwanted samplers read as unavailable and are reset once
+    assert_eq!(registry.get("b"), MetricValue::Unavailable);
+    registry.sample(|name| name == "a");
+    assert_eq!(resets.load(Relaxed), 1);
+    assert_eq!(registry.get("missing"), MetricValue::Unavailable);
+
+    // Registering the same name replaces the previous sampler
+    registry.register(counting("a"));
+    registry.sample(|_| true);
+    assert_eq!(registry.get("a").scalar(), Some(1.0));
+}
+
+#[test]
+fn test_throttle() {
+    use std::time::{Duration, Instant};
+
+    let mut throttle = sampler::Throttle::every(Duration::from_secs(3));
+    let start = Instant::now();
+    // Ticks of 2 s: every other one reads
+    let pattern: Vec<bool> = (0..5)
+        .map(|tick| throttle.due_at(start + Duration::from_secs(2 * tick)))
+        .collect();
+    assert_eq!(pattern, vec![true, false, true, false, true]);
+    // Ticks of 250 ms read just as often
+    let mut throttle = sampler::Throttle::every(Duration::from_secs(3));
+    let reads = (0..24)
+        .filter(|&tick| throttle.due_at(start + Duration::from_millis(250 * tick)))
+        .count();
+    assert_eq!(reads, 2);
+    throttle.reset();
+    assert!(throttle.due_at(start));
 }
 
 #[test]
@@ -26,6 +196,420 @@ fn test_should_update_threshold() {
     assert!(!should_update(10.0, 10.0, 2.0));
 }
 
+#[test]
+fn test_should_update_rate() {
+    // Flickering between "1.4M" and "1.5M" is held back by 10%
+    assert!(!should_update_rate(1_450_000.0, 1_550_000.0, 10));
+    assert!(should_update_rate(1_450_000.0, 1_550_000.0, 0));
+    assert!(should_update_rate(1_000_000.0, 1_200_000.0, 10));
+    assert!(should_update_rate(1_000_000.0, 800_000.0, 10));
+    // Changes under 50 KB/s never redraw
+    assert!(!should_update_rate(0.0, 40_000.0, 0));
+
+    let mut hysteresis = NetHysteresis::default();
+    let (shown, hovering) = ((1_450_000.0, 0.0), (1_550_000.0, 0.0));
+    assert!(!hysteresis.changed(shown, hovering, 10, 3));
+    assert!(!hysteresis.changed(shown, hovering, 10, 3));
+    // Drawn once it has lasted, then held back again
+    assert!(hysteresis.changed(shown, hovering, 10, 3));
+    assert!(!hysteresis.changed(shown, hovering, 10, 3));
+    assert!(hysteresis.changed(shown, (3_000_000.0, 0.0), 10, 3));
+    // Only the upload moved
+    assert!(hysteresis.changed(shown, (1_450_000.0, 500_000.0), 10, 3));
+    for _ in 0..5 {
+        assert!(!hysteresis.changed(shown, (1_460_000.0, 0.0), 10, 3));
+    }
+
+    let config = Config::default();
+    assert_eq!(
+        config.set(config::key::NET_HYSTERESIS, &serde_json::json!(80)),
+        Ok(serde_json::json!(50))
+    );
+    assert_eq!(
+        config.set(config::key::NET_HYSTERESIS_TICKS, &serde_json::json!(0)),
+        Ok(serde_json::json!(1))
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_effective_panel_scale() {
+    assert_eq!(effective_panel_scale(None, None), 1.0);
+    assert_eq!(effective_panel_scale(None, Some(2.0)), 2.0);
+    assert_eq!(effective_panel_scale(None, Some(1.25)), 1.25);
+    // GDK_SCALE overrides the monitor
+    assert_eq!(effective_panel_scale(Some("2"), Some(1.0)), 2.0);
+    // Invalid or out of range values
+    assert_eq!(effective_panel_scale(Some("0"), Some(1.5)), 1.5);
+    assert_eq!(effective_panel_scale(Some("x"), None), 1.0);
+    assert_eq!(effective_panel_scale(None, Some(f64::NAN)), 1.0);
+    assert_eq!(effective_panel_scale(None, Some(0.5)), 1.0);
+    assert_eq!(effective_panel_scale(Some("8"), None), 4.0);
+}
+
+#[test]
+fn test_click_action_setting() {
+    let config = Config::default();
+    assert_eq!(config.click_action(), ClickAction::Menu);
+    for action in ClickAction::ALL {
+        assert_eq!(ClickAction::parse(action.as_str()), Some(action));
+        assert_eq!(
+            config.set(config::key::CLICK_ACTION, &action.as_str().into()),
+            Ok(action.as_str().into())
+        );
+        assert_eq!(config.click_action(), action);
+    }
+    assert!(config
+        .set(config::key::CLICK_ACTION, &"launch_rockets".into())
+        .is_err());
+
+    assert_eq!(toggled_compact_layout(Layout::Standard), Layout::Compact);
+    assert_eq!(toggled_compact_layout(Layout::Compact), Layout::Standard);
+    assert_eq!(toggled_compact_layout(Layout::IconOnly), Layout::Compact);
+
+    assert!(system_monitor::CANDIDATES
+        .iter()
+        .all(|command| !command.is_empty()));
+}
+
+#[test]
+fn test_font_settings() {
+    let config = Config::default();
+    assert_eq!(config.font_family(), None);
+    assert_eq!(config.font_weight(), config::DEFAULT_FONT_WEIGHT);
+    let revision = config.font_revision();
+
+    assert_eq!(
+        config.set(config::key::FONT_FAMILY, &serde_json::json!("  Inter ")),
+        Ok(serde_json::json!("Inter"))
+    );
+    assert_eq!(config.font_family().as_deref(), Some("Inter"));
+    assert_eq!(
+        config.set(config::key::FONT_WEIGHT, &serde_json::json!(640)),
+        Ok(serde_json::json!(600))
+    );
+    assert_eq!(config.font_revision(), revision.wrapping_add(2));
+
+    assert!(config
+        .set(config::key::FONT_FAMILY, &serde_json::json!(12))
+        .is_err());
+    assert!(config
+        .set(
+            config::key::FONT_FAMILY,
+            &serde_json::json!("x".repeat(200))
+        )
+        .is_err());
+    assert_eq!(
+        config.set(config::key::FONT_FAMILY, &serde_json::json!("")),
+        Ok(serde_json::json!(""))
+    );
+    assert_eq!(config.font_family(), None);
+
+    assert_eq!(config::sanitize_font_weight(0), 100);
+    assert_eq!(config::sanitize_font_weight(449), 400);
+    assert_eq!(config::sanitize_font_weight(2000), 900);
+}
+
+#[test]
+fn test_text_scale() {
+    use config::key::TEXT_SCALE;
+
+    let config = Config::default();
+    assert_eq!(config.text_scale(), 1.0);
+    assert_eq!(app_sizing(&config), APP_SIZING);
+
+    assert_eq!(
+        config.set(TEXT_SCALE, &serde_json::json!(125)),
+        Ok(serde_json::json!(125))
+    );
+    assert_eq!(config.text_scale(), 1.25);
+    assert_eq!(
+        config.set(TEXT_SCALE, &serde_json::json!(10)),
+        Ok(serde_json::json!(80))
+    );
+    assert_eq!(
+        config.set(TEXT_SCALE, &serde_json::json!(400)),
+        Ok(serde_json::json!(150))
+    );
+    assert!(config.set(TEXT_SCALE, &serde_json::json!("large")).is_err());
+
+    let sizing = app_sizing(&config);
+    assert_eq!(sizing, APP_SIZING.scaled(1.5));
+    assert!(sizing.icon_height > APP_SIZING.icon_height);
+    assert!(sizing.font_size > APP_SIZING.font_size);
+}
+
+#[test]
+fn test_memory_pressure_from_level() {
+    use tray_render::MemoryPressure;
+
+    assert_eq!(memory::pressure_from_level(1), Some(MemoryPressure::Normal));
+    assert_eq!(
+        memory::pressure_from_level(4),
+        Some(MemoryPressure::Critical)
+    );
+    assert_eq!(memory::pressure_from_level(0), None);
+}
+
+#[test]
+fn test_mem_formula() {
+    use config::MemFormula;
+    const GIB: u64 = 1 << 30;
+
+    // 16 GiB with 10 GiB reported used, 9 GiB of it cache that can be freed
+    let (total, used, available) = (16 * GIB, 10 * GIB, 15 * GIB);
+    assert_eq!(
+        memory::used_bytes(MemFormula::Used, total, used, available, 0),
+        10 * GIB
+    );
+    assert_eq!(
+        memory::used_bytes(MemFormula::Available, total, used, available, 0),
+        GIB
+    );
+    // Readings taken mid-update never go past total or below zero
+    assert_eq!(
+        memory::used_bytes(MemFormula::Used, total, 20 * GIB, 0, 0),
+        total
+    );
+    assert_eq!(
+        memory::used_bytes(MemFormula::Available, total, used, 20 * GIB, 0),
+        0
+    );
+
+    let config = Config::default();
+    assert_eq!(config.mem_formula(), MemFormula::Used);
+    config
+        .set(config::key::MEM_FORMULA, &serde_json::json!("available"))
+        .unwrap();
+    assert_eq!(config.mem_formula(), MemFormula::Available);
+    assert_eq!(
+        config.get(config::key::MEM_FORMULA),
+        Some(serde_json::json!("available"))
+    );
+    assert!(config
+        .set(config::key::MEM_FORMULA, &serde_json::json!("free"))
+        .is_err());
+}
+
+#[test]
+fn test_mem_reclaimable() {
+    use config::{MemClass, MemFormula};
+    const GIB: u64 = 1 << 30;
+
+    let arcstats = "13 1 0x01 123 33456 5138962474 1093470462383\n\
+        name                            type data\n\
+        hits                            4    182734\n\
+        c_min                           4    1073741824\n\
+        c_max                           4    17179869184\n\
+        size                            4    9663676416\n";
+    assert_eq!(memory::parse_arcstats(arcstats), Some(8 * GIB));
+    assert_eq!(memory::parse_arcstats("size 4 1024\n"), Some(1024));
+    assert_eq!(memory::parse_arcstats("name type data\n"), None);
+
+    // A 12 GiB ARC that Linux counts as used, 8 GiB of it reclaimable
+    let (total, used, available) = (32 * GIB, 20 * GIB, 12 * GIB);
+    assert_eq!(
+        memory::used_bytes(MemFormula::Available, total, used, available, 8 * GIB),
+        12 * GIB
+    );
+    assert_eq!(
+        memory::used_bytes(MemFormula::Used, total, used, available, 40 * GIB),
+        0
+    );
+
+    let config = Config::default();
+    assert!(config.mem_reclaimable().is_empty());
+    assert_eq!(
+        config.set(
+            config::key::MEM_RECLAIMABLE,
+            &serde_json::json!(["file_cache", "zfs_arc"])
+        ),
+        Ok(serde_json::json!(["file_cache", "zfs_arc"]))
+    );
+    assert_eq!(
+        config.mem_reclaimable(),
+        vec![MemClass::FileCache, MemClass::ZfsArc]
+    );
+    assert!(config
+        .set(
+            config::key::MEM_RECLAIMABLE,
+            &serde_json::json!(["zfs_arc", "zfs_arc"])
+        )
+        .is_err());
+    assert!(config
+        .set(config::key::MEM_RECLAIMABLE, &serde_json::json!(["swap"]))
+        .is_err());
+}
+
+#[test]
+fn test_mem_breakdown() {
+    use tray_render::MemoryBreakdown;
+
+    let meminfo = "MemTotal:       16000000 kB\n\
+        MemFree:         2000000 kB\n\
+        MemAvailable:    8000000 kB\n\
+        Buffers:          500000 kB\n\
+        Cached:          5000000 kB\n\
+        SwapCached:        40000 kB\n\
+        Shmem:            500000 kB\n\
+        SReclaimable:    1000000 kB\n\
+        SUnreclaim:       300000 kB\n\
+        KernelStack:       50000 kB\n\
+        PageTables:       150000 kB\n";
+    let classes = memory::parse_meminfo(meminfo).unwrap();
+    // Shared memory stays with apps, as it can't be dropped like cache
+    assert_eq!(
+        classes,
+        MemoryBreakdown {
+            apps: 7_500_000 * 1024,
+            wired: 500_000 * 1024,
+            cached: 6_000_000 * 1024,
+            free: 2_000_000 * 1024,
+        }
+    );
+    assert_eq!(classes.total(), 16_000_000 * 1024);
+    assert_eq!(
+        memory::breakdown_line(&classes),
+        "Memory: Apps 7.7 GB · Wired 512 MB · Cached 6.1 GB · Free 2.0 GB"
+    );
+    assert_eq!(memory::parse_meminfo("MemFree: 1 kB\n"), None);
+
+    let config = Config::default();
+    assert_eq!(
+        config.set(config::key::MEM_DISPLAY, &serde_json::json!("breakdown")),
+        Ok(serde_json::json!("breakdown"))
+    );
+    assert_eq!(config.mem_display(), config::MemDisplay::Breakdown);
+}
+
+#[test]
+fn test_cgroup_parsing() {
+    use cgroup::{
+        cpu_percent, parse_cpu_max, parse_memory_max, parse_own_cgroup, parse_stat_field,
+    };
+
+    assert_eq!(
+        parse_own_cgroup("0::/user.slice/user-1000.slice/app.scope\n"),
+        Some("/user.slice/user-1000.slice/app.scope")
+    );
+    // Hybrid hierarchies list v1 controllers first
+    assert_eq!(
+        parse_own_cgroup("12:cpu,cpuacct:/\n0::/docker/3f2a\n"),
+        Some("/docker/3f2a")
+    );
+    assert_eq!(parse_own_cgroup("4:memory:/\n"), None);
+
+    assert_eq!(parse_cpu_max("150000 100000\n"), Some(1.5));
+    assert_eq!(parse_cpu_max("max 100000\n"), None);
+    assert_eq!(parse_memory_max("4294967296\n"), Some(4 << 30));
+    assert_eq!(parse_memory_max("max\n"), None);
+
+    let stat = "usage_usec 8123456\nuser_usec 6000000\nsystem_usec 2123456\n";
+    assert_eq!(parse_stat_field(stat, "usage_usec"), Some(8_123_456));
+    assert_eq!(parse_stat_field(stat, "user"), None);
+
+    // 1.5 s of CPU time over 1 s against a 2-core quota
+    assert_eq!(cpu_percent(1_500_000, 1_000_000, 2.0), Some(75.0));
+    assert_eq!(cpu_percent(3_000_000, 1_000_000, 2.0), Some(100.0));
+    assert_eq!(cpu_percent(1_000, 0, 2.0), None);
+
+    let config = Config::default();
+    config
+        .set(
+            config::key::CGROUP_PATH,
+            &serde_json::json!(" system.slice/docker-3f2a.scope "),
+        )
+        .unwrap();
+    assert_eq!(config.cgroup_path(), "system.slice/docker-3f2a.scope");
+    assert!(config
+        .set(config::key::CGROUP_PATH, &serde_json::json!("../../etc"))
+        .is_err());
+}
+
+#[test]
+fn test_sanitize_threshold() {
+    assert_eq!(config::sanitize_threshold(0), 1);
+    assert_eq!(config::sanitize_threshold(85), 85);
+    assert_eq!(config::sanitize_threshold(100), 100);
+    assert_eq!(config::sanitize_threshold(250), 100);
+}
+
+#[test]
+fn test_parse_threshold_menu_id() {
+    let id = threshold_menu_id(config::key::CPU_ALERT_THRESHOLD, 80);
+    assert_eq!(
+        parse_threshold_menu_id(&id),
+        Some((config::key::CPU_ALERT_THRESHOLD, 80))
+    );
+    assert_eq!(parse_threshold_menu_id("show_cpu"), None);
+    assert_eq!(parse_threshold_menu_id("unknown:80"), None);
+    assert_eq!(parse_threshold_menu_id("mem_alert_threshold:abc"), None);
+}
+
+#[test]
+fn test_history_file_name_round_trip() {
+    assert_eq!(history::file_name_for_day(0), "history-1970-01-01.csv");
+    // 2024-02-29 is day 19782 since the epoch
+    assert_eq!(history::file_name_for_day(19_782), "history-2024-02-29.csv");
+    assert_eq!(
+        history::day_from_file_name("history-2024-02-29.csv"),
+        Some(19_782)
+    );
+    assert_eq!(history::day_from_file_name("history-2024-13-01.csv"), None);
+    assert_eq!(history::day_from_file_name("notes.csv"), None);
+}
+
+#[test]
+fn test_history_retention() {
+    assert!(!history::is_expired(100, 100, 7));
+    assert!(!history::is_expired(94, 100, 7));
+    assert!(history::is_expired(93, 100, 7));
+    // Zero retention still keeps today's file
+    assert!(!history::is_expired(100, 100, 0));
+}
+
+#[test]
+fn test_history_csv_row() {
+    let sample = metrics::Sample {
+        timestamp: 1_700_000_000,
+        cpu: Some(12.345),
+        mem: Some(50.0),
+        gpu: None,
+        gpu_mem: Some(6_000_000_000),
+        net_down: Some(1_500.4),
+        net_up: Some(0.0),
+        cpu_power: Some(4.256),
+        gpu_temp: None,
+    };
+    assert_eq!(
+        history::csv_row(&sample),
+        "1700000000,12.3,50.0,,1500,0,6000000000,4.26,"
+    );
+}
+
+#[test]
+fn test_history_recorder_writes_header_once() {
+    let dir = std::env::temp_dir().join(format!("brm-history-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let mut recorder = history::HistoryRecorder::new(dir.clone(), 7);
+    let sample = metrics::Sample {
+        timestamp: 86_400 * 19_782,
+        cpu: Some(1.0),
+        ..Default::default()
+    };
+    recorder.record(&sample).expect("first write");
+    recorder.record(&sample).expect("second write");
+
+    let contents =
+        std::fs::read_to_string(dir.join("history-2024-02-29.csv")).expect("history file");
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("timestamp,"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
 #[test]
 fn test_format_speed() {
     // KB range (0.0 - 999.5)
@@ -65,301 +649,2083 @@ fn test_format_speed() {
 }
 
 #[test]
-fn test_render_svg_icon_valid() {
-    // Simple valid SVG
-    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24"><circle cx="12" cy="12" r="10" fill="currentColor"/></svg>"#;
-    let result = tray_render::render_svg_icon(svg, 16, (255, 255, 255));
-
-    // Should return non-empty pixel data
-    assert!(!result.is_empty());
-
-    // 16x16 RGBA = 1024 bytes
-    assert_eq!(result.len(), 16 * 16 * 4);
-}
-
-#[test]
-#[should_panic(expected = "Failed to parse SVG")]
-fn test_render_svg_icon_invalid_panics() {
-    // Invalid SVG should panic (current behavior uses .expect())
-    tray_render::render_svg_icon("not valid svg", 16, (255, 255, 255));
-}
-
-#[test]
-fn test_icon_buffer_reuse() {
-    let font = load_system_font().expect("test font required");
-
-    let mut renderer = tray_render::TrayRenderer::new();
-
-    // Create buffer with known capacity
-    let mut buffer: Vec<u8> = Vec::with_capacity(4 * 800 * APP_SIZING.icon_height as usize);
-    let initial_capacity = buffer.capacity();
-
-    // First render
-    let (width1, height1, _) = renderer.render_tray_icon_into(
-        &font,
-        &mut buffer,
-        APP_SIZING,
-        50.0,
-        60.0,
-        0.0,
-        "1.0 KB",
-        "0.5 KB",
-        true,
-        true,
-        false,
-        true,
-        false,
-        true,
-        None,
-    );
-    assert!(width1 > 0);
-    assert_eq!(height1, APP_SIZING.icon_height);
-    assert!(!buffer.is_empty());
-
-    // Capacity should be preserved or grown, never shrunk
-    let capacity_after_first = buffer.capacity();
-    assert!(capacity_after_first >= initial_capacity);
-
-    // Second render with different values - buffer should be reused
-    let (width2, height2, _) = renderer.render_tray_icon_into(
-        &font,
-        &mut buffer,
-        APP_SIZING,
-        70.0,
-        80.0,
-        0.0,
-        "2.0 KB",
-        "1.0 KB",
-        true,
-        true,
-        false,
-        true,
-        false,
-        true,
-        None,
-    );
-    assert!(width2 > 0);
-    assert_eq!(height2, APP_SIZING.icon_height);
-
-    // Capacity should still be preserved (key test: no reallocation for same-size renders)
-    assert!(buffer.capacity() >= capacity_after_first);
-}
-
-#[test]
-fn test_alert_colors_all_segments() {
-    let font = load_system_font().expect("test font required");
-    let mut buffer: Vec<u8> = Vec::new();
-
-    let mut renderer = tray_render::TrayRenderer::new();
-
-    // No alerts - has_active_alert should be false
-    let (_, _, has_alert_no) = renderer.render_tray_icon_into(
-        &font,
-        &mut buffer,
-        APP_SIZING,
-        50.0,
-        50.0,
-        0.0,
-        "0 KB",
-        "0 KB",
-        true,
-        true,
-        false,
-        false,
-        true, // alerts enabled
-        true,
-        None,
-    );
-    assert!(!has_alert_no);
-
-    // CPU at 95% with alerts enabled - has_active_alert should be true
-    let (_, _, has_alert_yes) = renderer.render_tray_icon_into(
-        &font,
-        &mut buffer,
-        APP_SIZING,
-        95.0,
-        50.0,
-        0.0,
-        "0 KB",
-        "0 KB",
-        true,
-        true,
-        false,
-        false,
-        true, // alerts enabled
-        true,
-        None,
-    );
-    assert!(has_alert_yes);
-
-    // CPU at 95% but alerts disabled - has_active_alert should be false
-    let (_, _, has_alert_disabled) = renderer.render_tray_icon_into(
-        &font,
-        &mut buffer,
-        APP_SIZING,
-        95.0,
-        50.0,
-        0.0,
-        "0 KB",
-        "0 KB",
-        true,
-        true,
-        false,
-        false,
-        false, // alerts disabled
-        true,
-        None,
-    );
-    assert!(!has_alert_disabled);
-}
-
-#[test]
-fn test_sizing_scaled_up() {
-    let scaled = tray_render::SIZING_LINUX.scaled(2.0);
-
-    assert_eq!(scaled.segment_width, 116);
-    assert_eq!(scaled.segment_width_net, 150);
-    assert_eq!(scaled.edge_padding, 10);
-    assert_eq!(scaled.segment_gap, 36);
-    assert_eq!(scaled.icon_height, 44);
-    assert_eq!(scaled.font_size, 38.0);
-}
-
-#[test]
-fn test_sizing_scaled_down() {
-    let scaled = tray_render::SIZING_LINUX.scaled(0.5);
-
-    assert_eq!(scaled.segment_width, 29);
-    assert_eq!(scaled.segment_width_net, 38);
-    assert_eq!(scaled.edge_padding, 3);
-    assert_eq!(scaled.segment_gap, 9);
-    assert_eq!(scaled.icon_height, 11);
-    assert_eq!(scaled.font_size, 9.5);
+fn test_format_bytes() {
+    const MIB: u64 = 1024 * 1024;
+    const GIB: u64 = MIB * 1024;
+    assert_eq!(format_bytes(0), "0M");
+    assert_eq!(format_bytes(850 * MIB), "850M");
+    assert_eq!(format_bytes(1023 * MIB), "1023M");
+    assert_eq!(format_bytes(1024 * MIB - MIB / 4), "1.0G"); // Rounds up to a whole GiB
+    assert_eq!(format_bytes(GIB), "1.0G");
+    assert_eq!(format_bytes(12 * GIB + 2 * GIB / 5), "12.4G");
+    assert_eq!(format_bytes(64 * GIB), "64.0G");
+    assert_eq!(format_bytes(128 * GIB), "128G");
 }
 
 #[test]
-fn test_sizing_scaled_rounding() {
-    let scaled = tray_render::SIZING_LINUX.scaled(0.333);
+fn test_format_frequency() {
+    assert_eq!(format_frequency(0), "unavailable");
+    assert_eq!(format_frequency(800), "800 MHz");
+    assert_eq!(format_frequency(3_200), "3.20 GHz");
+    assert_eq!(format_frequency(4_056), "4.06 GHz");
+}
 
-    assert_eq!(scaled.segment_width, 19);
-    assert_eq!(scaled.segment_width_net, 25);
-    assert_eq!(scaled.edge_padding, 2);
-    assert_eq!(scaled.segment_gap, 6);
-    assert_eq!(scaled.icon_height, 7);
-    assert_eq!(scaled.font_size, 19.0 * 0.333);
-}
-
-#[test]
-#[should_panic(expected = "scale must be > 0")]
-fn test_sizing_scaled_panics_on_zero() {
-    let _ = tray_render::SIZING_LINUX.scaled(0.0);
+#[test]
+fn test_format_watts() {
+    assert_eq!(format_watts(0.0), "0.0W");
+    assert_eq!(format_watts(4.26), "4.3W");
+    assert_eq!(format_watts(9.96), "10W");
+    assert_eq!(format_watts(18.4), "18W");
 }
 
 #[test]
-fn test_get_update_interval_ms_default_when_unset() {
-    let _guard = env_lock().lock().expect("env lock poisoned");
-    let previous = std::env::var("SILICON_UPDATE_INTERVAL").ok();
-    std::env::remove_var("SILICON_UPDATE_INTERVAL");
+fn test_format_temperature() {
+    assert_eq!(format_temperature(0.0), "0°C");
+    assert_eq!(format_temperature(71.6), "72°C");
+    assert_eq!(format_temperature(105.0), "105°C");
+}
+
+#[test]
+fn test_tray_tooltip() {
+    assert_eq!(format_uptime(59), "0m");
+    assert_eq!(format_uptime(3_600 + 12 * 60), "1h 12m");
+    assert_eq!(format_uptime(3 * 86_400 + 4 * 3_600 + 60), "3d 4h 1m");
 
-    assert_eq!(get_update_interval_ms(), UPDATE_INTERVAL_MS);
+    // 2024-02-29 08:05 UTC
+    let boot = 19_782 * 86_400 + 8 * 3_600 + 5 * 60;
+    assert_eq!(format_utc_timestamp(boot), "2024-02-29 08:05 UTC");
 
-    if let Some(value) = previous {
-        std::env::set_var("SILICON_UPDATE_INTERVAL", value);
+    // Boot time is shown in local time, here UTC+2
+    let tooltip = tray_tooltip(120, boot, 2 * 3_600, &["GPU: 5%".to_string()]);
+    assert_eq!(
+        tooltip,
+        "System Monitor\nUptime: 2m\nBooted: 2024-02-29 10:05\nGPU: 5%"
+    );
+}
+
+#[test]
+fn test_format_bitrate() {
+    assert_eq!(format_bitrate(0.0), "0.0 Kb");
+    assert_eq!(format_bitrate(1_500.0), "12 Kb");
+    assert_eq!(format_bitrate(125_000.0), "1.0 Mb");
+    assert_eq!(format_bitrate(1_250_000.0), "10 Mb");
+    assert_eq!(format_bitrate(125_000_000.0), "1.0 Gb");
+    assert_eq!(format_net(1_500.0, NetUnit::Bytes), "1.5 KB");
+    assert_eq!(format_net(1_500.0, NetUnit::Bits), "12 Kb");
+}
+
+#[test]
+fn test_config_set_validates_and_sanitizes() {
+    let config = Config::default();
+
+    assert_eq!(
+        config.set(config::key::CPU_ALERT_THRESHOLD, &serde_json::json!(250)),
+        Ok(serde_json::json!(100))
+    );
+    assert_eq!(config.alert_thresholds().cpu, 100.0);
+
+    assert_eq!(
+        config.set(config::key::UPDATE_INTERVAL_MS, &serde_json::json!(10)),
+        Ok(serde_json::json!(250))
+    );
+    assert_eq!(
+        config.set(config::key::RENDER_INTERVAL_MS, &serde_json::json!(250)),
+        Ok(serde_json::json!(1000))
+    );
+    assert_eq!(
+        config.set(config::key::RENDER_INTERVAL_MS, &serde_json::json!(0)),
+        Ok(serde_json::json!(0))
+    );
+    assert_eq!(
+        config.set(config::key::NET_UNIT, &serde_json::json!("bits")),
+        Ok(serde_json::json!("bits"))
+    );
+    assert_eq!(config.net_unit(), NetUnit::Bits);
+    assert_eq!(
+        config.set(config::key::CPU_DISPLAY, &serde_json::json!("load")),
+        Ok(serde_json::json!("load"))
+    );
+    assert_eq!(config.cpu_display(), CpuDisplay::LoadAverage);
+    assert_eq!(
+        config.set(config::key::CPU_DISPLAY, &serde_json::json!("user_system")),
+        Ok(serde_json::json!("user_system"))
+    );
+    assert_eq!(config.cpu_display(), CpuDisplay::UserSystem);
+    assert_eq!(
+        config.set(config::key::MEM_DISPLAY, &serde_json::json!("used")),
+        Ok(serde_json::json!("used"))
+    );
+    assert_eq!(config.mem_display(), MemDisplay::Used);
+    assert!(config
+        .set(config::key::MEM_DISPLAY, &serde_json::json!("bytes"))
+        .is_err());
+    assert!(config
+        .set(config::key::CPU_DISPLAY, &serde_json::json!("loadavg"))
+        .is_err());
+    assert!(config
+        .set(config::key::STATUS_SERVER_PORT, &serde_json::json!(80))
+        .is_err());
+    assert_eq!(
+        config.set(config::key::STATUS_SERVER_PORT, &serde_json::json!(8080)),
+        Ok(serde_json::json!(8080))
+    );
+
+    assert!(config
+        .set(config::key::SHOW_CPU, &serde_json::json!("yes"))
+        .is_err());
+    assert!(config
+        .set(config::key::NET_UNIT, &serde_json::json!("nibbles"))
+        .is_err());
+    assert!(config.set("unknown", &serde_json::json!(true)).is_err());
+}
+
+#[test]
+fn test_config_to_json_covers_all_keys() {
+    let config = Config::default();
+    let json = config.to_json();
+    for key in config::ALL_KEYS {
+        assert!(json.get(key).is_some(), "missing {key}");
     }
+    assert_eq!(json[config::key::SHOW_CPU], serde_json::json!(true));
+    assert_eq!(json[config::key::NET_UNIT], serde_json::json!("bytes"));
+    assert_eq!(
+        json[config::key::SEGMENT_ORDER],
+        serde_json::json!(["mem", "cpu", "gpu", "net"])
+    );
 }
 
 #[test]
-fn test_get_update_interval_ms_valid_env() {
-    let _guard = env_lock().lock().expect("env lock poisoned");
-    let previous = std::env::var("SILICON_UPDATE_INTERVAL").ok();
-    std::env::set_var("SILICON_UPDATE_INTERVAL", "1234");
+fn test_config_colors() {
+    let config = Config::default();
+    assert_eq!(config.palette(false), Palette::for_theme(false));
+
+    assert_eq!(
+        config.set(config::key::ALERT_COLOR, &serde_json::json!("#00ff00")),
+        Ok(serde_json::json!("#00FF00"))
+    );
+    assert_eq!(
+        config.set(config::key::TEXT_COLOR, &serde_json::json!("#336699")),
+        Ok(serde_json::json!("#336699"))
+    );
+    let palette = config.palette(true);
+    assert_eq!(palette.alert, (0, 255, 0));
+    assert_eq!(palette.foreground, (0x33, 0x66, 0x99));
 
-    assert_eq!(get_update_interval_ms(), 1234);
+    // Empty text color follows the theme again
+    assert_eq!(
+        config.set(config::key::TEXT_COLOR, &serde_json::json!("")),
+        Ok(serde_json::json!(""))
+    );
+    assert_eq!(config.palette(false).foreground, (0, 0, 0));
 
-    if let Some(value) = previous {
-        std::env::set_var("SILICON_UPDATE_INTERVAL", value);
-    } else {
-        std::env::remove_var("SILICON_UPDATE_INTERVAL");
+    assert!(config
+        .set(config::key::ALERT_COLOR, &serde_json::json!(""))
+        .is_err());
+    assert!(config
+        .set(config::key::TEXT_COLOR, &serde_json::json!("red"))
+        .is_err());
+}
+
+#[test]
+fn test_icon_color_override() {
+    let config = Config::default();
+    assert_eq!(config.icon_color(), IconColor::Auto);
+    assert!(config.follows_theme());
+    assert_eq!(config.palette_on((35, 38, 41)).foreground, (35, 38, 41));
+
+    // Forced colors win over detection
+    assert_eq!(
+        config.set(config::key::ICON_COLOR, &serde_json::json!("light")),
+        Ok(serde_json::json!("light"))
+    );
+    assert!(!config.follows_theme());
+    assert_eq!(config.palette(false).foreground, (255, 255, 255));
+    config
+        .set(config::key::ICON_COLOR, &serde_json::json!("dark"))
+        .unwrap();
+    assert_eq!(config.palette_on((252, 252, 252)).foreground, (0, 0, 0));
+
+    // A custom text color wins over both
+    config
+        .set(config::key::TEXT_COLOR, &serde_json::json!("#336699"))
+        .unwrap();
+    assert_eq!(config.palette(true).foreground, (0x33, 0x66, 0x99));
+
+    assert!(config
+        .set(config::key::ICON_COLOR, &serde_json::json!("blue"))
+        .is_err());
+
+    // Metric colors can't be drawn as a macOS template either
+    config
+        .set(config::key::TEXT_COLOR, &serde_json::json!(""))
+        .unwrap();
+    config
+        .set(config::key::ICON_COLOR, &serde_json::json!("auto"))
+        .unwrap();
+    assert!(config.palette(true).metric_colors.is_none());
+    config.metric_colors.store(true, Relaxed);
+    assert_eq!(
+        config.set(config::key::GPU_COLOR, &serde_json::json!("#112233")),
+        Ok(serde_json::json!("#112233"))
+    );
+    assert!(!config.follows_theme());
+    config.metric_colors.store(false, Relaxed);
+    assert!(config.follows_theme());
+    // Nor can memory colored by its pressure level
+    config.set_mem_display(MemDisplay::Pressure);
+    assert!(!config.follows_theme());
+    config.set_mem_display(MemDisplay::Percent);
+    // Nor an outline or shadow, which template mode would flatten
+    config
+        .set(config::key::TEXT_EFFECT, &serde_json::json!("outline"))
+        .unwrap();
+    assert!(!config.follows_theme());
+    config
+        .set(config::key::TEXT_EFFECT, &serde_json::json!("none"))
+        .unwrap();
+    config.metric_colors.store(true, Relaxed);
+    let colors = config.palette(true).metric_colors.expect("metric colors");
+    assert_eq!(colors.gpu, (0x11, 0x22, 0x33));
+    assert_eq!(colors.cpu, tray_render::MetricColors::DEFAULT.cpu);
+    assert_eq!(
+        parse_icon_color_menu_id(&icon_color_menu_id(IconColor::Light)),
+        Some(IconColor::Light)
+    );
+    assert_eq!(parse_icon_color_menu_id("layout:light"), None);
+}
+
+#[test]
+fn test_config_segment_order() {
+    let config = Config::default();
+    assert_eq!(config.segment_order(), SegmentKind::ALL);
+
+    // Missing segments are appended in default order
+    assert_eq!(
+        config.set(
+            config::key::SEGMENT_ORDER,
+            &serde_json::json!(["net", "cpu"])
+        ),
+        Ok(serde_json::json!(["net", "cpu", "mem", "gpu"]))
+    );
+    assert_eq!(
+        config.segment_order(),
+        [
+            SegmentKind::Network,
+            SegmentKind::Cpu,
+            SegmentKind::Memory,
+            SegmentKind::Gpu
+        ]
+    );
+
+    assert!(config
+        .set(
+            config::key::SEGMENT_ORDER,
+            &serde_json::json!(["cpu", "cpu"])
+        )
+        .is_err());
+    assert!(config
+        .set(config::key::SEGMENT_ORDER, &serde_json::json!(["disk"]))
+        .is_err());
+    assert!(config
+        .set(config::key::SEGMENT_ORDER, &serde_json::json!("cpu,mem"))
+        .is_err());
+    assert_eq!(config.segment_order()[0], SegmentKind::Network);
+}
+
+#[test]
+fn test_config_alert_sound() {
+    let config = Config::default();
+    assert_eq!(config.alert_sound(), sound::AlertSound::Off);
+    assert_eq!(
+        config.set(config::key::ALERT_SOUND, &serde_json::json!("chime")),
+        Ok(serde_json::json!("chime"))
+    );
+    assert_eq!(config.alert_sound(), sound::AlertSound::Chime);
+    assert!(config
+        .set(config::key::ALERT_SOUND, &serde_json::json!("klaxon"))
+        .is_err());
+    assert_eq!(
+        config.set(
+            config::key::ALERT_SOUND_COOLDOWN_SECS,
+            &serde_json::json!(1)
+        ),
+        Ok(serde_json::json!(sound::MIN_COOLDOWN_SECS))
+    );
+
+    // Nothing is played, or counted against the cooldown, while off
+    let mut sounder = sound::AlertSounder::default();
+    assert!(!sounder.alert(sound::AlertSound::Off, Duration::ZERO));
+}
+
+#[test]
+fn test_ema_smoothing() {
+    use smoothing::Ema;
+
+    let mut ema = Ema::default();
+    assert_eq!(ema.update(Some(60.0), 50), Some(60.0));
+    assert_eq!(ema.update(Some(20.0), 50), Some(40.0));
+    assert_eq!(ema.update(Some(40.0), 0), Some(40.0));
+    // A gap starts over rather than blending in a stale value
+    assert_eq!(ema.update(None, 50), None);
+    assert_eq!(ema.update(Some(5.0), 50), Some(5.0));
+
+    let config = Config::default();
+    assert_eq!(
+        config.get(config::key::GPU_SMOOTHING),
+        Some(serde_json::json!(0))
+    );
+    assert_eq!(
+        config.set(config::key::GPU_SMOOTHING, &serde_json::json!(99)),
+        Ok(serde_json::json!(smoothing::MAX_SMOOTHING))
+    );
+    assert_eq!(config.gpu_smoothing.load(Relaxed), 90);
+    assert_eq!(config.cpu_smoothing.load(Relaxed), 0);
+}
+
+#[test]
+fn test_value_interpolation() {
+    use interpolation::{ease_out, frames, sub_frame_interval, Shown, SUB_FRAMES};
+    use std::time::Duration;
+
+    assert_eq!(ease_out(0.0), 0.0);
+    assert_eq!(ease_out(1.0), 1.0);
+    assert!(ease_out(0.5) > 0.5);
+
+    let from = Shown {
+        cpu: 10.0,
+        down: 1_000.0,
+        ..Shown::default()
+    };
+    let to = Shown {
+        cpu: 80.0,
+        mem: 40.0,
+        down: 4_000.0,
+        ..Shown::default()
+    };
+    let steps = frames(from, to, SUB_FRAMES);
+    assert_eq!(steps.len(), 3);
+    // Most of the way on the first frame, then settling onto the reading
+    assert!((steps[0].cpu - 59.26).abs() < 0.01);
+    assert!((steps[1].cpu - 77.41).abs() < 0.01);
+    assert!(steps[0].down > 1_000.0 && steps[0].down < steps[1].down);
+    assert_eq!(steps[2], to);
+    assert_eq!(frames(from, to, 1), vec![to]);
+
+    assert_eq!(
+        sub_frame_interval(Duration::from_secs(1)),
+        Duration::from_millis(80)
+    );
+    assert_eq!(
+        sub_frame_interval(Duration::from_millis(300)),
+        Duration::from_millis(50)
+    );
+
+    let config = Config::default();
+    assert_eq!(
+        config.get(config::key::INTERPOLATE_VALUES),
+        Some(serde_json::json!(false))
+    );
+}
+
+#[test]
+fn test_quiet_hours() {
+    use quiet_hours::QuietHours;
+
+    let night = QuietHours::parse("22:00-08:00").unwrap();
+    assert_eq!((night.start, night.end), (22 * 60, 8 * 60));
+    assert!(night.contains(23 * 60));
+    assert!(night.contains(0));
+    assert!(night.contains(7 * 60 + 59));
+    assert!(!night.contains(8 * 60));
+    assert!(!night.contains(12 * 60));
+
+    let lunch = QuietHours::parse("12:30 – 13:15").unwrap();
+    assert!(lunch.contains(13 * 60));
+    assert!(!lunch.contains(22 * 60));
+    assert_eq!(lunch.to_setting(), "12:30-13:15");
+    assert_eq!(QuietHours::unpack(lunch.pack()), Some(lunch));
+
+    for invalid in ["", "22:00", "24:00-08:00", "22:60-08:00", "08:00-08:00"] {
+        assert_eq!(QuietHours::parse(invalid), None, "{invalid}");
     }
+
+    let config = Config::default();
+    assert_eq!(config.quiet_hours(), None);
+    assert_eq!(
+        config.set(config::key::QUIET_HOURS, &serde_json::json!(" 22:00-8:00 ")),
+        Ok(serde_json::json!("22:00-08:00"))
+    );
+    assert_eq!(config.quiet_hours(), Some(night));
+    assert!(config
+        .set(config::key::QUIET_HOURS, &serde_json::json!("late"))
+        .is_err());
+    assert_eq!(
+        config.set(config::key::QUIET_HOURS, &serde_json::json!("")),
+        Ok(serde_json::json!(""))
+    );
+    assert_eq!(config.quiet_hours(), None);
+
+    assert_eq!(alerts::menu_title(None), "Recent Alerts");
+    assert_eq!(
+        alerts::menu_title(Some(night)),
+        "Recent Alerts (Quiet until 08:00)"
+    );
 }
 
 #[test]
-fn test_get_update_interval_ms_invalid_env_falls_back() {
-    let _guard = env_lock().lock().expect("env lock poisoned");
-    let previous = std::env::var("SILICON_UPDATE_INTERVAL").ok();
-    std::env::set_var("SILICON_UPDATE_INTERVAL", "abc");
+fn test_idle_segments_hide_and_return() {
+    use idle::{Activity, IdleSegments};
+    use SegmentKind::{Cpu, Memory, Network};
 
-    assert_eq!(get_update_interval_ms(), UPDATE_INTERVAL_MS);
+    let visible = vec![Memory, Cpu, Network];
+    let quiet = Activity {
+        cpu: Some(30.0),
+        mem: Some(60.0),
+        gpu: None,
+        net: Some((2_000.0, 500.0)),
+    };
+    let mut idle = IdleSegments::default();
+    for _ in 0..2 {
+        idle.observe(&quiet);
+        assert_eq!(idle.filter(visible.clone(), &[Network], 3), visible);
+    }
+    idle.observe(&quiet);
+    assert_eq!(idle.filter(visible.clone(), &[Network], 3), [Memory, Cpu]);
+    // Only listed segments hide, and CPU at 30% isn't idle anyway
+    assert_eq!(idle.filter(visible.clone(), &[Cpu], 3), visible);
 
-    if let Some(value) = previous {
-        std::env::set_var("SILICON_UPDATE_INTERVAL", value);
-    } else {
-        std::env::remove_var("SILICON_UPDATE_INTERVAL");
+    idle.observe(&Activity {
+        net: Some((2_000_000.0, 500.0)),
+        ..quiet
+    });
+    assert_eq!(idle.filter(visible.clone(), &[Network], 3), visible);
+
+    // The icon keeps one segment when everything listed is idle
+    for _ in 0..3 {
+        idle.observe(&quiet);
     }
+    assert_eq!(idle.filter(vec![Network], &[Network], 3), [Network]);
+
+    let config = Config::default();
+    assert!(config.auto_hide().is_empty());
+    assert_eq!(
+        config.set(config::key::AUTO_HIDE, &serde_json::json!(["net", "gpu"])),
+        Ok(serde_json::json!(["net", "gpu"]))
+    );
+    assert_eq!(config.auto_hide(), [Network, SegmentKind::Gpu]);
+    assert!(config
+        .set(config::key::AUTO_HIDE, &serde_json::json!(["net", "net"]))
+        .is_err());
+    assert_eq!(
+        config.set(config::key::AUTO_HIDE_CYCLES, &serde_json::json!(0)),
+        Ok(serde_json::json!(1))
+    );
 }
 
 #[test]
-fn test_render_with_all_segments_disabled() {
-    let font = load_system_font().expect("test font required");
-    let mut buffer = Vec::new();
-    let mut renderer = tray_render::TrayRenderer::new();
-
-    let (width, height, has_alert) = renderer.render_tray_icon_into(
-        &font,
-        &mut buffer,
-        APP_SIZING,
-        50.0,
-        50.0,
-        50.0,
-        "0 KB",
-        "0 KB",
-        false,
-        false,
-        false,
-        false,
-        true,
-        true,
-        None,
-    );
-
-    assert_eq!(width, APP_SIZING.edge_padding * 2);
-    assert_eq!(height, APP_SIZING.icon_height);
-    assert!(!has_alert);
-    assert_eq!(buffer.len(), (width * height * 4) as usize);
-}
-
-#[test]
-fn test_render_with_long_network_strings() {
-    let font = load_system_font().expect("test font required");
-    let mut buffer = Vec::new();
-    let mut renderer = tray_render::TrayRenderer::new();
-    let long_down = "9".repeat(512);
-    let long_up = "8".repeat(512);
-
-    let (width, height, has_alert) = renderer.render_tray_icon_into(
-        &font,
-        &mut buffer,
-        APP_SIZING,
-        0.0,
-        0.0,
-        0.0,
-        &long_down,
-        &long_up,
-        false,
-        false,
-        false,
-        true,
-        true,
-        true,
-        None,
-    );
-
-    let expected_width = APP_SIZING.edge_padding * 2
-        + (APP_SIZING.segment_width_net * 2)
-        + APP_SIZING.segment_gap;
-
-    assert_eq!(width, expected_width);
-    assert_eq!(height, APP_SIZING.icon_height);
-    assert!(!has_alert);
-    assert_eq!(buffer.len(), (width * height * 4) as usize);
+fn test_config_tray_icons() {
+    use SegmentKind::{Cpu, Gpu, Memory, Network};
+
+    let config = Config::default();
+    assert!(config.tray_icons().is_empty());
+    let icons = serde_json::json!([["mem", "cpu"], ["net"]]);
+    assert_eq!(config.set(config::key::TRAY_ICONS, &icons), Ok(icons));
+    assert_eq!(config.tray_icons(), vec![vec![Memory, Cpu], vec![Network]]);
+
+    for invalid in [
+        serde_json::json!([["cpu"], ["cpu"]]),
+        serde_json::json!([[]]),
+        serde_json::json!([["disk"]]),
+        serde_json::json!(["cpu"]),
+    ] {
+        assert!(config.set(config::key::TRAY_ICONS, &invalid).is_err());
+    }
+
+    // Visible segments keep their order; unlisted ones go on the first icon
+    let visible = [Network, Cpu, Gpu];
+    assert_eq!(icon_segments(&[], &visible), vec![visible.to_vec()]);
+    assert_eq!(
+        icon_segments(&config.tray_icons(), &visible),
+        vec![vec![Cpu, Gpu], vec![Network]]
+    );
+    assert_eq!(
+        icon_segments(&config.tray_icons(), &[Memory]),
+        vec![vec![Memory], vec![]]
+    );
+    assert_eq!(tray_id(0), TRAY_ID);
+    assert_eq!(tray_id(1), "main-2");
+}
+
+#[test]
+fn test_menu_item_checked() {
+    let config = Config::default();
+    config.show_mem.store(false, Relaxed);
+
+    assert_eq!(menu_item_checked(&config, menu_id::SHOW_CPU), Some(true));
+    assert_eq!(menu_item_checked(&config, menu_id::SHOW_MEM), Some(false));
+    assert_eq!(
+        menu_item_checked(
+            &config,
+            &threshold_menu_id(config::key::GPU_ALERT_THRESHOLD, 90)
+        ),
+        Some(true)
+    );
+    assert_eq!(
+        menu_item_checked(
+            &config,
+            &threshold_menu_id(config::key::GPU_ALERT_THRESHOLD, 80)
+        ),
+        Some(false)
+    );
+    assert_eq!(
+        menu_item_checked(&config, menu_id::CPU_LOAD_AVERAGE),
+        Some(false)
+    );
+    config.set_layout(Layout::Compact);
+    assert_eq!(
+        menu_item_checked(&config, &layout_menu_id(Layout::Compact)),
+        Some(true)
+    );
+    assert_eq!(
+        menu_item_checked(&config, &layout_menu_id(Layout::IconOnly)),
+        Some(false)
+    );
+    assert_eq!(menu_item_checked(&config, menu_id::QUIT), None);
+}
+
+#[test]
+fn test_top_processes_ranking() {
+    let process = |pid, cpu, memory| processes::ProcessInfo {
+        pid,
+        name: format!("p{pid}"),
+        cpu,
+        memory,
+    };
+    let list = vec![
+        process(1, 5.0, 300),
+        process(2, 80.0, 100),
+        process(3, 80.0, 900),
+        process(4, 0.0, 500),
+    ];
+
+    let by_cpu: Vec<u32> = processes::top_by_cpu(&list, 3)
+        .iter()
+        .map(|p| p.pid)
+        .collect();
+    assert_eq!(by_cpu, vec![2, 3, 1]);
+
+    let by_mem: Vec<u32> = processes::top_by_memory(&list, 2)
+        .iter()
+        .map(|p| p.pid)
+        .collect();
+    assert_eq!(by_mem, vec![3, 4]);
+
+    assert!(processes::top_by_cpu(&[], 5).is_empty());
+}
+
+#[test]
+fn test_watched_processes() {
+    use watched::{aggregate, over_limits, parse_names, status_text, Limits};
+
+    let process = |pid, name: &str, cpu, memory| processes::ProcessInfo {
+        pid,
+        name: name.to_string(),
+        cpu,
+        memory,
+    };
+    let list = vec![
+        process(1, "chrome", 40.0, 900_000_000),
+        process(2, "Google Chrome Helper (Renderer)", 90.0, 1_600_000_000),
+        process(3, "node", 12.0, 150_000_000),
+        process(4, "bash", 1.0, 5_000_000),
+    ];
+    let names = vec!["chrome".to_string(), "node".to_string(), "java".to_string()];
+    let usages = aggregate(&list, &names);
+    assert_eq!(usages.len(), 2);
+    assert_eq!(usages[0].name, "chrome");
+    assert_eq!(usages[0].cpu, 130.0);
+    assert_eq!(usages[0].memory, 2_500_000_000);
+    assert_eq!(usages[1].memory, 150_000_000);
+
+    let limits = Limits {
+        cpu: 100,
+        mem_mb: 2000,
+    };
+    assert!((limits.percent_of(&usages[0]) - 130.0).abs() < 0.01);
+    let over = over_limits(usages.clone(), limits);
+    assert_eq!(over.len(), 1);
+    assert_eq!(status_text(&over), "Over limit: chrome 130% · 2.5 GB");
+    // With both limits off nothing is ever over
+    assert!(over_limits(usages, Limits::default()).is_empty());
+
+    assert_eq!(
+        parse_names(&serde_json::json!([" chrome ", "Chrome", "node"])),
+        Ok(vec!["chrome".to_string(), "node".to_string()])
+    );
+    assert!(parse_names(&serde_json::json!([""])).is_err());
+    assert!(parse_names(&serde_json::json!("chrome")).is_err());
+}
+
+#[test]
+fn test_process_menu_helpers() {
+    assert_eq!(
+        processes::parse_kill_menu_id(&processes::kill_menu_id(42)),
+        Some(42)
+    );
+    assert_eq!(processes::parse_kill_menu_id("kill:abc"), None);
+    assert_eq!(processes::parse_kill_menu_id("quit"), None);
+
+    assert_eq!(processes::format_memory(350_000_000), "350 MB");
+    assert_eq!(processes::format_memory(1_250_000_000), "1.2 GB");
+}
+
+#[test]
+fn test_frame_dump_file_names_sort_in_order() {
+    let first = frame_dump::frame_file_name(1_700_000_000_123, 9);
+    let second = frame_dump::frame_file_name(1_700_000_000_123, 10);
+    assert_eq!(first, "frame-1700000000123-00009.png");
+    assert!(first < second);
+}
+
+#[test]
+fn test_recent_samples_window() {
+    let sample = |timestamp| Sample {
+        timestamp,
+        ..Sample::default()
+    };
+    let mut samples = std::collections::VecDeque::new();
+    for timestamp in [100, 130, 159, 160] {
+        dashboard::push_within_window(&mut samples, sample(timestamp), 60);
+    }
+    assert_eq!(samples.len(), 4);
+
+    dashboard::push_within_window(&mut samples, sample(195), 60);
+    let kept: Vec<u64> = samples.iter().map(|s| s.timestamp).collect();
+    assert_eq!(kept, vec![159, 160, 195]);
+}
+
+#[test]
+fn test_chart_history_keeps_an_hour() {
+    let history = charts::ChartHistory::default();
+    for minute in 0..=90 {
+        charts::remember(
+            &history,
+            Sample {
+                timestamp: minute * 60,
+                ..Sample::default()
+            },
+        );
+    }
+    let samples = history.lock().unwrap();
+    assert_eq!(samples.front().map(|s| s.timestamp), Some(30 * 60));
+    assert_eq!(samples.len(), 61);
+}
+
+#[test]
+fn test_popover_origin() {
+    // Centered below the icon
+    assert_eq!(
+        dashboard::popover_origin((1000.0, 0.0), (40.0, 24.0), 320.0),
+        (860.0, 24.0)
+    );
+    // Clamped to the left screen edge
+    assert_eq!(
+        dashboard::popover_origin((20.0, 0.0), (40.0, 24.0), 320.0),
+        (0.0, 24.0)
+    );
+}
+
+#[test]
+fn test_parse_ss_output() {
+    let output = "\
+ESTAB 0 0 192.168.1.5:51234 140.82.112.26:443 users:((\"firefox\",pid=2345,fd=87))
+\t cubic wscale:7,7 rto:232 bytes_sent:1300 bytes_acked:1200 bytes_received:54000 segs_out:20
+ESTAB 0 0 192.168.1.5:51240 151.101.1.69:443 users:((\"firefox\",pid=2345,fd=91))
+\t cubic rto:204 bytes_acked:800 bytes_received:1000
+ESTAB 0 0 127.0.0.1:8080 127.0.0.1:40000 users:((\"python3\",pid=77,fd=5),(\"python3\",pid=78,fd=5))
+\t cubic rto:204 bytes_sent:64
+ESTAB 0 0 192.168.1.5:22 192.168.1.9:50000
+\t cubic rto:204 bytes_acked:999 bytes_received:999
+";
+    let counters = net_processes::parse_ss_output(output);
+    assert_eq!(
+        counters,
+        vec![
+            net_processes::Counters {
+                pid: 2345,
+                name: "firefox".into(),
+                rx: 55_000,
+                tx: 2_000,
+            },
+            net_processes::Counters {
+                pid: 77,
+                name: "python3".into(),
+                rx: 0,
+                tx: 64,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_nettop_output() {
+    let output = "\
+,bytes_in,bytes_out,
+Google Chrome H.812,1048576,2048,
+mDNSResponder.301,12,40,
+garbage line
+";
+    let counters = net_processes::parse_nettop_output(output);
+    assert_eq!(counters.len(), 2);
+    assert_eq!(counters[0].name, "Google Chrome H");
+    assert_eq!(counters[0].pid, 812);
+    assert_eq!((counters[0].rx, counters[0].tx), (1_048_576, 2048));
+    assert_eq!(counters[1].pid, 301);
+}
+
+#[test]
+fn test_top_talkers() {
+    let counters = |pid, rx, tx| net_processes::Counters {
+        pid,
+        name: format!("p{pid}"),
+        rx,
+        tx,
+    };
+    let previous = std::collections::HashMap::from([(1, (0, 0)), (2, (100, 100)), (3, (50, 0))]);
+    let current = vec![
+        counters(1, 1000, 1000),
+        counters(2, 100, 100),
+        counters(3, 5050, 0),
+        counters(4, 99_999, 0),
+    ];
+
+    let top = net_processes::top_talkers(&previous, &current, 2.0, 3);
+    let pids: Vec<u32> = top.iter().map(|t| t.pid).collect();
+    // Idle processes and processes without a baseline are skipped
+    assert_eq!(pids, vec![3, 1]);
+    assert_eq!((top[0].down, top[0].up), (2500.0, 0.0));
+    assert_eq!((top[1].down, top[1].up), (500.0, 500.0));
+
+    assert_eq!(
+        net_processes::top_talkers(&previous, &current, 2.0, 1).len(),
+        1
+    );
+    assert!(net_processes::top_talkers(&previous, &current, 0.0, 3).is_empty());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_parse_name_has_owner() {
+    assert_eq!(tray_host::parse_name_has_owner("(true,)\n"), Some(true));
+    assert_eq!(tray_host::parse_name_has_owner("(false,)\n"), Some(false));
+    assert_eq!(tray_host::parse_name_has_owner(""), None);
+
+    assert!(tray_host::notice_message(true).contains("AppIndicator"));
+    assert!(!tray_host::notice_message(false).contains("AppIndicator"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_parse_color_scheme() {
+    assert_eq!(parse_portal_color_scheme("(<<uint32 1>>,)\n"), Some(true));
+    assert_eq!(parse_portal_color_scheme("(<uint32 2>,)\n"), Some(false));
+    assert_eq!(parse_portal_color_scheme("(<<uint32 0>>,)\n"), None);
+    assert_eq!(parse_portal_color_scheme(""), None);
+
+    assert_eq!(parse_gsettings_color_scheme("'prefer-dark'\n"), Some(true));
+    assert_eq!(
+        parse_gsettings_color_scheme("'prefer-light'\n"),
+        Some(false)
+    );
+    assert_eq!(parse_gsettings_color_scheme("'default'\n"), None);
+
+    assert_eq!(parse_xfconf_bool("true\n"), Some(true));
+    assert_eq!(parse_xfconf_bool("false\n"), Some(false));
+    assert_eq!(parse_xfconf_bool(""), None);
+
+    assert!(theme_has_dark_panel("Adwaita-dark\n"));
+    assert!(theme_has_dark_panel("'Ambiant-MATE'\n"));
+    assert!(theme_has_dark_panel("'BlackMATE'"));
+    assert!(!theme_has_dark_panel("'Yaru-MATE-light'\n"));
+    assert!(!theme_has_dark_panel("Greybird"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_kde_colors() {
+    use kde::{parse_color_scheme_foreground, parse_plasma_theme};
+
+    assert_eq!(
+        parse_plasma_theme("[Theme]\nname=breeze-dark\n"),
+        Some(true)
+    );
+    assert_eq!(
+        parse_plasma_theme("[Theme]\nname=breeze-light\n"),
+        Some(false)
+    );
+    assert_eq!(parse_plasma_theme("[Theme]\nname=default\n"), None);
+    assert_eq!(parse_plasma_theme("[Other]\nname=breeze-dark\n"), None);
+
+    let kdeglobals = "\
+[Colors:Window]
+ForegroundNormal=35,38,41
+
+[Colors:Header][Inactive]
+ForegroundNormal=1,2,3
+
+[Colors:Header]
+ForegroundNormal[$e]=9,9,9
+ForegroundNormal=252,252,252,255
+";
+    assert_eq!(
+        parse_color_scheme_foreground(kdeglobals),
+        Some((252, 252, 252))
+    );
+    assert_eq!(
+        parse_color_scheme_foreground("[Colors:Window]\nForegroundNormal = 35,38,41\n"),
+        Some((35, 38, 41))
+    );
+    assert_eq!(
+        parse_color_scheme_foreground("[Colors:Window]\nForegroundNormal=35,38\n"),
+        None
+    );
+}
+
+#[test]
+fn test_status_server_responses() {
+    let sample = metrics::Sample {
+        timestamp: 1_700_000_000,
+        cpu: Some(12.5),
+        net_down: Some(2_048.0),
+        ..Default::default()
+    };
+
+    // Only `/history` reads the history
+    let ok =
+        status_server::response_for("GET /status HTTP/1.1\r\n", Some(&sample), || unreachable!());
+    assert!(ok.starts_with("HTTP/1.1 200 OK\r\n"));
+    let body = ok.split("\r\n\r\n").nth(1).expect("body");
+    let json: serde_json::Value = serde_json::from_str(body).expect("valid json");
+    assert_eq!(json["cpu"], serde_json::json!(12.5));
+    assert_eq!(json["gpu"], serde_json::Value::Null);
+    assert_eq!(json["timestamp"], serde_json::json!(1_700_000_000u64));
+
+    let pending = status_server::response_for("GET /status HTTP/1.1", None, || unreachable!());
+    assert!(pending.starts_with("HTTP/1.1 503"));
+    let missing = status_server::response_for("GET /other HTTP/1.1", Some(&sample), Vec::new);
+    assert!(missing.starts_with("HTTP/1.1 404"));
+    let post = status_server::response_for("POST /status HTTP/1.1", Some(&sample), Vec::new);
+    assert!(post.starts_with("HTTP/1.1 405"));
+
+    let history =
+        status_server::response_for("GET /history HTTP/1.1", None, || vec![sample, sample]);
+    assert!(history.starts_with("HTTP/1.1 200 OK\r\n"));
+    let body = history.split("\r\n\r\n").nth(1).expect("body");
+    let json: serde_json::Value = serde_json::from_str(body).expect("valid json");
+    assert_eq!(json.as_array().map(Vec::len), Some(2));
+    assert_eq!(json[1]["cpu"], serde_json::json!(12.5));
+}
+
+#[test]
+fn test_minute_history() {
+    use minute_history::MinuteHistory;
+
+    let sample = |timestamp, cpu| Sample {
+        timestamp,
+        cpu,
+        net_down: Some(1_000.0),
+        ..Sample::default()
+    };
+    let mut history = MinuteHistory::default();
+    assert_eq!(history.record(sample(60, Some(10.0))), None);
+    assert_eq!(history.record(sample(90, None)), None);
+    assert_eq!(history.record(sample(119, Some(30.0))), None);
+    // The first sample of the next minute finishes the previous one
+    let minute = history.record(sample(120, Some(50.0))).expect("finished");
+    assert_eq!(minute.timestamp, 60);
+    assert_eq!(minute.cpu, Some(20.0));
+    assert_eq!(minute.net_down, Some(1_000.0));
+    assert_eq!(minute.gpu, None);
+
+    // Only the last day is kept
+    let day = minute_history::MINUTES as u64 * 60;
+    history.record(sample(day + 120, Some(1.0)));
+    history.record(sample(day + 180, Some(1.0)));
+    let kept: Vec<u64> = history.minutes().map(|m| m.timestamp).collect();
+    assert_eq!(kept, vec![120, day + 120]);
+
+    // The file format round-trips, missing values included
+    let bytes = minute_history::encode(history.minutes());
+    let decoded = minute_history::decode(&bytes).expect("decodes");
+    assert_eq!(
+        decoded.iter().collect::<Vec<_>>(),
+        history.minutes().collect::<Vec<_>>()
+    );
+    assert_eq!(decoded[0].gpu_mem, None);
+    assert!(minute_history::decode(&bytes[..bytes.len() - 1]).is_none());
+    assert!(minute_history::decode(b"nope").is_none());
+
+    let dir = std::env::temp_dir().join(format!("brm-minutes-{}", std::process::id()));
+    history.save(&dir).unwrap();
+    assert_eq!(MinuteHistory::load(&dir, day + 120).minutes().count(), 2);
+    assert_eq!(MinuteHistory::load(&dir, day + 150).minutes().count(), 1);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_status_sample_round_trip() {
+    let sample = metrics::Sample {
+        timestamp: 1_700_000_000,
+        cpu: Some(12.5),
+        gpu_mem: Some(1_048_576),
+        net_down: Some(2_048.0),
+        net_up: Some(512.0),
+        ..Default::default()
+    };
+    let body = status_server::sample_json(&sample).to_string();
+    assert_eq!(status_server::parse_sample(&body), Ok(sample));
+    assert!(status_server::parse_sample("{}").is_err());
+    assert!(status_server::parse_sample("not json").is_err());
+
+    let response = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}";
+    assert_eq!(http::response_body(response, "Status server"), Ok("{}"));
+    assert!(http::response_body("HTTP/1.1 404 Not Found\r\n\r\n{}", "Status server").is_err());
+}
+
+#[test]
+fn test_remote_hosts_setting() {
+    use remote::{parse_host, RemoteHost};
+
+    assert_eq!(
+        parse_host("alex@homeserver"),
+        Ok(RemoteHost::Ssh {
+            destination: "alex@homeserver".to_string(),
+            ssh_port: None,
+            status_port: status_server::DEFAULT_PORT,
+        })
+    );
+    // The URI's port is SSH's, the status server's comes after it
+    assert_eq!(
+        parse_host("ssh://nas.local:2222"),
+        Ok(RemoteHost::Ssh {
+            destination: "nas.local".to_string(),
+            ssh_port: Some(2222),
+            status_port: status_server::DEFAULT_PORT,
+        })
+    );
+    assert_eq!(
+        parse_host("ssh://alex@nas.local:2222/?status_port=9900"),
+        Ok(RemoteHost::Ssh {
+            destination: "alex@nas.local".to_string(),
+            ssh_port: Some(2222),
+            status_port: 9900,
+        })
+    );
+    assert_eq!(
+        parse_host("http://10.0.0.2:8080/"),
+        Ok(RemoteHost::Http {
+            host: "10.0.0.2".to_string(),
+            port: 8080,
+        })
+    );
+    // Would be passed to ssh as an option
+    assert!(parse_host("-oProxyCommand=touch").is_err());
+    assert!(parse_host("host; rm -rf ~").is_err());
+    assert!(parse_host("ssh://host:port").is_err());
+    assert!(parse_host("ssh://host?status_port=x").is_err());
+    assert!(parse_host("http://host?status_port=9900").is_err());
+    assert!(parse_host("").is_err());
+
+    let config = config::Config::default();
+    let hosts = serde_json::json!(["alex@homeserver", "http://10.0.0.2"]);
+    assert_eq!(config.set(config::key::REMOTE_HOSTS, &hosts), Ok(hosts));
+    assert!(config
+        .set(config::key::REMOTE_HOST, &serde_json::json!("elsewhere"))
+        .is_err());
+    config
+        .set(
+            config::key::REMOTE_HOST,
+            &serde_json::json!("alex@homeserver"),
+        )
+        .unwrap();
+    assert_eq!(config.remote_host(), "alex@homeserver");
+
+    // Removing the selected host switches back to this machine
+    config
+        .set(
+            config::key::REMOTE_HOSTS,
+            &serde_json::json!(["http://10.0.0.2"]),
+        )
+        .unwrap();
+    assert_eq!(config.remote_host(), "");
+    assert!(config
+        .set(config::key::REMOTE_HOSTS, &serde_json::json!(["bad host"]))
+        .is_err());
+
+    assert_eq!(
+        remote::parse_menu_id(&remote::menu_id("alex@homeserver")),
+        Some("alex@homeserver")
+    );
+    assert_eq!(remote::parse_menu_id(&remote::menu_id("")), Some(""));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_dbus_properties() {
+    use zbus::zvariant::Value;
+
+    let sample = metrics::Sample {
+        timestamp: 1_700_000_000,
+        cpu: Some(12.5),
+        gpu_mem: Some(1_048_576),
+        net_down: Some(2_048.0),
+        ..Default::default()
+    };
+
+    let properties = dbus::properties(&sample);
+    assert_eq!(properties.len(), 9);
+    assert_eq!(properties["Timestamp"], Value::from(1_700_000_000u64));
+    assert_eq!(properties["Cpu"], Value::from(12.5));
+    assert_eq!(properties["GpuMemory"], Value::from(1_048_576.0));
+    assert_eq!(properties["NetDown"], Value::from(2_048.0));
+    // Unavailable readings are -1 rather than missing
+    assert_eq!(properties["Gpu"], Value::from(-1.0));
+    assert_eq!(properties["GpuTemperature"], Value::from(-1.0));
+}
+
+#[test]
+fn test_get_update_interval_ms_default_when_unset() {
+    let _guard = env_lock().lock().expect("env lock poisoned");
+    let previous = std::env::var("SILICON_UPDATE_INTERVAL").ok();
+    std::env::remove_var("SILICON_UPDATE_INTERVAL");
+
+    assert_eq!(
+        get_update_interval_ms(UPDATE_INTERVAL_MS),
+        UPDATE_INTERVAL_MS
+    );
+
+    if let Some(value) = previous {
+        std::env::set_var("SILICON_UPDATE_INTERVAL", value);
+    }
+}
+
+#[test]
+fn test_get_update_interval_ms_valid_env() {
+    let _guard = env_lock().lock().expect("env lock poisoned");
+    let previous = std::env::var("SILICON_UPDATE_INTERVAL").ok();
+    std::env::set_var("SILICON_UPDATE_INTERVAL", "1234");
+
+    assert_eq!(get_update_interval_ms(UPDATE_INTERVAL_MS), 1234);
+
+    if let Some(value) = previous {
+        std::env::set_var("SILICON_UPDATE_INTERVAL", value);
+    } else {
+        std::env::remove_var("SILICON_UPDATE_INTERVAL");
+    }
+}
+
+#[test]
+fn test_get_update_interval_ms_invalid_env_falls_back() {
+    let _guard = env_lock().lock().expect("env lock poisoned");
+    let previous = std::env::var("SILICON_UPDATE_INTERVAL").ok();
+    std::env::set_var("SILICON_UPDATE_INTERVAL", "abc");
+
+    assert_eq!(
+        get_update_interval_ms(UPDATE_INTERVAL_MS),
+        UPDATE_INTERVAL_MS
+    );
+
+    if let Some(value) = previous {
+        std::env::set_var("SILICON_UPDATE_INTERVAL", value);
+    } else {
+        std::env::remove_var("SILICON_UPDATE_INTERVAL");
+    }
+}
+
+#[test]
+fn test_layout_menu_id_round_trip() {
+    for layout in Layout::ALL {
+        assert_eq!(parse_layout_menu_id(&layout_menu_id(layout)), Some(layout));
+    }
+    assert_eq!(parse_layout_menu_id("layout:grid"), None);
+    assert_eq!(
+        parse_layout_menu_id(&threshold_menu_id(config::key::CPU_ALERT_THRESHOLD, 80)),
+        None
+    );
+}
+
+#[test]
+fn test_alert_log_tracks_peak_until_alert_ends() {
+    use alerts::{AlertChange, AlertEvent, AlertLog, AlertMetric};
+
+    let mut log = AlertLog::default();
+    assert_eq!(log.observe(AlertMetric::Cpu, 50.0, false, 100), None);
+    assert_eq!(
+        log.observe(AlertMetric::Cpu, 92.0, true, 101),
+        Some(AlertChange::Started(AlertEvent {
+            metric: AlertMetric::Cpu,
+            peak: 92.0,
+            started: 101,
+            ended: None,
+        }))
+    );
+    assert_eq!(log.observe(AlertMetric::Cpu, 97.0, true, 102), None);
+    assert_eq!(log.observe(AlertMetric::Cpu, 94.0, true, 103), None);
+    // Other metrics don't touch the open CPU event
+    assert_eq!(log.observe(AlertMetric::Memory, 40.0, false, 103), None);
+
+    let Some(AlertChange::Ended(event)) = log.observe(AlertMetric::Cpu, 30.0, false, 281) else {
+        panic!("expected the CPU alert to end");
+    };
+    assert_eq!(event.peak, 97.0);
+    assert_eq!(event.started, 101);
+    assert_eq!(event.ended, Some(281));
+    assert_eq!(log.recent().count(), 1);
+}
+
+#[test]
+fn test_alert_webhook_payload() {
+    use alerts::{AlertChange, AlertEvent, AlertMetric};
+
+    let mut event = AlertEvent {
+        metric: AlertMetric::Memory,
+        peak: 93.0,
+        started: 1_700_000_000,
+        ended: None,
+    };
+    let started = alerts::webhook_payload(&AlertChange::Started(event.clone()));
+    assert_eq!(started["event"], "started");
+    assert_eq!(started["metric"], "mem");
+    assert_eq!(started["value"], 93.0);
+    assert_eq!(started["text"], "Alert: Memory 93%");
+
+    event.peak = 97.0;
+    event.ended = Some(1_700_000_000 + 750);
+    let ended = alerts::webhook_payload(&AlertChange::Ended(event));
+    assert_eq!(ended["event"], "ended");
+    assert_eq!(ended["duration_secs"], 750);
+    assert_eq!(ended["content"], "Alert over: Memory 97% peak, lasted 12m");
+
+    assert!(alerts::is_valid_webhook_url(""));
+    assert!(alerts::is_valid_webhook_url("https://ntfy.sh/my-topic"));
+    assert!(!alerts::is_valid_webhook_url("ftp://example.com"));
+    assert!(!alerts::is_valid_webhook_url("https://example.com/a b"));
+    assert!(!alerts::is_valid_webhook_url("-https://x"));
+}
+
+#[test]
+fn test_alert_log_keeps_latest_events() {
+    use alerts::{AlertLog, AlertMetric, MAX_EVENTS};
+
+    let mut log = AlertLog::default();
+    for i in 0..(MAX_EVENTS as u64 + 5) {
+        log.observe(AlertMetric::Gpu, 95.0, true, i * 2);
+        log.observe(AlertMetric::Gpu, 0.0, false, i * 2 + 1);
+    }
+    assert_eq!(log.recent().count(), MAX_EVENTS);
+    assert_eq!(
+        log.recent().next().map(|e| e.started),
+        Some((MAX_EVENTS as u64 + 4) * 2)
+    );
+}
+
+#[test]
+fn test_alert_csv_round_trip() {
+    use alerts::{AlertEvent, AlertMetric};
+
+    let finished = AlertEvent {
+        metric: AlertMetric::GpuThrottling,
+        peak: 87.0,
+        started: 1_767_621_720,
+        ended: Some(1_767_621_900),
+    };
+    let ongoing = AlertEvent {
+        metric: AlertMetric::Memory,
+        peak: 93.5,
+        started: 1_767_621_720,
+        ended: None,
+    };
+    assert_eq!(
+        alerts::csv_row(&finished),
+        "gpu_throttling,87.0,1767621720,1767621900"
+    );
+    assert_eq!(alerts::csv_row(&ongoing), "mem,93.5,1767621720,");
+    assert_eq!(
+        alerts::parse_csv_row(&alerts::csv_row(&finished)),
+        Some(finished.clone())
+    );
+    assert_eq!(
+        alerts::parse_csv_row(&alerts::csv_row(&ongoing)),
+        Some(ongoing)
+    );
+    // The header line and unknown metrics are skipped
+    assert_eq!(alerts::parse_csv_row("metric,peak,started,ended"), None);
+    assert_eq!(alerts::parse_csv_row("disk,90.0,1,2"), None);
+
+    let csv = alerts::export_csv([&finished]);
+    assert_eq!(
+        csv,
+        "metric,peak,started,ended\ngpu_throttling,87.0,1767621720,1767621900\n"
+    );
+
+    // Loading cuts the file back to the events kept
+    let dir = std::env::temp_dir().join(format!("brm-alerts-{}", std::process::id()));
+    for started in 0..alerts::MAX_EVENTS as u64 + 20 {
+        let event = AlertEvent {
+            started,
+            ..finished.clone()
+        };
+        alerts::append(&dir, &event).unwrap();
+    }
+    let events = alerts::load(&dir);
+    assert_eq!(events.len(), alerts::MAX_EVENTS);
+    assert_eq!(events[0].started, 20);
+    let contents = std::fs::read_to_string(dir.join(alerts::ALERTS_FILE)).unwrap();
+    assert_eq!(contents.lines().count(), alerts::MAX_EVENTS + 1);
+    assert_eq!(alerts::load(&dir), events);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_alert_event_label() {
+    use alerts::{AlertEvent, AlertMetric};
+
+    let mut event = AlertEvent {
+        metric: AlertMetric::Cpu,
+        peak: 97.4,
+        started: 1_767_621_720,
+        ended: Some(1_767_621_900),
+    };
+    assert_eq!(
+        alerts::event_label(&event),
+        "CPU 97% · 2026-01-05 14:02 UTC · 3m"
+    );
+    event.ended = None;
+    assert_eq!(
+        alerts::event_label(&event),
+        "CPU 97% · 2026-01-05 14:02 UTC · ongoing"
+    );
+}
+
+#[test]
+fn test_parse_ping_time() {
+    let linux = "PING time.google.com (216.239.35.0) 56(84) bytes of data.\n\
+                 64 bytes from 216.239.35.0: icmp_seq=1 ttl=115 time=12.3 ms\n\n\
+                 --- time.google.com ping statistics ---\n\
+                 1 packets transmitted, 1 received, 0% packet loss, time 0ms\n";
+    assert_eq!(latency::parse_ping_time(linux), Some(12.3));
+
+    let macos = "PING 192.168.1.1 (192.168.1.1): 56 data bytes\n\
+                 64 bytes from 192.168.1.1: icmp_seq=0 ttl=64 time=3.412 ms\n";
+    assert_eq!(latency::parse_ping_time(macos), Some(3.412));
+    assert_eq!(
+        latency::parse_ping_time("1 packets transmitted, 0 received"),
+        None
+    );
+}
+
+#[test]
+fn test_default_gateway_parsing() {
+    let table = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\n\
+                 wlan0\t0000A8C0\t00000000\t0001\t0\t0\t600\t00FFFFFF\n\
+                 wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\n";
+    assert_eq!(
+        latency::parse_proc_net_route(table).as_deref(),
+        Some("192.168.1.1")
+    );
+    assert_eq!(
+        latency::parse_proc_net_route("Iface\tDestination\tGateway\n"),
+        None
+    );
+
+    let route = "   route to: default\ndestination: default\n       mask: default\n    gateway: 10.0.0.1\n  interface: en0\n";
+    assert_eq!(latency::parse_route_get(route).as_deref(), Some("10.0.0.1"));
+}
+
+#[test]
+fn test_latency_display() {
+    use latency::Latency;
+
+    assert_eq!(latency::format_latency(0.4), "<1ms");
+    assert_eq!(latency::format_latency(23.4), "23ms");
+    assert_eq!(latency::format_latency(1234.0), "1.2s");
+
+    let shown = Some(Latency::Reply(20.0));
+    assert!(latency::should_show(None, Latency::Reply(20.0), 100.0));
+    assert!(!latency::should_show(shown, Latency::Reply(23.0), 100.0));
+    assert!(latency::should_show(shown, Latency::Reply(26.0), 100.0));
+    assert!(latency::should_show(
+        Some(Latency::Reply(98.0)),
+        Latency::Reply(101.0),
+        100.0
+    ));
+    assert!(latency::should_show(shown, Latency::Timeout, 100.0));
+    assert!(!latency::should_show(
+        Some(Latency::Timeout),
+        Latency::Timeout,
+        100.0
+    ));
+}
+
+#[test]
+fn test_latency_host_setting() {
+    let config = Config::default();
+    assert_eq!(config.latency_host(), "");
+    for host in ["1.1.1.1", " router.local ", "fe80::1%en0", ""] {
+        assert_eq!(
+            config.set(config::key::LATENCY_HOST, &serde_json::json!(host)),
+            Ok(serde_json::json!(host.trim()))
+        );
+    }
+    // Would be read by ping as options or split into extra arguments
+    for host in ["-f", "1.1.1.1 -c 100", "a;b"] {
+        assert!(config
+            .set(config::key::LATENCY_HOST, &serde_json::json!(host))
+            .is_err());
+    }
+    assert_eq!(
+        config.set(config::key::LATENCY_ALERT_MS, &serde_json::json!(5000)),
+        Ok(serde_json::json!(2000))
+    );
+    assert_eq!(
+        config.set(config::key::WIFI_ALERT_DBM, &serde_json::json!(-120)),
+        Ok(serde_json::json!(-100))
+    );
+    assert!(config
+        .set(config::key::WIFI_ALERT_DBM, &serde_json::json!("weak"))
+        .is_err());
+}
+
+#[test]
+fn test_vpn_tunnel_detection() {
+    use std::net::IpAddr;
+
+    let link_local: IpAddr = "fe80::1".parse().unwrap();
+    let routable_v6: IpAddr = "fd7a:115c:a1e0::1".parse().unwrap();
+    let v4: IpAddr = "10.8.0.2".parse().unwrap();
+
+    // macOS keeps system utun interfaces with link-local addresses only
+    assert!(!vpn::is_active_tunnel("utun0", &[link_local]));
+    assert!(vpn::is_active_tunnel("utun4", &[link_local, v4]));
+    assert!(vpn::is_active_tunnel("wg0", &[v4]));
+    assert!(vpn::is_active_tunnel("tailscale0", &[routable_v6]));
+    assert!(!vpn::is_active_tunnel("tun0", &[]));
+    assert!(!vpn::is_active_tunnel("en0", &[v4]));
+
+    assert_eq!(vpn::status_text(&[]), "VPN: Not connected");
+    assert_eq!(
+        vpn::status_text(&["utun4".to_string(), "wg0".to_string()]),
+        "VPN: utun4, wg0"
+    );
+}
+
+#[test]
+fn test_headless_args() {
+    let args = |list: &[&str]| -> Vec<String> { list.iter().map(|s| s.to_string()).collect() };
+
+    assert_eq!(headless::parse_args(&args(&["--headless"])), Ok(vec![]));
+    assert_eq!(
+        headless::parse_args(&args(&["--headless", "--status-port", "9900", "--history"])),
+        Ok(vec![
+            (config::key::STATUS_SERVER_ENABLED, serde_json::json!(true)),
+            (config::key::STATUS_SERVER_PORT, serde_json::json!(9900)),
+            (config::key::HISTORY_ENABLED, serde_json::json!(true)),
+        ])
+    );
+    assert!(headless::parse_args(&args(&["--headless", "--status-port"])).is_err());
+    assert!(headless::parse_args(&args(&["--headless", "--status-port", "http"])).is_err());
+    assert!(headless::parse_args(&args(&["--headless", "--tray"])).is_err());
+}
+
+#[test]
+fn test_waybar_output() {
+    let config = config::Config::default();
+    config.show_gpu.store(false, Relaxed);
+    let sample = metrics::Sample {
+        cpu: Some(95.2),
+        mem: Some(48.0),
+        gpu: Some(10.0),
+        net_down: Some(1_500_000.0),
+        net_up: Some(200_000.0),
+        ..Default::default()
+    };
+
+    let output = waybar::output(&sample, &config, 8);
+    assert_eq!(output["text"], "MEM 48%  CPU 95%  ↓ 1.5 MB ↑ 200 KB");
+    // Hidden segments are still in the tooltip, missing readings are not
+    let tooltip = output["tooltip"].as_str().unwrap();
+    assert!(tooltip.contains("GPU: 10%"));
+    assert!(!tooltip.contains("Temperature"));
+    assert_eq!(output["class"], serde_json::json!(["alert", "cpu-alert"]));
+    assert_eq!(output["percentage"], 95);
+
+    config.show_alerts.store(false, Relaxed);
+    let output = waybar::output(&sample, &config, 8);
+    assert_eq!(output["class"], serde_json::json!([]));
+}
+
+#[test]
+fn test_pressure_parsing() {
+    let cpu = "some avg10=1.53 avg60=0.87 avg300=0.42 total=1234567\n\
+               full avg10=0.00 avg60=0.00 avg300=0.00 total=0\n";
+    assert_eq!(pressure::parse_some_avg10(cpu), Some(1.53));
+    // The full line alone doesn't count
+    assert_eq!(
+        pressure::parse_some_avg10("full avg10=4.00 avg60=0.00 avg300=0.00 total=0\n"),
+        None
+    );
+    assert_eq!(pressure::parse_some_avg10(""), None);
+
+    let pressure = pressure::Pressure {
+        cpu: Some(2.14),
+        memory: Some(0.0),
+        io: Some(5.3),
+    };
+    assert_eq!(
+        pressure::status_text(&pressure),
+        "Pressure: CPU 2.1% · Memory 0.0% · IO 5.3%"
+    );
+    assert_eq!(
+        pressure::status_text(&pressure::Pressure::default()),
+        "Pressure: unavailable"
+    );
+}
+
+#[test]
+fn test_net_totals_formatting() {
+    use net_totals::{format_total, line};
+
+    assert_eq!(format_total(0), "0 MB");
+    assert_eq!(format_total(850_000_000), "850 MB");
+    assert_eq!(format_total(999_600_000), "1.0 GB");
+    assert_eq!(format_total(4_200_000_000), "4.2 GB");
+    assert_eq!(format_total(1_300_000_000_000), "1.3 TB");
+    assert_eq!(
+        line("Session", (4_200_000_000, 1_100_000_000)),
+        "Session: 4.2 GB down / 1.1 GB up"
+    );
+}
+
+#[test]
+fn test_data_cap_usage() {
+    use data_cap::{counts_interface, menu_line, month_key, DataUsage};
+
+    for physical in ["eth0", "enp3s0", "wlan0", "en0", "wwan0"] {
+        assert!(counts_interface(physical), "{physical}");
+    }
+    for virtual_ in [
+        "lo",
+        "lo0",
+        "docker0",
+        "br-1a2b3c",
+        "veth9f8e",
+        "virbr0",
+        "utun3",
+        "wg0",
+    ] {
+        assert!(!counts_interface(virtual_), "{virtual_}");
+    }
+
+    // 2026-10-16 12:00 and the first second of November
+    assert_eq!(month_key(1_792_152_000), "2026-10");
+    assert_eq!(month_key(1_793_491_200), "2026-11");
+
+    let mut usage = DataUsage::parse(r#"{"month":"2026-10","bytes":40000000000}"#).unwrap();
+    assert_eq!(usage.percent_of(50), 80.0);
+    assert_eq!(usage.percent_of(0), 0.0);
+    usage.add("2026-10", 2_100_000_000);
+    assert_eq!(usage.bytes, 42_100_000_000);
+    assert_eq!(
+        menu_line(usage.bytes, 50),
+        "This Month: 42.1 GB of 50 GB · 7.9 GB left"
+    );
+    assert_eq!(
+        menu_line(53_200_000_000, 50),
+        "This Month: 53.2 GB of 50 GB · 3.2 GB over"
+    );
+
+    // A new month starts from zero
+    usage.add("2026-11", 1_000_000);
+    assert_eq!((usage.month.as_str(), usage.bytes), ("2026-11", 1_000_000));
+    let saved = DataUsage::parse(&usage.to_json()).unwrap();
+    assert_eq!((saved.month, saved.bytes), (usage.month, usage.bytes));
+    assert!(DataUsage::parse("{}").is_none());
+}
+
+#[test]
+fn test_wifi_parsing() {
+    use wifi::{parse_iw_link, parse_proc_net_wireless, should_show, status_text, WifiStatus};
+
+    let table =
+        "Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE\n \
+                 face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22\n \
+                 wlan0: 0000   54.  -56.  -256        0      0      0      0     12        0\n";
+    assert_eq!(
+        parse_proc_net_wireless(table),
+        Some(("wlan0".to_string(), Some(-56)))
+    );
+    assert_eq!(
+        parse_proc_net_wireless(&table[..table.find(" wlan0").unwrap()]),
+        None
+    );
+
+    let link = "Connected to 12:34:56:78:9a:bc (on wlan0)\n\
+                \tSSID: Home Net\n\
+                \tfreq: 5180\n\
+                \tsignal: -56 dBm\n\
+                \ttx bitrate: 866.7 MBit/s\n";
+    let status = WifiStatus {
+        ssid: Some("Home Net".to_string()),
+        rssi: -56,
+    };
+    assert_eq!(parse_iw_link(link), Some(status.clone()));
+    assert_eq!(parse_iw_link("Not connected.\n"), None);
+
+    assert_eq!(status_text(Some(&status)), "Wi-Fi: Home Net · -56 dBm");
+    assert_eq!(
+        status_text(Some(&WifiStatus {
+            ssid: None,
+            rssi: -70
+        })),
+        "Wi-Fi: -70 dBm"
+    );
+    assert_eq!(status_text(None), "Wi-Fi: Not connected");
+
+    assert!(should_show(None, -60, -75));
+    assert!(!should_show(Some(-60), -62, -75));
+    assert!(should_show(Some(-60), -63, -75));
+    // Crossing the threshold always shows, however small the change
+    assert!(should_show(Some(-74), -76, -75));
+}
+
+#[test]
+fn test_watchdog() {
+    use std::time::{Duration, Instant};
+    use watchdog::{backoff, stall_limit, Heartbeat};
+
+    assert_eq!(stall_limit(Duration::from_secs(2)), Duration::from_secs(26));
+    assert_eq!(
+        stall_limit(Duration::from_secs(60)),
+        Duration::from_secs(200)
+    );
+
+    assert_eq!(backoff(0), Duration::ZERO);
+    assert_eq!(backoff(1), Duration::from_secs(30));
+    assert_eq!(backoff(2), Duration::from_secs(60));
+    assert_eq!(backoff(5), Duration::from_secs(480));
+    assert_eq!(backoff(6), Duration::from_secs(600));
+    assert_eq!(backoff(u32::MAX), Duration::from_secs(600));
+
+    let heartbeat = Heartbeat::default();
+    heartbeat.beat(Duration::from_secs(2));
+    let beat = heartbeat.last_beat();
+    assert!(!heartbeat.is_stalled_at(beat + Duration::from_secs(26)));
+    assert!(heartbeat.is_stalled_at(beat + Duration::from_secs(27)));
+    // A slower interval allows a longer gap
+    heartbeat.beat(Duration::from_secs(10));
+    assert!(!heartbeat.is_stalled_at(heartbeat.last_beat() + Duration::from_secs(27)));
+    assert!(!heartbeat.is_stalled_at(Instant::now()));
+}
+
+#[test]
+fn test_update_check() {
+    use updates::{is_newer, parse_release, Release};
+
+    let body = r#"{"tag_name":"v1.2.0","html_url":"https://github.com/alexx855/better-resource-monitor/releases/tag/v1.2.0","draft":false}"#;
+    assert_eq!(
+        parse_release(body),
+        Some(Release {
+            version: "1.2.0".to_string(),
+            url: "https://github.com/alexx855/better-resource-monitor/releases/tag/v1.2.0"
+                .to_string(),
+        })
+    );
+    assert_eq!(parse_release(r#"{"message":"Not Found"}"#), None);
+    assert_eq!(parse_release("<html>"), None);
+
+    assert!(is_newer("1.2.0", "1.0.3"));
+    assert!(is_newer("1.10.0", "1.9.2"));
+    assert!(is_newer("v2", "1.9.9"));
+    assert!(!is_newer("1.0.3", "1.0.3"));
+    assert!(!is_newer("1.0.2", "1.0.3"));
+    // Pre-release suffixes compare by their version alone
+    assert!(!is_newer("1.0.3-beta.1", "1.0.3"));
+    assert!(!is_newer("nightly", "1.0.3"));
+
+    assert!(!Config::default().check_updates.load(Relaxed));
+}
+
+#[test]
+fn test_i18n() {
+    use config::key;
+    use i18n::{fill, find, parse_table, LANGUAGES};
+    use serde_json::json;
+
+    assert_eq!(find("de_AT.UTF-8").map(|l| l.code), Some("de"));
+    assert_eq!(find("fr-CA").map(|l| l.code), Some("fr"));
+    assert_eq!(find("JA").map(|l| l.code), Some("ja"));
+    assert!(find("pt_BR.UTF-8").is_none());
+    assert!(find("C").is_none());
+    assert!(find("").is_none());
+
+    assert_eq!(
+        fill("{} down / {} up", &["4 GB", "1 GB"]),
+        "4 GB down / 1 GB up"
+    );
+    assert_eq!(
+        fill("受信 {} / 送信 {}", &["4 GB", "1 GB"]),
+        "受信 4 GB / 送信 1 GB"
+    );
+    assert_eq!(fill("{} left", &[]), " left");
+    assert_eq!(fill("Quit", &["unused"]), "Quit");
+
+    // Every bundled table parses and keeps each text's placeholders
+    for language in LANGUAGES {
+        let table = parse_table(language.table).unwrap();
+        assert!(!table.is_empty(), "{}", language.code);
+        for (text, translation) in &table {
+            assert!(!translation.is_empty(), "{}: {text}", language.code);
+            assert_eq!(
+                text.matches("{}").count(),
+                translation.matches("{}").count(),
+                "{}: {text}",
+                language.code
+            );
+        }
+    }
+
+    // Without a language chosen, texts stay in English
+    assert_eq!(i18n::tr("Quit"), "Quit");
+
+    let config = Config::default();
+    assert_eq!(config.language(), "");
+    assert_eq!(
+        config.set(key::LANGUAGE, &json!("ja")).unwrap(),
+        json!("ja")
+    );
+    assert_eq!(
+        config.set(key::LANGUAGE, &json!("en")).unwrap(),
+        json!("en")
+    );
+    assert_eq!(config.set(key::LANGUAGE, &json!("")).unwrap(), json!(""));
+    assert!(config.set(key::LANGUAGE, &json!("xx")).is_err());
+    assert!(config.set(key::LANGUAGE, &json!(1)).is_err());
+}
+
+#[test]
+fn test_logging() {
+    use config::key;
+    use logging::LogLevel;
+    use serde_json::json;
+
+    for level in LogLevel::ALL {
+        assert_eq!(LogLevel::parse(level.as_str()), Some(level));
+        assert_eq!(LogLevel::from_u8(level as u8), level);
+    }
+    assert_eq!(LogLevel::parse("verbose"), None);
+    assert_eq!(LogLevel::from_u8(200), LogLevel::Info);
+
+    let config = Config::default();
+    assert_eq!(config.log_level(), LogLevel::Info);
+    assert!(!config.log_to_file.load(Relaxed));
+    assert_eq!(
+        config.set(key::LOG_LEVEL, &json!("debug")).unwrap(),
+        json!("debug")
+    );
+    assert_eq!(config.log_level(), LogLevel::Debug);
+    assert!(config.set(key::LOG_LEVEL, &json!("loud")).is_err());
+    assert_eq!(config.log_level(), LogLevel::Debug);
+    config.set(key::LOG_TO_FILE, &json!(true)).unwrap();
+    assert!(config.log_to_file.load(Relaxed));
+}
+
+#[test]
+fn test_diagnostics() {
+    use diagnostics::{format_report, redact, Redactions};
+
+    let report = format_report(
+        &[("App", "1.0.0".to_string()), ("Desktop", "KDE".to_string())],
+        &[],
+    );
+    assert_eq!(
+        report,
+        "Better Resource Monitor diagnostics\nApp: 1.0.0\nDesktop: KDE\n\n\
+         Recent warnings and errors:\n(none)\n"
+    );
+    assert!(
+        format_report(&[], &["14:05 WARN Update check failed".to_string()])
+            .ends_with("errors:\n14:05 WARN Update check failed\n")
+    );
+
+    let redactions = Redactions {
+        home: Some("/home/ann".to_string()),
+        user: Some("ann".to_string()),
+        host: Some("ann-laptop".to_string()),
+    };
+    assert_eq!(
+        redact(
+            "Failed to read /home/ann/.config/x: denied\nann on ann-laptop, annotated\n",
+            &redactions
+        ),
+        "Failed to read ~/.config/x: denied\n<user> on <host>, annotated\n"
+    );
+    assert_eq!(
+        redact(
+            "Failed to send alert webhook: https://hooks.example/T0/secret failed",
+            &Redactions::default()
+        ),
+        "Failed to send alert webhook: <url> failed"
+    );
+}
+
+#[test]
+fn test_task_counts() {
+    use task_counts::{parse_loadavg_threads, status_text, TaskCounts};
+
+    assert_eq!(
+        parse_loadavg_threads("0.52 0.58 0.59 2/1873 41237\n"),
+        Some(1873)
+    );
+    assert_eq!(parse_loadavg_threads("0.52 0.58 0.59"), None);
+
+    let counts = TaskCounts {
+        processes: 412,
+        threads: Some(1873),
+    };
+    assert_eq!(status_text(counts), "Processes: 412 · Threads: 1873");
+    assert_eq!(
+        status_text(TaskCounts {
+            threads: None,
+            ..counts
+        }),
+        "Processes: 412"
+    );
+}
+
+#[test]
+fn test_profiles_setting() {
+    use config::key;
+    use serde_json::json;
+
+    let config = Config::default();
+    let names: Vec<String> = config.profiles().into_iter().map(|p| p.name).collect();
+    assert_eq!(names, ["Minimal", "Full", "Network debugging"]);
+    // The built-in profiles pass their own validation
+    assert!(config
+        .set(key::PROFILES, &config.get(key::PROFILES).unwrap())
+        .is_ok());
+
+    let saved = config
+        .set(
+            key::PROFILES,
+            &json!([{ "name": " Quiet ", "settings": { "update_interval_ms": 100 } }]),
+        )
+        .unwrap();
+    assert_eq!(
+        saved,
+        json!([{ "name": "Quiet", "settings": { "update_interval_ms": 500 } }])
+    );
+
+    assert!(config.set(key::ACTIVE_PROFILE, &json!("Quiet")).is_ok());
+    assert!(config.set(key::ACTIVE_PROFILE, &json!("Minimal")).is_err());
+    assert_eq!(config.active_profile(), "Quiet");
+
+    for invalid in [
+        json!({}),
+        json!([{ "settings": {} }]),
+        json!([{ "name": "A", "settings": {} }, { "name": "A", "settings": {} }]),
+        json!([{ "name": "A", "settings": { "autostart": true } }]),
+        json!([{ "name": "A", "settings": { "layout": "huge" } }]),
+        json!([{ "name": "A", "settings": {
+            "show_cpu": false, "show_mem": false, "show_gpu": false, "show_net": false
+        } }]),
+    ] {
+        assert!(config.set(key::PROFILES, &invalid).is_err(), "{invalid}");
+    }
+
+    // Dropping the active profile from the list clears it
+    config.set(key::PROFILES, &json!([])).unwrap();
+    assert_eq!(config.active_profile(), "");
+
+    // A profile that hides some segments can't be chosen while it would
+    // hide the rest too
+    let settings = json!({ "show_cpu": false, "show_mem": false, "show_gpu": false });
+    let settings = settings.as_object().unwrap();
+    assert!(!profiles::hides_every_segment(settings, |key| key == key::SHOW_NET));
+    assert!(profiles::hides_every_segment(settings, |_| false));
+}
+
+#[test]
+fn test_profile_menu_id_round_trip() {
+    let id = profiles::menu_id("Network debugging");
+    assert_eq!(profiles::parse_menu_id(&id), Some("Network debugging"));
+    assert_eq!(profiles::parse_menu_id("profiles:x"), None);
+    assert_eq!(profiles::parse_menu_id(config::key::LAYOUT), None);
+}
+
+#[test]
+fn test_thermal_throttling_detection() {
+    let intel = "Note: No thermal warning level has been recorded\n\
+                 CPU Power notify\n\
+                 \tCPU_Scheduler_Limit \t= 100\n\
+                 \tCPU_Available_CPUs \t= 8\n\
+                 \tCPU_Speed_Limit \t= 100\n";
+    assert!(!thermal::parse_pmset_therm(intel));
+    assert!(thermal::parse_pmset_therm(
+        &intel.replace("CPU_Speed_Limit \t= 100", "CPU_Speed_Limit \t= 78")
+    ));
+    assert!(thermal::parse_pmset_therm(
+        "Thermal warning level set to 1.\nNote: No performance warning level has been recorded\n"
+    ));
+    assert!(!thermal::parse_pmset_therm(
+        "Note: No thermal warning level has been recorded\n\
+         Note: No performance warning level has been recorded\n"
+    ));
+
+    assert_eq!(throttling_note(false, false), None);
+    assert_eq!(
+        throttling_note(true, true).as_deref(),
+        Some("Thermal throttling: CPU, GPU")
+    );
+}
+
+#[test]
+fn test_container_api_parsing() {
+    let ok = "HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n[]";
+    assert_eq!(http::response_body(ok, "Container runtime"), Ok("[]"));
+    assert!(http::response_body("HTTP/1.0 404 Not Found\r\n\r\n{}", "Container runtime").is_err());
+    assert!(http::response_body("garbage", "Container runtime").is_err());
+
+    let container = serde_json::json!({"Id": "4f66ad9a0b2e1c", "Names": ["/postgres"]});
+    assert_eq!(containers::container_name(&container), "postgres");
+    let unnamed = serde_json::json!({"Id": "4f66ad9a0b2e1c5d"});
+    assert_eq!(containers::container_name(&unnamed), "4f66ad9a0b2e");
+
+    let stats = serde_json::json!({
+        "cpu_stats": {
+            "cpu_usage": {"total_usage": 2_000_000_000u64},
+            "system_cpu_usage": 100_000_000_000u64,
+            "online_cpus": 4
+        },
+        "memory_stats": {"usage": 300_000_000u64, "stats": {"inactive_file": 100_000_000u64}}
+    });
+    let current = containers::cpu_counters(&stats).unwrap();
+    assert_eq!(containers::online_cpus(&stats), 4);
+    // 8s of system time on 4 cores is 2s of wall time, 0.5s of it busy: a quarter core
+    let previous = (1_500_000_000, 92_000_000_000);
+    assert_eq!(containers::cpu_percent(previous, current, 4), 25.0);
+    assert_eq!(containers::cpu_percent(current, current, 4), 0.0);
+    assert_eq!(containers::memory_in_use(&stats), 200_000_000);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_parse_nvidia_smi() {
+    use gpu::{parse_smi_output, GpuMemory, SmiGpu};
+
+    let gpus = parse_smi_output("45, 1024, 8192, 61\n3, [N/A], [N/A], [Not Supported]\n\n");
+    assert_eq!(
+        gpus,
+        [
+            SmiGpu {
+                utilization: Some(45.0),
+                memory: Some(GpuMemory {
+                    used: 1024 * 1024 * 1024,
+                    total: 8 * 1024 * 1024 * 1024,
+                }),
+                celsius: Some(61),
+            },
+            SmiGpu {
+                utilization: Some(3.0),
+                memory: None,
+                celsius: None,
+            },
+        ]
+    );
+    assert!(parse_smi_output("").is_empty());
+}
+
+#[test]
+fn test_gpu_codec() {
+    use alerts::AlertMetric;
+    use sampler::MetricValue;
+
+    let value = MetricValue::Codec {
+        encoder: 97.4,
+        decoder: 12.0,
+    };
+    assert_eq!(value.codec(), Some((97.4, 12.0)));
+    assert_eq!(value.percent(), None);
+    assert_eq!(MetricValue::Percent(40.0).codec(), None);
+
+    assert_eq!(
+        codec_status_text(97.4, 12.0),
+        "GPU Encoder: 97% · Decoder: 12%"
+    );
+    assert_eq!(
+        AlertMetric::parse("gpu_encoder"),
+        Some(AlertMetric::GpuEncoder)
+    );
+}
+
+#[test]
+fn test_gpu_processes() {
+    use gpu_processes::{parse_ioreg_clients, top_by_memory, GpuProcess};
+
+    let output = r#"
+    | |   {
+    | |     "IOUserClientCreator" = "pid 412, WindowServer"
+    | |   }
+    | |   {
+    | |     "IOUserClientCreator" = "pid 1873, Google Chrome Helper (GPU)"
+    | |   }
+    | |   {
+    | |     "IOUserClientCreator" = "pid 412, WindowServer"
+    | |     "IOUserClientDefaultLocking" = Yes
+    | |   }
+"#;
+    let clients = parse_ioreg_clients(output);
+    let names: Vec<_> = clients.iter().map(|p| (p.pid, p.name.as_str())).collect();
+    assert_eq!(
+        names,
+        [(412, "WindowServer"), (1873, "Google Chrome Helper (GPU)")]
+    );
+    assert!(clients.iter().all(|p| p.memory.is_none()));
+
+    let process = |pid, memory| GpuProcess {
+        pid,
+        name: format!("p{pid}"),
+        memory,
+    };
+    let top = top_by_memory(
+        vec![
+            process(30, None),
+            process(20, Some(512 << 20)),
+            process(10, None),
+            process(40, Some(2 << 30)),
+        ],
+        3,
+    );
+    let pids: Vec<_> = top.iter().map(|p| p.pid).collect();
+    assert_eq!(pids, [40, 20, 10]);
+}
+
+#[test]
+fn test_details_lines() {
+    use config::NetUnit;
+    use details::{exact_rate, lines, Readings};
+
+    assert_eq!(exact_rate(512.0, NetUnit::Bytes), "512 B/s");
+    assert_eq!(exact_rate(1_520_000.0, NetUnit::Bytes), "1.52 MB/s");
+    assert_eq!(exact_rate(1_520_000.0, NetUnit::Bits), "12.16 Mb/s");
+
+    const GIB: u64 = 1 << 30;
+    let readings = Readings {
+        cpu: Some(34.24),
+        mem: Some(39.4),
+        mem_bytes: Some((GIB * 126 / 10, GIB * 32)),
+        gpu: Some(18.0),
+        gpu_mem: None,
+        net: Some((1_520_000.0, 210_000.0)),
+    };
+    assert_eq!(
+        lines(&readings, NetUnit::Bytes),
+        [
+            "CPU: 34.2%",
+            "Memory: 12.6 / 32.0 GB",
+            "GPU: 18.0%",
+            "Network: ↓1.52 MB/s ↑210.00 KB/s",
+        ]
+    );
+
+    // A remote host reports memory as a percentage; no GPU line without one
+    let remote = Readings {
+        mem_bytes: None,
+        gpu: None,
+        ..readings
+    };
+    assert_eq!(lines(&remote, NetUnit::Bytes)[1], "Memory: 39.4%");
+    assert_eq!(lines(&remote, NetUnit::Bytes).len(), 3);
+    assert!(lines(&Readings::default(), NetUnit::Bytes).is_empty());
+}
+
+#[test]
+fn test_copy_stats() {
+    use config::NetUnit;
+    use metrics::Sample;
+
+    let sample = Sample {
+        timestamp: 1_700_000_000,
+        cpu: Some(34.2),
+        mem: Some(41.3),
+        gpu: Some(18.0),
+        net_down: Some(1_520_000.0),
+        net_up: Some(210_000.0),
+        ..Sample::default()
+    };
+    assert_eq!(
+        copy_stats::format(&sample, false, NetUnit::Bytes),
+        "CPU 34.2% · Mem 41.3% · GPU 18.0% · ↓1.52 MB/s ↑210.00 KB/s"
+    );
+    let json: serde_json::Value =
+        serde_json::from_str(&copy_stats::format(&sample, true, NetUnit::Bytes)).unwrap();
+    assert_eq!(json["timestamp"], 1_700_000_000);
+    assert_eq!(json["gpu_mem"], serde_json::Value::Null);
+    assert_eq!(copy_stats::line(&Sample::default(), NetUnit::Bits), "");
+}
+
+#[test]
+fn test_save_icon_paths() {
+    use save_icon::scaled_paths;
+    use std::path::{Path, PathBuf};
+
+    assert_eq!(
+        scaled_paths(Path::new("/tmp/shots/icon.png")),
+        [
+            PathBuf::from("/tmp/shots/icon.png"),
+            PathBuf::from("/tmp/shots/icon@2x.png")
+        ]
+    );
+    // A name typed without an extension still gets one
+    assert_eq!(
+        scaled_paths(Path::new("/tmp/panel")),
+        [
+            PathBuf::from("/tmp/panel.png"),
+            PathBuf::from("/tmp/panel@2x.png")
+        ]
+    );
+}
+
+#[test]
+fn test_gpu_recovery() {
+    use gpu::{reinit_backoff, Recovery, HIDE_AFTER, LOST_AFTER_READS, MAX_REINIT_BACKOFF};
+    use std::time::{Duration, Instant};
+
+    assert_eq!(reinit_backoff(0), Duration::ZERO);
+    assert_eq!(reinit_backoff(1), Duration::from_secs(2));
+    assert_eq!(reinit_backoff(3), Duration::from_secs(8));
+    assert_eq!(reinit_backoff(40), MAX_REINIT_BACKOFF);
+
+    let start = Instant::now();
+    let mut recovery = Recovery::default();
+    // A successful read clears earlier failures
+    assert!(!recovery.record_read(false, start));
+    assert!(!recovery.record_read(true, start));
+    for _ in 1..LOST_AFTER_READS {
+        assert!(!recovery.record_read(false, start));
+    }
+    assert!(recovery.record_read(false, start));
+    assert!(!recovery.record_read(false, start));
+
+    // Retried at once, then after doubling waits
+    assert!(recovery.reinit_due(start));
+    recovery.reinit_failed(start);
+    assert!(!recovery.reinit_due(start + Duration::from_secs(1)));
+    assert!(recovery.reinit_due(start + Duration::from_secs(2)));
+
+    assert!(!recovery.is_hidden(start + HIDE_AFTER - Duration::from_secs(1)));
+    assert!(recovery.is_hidden(start + HIDE_AFTER));
+    recovery.recovered();
+    assert!(!recovery.is_hidden(start + HIDE_AFTER));
+    assert!(recovery.reinit_due(start));
 }
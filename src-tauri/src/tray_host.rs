@@ -0,0 +1,103 @@
+//! Missing tray host detection (Linux)
+//!
+//! The tray icon is exported as a StatusNotifierItem over D-Bus. On X11,
+//! libappindicator falls back to the legacy XEmbed tray when nobody implements
+//! `org.kde.StatusNotifierWatcher`, but Wayland has no such fallback: on stock
+//! GNOME the icon silently never appears. This probes for the watcher once after
+//! startup and explains how to get one, a single time per install.
+
+use std::time::Duration;
+
+use tauri::AppHandle;
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+use tauri_plugin_store::StoreExt;
+use tracing::warn;
+
+use crate::config::{save_setting, SETTINGS_FILE};
+
+/// Store key remembering that the notice was already shown.
+const NOTICE_SHOWN_KEY: &str = "tray_host_notice_shown";
+
+/// Desktop shells that autostart alongside the app may register the watcher a
+/// little later than we start.
+const PROBE_DELAY: Duration = Duration::from_secs(10);
+
+const WATCHER_NAME: &str = "org.kde.StatusNotifierWatcher";
+
+/// Spawns the one-time probe. Does nothing outside Wayland sessions.
+pub fn check_in_background(app: AppHandle) {
+    if std::env::var_os("WAYLAND_DISPLAY").is_none() || notice_shown(&app) {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        std::thread::sleep(PROBE_DELAY);
+        // gdbus missing or the session bus unreachable: nothing reliable to report
+        if watcher_running() != Some(false) {
+            return;
+        }
+
+        warn!("No StatusNotifierWatcher on the session bus; the tray icon will not be shown");
+        let gnome = std::env::var("XDG_CURRENT_DESKTOP")
+            .is_ok_and(|desktop| desktop.to_ascii_uppercase().contains("GNOME"));
+        app.dialog()
+            .message(notice_message(gnome))
+            .title("Tray Icon Not Visible")
+            .kind(MessageDialogKind::Warning)
+            .show(|_| {});
+        save_setting(&app, NOTICE_SHOWN_KEY, true);
+    });
+}
+
+fn watcher_running() -> Option<bool> {
+    crate::command_stdout(
+        "gdbus",
+        &[
+            "call",
+            "--session",
+            "--timeout",
+            "1",
+            "--dest",
+            "org.freedesktop.DBus",
+            "--object-path",
+            "/org/freedesktop/DBus",
+            "--method",
+            "org.freedesktop.DBus.NameHasOwner",
+            WATCHER_NAME,
+        ],
+    )
+    .as_deref()
+    .and_then(parse_name_has_owner)
+}
+
+/// Parses `gdbus` output for a boolean reply, e.g. `(true,)`.
+pub(crate) fn parse_name_has_owner(output: &str) -> Option<bool> {
+    match output.trim().trim_start_matches('(').trim_end_matches(",)") {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+pub(crate) fn notice_message(gnome: bool) -> String {
+    let fix = if gnome {
+        "GNOME needs the \"AppIndicator and KStatusNotifierItem Support\" extension \
+         (package gnome-shell-extension-appindicator on most distributions). \
+         Enable it in the Extensions app, then log out and back in."
+    } else {
+        "Enable the system tray or status notifier applet of your panel, \
+         or start a tray host such as waybar's tray module."
+    };
+    format!(
+        "Better Resource Monitor is running, but your desktop has no system tray host \
+         (StatusNotifierWatcher), so its icon cannot be shown.\n\n{fix}"
+    )
+}
+
+fn notice_shown(app: &AppHandle) -> bool {
+    app.store(SETTINGS_FILE)
+        .ok()
+        .and_then(|store| store.get(NOTICE_SHOWN_KEY))
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
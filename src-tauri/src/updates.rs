@@ -0,0 +1,139 @@
+//! Update check
+//!
+//! With `check_updates` on, a background thread asks GitHub for the latest
+//! release once a day (via `curl`, like the alert webhook) and the monitoring
+//! loop adds an "Update Available" item above Preferences while a newer
+//! version exists. Choosing it opens the release page to download the new
+//! build; the app doesn't replace itself. Off by default, so nothing leaves the
+//! machine unless asked.
+//!
+//! The check isn't built on macOS, where the App Store delivers updates and
+//! doesn't allow apps to look for them on their own; the setting is hidden
+//! there.
+
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use tauri::menu::{Menu, MenuItem};
+use tauri::Wry;
+
+#[cfg(not(target_os = "macos"))]
+pub use checker::start;
+
+pub const MENU_ID: &str = "install_update";
+
+/// The newer release, if the last check found one; written by the checking
+/// thread, read by the monitoring loop and the menu handler.
+pub type SharedUpdate = Arc<Mutex<Option<Release>>>;
+
+/// The tray menu and the item offering the update, present while there is one.
+pub struct UpdateMenu {
+    pub menu: Menu<Wry>,
+    pub item: MenuItem<Wry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Release {
+    /// "1.2.0", without the tag's "v"
+    pub version: String,
+    /// Release page on GitHub
+    pub url: String,
+}
+
+#[cfg(not(target_os = "macos"))]
+mod checker {
+    use std::process::{Command, Stdio};
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use tracing::warn;
+
+    use super::{is_newer, parse_release, Release, SharedUpdate};
+    use crate::config::Config;
+
+    const RELEASES_URL: &str =
+        "https://api.github.com/repos/alexx855/better-resource-monitor/releases/latest";
+    const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+    /// How soon turning the setting on is noticed.
+    const POLL_INTERVAL: Duration = Duration::from_secs(60);
+    const TIMEOUT_SECS: u32 = 15;
+
+    pub fn start(config: Arc<Config>, current: String, available: SharedUpdate) {
+        thread::spawn(move || {
+            let mut last_check: Option<Instant> = None;
+            loop {
+                let due = last_check.is_none_or(|checked| checked.elapsed() >= CHECK_INTERVAL);
+                if config.check_updates.load(Relaxed) && due {
+                    last_check = Some(Instant::now());
+                    match fetch_latest() {
+                        Ok(release) => {
+                            *available.lock().unwrap_or_else(|e| e.into_inner()) =
+                                is_newer(&release.version, &current).then_some(release);
+                        }
+                        Err(e) => warn!("Update check failed: {e}"),
+                    }
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        });
+    }
+
+    fn fetch_latest() -> Result<Release, String> {
+        let output = Command::new("curl")
+            .args([
+                "-sSfL",
+                "--max-time",
+                &TIMEOUT_SECS.to_string(),
+                "-H",
+                "Accept: application/vnd.github+json",
+                "--",
+                RELEASES_URL,
+            ])
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("Failed to run curl: {e}"))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        parse_release(&String::from_utf8_lossy(&output.stdout))
+            .ok_or_else(|| "Unexpected release response".to_string())
+    }
+}
+
+/// Version and page of a GitHub "latest release" response.
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+pub(crate) fn parse_release(body: &str) -> Option<Release> {
+    let value: Value = serde_json::from_str(body).ok()?;
+    let tag = value.get("tag_name")?.as_str()?;
+    Some(Release {
+        version: tag.trim_start_matches('v').to_string(),
+        url: value.get("html_url")?.as_str()?.to_string(),
+    })
+}
+
+/// Whether `latest` is a higher version than `current`, comparing dotted
+/// numbers ("1.10.0" > "1.9.2"); a pre-release suffix is ignored and an
+/// unparseable version is never newer.
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+pub(crate) fn is_newer(latest: &str, current: &str) -> bool {
+    fn parts(version: &str) -> Option<[u64; 3]> {
+        let version = version.trim_start_matches('v');
+        let core = version.split(['-', '+']).next()?;
+        let mut parts = [0; 3];
+        for (slot, part) in parts.iter_mut().zip(core.split('.')) {
+            *slot = part.parse().ok()?;
+        }
+        Some(parts)
+    }
+    match (parts(latest), parts(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+/// Opens the release page in the default browser.
+pub fn open(release: &Release) -> Result<(), String> {
+    crate::open_with_default_app(release.url.as_ref())
+}
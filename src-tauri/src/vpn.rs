@@ -0,0 +1,74 @@
+//! VPN status line
+//!
+//! Shows in the tray menu whether a tunnel interface (`utun*`, `wg*`, `tun*`,
+//! ...) is up, and which. macOS keeps a few `utun` interfaces around for system
+//! services even without a VPN; those only carry IPv6 link-local addresses, so
+//! an interface only counts once it has a routable address.
+
+use std::net::IpAddr;
+
+use sysinfo::Networks;
+
+use crate::i18n::{tr, tr_args};
+
+/// Interface name prefixes used by common VPN clients.
+pub(crate) const TUNNEL_PREFIXES: &[&str] = &[
+    "utun",
+    "wg",
+    "tun",
+    "tap",
+    "ppp",
+    "ipsec",
+    "tailscale",
+    "nordlynx",
+];
+
+pub struct TunnelMonitor {
+    networks: Networks,
+}
+
+impl TunnelMonitor {
+    pub fn new() -> Self {
+        Self {
+            networks: Networks::new_with_refreshed_list(),
+        }
+    }
+
+    /// Names of the tunnel interfaces that are currently up, sorted.
+    pub fn active(&mut self) -> Vec<String> {
+        // Removing unlisted interfaces drops tunnels that were torn down
+        self.networks.refresh(true);
+        let mut names: Vec<String> = self
+            .networks
+            .iter()
+            .filter(|(name, data)| {
+                let addrs: Vec<IpAddr> = data.ip_networks().iter().map(|n| n.addr).collect();
+                is_active_tunnel(name, &addrs)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+pub(crate) fn is_active_tunnel(name: &str, addrs: &[IpAddr]) -> bool {
+    let is_tunnel = TUNNEL_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix));
+    is_tunnel
+        && addrs.iter().any(|addr| match addr {
+            IpAddr::V4(_) => true,
+            // fe80::/10
+            IpAddr::V6(v6) => v6.segments()[0] & 0xffc0 != 0xfe80,
+        })
+}
+
+/// "VPN: wg0", "VPN: utun4, wg0" or "VPN: Not connected".
+pub(crate) fn status_text(active: &[String]) -> String {
+    if active.is_empty() {
+        tr("VPN: Not connected").to_string()
+    } else {
+        tr_args("VPN: {}", &[&active.join(", ")])
+    }
+}
@@ -0,0 +1,121 @@
+//! Monitoring watchdog
+//!
+//! The monitoring loop beats a heartbeat on every tick. A supervisor thread
+//! watches it: when no beat has come for several update intervals (a sampler
+//! call such as NVML never returned), the tray icons switch to a "stalled"
+//! glyph and the loop is started again with freshly created samplers. Restarts
+//! back off while the new loop keeps stalling, and the glyph stays up until a
+//! loop draws readings again.
+//!
+//! Release builds abort on panic, so a panicking loop ends the app; the
+//! watchdog is only for stuck ones.
+
+use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tracing::warn;
+
+/// Ticks that may go by before the loop counts as stalled.
+const MISSED_TICKS: u32 = 3;
+/// Slack on top, for a slow tick or a busy machine.
+const GRACE: Duration = Duration::from_secs(20);
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+const FIRST_BACKOFF: Duration = Duration::from_secs(30);
+const MAX_BACKOFF: Duration = Duration::from_secs(10 * 60);
+
+pub type SharedHeartbeat = Arc<Heartbeat>;
+
+pub struct Heartbeat {
+    started: Instant,
+    /// Milliseconds since `started` at the last beat
+    last_ms: AtomicU64,
+    /// The loop's update interval at the last beat
+    period_ms: AtomicU64,
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            last_ms: AtomicU64::new(0),
+            period_ms: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Heartbeat {
+    /// Called by the loop on every tick, with the interval until the next one.
+    pub fn beat(&self, period: Duration) {
+        self.last_ms
+            .store(self.started.elapsed().as_millis() as u64, Relaxed);
+        self.period_ms.store(period.as_millis() as u64, Relaxed);
+    }
+
+    /// When the last beat came.
+    pub(crate) fn last_beat(&self) -> Instant {
+        self.started + Duration::from_millis(self.last_ms.load(Relaxed))
+    }
+
+    pub(crate) fn is_stalled_at(&self, now: Instant) -> bool {
+        let period = Duration::from_millis(self.period_ms.load(Relaxed));
+        now.saturating_duration_since(self.last_beat()) > stall_limit(period)
+    }
+}
+
+/// How long a loop updating every `period` may go without a beat.
+pub(crate) fn stall_limit(period: Duration) -> Duration {
+    period * MISSED_TICKS + GRACE
+}
+
+/// Wait after `restarts` recent restarts before the next one: none for the
+/// first, then doubling up to [`MAX_BACKOFF`].
+pub(crate) fn backoff(restarts: u32) -> Duration {
+    match restarts {
+        0 => Duration::ZERO,
+        n => FIRST_BACKOFF
+            .saturating_mul(1 << (n - 1).min(16))
+            .min(MAX_BACKOFF),
+    }
+}
+
+/// Watches `heartbeat` on a background thread. `on_stall` is called when a
+/// stall is noticed, and `restart` whenever the backoff allows while it lasts.
+/// The backoff only resets once the loop has kept running for a while, so a
+/// loop that stalls again right after restarting isn't restarted in a tight
+/// cycle.
+pub fn start(
+    heartbeat: SharedHeartbeat,
+    on_stall: impl Fn() + Send + 'static,
+    restart: impl Fn() + Send + 'static,
+) {
+    thread::spawn(move || {
+        let mut stalled = false;
+        let mut restarts = 0;
+        let mut last_restart: Option<Instant> = None;
+        loop {
+            thread::sleep(CHECK_INTERVAL);
+            let now = Instant::now();
+            if !heartbeat.is_stalled_at(now) {
+                stalled = false;
+                if last_restart.is_some_and(|at| now.duration_since(at) > MAX_BACKOFF) {
+                    restarts = 0;
+                    last_restart = None;
+                }
+                continue;
+            }
+            if !stalled {
+                stalled = true;
+                warn!("Monitoring stalled");
+                on_stall();
+            }
+            if last_restart.is_none_or(|at| now.duration_since(at) >= backoff(restarts)) {
+                warn!("Restarting monitoring");
+                restart();
+                restarts += 1;
+                last_restart = Some(now);
+            }
+        }
+    });
+}
@@ -0,0 +1,122 @@
+//! Watched processes
+//!
+//! `watched_processes` names processes to keep an eye on, e.g. "chrome" or
+//! "node". Each tick the monitoring loop adds up the CPU and resident memory
+//! of every process whose name contains one (ignoring case, so "chrome" also
+//! covers "Google Chrome Helper"). A name over `watched_cpu_limit` (percent of
+//! one core) or `watched_mem_limit_mb` raises the `watched_process` alert and
+//! is listed on a line near the top of the tray menu until it drops back.
+
+use serde_json::Value;
+
+use crate::i18n::tr_args;
+use crate::processes::{format_memory, ProcessInfo};
+
+const MAX_NAMES: usize = 16;
+const MAX_NAME_LEN: usize = 64;
+
+/// The processes matching one watched name, added up.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WatchedUsage {
+    pub name: String,
+    /// Percent of one core
+    pub cpu: f32,
+    /// Resident memory in bytes
+    pub memory: u64,
+}
+
+/// `watched_cpu_limit` and `watched_mem_limit_mb`; 0 turns a limit off.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Limits {
+    pub cpu: u32,
+    pub mem_mb: u32,
+}
+
+impl Limits {
+    /// The larger of CPU and memory as a percentage of its limit, e.g. 150
+    /// for a process using 3 GB against 2 GB.
+    pub fn percent_of(self, usage: &WatchedUsage) -> f32 {
+        let cpu = match self.cpu {
+            0 => 0.0,
+            limit => usage.cpu / limit as f32 * 100.0,
+        };
+        let mem = match self.mem_mb {
+            0 => 0.0,
+            limit => usage.memory as f32 / (limit as f32 * 1_000_000.0) * 100.0,
+        };
+        cpu.max(mem)
+    }
+}
+
+/// Usage per watched name, in the order of `names`; names without a running
+/// process are left out.
+pub fn aggregate(processes: &[ProcessInfo], names: &[String]) -> Vec<WatchedUsage> {
+    names
+        .iter()
+        .filter_map(|name| {
+            let needle = name.to_lowercase();
+            let mut matches = processes
+                .iter()
+                .filter(|process| process.name.to_lowercase().contains(&needle))
+                .peekable();
+            matches.peek()?;
+            let (cpu, memory) = matches.fold((0.0, 0), |(cpu, memory), process| {
+                (cpu + process.cpu, memory + process.memory)
+            });
+            Some(WatchedUsage {
+                name: name.clone(),
+                cpu,
+                memory,
+            })
+        })
+        .collect()
+}
+
+/// The usages at or past 100% of `limits`.
+pub fn over_limits(usages: Vec<WatchedUsage>, limits: Limits) -> Vec<WatchedUsage> {
+    usages
+        .into_iter()
+        .filter(|usage| limits.percent_of(usage) >= 100.0)
+        .collect()
+}
+
+/// "Over limit: chrome 142% · 3.1 GB, node 12% · 2.4 GB".
+pub fn status_text(over: &[WatchedUsage]) -> String {
+    let list = over
+        .iter()
+        .map(|usage| {
+            format!(
+                "{} {:.0}% · {}",
+                usage.name,
+                usage.cpu,
+                format_memory(usage.memory)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    tr_args("Over limit: {}", &[&list])
+}
+
+/// Validates a `watched_processes` setting: an array of distinct names.
+pub fn parse_names(value: &Value) -> Result<Vec<String>, String> {
+    let entries = value
+        .as_array()
+        .filter(|entries| entries.len() <= MAX_NAMES)
+        .ok_or_else(|| {
+            format!("watched_processes must be an array of at most {MAX_NAMES} names")
+        })?;
+    let mut names: Vec<String> = Vec::new();
+    for entry in entries {
+        let name = entry
+            .as_str()
+            .map(str::trim)
+            .filter(|name| !name.is_empty() && name.len() <= MAX_NAME_LEN)
+            .ok_or_else(|| {
+                format!("Every watched process must be a name of up to {MAX_NAME_LEN} bytes")
+            })?;
+        if !names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+            names.push(name.to_string());
+        }
+    }
+    Ok(names)
+}
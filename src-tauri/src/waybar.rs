@@ -0,0 +1,134 @@
+//! Waybar output mode (`--waybar`)
+//!
+//! Prints one JSON line per sampling cycle in the format of Waybar's `custom`
+//! module, so tiling window manager bars can show the tray's readings without a
+//! tray at all:
+//!
+//! ```text
+//! "custom/resources": {
+//!     "exec": "better-resource-monitor --waybar",
+//!     "return-type": "json"
+//! }
+//! ```
+//!
+//! `text` lists the segments enabled in the app, in its order, and `tooltip`
+//! every available reading. While a metric is over its alert threshold `class`
+//! holds `alert` and e.g. `cpu-alert`, for the bar's stylesheet. Polybar can use
+//! the text with `exec = better-resource-monitor --waybar | jq --unbuffered -r .text`
+//! and `tail = true`.
+
+use std::io::{self, Write};
+use std::sync::atomic::Ordering::Relaxed;
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::config::Config;
+use crate::metrics::Sample;
+use crate::processes::format_memory;
+use crate::sampler::SamplerRegistry;
+use crate::tray_render::{format_percent, SegmentKind};
+use crate::{cpu, headless};
+
+pub const FLAG: &str = "--waybar";
+
+/// One module update for `sample`, following the settings the tray would use.
+pub(crate) fn output(sample: &Sample, config: &Config, cpu_cores: u32) -> Value {
+    let cap = config.percent_cap();
+    let net_unit = config.net_unit();
+    let percent = |value: f32, is_cpu: bool| format_percent(value, cap, is_cpu, cpu_cores);
+    let net = |(down, up): (f64, f64)| {
+        format!(
+            "↓ {} ↑ {}",
+            crate::format_net(down, net_unit),
+            crate::format_net(up, net_unit)
+        )
+    };
+    let net_rates = sample.net_down.zip(sample.net_up);
+
+    let segments = crate::visible_segments(
+        config.segment_order(),
+        config.show_cpu.load(Relaxed),
+        config.show_mem.load(Relaxed),
+        config.show_gpu.load(Relaxed),
+        config.show_net.load(Relaxed),
+    );
+    let text: Vec<String> = segments
+        .into_iter()
+        .filter_map(|kind| match kind {
+            SegmentKind::Cpu => sample.cpu.map(|v| format!("CPU {}", percent(v, true))),
+            SegmentKind::Memory => sample.mem.map(|v| format!("MEM {}", percent(v, false))),
+            SegmentKind::Gpu => sample.gpu.map(|v| format!("GPU {}", percent(v, false))),
+            SegmentKind::Network => net_rates.map(net),
+        })
+        .collect();
+
+    let tooltip: Vec<String> = [
+        sample.cpu.map(|v| format!("CPU: {}", percent(v, true))),
+        sample.mem.map(|v| format!("Memory: {}", percent(v, false))),
+        sample.gpu.map(|v| format!("GPU: {}", percent(v, false))),
+        sample
+            .gpu_mem
+            .map(|bytes| format!("GPU Memory: {}", format_memory(bytes))),
+        sample
+            .gpu_temp
+            .map(|celsius| format!("GPU Temperature: {}", crate::format_temperature(celsius))),
+        sample
+            .cpu_power
+            .map(|watts| format!("CPU Power: {}", crate::format_watts(watts))),
+        net_rates.map(|rates| format!("Network: {}", net(rates))),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let thresholds = config.alert_thresholds();
+    let over: Vec<&str> = [
+        ("cpu-alert", sample.cpu, thresholds.cpu),
+        ("mem-alert", sample.mem, thresholds.mem),
+        ("gpu-alert", sample.gpu, thresholds.gpu),
+    ]
+    .into_iter()
+    .filter(|(_, value, threshold)| value.is_some_and(|v| v >= *threshold))
+    .map(|(class, _, _)| class)
+    .collect();
+    let class: Vec<&str> = if config.show_alerts.load(Relaxed) && !over.is_empty() {
+        std::iter::once("alert").chain(over).collect()
+    } else {
+        Vec::new()
+    };
+
+    json!({
+        "text": text.join("  "),
+        "tooltip": tooltip.join("\n"),
+        "class": class,
+        // For `format-icons`
+        "percentage": sample.cpu.map_or(0, |v| v.clamp(0.0, 100.0).round() as u32),
+    })
+}
+
+pub fn run() {
+    let data_dir = headless::app_data_dir();
+    let config = headless::load_config(data_dir.as_ref());
+    crate::logging::configure(&config, data_dir.as_deref());
+    config.configure_samplers();
+    let cpu_cores = cpu::logical_cores();
+    let mut samplers = SamplerRegistry::with_default_samplers();
+    let mut stdout = io::stdout().lock();
+    loop {
+        samplers.sample(|_| true);
+        let line = output(&Sample::from_registry(&samplers), &config, cpu_cores);
+        if writeln!(stdout, "{line}")
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            // The bar closed the pipe, e.g. on reload
+            return;
+        }
+
+        let interval =
+            crate::get_update_interval_ms(config.update_interval_ms.load(Relaxed) as u64);
+        thread::sleep(Duration::from_millis(interval));
+    }
+}
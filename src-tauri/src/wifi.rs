@@ -0,0 +1,207 @@
+//! Wi-Fi signal segment and network line
+//!
+//! Shows the signal strength (RSSI) of the current Wi-Fi connection as a tray
+//! segment, alert-colored below `wifi_alert_dbm`, and the network name in a
+//! tray menu line. Nothing is shown on the tray while not on Wi-Fi.
+//!
+//! - Linux: the wireless interface from `/proc/net/wireless`, then `iw dev
+//!   <interface> link` (nl80211) for the SSID and signal
+//! - macOS: CoreWLAN's `CWWiFiClient`. The SSID needs Location Services access,
+//!   so without it only the signal is known.
+
+use std::time::{Duration, Instant};
+
+use crate::i18n::{tr, tr_args};
+
+pub const DEFAULT_ALERT_DBM: i32 = -75;
+pub const MIN_ALERT_DBM: i64 = -100;
+pub const MAX_ALERT_DBM: i64 = -30;
+
+/// Signal readings jitter by a few dB; smaller changes keep the shown value so
+/// the icon isn't redrawn on every check.
+const HYSTERESIS_DBM: i32 = 3;
+
+/// The connection changes rarely and `iw` is a separate process, so it is
+/// checked every few seconds rather than every tick.
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WifiStatus {
+    /// `None` where the name can't be read
+    pub ssid: Option<String>,
+    /// Signal strength in dBm
+    pub rssi: i32,
+}
+
+/// Owned by the monitoring loop while Wi-Fi is shown.
+#[derive(Default)]
+pub struct WifiMonitor {
+    last_check: Option<Instant>,
+    last: Option<WifiStatus>,
+}
+
+impl WifiMonitor {
+    /// The current connection, cached for [`CHECK_INTERVAL`]; `None` while not
+    /// connected to Wi-Fi.
+    pub fn status(&mut self) -> Option<&WifiStatus> {
+        let stale = self
+            .last_check
+            .is_none_or(|checked| checked.elapsed() >= CHECK_INTERVAL);
+        if stale {
+            self.last = read();
+            self.last_check = Some(Instant::now());
+        }
+        self.last.as_ref()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read() -> Option<WifiStatus> {
+    let (interface, level) =
+        parse_proc_net_wireless(&std::fs::read_to_string("/proc/net/wireless").ok()?)?;
+    match crate::command_stdout("iw", &["dev", &interface, "link"]) {
+        Some(output) => parse_iw_link(&output),
+        // Without `iw` the signal level is still known, just not the name
+        None => level.map(|rssi| WifiStatus { ssid: None, rssi }),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn read() -> Option<WifiStatus> {
+    corewlan::status()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn read() -> Option<WifiStatus> {
+    None
+}
+
+/// First interface listed in `/proc/net/wireless` after its two header lines,
+/// with its signal level in dBm while associated.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub(crate) fn parse_proc_net_wireless(table: &str) -> Option<(String, Option<i32>)> {
+    let line = table.lines().nth(2)?;
+    let (name, fields) = line.split_once(':')?;
+    // Status, link quality, then the level; values may end in a dot
+    let level = fields
+        .split_whitespace()
+        .nth(2)
+        .and_then(|level| level.trim_end_matches('.').parse::<f32>().ok())
+        .map(|dbm| dbm as i32)
+        .filter(|dbm| (-120..0).contains(dbm));
+    Some((name.trim().to_string(), level))
+}
+
+/// SSID and signal from `iw dev <interface> link`; `None` for "Not connected.".
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub(crate) fn parse_iw_link(output: &str) -> Option<WifiStatus> {
+    let mut ssid = None;
+    let mut rssi = None;
+    for line in output.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix("SSID:") {
+            ssid = Some(name.trim().to_string()).filter(|name| !name.is_empty());
+        } else if let Some(signal) = line.strip_prefix("signal:") {
+            rssi = signal.trim().trim_end_matches("dBm").trim().parse().ok();
+        }
+    }
+    Some(WifiStatus { ssid, rssi: rssi? })
+}
+
+/// Whether `new` should replace the `shown` signal: on the first reading, when
+/// crossing `alert_dbm`, or on a change of at least [`HYSTERESIS_DBM`].
+pub(crate) fn should_show(shown: Option<i32>, new: i32, alert_dbm: i32) -> bool {
+    match shown {
+        Some(prev) => {
+            (prev < alert_dbm) != (new < alert_dbm) || (new - prev).abs() >= HYSTERESIS_DBM
+        }
+        None => true,
+    }
+}
+
+/// "-56" for the tray segment: the unit wouldn't fit next to the icon, which
+/// already says what the number is.
+pub fn format_rssi(rssi: i32) -> String {
+    rssi.to_string()
+}
+
+/// "Wi-Fi: HomeNet · -56 dBm", "Wi-Fi: -56 dBm" when the name is unknown, or
+/// "Wi-Fi: Not connected".
+pub(crate) fn status_text(status: Option<&WifiStatus>) -> String {
+    match status {
+        Some(WifiStatus {
+            ssid: Some(ssid),
+            rssi,
+        }) => tr_args("Wi-Fi: {}", &[&format!("{ssid} · {rssi} dBm")]),
+        Some(WifiStatus { ssid: None, rssi }) => tr_args("Wi-Fi: {}", &[&format!("{rssi} dBm")]),
+        None => tr("Wi-Fi: Not connected").to_string(),
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod corewlan {
+    //! Minimal Objective-C runtime calls into CoreWLAN, to avoid pulling in
+    //! bindings for three messages.
+
+    use std::ffi::{c_char, c_void, CStr};
+
+    use super::WifiStatus;
+
+    type Id = *mut c_void;
+    type Sel = *mut c_void;
+
+    #[link(name = "CoreWLAN", kind = "framework")]
+    unsafe extern "C" {}
+
+    #[link(name = "objc")]
+    unsafe extern "C" {
+        fn objc_getClass(name: *const c_char) -> Id;
+        fn sel_registerName(name: *const c_char) -> Sel;
+        fn objc_msgSend();
+        fn objc_autoreleasePoolPush() -> *mut c_void;
+        fn objc_autoreleasePoolPop(pool: *mut c_void);
+    }
+
+    /// Sends a message without arguments that returns `T`.
+    ///
+    /// SAFETY: `receiver` must be an object (or nil) that responds to
+    /// `selector` with a return type matching `T`.
+    unsafe fn send<T>(receiver: Id, selector: &CStr) -> T {
+        let msg_send: unsafe extern "C" fn(Id, Sel) -> T =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        msg_send(receiver, sel_registerName(selector.as_ptr()))
+    }
+
+    pub fn status() -> Option<WifiStatus> {
+        // SAFETY: every message below is part of CoreWLAN's and NSString's
+        // public API with the return type given; nil receivers return nil or 0.
+        // Returned objects are autoreleased, so a pool scopes them to this call.
+        unsafe {
+            let pool = objc_autoreleasePoolPush();
+            let client: Id = send(objc_getClass(c"CWWiFiClient".as_ptr()), c"sharedWiFiClient");
+            let interface: Id = if client.is_null() {
+                std::ptr::null_mut()
+            } else {
+                send(client, c"interface")
+            };
+            let status = if interface.is_null() {
+                None
+            } else {
+                let rssi: isize = send(interface, c"rssiValue");
+                let ssid: Id = send(interface, c"ssid");
+                let ssid = if ssid.is_null() {
+                    None
+                } else {
+                    let utf8: *const c_char = send(ssid, c"UTF8String");
+                    (!utf8.is_null()).then(|| CStr::from_ptr(utf8).to_string_lossy().into_owned())
+                };
+                // 0 means the interface isn't associated
+                (rssi != 0).then_some(WifiStatus {
+                    ssid,
+                    rssi: rssi as i32,
+                })
+            };
+            objc_autoreleasePoolPop(pool);
+            status
+        }
+    }
+}